@@ -27,5 +27,18 @@ pub fn benchmark(c: &mut Criterion) {
     }));
 }
 
-criterion_group!(benches, benchmark);
+pub fn corpora(c: &mut Criterion) {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::html::add(md);
+    markdown_it::plugins::extra::add(md);
+
+    for corpus in markdown_it::bench::corpora() {
+        c.bench_function(&format!("corpus/{}", corpus.name), |b| b.iter(|| {
+            black_box(md.parse(&corpus.source).render());
+        }));
+    }
+}
+
+criterion_group!(benches, benchmark, corpora);
 criterion_main!(benches);
@@ -0,0 +1,215 @@
+//! Performance checks for parser configurations (requires `bench` feature).
+//!
+//! Plugins added by applications can make parsing much slower on some inputs,
+//! [run] measures a configured parser on representative documents (CommonMark spec,
+//! a large readme, pathological nesting), with time spent in each core rule,
+//! so CI can compare results with a baseline using [regressions].
+//!
+//! ```rust
+//! use markdown_it::bench::{self, BenchOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let options = BenchOptions { iterations: 1, warmup: 0, render: true };
+//! let results = bench::run_with(md, &bench::corpora(), &options);
+//!
+//! for result in &results {
+//!     println!("{result}");
+//!     for (rule, time) in &result.rules {
+//!         println!("  {rule}: {time:?}");
+//!     }
+//! }
+//! assert!(bench::regressions(&results, &results, 0.1).is_empty());
+//! ```
+use std::fmt::{self, Display};
+use std::time::{Duration, Instant};
+
+use crate::MarkdownIt;
+
+#[derive(Debug, Clone)]
+/// Document used for measurements.
+pub struct Corpus {
+    pub name: String,
+    pub source: String,
+}
+
+impl Corpus {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self { name: name.into(), source: source.into() }
+    }
+}
+
+/// Built-in documents: CommonMark spec, large readme and pathological nesting.
+pub fn corpora() -> Vec<Corpus> {
+    vec![
+        Corpus::new("commonmark-spec", include_str!("../tests/fixtures/commonmark/spec.txt")),
+        Corpus::new("readme", include_str!("../README.md").repeat(50)),
+        Corpus::new("nested-blockquotes", "> ".repeat(5000) + "a"),
+        Corpus::new("nested-lists", "- ".repeat(5000) + "a"),
+        Corpus::new("nested-emphasis", format!("{}a{}", "*a **a ".repeat(2000), " a** a*".repeat(2000))),
+        Corpus::new("nested-brackets", format!("{}a{}", "[".repeat(20000), "]".repeat(20000))),
+    ]
+}
+
+#[derive(Debug, Clone)]
+/// Options for [run_with].
+pub struct BenchOptions {
+    /// Number of measured runs for each document, default 10.
+    pub iterations: u32,
+    /// Number of runs before measurements start, default 1.
+    pub warmup: u32,
+    /// Measure rendering to html as well, enabled by default.
+    pub render: bool,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            warmup: 1,
+            render: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Measurements for a single document.
+pub struct BenchResult {
+    /// Name of the document.
+    pub name: String,
+    /// Size of the document in bytes.
+    pub bytes: usize,
+    pub iterations: u32,
+    /// Fastest run, it's the most stable one to compare with a baseline.
+    pub min: Duration,
+    pub mean: Duration,
+    /// Mean time spent in each core rule (including block and inline parsers),
+    /// in order of execution, rendering is listed as `"render"`.
+    pub rules: Vec<(&'static str, Duration)>,
+}
+
+impl BenchResult {
+    /// Throughput of the fastest run in MiB/s.
+    pub fn throughput(&self) -> f64 {
+        self.bytes as f64 / (1024. * 1024.) / self.min.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:?} mean, {:?} min, {:.1} MiB/s",
+            self.name, self.mean, self.min, self.throughput(),
+        )
+    }
+}
+
+/// Measure parser on [built-in documents](corpora) with default options.
+pub fn run(md: &MarkdownIt) -> Vec<BenchResult> {
+    run_with(md, &corpora(), &BenchOptions::default())
+}
+
+/// Measure parser on given documents.
+pub fn run_with(md: &MarkdownIt, corpora: &[Corpus], options: &BenchOptions) -> Vec<BenchResult> {
+    corpora.iter().map(|corpus| measure(md, corpus, options)).collect()
+}
+
+fn measure(md: &MarkdownIt, corpus: &Corpus, options: &BenchOptions) -> BenchResult {
+    for _ in 0..options.warmup {
+        let ast = md.parse(&corpus.source);
+        if options.render { ast.render(); }
+    }
+
+    let iterations = options.iterations.max(1);
+    let mut rules : Vec<(&'static str, Duration)> = Vec::new();
+    let mut min = Duration::MAX;
+    let mut total = Duration::ZERO;
+
+    for _ in 0..iterations {
+        let mut idx = 0;
+        let mut add_time = |name: &'static str, time: Duration| {
+            match rules.get_mut(idx) {
+                Some(rule) => rule.1 += time,
+                None => rules.push((name, time)),
+            }
+            idx += 1;
+        };
+
+        let start = Instant::now();
+        let ast = md.parse_profiled(&corpus.source, |rule, time| add_time(rule.name, time));
+        if options.render {
+            let render_start = Instant::now();
+            ast.render();
+            add_time("render", render_start.elapsed());
+        }
+        let elapsed = start.elapsed();
+
+        min = min.min(elapsed);
+        total += elapsed;
+    }
+
+    for rule in rules.iter_mut() {
+        rule.1 /= iterations;
+    }
+
+    BenchResult {
+        name: corpus.name.clone(),
+        bytes: corpus.source.len(),
+        iterations,
+        min,
+        mean: total / iterations,
+        rules,
+    }
+}
+
+/// Results that are slower than results with the same name in `baseline`
+/// by more than `tolerance` (e.g. `0.2` for 20%), compared by fastest run.
+pub fn regressions<'a>(results: &'a [BenchResult], baseline: &[BenchResult], tolerance: f64) -> Vec<&'a BenchResult> {
+    results.iter().filter(|result| {
+        baseline.iter()
+            .find(|base| base.name == result.name)
+            .is_some_and(|base| result.min.as_secs_f64() > base.min.as_secs_f64() * (1. + tolerance))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BenchOptions, BenchResult, Corpus};
+    use std::time::Duration;
+
+    #[test]
+    fn profile_rules() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let options = BenchOptions { iterations: 3, warmup: 0, render: true };
+        let results = super::run_with(md, &[Corpus::new("small", "# a\n\n*b*")], &options);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].bytes, 8);
+        assert_eq!(results[0].iterations, 3);
+        let rules : Vec<_> = results[0].rules.iter().map(|(name, _)| *name).collect();
+        assert!(rules.iter().any(|name| name.ends_with("BlockParserRule")), "{rules:?}");
+        assert!(rules.iter().any(|name| name.ends_with("InlineParserRule")), "{rules:?}");
+        assert_eq!(rules.last(), Some(&"render"));
+    }
+
+    #[test]
+    fn regressions() {
+        let result = |name: &str, ms| BenchResult {
+            name: name.into(),
+            bytes: 1,
+            iterations: 1,
+            min: Duration::from_millis(ms),
+            mean: Duration::from_millis(ms),
+            rules: Vec::new(),
+        };
+
+        let baseline = [result("a", 100), result("b", 100)];
+        let results = [result("a", 110), result("b", 130), result("c", 1000)];
+        let slower : Vec<_> = super::regressions(&results, &baseline, 0.2).iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(slower, ["b"]);
+    }
+}
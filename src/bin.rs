@@ -1,5 +1,8 @@
 use markdown_it::parser::inline::{Text, TextSpecial};
+use markdown_it::plugins::PluginId;
+use markdown_it::{MarkdownIt, Node};
 use std::io::{Read, Write};
+use std::time::Duration;
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
@@ -11,6 +14,9 @@ fn main() {
     let mut typographer = false;
     let mut sourcepos = false;
     let mut show_tree = false;
+    let mut plugins : Vec<String> = Vec::new();
+    let mut format = "html".to_owned();
+    let mut watch = false;
 
     {
         let mut cli = argparse::ArgumentParser::new();
@@ -21,6 +27,18 @@ fn main() {
             .refer(&mut output)
             .add_option(&["-o", "--output"], argparse::Store, "File to write");
 
+        cli
+            .refer(&mut format)
+            .add_option(&["-f", "--format"], argparse::Store, "Output format: html, json, text or markdown");
+
+        cli
+            .refer(&mut plugins)
+            .add_option(&["-p", "--plugin"], argparse::Collect, "Plugin to use instead of the default set (can be repeated)");
+
+        cli
+            .refer(&mut watch)
+            .add_option(&["-w", "--watch"], argparse::StoreTrue, "Render file again every time it changes");
+
         cli
             .refer(&mut sourcepos)
             .add_option(&["--sourcepos"], argparse::StoreTrue, "Include source mappings in HTML attributes");
@@ -49,25 +67,29 @@ fn main() {
         cli.parse_args_or_exit();
     }
 
-    let vec = if input == "-" {
-        let mut vec = Vec::new();
-        std::io::stdin().read_to_end(&mut vec).unwrap();
-        vec
+    if !["html", "json", "text", "markdown"].contains(&format.as_str()) {
+        exit_with_error(&format!("unknown output format: {format}"));
+    }
+
+    #[cfg(not(feature = "serde"))]
+    if format == "json" {
+        exit_with_error("json output requires `serde` feature");
+    }
+
+    if watch && input == "-" {
+        exit_with_error("--watch requires an input file");
+    }
+
+    let md = &mut if plugins.is_empty() {
+        default_parser(no_html)
     } else {
-        std::fs::read(input).unwrap()
+        let ids = plugins.iter()
+            .map(|name| name.parse::<PluginId>())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+        MarkdownIt::with_plugins(&ids)
     };
 
-    let source = String::from_utf8_lossy(&vec);
-    let md = &mut markdown_it::MarkdownIt::new();
-    markdown_it::plugins::cmark::add(md);
-    #[cfg(feature = "syntect")]
-    markdown_it::plugins::extra::syntect::add(md);
-    markdown_it::plugins::extra::tables::add(md);
-    markdown_it::plugins::extra::strikethrough::add(md);
-    markdown_it::plugins::extra::beautify_links::add(md);
-    if !no_html {
-        markdown_it::plugins::html::add(md);
-    }
     if sourcepos {
         markdown_it::plugins::sourcepos::add(md);
     }
@@ -80,28 +102,94 @@ fn main() {
         markdown_it::plugins::extra::typographer::add(md);
     }
 
-    let ast = md.parse(&source);
-
-    if show_tree {
-        ast.walk(|node, depth| {
-            print!("{}", "    ".repeat(depth as usize));
-            let name = &node.name()[node.name().rfind("::").map(|x| x+2).unwrap_or_default()..];
-            if let Some(data) = node.cast::<Text>() {
-                println!("{name}: {:?}", data.content);
-            } else if let Some(data) = node.cast::<TextSpecial>() {
-                println!("{name}: {:?}", data.content);
-            } else {
-                println!("{name}");
+    let run = |src: &str| {
+        let ast = md.parse(src);
+
+        if show_tree {
+            print_tree(&ast);
+            return;
+        }
+
+        let result = match format.as_str() {
+            #[cfg(feature = "serde")]
+            "json" => serde_json::to_string(&ast).unwrap() + "\n",
+            "text" => ast.render_text(),
+            "markdown" => ast.render_markdown(),
+            _ => ast.render(),
+        };
+
+        if output == "-" {
+            std::io::stdout().write_all(result.as_bytes()).unwrap();
+        } else {
+            std::fs::write(&output, &result).unwrap();
+        }
+    };
+
+    run(&read_input(&input).unwrap_or_else(|err| exit_with_error(&format!("{input}: {err}"))));
+
+    if watch {
+        // polling is good enough for a single file and doesn't need extra dependencies
+        let modified = || std::fs::metadata(&input).and_then(|meta| meta.modified()).ok();
+        let mut last_modified = modified();
+        loop {
+            std::thread::sleep(Duration::from_millis(250));
+            let current = modified();
+            if current.is_some() && current != last_modified {
+                last_modified = current;
+                // file can be removed or unreadable while it's being saved, keep watching
+                match read_input(&input) {
+                    Ok(src) => {
+                        run(&src);
+                        eprintln!("markdown-it: {input} changed, rendered again");
+                    }
+                    Err(err) => eprintln!("markdown-it: {input}: {err}"),
+                }
             }
-        });
-        return;
+        }
     }
+}
 
-    let result = ast.render();
+fn default_parser(no_html: bool) -> MarkdownIt {
+    let mut md = MarkdownIt::new();
+    markdown_it::plugins::cmark::add(&mut md);
+    #[cfg(feature = "syntect")]
+    markdown_it::plugins::extra::syntect::add(&mut md);
+    markdown_it::plugins::extra::tables::add(&mut md);
+    markdown_it::plugins::extra::strikethrough::add(&mut md);
+    markdown_it::plugins::extra::beautify_links::add(&mut md);
+    if !no_html {
+        markdown_it::plugins::html::add(&mut md);
+    }
+    md
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("markdown-it: {message}");
+    std::process::exit(2);
+}
 
-    if output == "-" {
-        std::io::stdout().write_all(result.as_bytes()).unwrap();
+fn read_input(input: &str) -> std::io::Result<String> {
+    let vec = if input == "-" {
+        let mut vec = Vec::new();
+        std::io::stdin().read_to_end(&mut vec)?;
+        vec
     } else {
-        std::fs::write(output, &result).unwrap();
-    }
+        std::fs::read(input)?
+    };
+
+    Ok(String::from_utf8_lossy(&vec).into_owned())
+}
+
+fn print_tree(ast: &Node) {
+    ast.walk(|node, depth| {
+        print!("{}", "    ".repeat(depth as usize));
+        let name = &node.name()[node.name().rfind("::").map(|x| x+2).unwrap_or_default()..];
+        if let Some(data) = node.cast::<Text>() {
+            println!("{name}: {:?}", data.content);
+        } else if let Some(data) = node.cast::<TextSpecial>() {
+            println!("{name}: {:?}", data.content);
+        } else {
+            println!("{name}");
+        }
+    });
 }
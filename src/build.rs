@@ -0,0 +1,211 @@
+//! Parse and render many documents in parallel.
+//!
+//! Static site generators and documentation tools usually process a whole directory
+//! of files with the same parser configuration. [Build] does this on multiple threads,
+//! runs user-defined checks on each document, and passes results to a callback
+//! as soon as each file is done (so results come in order of completion, use
+//! [BuildResult::index] to restore input order if needed).
+//!
+//! ```rust
+//! # #[cfg(feature = "image_alt")] {
+//! use markdown_it::build::Build;
+//! use markdown_it::parser::diagnostics::Diagnostic;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let files = vec![
+//!     ("index.md", "# Home\n\n![](logo.png)".to_owned()),
+//!     ("about.md", "*About*".to_owned()),
+//! ];
+//!
+//! let mut results = Vec::new();
+//! Build::new(md)
+//!     .check(|ast| {
+//!         markdown_it::plugins::extra::image_alt::missing_alt_text(ast).into_iter()
+//!             .map(|missing| Diagnostic::warning("image-alt", missing.to_string(), missing.srcmap))
+//!             .collect()
+//!     })
+//!     .run(files, |result| results.push(result));
+//!
+//! results.sort_by_key(|result| result.index);
+//! assert_eq!(results[0].path, "index.md");
+//! assert_eq!(results[0].diagnostics[0].message, "image `logo.png` at 8..21 has no alt text");
+//! assert_eq!(results[1].html, "<p><em>About</em></p>\n");
+//! assert!(results[1].diagnostics.is_empty());
+//! # }
+//! ```
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::sync::mpsc;
+use std::sync::Mutex;
+
+use crate::parser::diagnostics::{Diagnostic, Diagnostics};
+use crate::{MarkdownIt, Node, RenderOptions};
+
+#[derive(Debug, Clone)]
+/// Output for a single document.
+pub struct BuildResult<P> {
+    /// Position of the document in the input.
+    pub index: usize,
+    pub path: P,
+    /// Rendered html.
+    pub html: String,
+    /// Diagnostics reported while parsing (see [diagnostics](crate::parser::diagnostics)),
+    /// followed by results of all check functions.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Function that checks a document and returns problems found in it.
+pub type CheckFn<'a> = Box<dyn Fn(&Node) -> Vec<Diagnostic> + Send + Sync + 'a>;
+
+/// Parallel build driver, see [module documentation](self).
+pub struct Build<'a> {
+    md: &'a MarkdownIt,
+    threads: usize,
+    render_options: RenderOptions,
+    checks: Vec<CheckFn<'a>>,
+}
+
+impl fmt::Debug for Build<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Build")
+            .field("threads", &self.threads)
+            .field("render_options", &self.render_options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Build<'a> {
+    pub fn new(md: &'a MarkdownIt) -> Self {
+        Self {
+            md,
+            threads: std::thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            render_options: RenderOptions::default(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Number of worker threads, defaults to available parallelism.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Options used to render html.
+    pub fn render_options(mut self, options: RenderOptions) -> Self {
+        self.render_options = options;
+        self
+    }
+
+    /// Add a function that checks each parsed document.
+    pub fn check(mut self, check: impl Fn(&Node) -> Vec<Diagnostic> + Send + Sync + 'a) -> Self {
+        self.checks.push(Box::new(check));
+        self
+    }
+
+    fn process<P>(&self, index: usize, path: P, source: &str) -> BuildResult<P> {
+        let ast = self.md.parse(source);
+        let diagnostics = Diagnostics::of(&ast).iter().cloned()
+            .chain(self.checks.iter().flat_map(|check| check(&ast)))
+            .collect();
+        let html = ast.render_with_options(&self.render_options);
+        BuildResult { index, path, html, diagnostics }
+    }
+
+    /// Process all files, calling `on_result` for each one as soon as it's done.
+    ///
+    /// Input is read lazily on the calling thread, so it can come from
+    /// a directory walker without reading all files into memory first.
+    pub fn run<P: Send>(
+        &self,
+        files: impl IntoIterator<Item = (P, String)>,
+        mut on_result: impl FnMut(BuildResult<P>),
+    ) {
+        let (job_tx, job_rx) = mpsc::sync_channel::<(usize, P, String)>(self.threads * 2);
+        let (result_tx, result_rx) = mpsc::channel();
+        let job_rx = Mutex::new(job_rx);
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads {
+                let result_tx = result_tx.clone();
+                let job_rx = &job_rx;
+                scope.spawn(move || loop {
+                    let job = job_rx.lock().unwrap().recv();
+                    let Ok((index, path, source)) = job else { break; };
+                    if result_tx.send(self.process(index, path, &source)).is_err() { break; }
+                });
+            }
+            drop(result_tx);
+
+            for (index, (path, source)) in files.into_iter().enumerate() {
+                if job_tx.send((index, path, source)).is_err() { break; }
+                while let Ok(result) = result_rx.try_recv() {
+                    on_result(result);
+                }
+            }
+            drop(job_tx);
+
+            for result in result_rx {
+                on_result(result);
+            }
+        });
+    }
+
+    /// Process all files and return results in input order.
+    pub fn run_all<P: Send>(&self, files: impl IntoIterator<Item = (P, String)>) -> Vec<BuildResult<P>> {
+        let mut results = Vec::new();
+        self.run(files, |result| results.push(result));
+        results.sort_by_key(|result| result.index);
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Build;
+    use crate::parser::diagnostics::Diagnostic;
+
+    #[test]
+    fn many_files() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let files = (0..100).map(|n| (n, format!("*{n}*")));
+        let results = Build::new(md)
+            .threads(3)
+            .check(|ast| {
+                let text = ast.collect_text();
+                if text.ends_with('7') {
+                    vec![Diagnostic::warning("seven", format!("{text} ends with 7"), None)]
+                } else {
+                    vec![]
+                }
+            })
+            .run_all(files);
+
+        assert_eq!(results.len(), 100);
+        for (n, result) in results.iter().enumerate() {
+            assert_eq!(result.index, n);
+            assert_eq!(result.path, n);
+            assert_eq!(result.html, format!("<p><em>{n}</em></p>\n"));
+        }
+        let diagnostics : Vec<_> = results.iter().flat_map(|r| &r.diagnostics).map(|d| d.to_string()).collect();
+        assert_eq!(diagnostics.len(), 10);
+        assert_eq!(diagnostics[0], "warning[seven]: 7 ends with 7");
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn parse_diagnostics() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::lint::add(md);
+
+        let results = Build::new(md)
+            .check(|_| vec![Diagnostic::warning("checked", "checked", None)])
+            .run_all([("a.md", "# a\n\n### b".to_owned())]);
+        let diagnostics : Vec<_> = results[0].diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(diagnostics, ["warning[heading-level]: heading level 3 after level 1 (5..10)", "warning[checked]: checked"]);
+    }
+}
@@ -70,21 +70,75 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
 
     /// Remove all rules identified by `mark`.
     pub fn remove(&mut self, mark: M) {
+        self.compiled = OnceCell::new();
         self.deps.retain(|dep| !dep.marks.contains(&mark));
     }
 
     /// Check if there are any rules identified by `mark`.
-    pub fn contains(&mut self, mark: M) -> bool {
+    pub fn contains(&self, mark: M) -> bool {
         self.deps.iter().any(|dep| dep.marks.contains(&mark))
     }
 
+    /// Enable or disable all rules identified by `mark`, returns `false` if there are none.
+    ///
+    /// Disabled rules are skipped by [iter](Ruler::iter), but they are still taken into
+    /// account when rules are ordered (so rules that require them keep working).
+    /// ```
+    /// use markdown_it::common::ruler::Ruler;
+    /// let mut chain = Ruler::<&str, &str>::new();
+    ///
+    /// chain.add("a", "A");
+    /// chain.add("b", "B").require("a").after("a");
+    /// chain.set_enabled("a", false);
+    ///
+    /// assert_eq!(chain.iter().copied().collect::<String>(), "B");
+    /// assert_eq!(chain.marks(), [("a", false), ("b", true)]);
+    /// ```
+    pub fn set_enabled(&mut self, mark: M, enabled: bool) -> bool {
+        self.compiled = OnceCell::new();
+        let mut found = false;
+        for dep in self.deps.iter_mut().filter(|dep| dep.marks.contains(&mark)) {
+            dep.enabled = enabled;
+            found = true;
+        }
+        found
+    }
+
+    /// Main identifiers of all rules in the order they run, and whether they are enabled.
+    pub fn marks(&self) -> Vec<(M, bool)> {
+        self.compiled.get_or_init(|| self.compile()).0.iter().map(|idx| {
+            let dep = &self.deps[*idx];
+            (*dep.marks.first().unwrap(), dep.enabled)
+        }).collect()
+    }
+
     /// Ordered iteration through rules.
     #[inline]
     pub fn iter(&self) -> Iter<'_, T> {
         self.compiled.get_or_init(|| self.compile()).1.iter()
     }
 
+    /// Check that all rules can be ordered, i.e. there are no cyclic
+    /// dependencies and all required rules exist.
+    ///
+    /// Same checks are done on first [iter](Ruler::iter) call, which panics instead.
+    /// ```
+    /// use markdown_it::common::ruler::{Ruler, RulerError};
+    /// let mut chain = Ruler::<&str, ()>::new();
+    ///
+    /// chain.add("a", ()).after("b");
+    /// chain.add("b", ()).after("a");
+    /// assert_eq!(chain.validate(), Err(RulerError::CyclicDependency(vec!["a", "b", "a"])));
+    /// ```
+    pub fn validate(&self) -> Result<(), RulerError<M>> {
+        self.compiled.get_or_try_init(|| self.try_compile()).map(|_| ())
+    }
+
     fn compile(&self) -> (Vec<usize>, Vec<T>) {
+        self.try_compile().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn try_compile(&self) -> Result<(Vec<usize>, Vec<T>), RulerError<M>> {
         // ID -> [RuleItem index]
         let mut idhash = HashMap::<M, Vec<usize>>::new();
 
@@ -140,10 +194,9 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
                         }
                     }
                     RuleItemConstraint::Require(v) => {
-                        assert!(
-                            idhash.contains_key(v),
-                            "missing dependency: {:?} requires {:?}", dep.marks.first().unwrap(), v
-                        );
+                        if !idhash.contains_key(v) {
+                            return Err(RulerError::MissingDependency(*dep.marks.first().unwrap(), *v));
+                        }
                     }
                 }
             }
@@ -158,7 +211,9 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
                 let dlist = deps_graph.get(idx).unwrap();
                 if dlist.is_empty() {
                     let dep = self.deps.get(idx).unwrap();
-                    result.push(dep.value.clone());
+                    if dep.enabled {
+                        result.push(dep.value.clone());
+                    }
                     result_idx.push(idx);
                     *inserted = true;
                     deps_remaining -= 1;
@@ -169,44 +224,40 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
                 }
             }
 
-            #[cfg(debug_assertions)] {
-                // check cycles in dependency graph;
-                // this is very suboptimal, but only used to generate a nice panic message.
-                // in release mode we'll just simply panic
-                for idx in deps_order.iter().copied() {
-                    let mut seen = HashMap::new();
-                    let mut vec = vec![idx];
-                    while let Some(didx) = vec.pop() {
-                        let dlist = deps_graph.get(didx).unwrap();
-                        for x in dlist.iter() {
-                            if seen.contains_key(x) { continue; }
-                            vec.push(*x);
-                            seen.insert(*x, didx);
-                            if *x == idx {
-                                let mut backtrack = vec![];
-                                let mut curr = idx;
-                                while !backtrack.contains(&curr) {
-                                    backtrack.push(curr);
-                                    curr = *seen.get(&curr).unwrap();
-                                }
+            // find cycle in dependency graph to generate a nice error message;
+            // this is very suboptimal, but only used when ordering fails
+            for idx in deps_order.iter().copied() {
+                let mut seen = HashMap::new();
+                let mut vec = vec![idx];
+                while let Some(didx) = vec.pop() {
+                    let dlist = deps_graph.get(didx).unwrap();
+                    for x in dlist.iter() {
+                        if seen.contains_key(x) { continue; }
+                        vec.push(*x);
+                        seen.insert(*x, didx);
+                        if *x == idx {
+                            let mut backtrack = vec![];
+                            let mut curr = idx;
+                            while !backtrack.contains(&curr) {
                                 backtrack.push(curr);
-                                let path = backtrack.iter()
-                                    .rev()
-                                    .map(|x| format!("{:?}", self.deps.get(*x).unwrap().marks.first().unwrap()))
-                                    .collect::<Vec<String>>()
-                                    .join(" < ");
-                                panic!("cyclic dependency: {}", path);
+                                curr = *seen.get(&curr).unwrap();
                             }
+                            backtrack.push(curr);
+                            let path = backtrack.iter()
+                                .rev()
+                                .map(|x| *self.deps.get(*x).unwrap().marks.first().unwrap())
+                                .collect();
+                            return Err(RulerError::CyclicDependency(path));
                         }
                     }
                 }
             }
 
-            // if you see this in debug mode, report it as a bug
-            panic!("cyclic dependency: (use debug mode for more details)");
+            // if you see this, report it as a bug
+            unreachable!("cyclic dependency not found");
         }
 
-        (result_idx, result)
+        Ok((result_idx, result))
     }
 }
 
@@ -244,6 +295,7 @@ pub struct RuleItem<M, T> {
     value: T,
     prio: RuleItemPriority,
     cons: Vec<RuleItemConstraint<M>>,
+    enabled: bool,
 }
 
 impl<M, T> RuleItem<M, T> {
@@ -253,6 +305,7 @@ impl<M, T> RuleItem<M, T> {
             value,
             prio: RuleItemPriority::Normal,
             cons: vec![],
+            enabled: true,
         }
     }
 }
@@ -350,6 +403,35 @@ enum RuleItemPriority {
     AfterAll,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Error returned by [Ruler::validate](Ruler::validate) when rules can't be ordered.
+pub enum RulerError<M> {
+    /// Rule identified by the first mark requires a rule that doesn't exist.
+    MissingDependency(M, M),
+    /// Rules must be inserted after each other in a cycle (first and last marks are the same).
+    CyclicDependency(Vec<M>),
+}
+
+impl<M: Debug> std::fmt::Display for RulerError<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingDependency(rule, required) => {
+                write!(f, "missing dependency: {:?} requires {:?}", rule, required)
+            }
+            Self::CyclicDependency(path) => {
+                f.write_str("cyclic dependency: ")?;
+                for (idx, mark) in path.iter().enumerate() {
+                    if idx > 0 { f.write_str(" < ")?; }
+                    write!(f, "{:?}", mark)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<M: Debug> std::error::Error for RulerError<M> {}
+
 
 #[cfg(test)]
 mod tests {
@@ -357,7 +439,6 @@ mod tests {
 
     #[test]
     #[should_panic(expected=r#"cyclic dependency: "A" < "B" < "C" < "D" < "E" < "F" < "A""#)]
-    #[cfg(debug_assertions)]
     fn cyclic_dependency_debug() {
         let mut r = Ruler::new();
         r.add("%", ()).after("D");
@@ -390,4 +471,32 @@ mod tests {
         r.add("C", ()).require("Z");
         r.compile();
     }
+
+    #[test]
+    fn validate() {
+        use super::RulerError;
+
+        let mut r = Ruler::new();
+        r.add("A", ());
+        r.add("B", ()).require("A").before("A");
+        assert_eq!(r.validate(), Ok(()));
+
+        r.add("C", ()).require("Z");
+        assert_eq!(r.validate(), Err(RulerError::MissingDependency("C", "Z")));
+        assert_eq!(r.validate().unwrap_err().to_string(), r#"missing dependency: "C" requires "Z""#);
+
+        r.remove("C");
+        r.add("C", ()).after("A").before("B");
+        assert_eq!(r.validate(), Err(RulerError::CyclicDependency(vec!["A", "B", "C", "A"])));
+    }
+
+    #[test]
+    fn remove_after_compile() {
+        let mut r = Ruler::new();
+        r.add("A", 1);
+        r.add("B", 2);
+        assert_eq!(r.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        r.remove("A");
+        assert_eq!(r.iter().copied().collect::<Vec<_>>(), [2]);
+    }
 }
@@ -93,6 +93,14 @@ impl SourcePos {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for SourcePos {
+    /// Serialize as a pair of byte offsets, `[start, end]`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.byte_offset.serialize(serializer)
+    }
+}
+
 impl std::fmt::Debug for SourcePos {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.byte_offset.fmt(f)
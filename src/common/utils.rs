@@ -108,6 +108,30 @@ pub fn unescape_all(str: &str) -> Cow<'_, str> {
     })
 }
 
+/// Remove html tags and decode entities, i.e. return text that browser would display.
+/// ```
+/// # use markdown_it::common::utils::html_to_text;
+/// assert_eq!(html_to_text("<b>a</b> &amp; <i>b</i>"), "a & b");
+/// ```
+pub fn html_to_text(str: &str) -> Cow<'_, str> {
+    static HTML_TO_TEXT_RE : Lazy<Regex> = Lazy::new(||
+        Regex::new(&format!("<[^>]*>|{ENTITY_RE}")).unwrap()
+    );
+
+    if !str.contains('<') && !str.contains('&') { return Cow::Borrowed(str); }
+
+    HTML_TO_TEXT_RE.replace_all(str, |captures: &regex::Captures| {
+        let s = captures.get(0).unwrap().as_str();
+        if s.starts_with('<') {
+            String::new()
+        } else if let Some(replacement) = replace_entity_pattern(s) {
+            replacement
+        } else {
+            s.to_owned()
+        }
+    })
+}
+
 /// Escape `" < > &` with corresponding HTML entities;
 /// ```
 /// # use markdown_it::common::utils::escape_html;
@@ -117,6 +141,20 @@ pub fn escape_html(str: &str) -> Cow<'_, str> {
     html_escape::encode_double_quoted_attribute(str)
 }
 
+/// Check that attribute name is valid in html (can't break out of a tag).
+/// ```
+/// # use markdown_it::common::utils::is_valid_attr_name;
+/// assert!(is_valid_attr_name("data-x"));
+/// assert!(!is_valid_attr_name("on click"));
+/// assert!(!is_valid_attr_name("a=\"b\""));
+/// ```
+pub fn is_valid_attr_name(name: &str) -> bool {
+    // html spec also excludes noncharacters, which are harmless
+    !name.is_empty() && !name.contains(|ch: char| {
+        ch.is_whitespace() || ch.is_control() || matches!(ch, '"' | '\'' | '>' | '<' | '/' | '=')
+    })
+}
+
 /// Unicode case folding + space normalization, used for for reference labels.
 ///
 /// So that strings equal according to commonmark standard are converted to
@@ -195,7 +233,16 @@ pub fn rfind_and_count(source: &str, char: char) -> usize {
 /// # use markdown_it::common::utils::find_indent_of;
 /// assert_eq!(find_indent_of("\tfoo", 0), (4, 1));
 /// ```
-pub fn find_indent_of(line: &str, mut pos: usize) -> (usize, usize) {
+pub fn find_indent_of(line: &str, pos: usize) -> (usize, usize) {
+    find_indent_with_tab_width(line, pos, 4)
+}
+
+/// Same as [find_indent_of], with tabstop = `tab_width`.
+/// ```
+/// # use markdown_it::common::utils::find_indent_with_tab_width;
+/// assert_eq!(find_indent_with_tab_width(" \tfoo", 0, 8), (8, 2));
+/// ```
+pub fn find_indent_with_tab_width(line: &str, mut pos: usize, tab_width: usize) -> (usize, usize) {
     let mut chars = line[pos..].chars();
     let mut indent = 0;
 
@@ -203,7 +250,7 @@ pub fn find_indent_of(line: &str, mut pos: usize) -> (usize, usize) {
         match chars.next() {
             Some('\t') => {
                 let bs_count = rfind_and_count(&line[..pos], '\t');
-                indent += 4 - bs_count % 4;
+                indent += tab_width - bs_count % tab_width;
                 pos += 1;
             }
             Some(' ') => {
@@ -250,23 +297,32 @@ pub fn cut_right_whitespace_with_tabstops(source: &str, indent: i32) -> Cow<'_,
 /// # use markdown_it::common::utils::calc_right_whitespace_with_tabstops;
 /// assert_eq!(calc_right_whitespace_with_tabstops("\t\t", 6), (2, 1));
 /// ```
-pub fn calc_right_whitespace_with_tabstops(source: &str, mut indent: i32) -> (usize, usize) {
+pub fn calc_right_whitespace_with_tabstops(source: &str, indent: i32) -> (usize, usize) {
+    calc_right_whitespace_with_tab_width(source, indent, 4)
+}
+
+/// Same as [calc_right_whitespace_with_tabstops], with tabstop = `tab_width`.
+/// ```
+/// # use markdown_it::common::utils::calc_right_whitespace_with_tab_width;
+/// assert_eq!(calc_right_whitespace_with_tab_width("\t\t", 10, 8), (2, 1));
+/// ```
+pub fn calc_right_whitespace_with_tab_width(source: &str, mut indent: i32, tab_width: usize) -> (usize, usize) {
     let mut start = source.len();
     let mut chars = source.char_indices().rev();
 
     while indent > 0 {
         match chars.next() {
             Some((pos, '\t')) => {
-                // previous tab is guaranteed to finish at 0 modulo 4,
+                // previous tab is guaranteed to finish at 0 modulo tab width,
                 // so we can finish counting there
                 let indent_from_start = rfind_and_count(&source[..pos], '\t');
-                let tab_width = 4 - indent_from_start as i32 % 4;
+                let width = tab_width as i32 - indent_from_start as i32 % tab_width as i32;
 
-                if indent < tab_width {
+                if indent < width {
                     return ( indent as usize, start );
                 }
 
-                indent -= tab_width;
+                indent -= width;
                 start = pos;
             }
             Some((pos, _)) => {
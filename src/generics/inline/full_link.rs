@@ -15,10 +15,12 @@
 //!
 use std::collections::HashMap;
 
-use crate::common::utils::unescape_all;
+use crate::common::sourcemap::SourcePos;
+use crate::common::utils::{normalize_reference, unescape_all};
+use crate::parser::definitions::Definitions;
 use crate::parser::extset::{InlineRootExt, MarkdownItExt};
 use crate::parser::inline::{InlineRule, InlineState};
-use crate::plugins::cmark::block::reference::ReferenceMap;
+use crate::plugins::cmark::block::reference::{self, definition_srcmap, Definition, DefinitionSrcmap};
 use crate::{MarkdownIt, Node};
 
 #[derive(Debug)]
@@ -115,7 +117,20 @@ fn rule_run(
     f: fn (Option<String>, Option<String>) -> Node
 ) -> Option<(Node, usize)> {
     let start = state.pos;
-    let result = parse_link(state, state.pos + offset, enable_nested)?;
+    let Some(result) = parse_link(state, state.pos + offset, enable_nested) else {
+        if let Some((label, end)) = missing_reference(state, state.pos + offset) {
+            let srcmap = state.get_map(start, end);
+            let definitions = state.root_ext.get_or_insert_default::<Definitions<Definition>>();
+            definitions.reference(&normalize_reference(&label), &label, srcmap);
+        }
+        return None;
+    };
+
+    if let Some(label) = &result.reference {
+        let srcmap = state.get_map(start, result.end);
+        let definitions = state.root_ext.get_or_insert_default::<Definitions<Definition>>();
+        definitions.reference(&normalize_reference(label), label, srcmap);
+    }
 
     //
     // We found the end of the link, and know for a fact it's a valid link;
@@ -125,14 +140,19 @@ fn rule_run(
     let max = state.pos_max;
 
     state.link_level += 1;
+    state.level += 1;
     state.pos = result.label_start;
     state.pos_max = result.label_end;
     state.md.inline.tokenize(state);
     state.pos = start;
     state.pos_max = max;
+    state.level -= 1;
     state.link_level -= 1;
 
-    let node = std::mem::replace(&mut state.node, old_node);
+    let mut node = std::mem::replace(&mut state.node, old_node);
+    if let Some(srcmap) = result.definition {
+        node.ext.insert(DefinitionSrcmap(srcmap));
+    }
     Some((node, result.end - state.pos))
 }
 
@@ -333,6 +353,9 @@ struct ParseLinkResult {
     pub href: Option<String>,
     pub title: Option<String>,
     pub end: usize,
+    pub definition: Option<SourcePos>,
+    /// label of the document's reference definition used by the link
+    pub reference: Option<String>,
 }
 
 // Parses [link](<to> "stuff")
@@ -395,6 +418,8 @@ fn parse_link(state: &mut InlineState, pos: usize, enable_nested: bool) -> Optio
                 href,
                 title,
                 end: pos + 1,
+                definition: None,
+                reference: None,
             })
         }
     }
@@ -418,8 +443,6 @@ fn parse_link(state: &mut InlineState, pos: usize, enable_nested: bool) -> Optio
         _ => pos = label_end + 1,
     }
 
-    let references = state.root_ext.get::<ReferenceMap>()?;
-
     // covers label === '' and label === undefined
     // (collapsed reference link and shortcut reference link respectively)
     let label = if matches!(maybe_label, None | Some("")) {
@@ -428,7 +451,8 @@ fn parse_link(state: &mut InlineState, pos: usize, enable_nested: bool) -> Optio
         maybe_label.unwrap()
     };
 
-    let (destination, title) = references.get(label)?;
+    let (destination, title) = reference::lookup(state.root_ext, label)?;
+    let definition = definition_srcmap(state.root_ext, label);
 
     Some(ParseLinkResult {
         label_start,
@@ -436,5 +460,26 @@ fn parse_link(state: &mut InlineState, pos: usize, enable_nested: bool) -> Optio
         href: Some(destination.to_owned()),
         title: title.map(|s| s.to_owned()),
         end: pos,
+        definition,
+        reference: definition.map(|_| label.to_owned()),
     })
 }
+
+// Find label of a full or collapsed reference (`[text][label]`, `[label][]`)
+// that isn't defined, and the end of it
+//
+fn missing_reference(state: &mut InlineState, pos: usize) -> Option<(String, usize)> {
+    let label_end = parse_link_label(state, pos, false)?;
+    let ref_start = label_end + 1;
+    if !state.src[ref_start..state.pos_max].starts_with('[') { return None; }
+    let ref_end = parse_link_label(state, ref_start, false)?;
+
+    let label = match &state.src[ref_start + 1..ref_end] {
+        "" => &state.src[pos + 1..label_end],
+        label => label,
+    };
+    if normalize_reference(label).is_empty() { return None; }
+    if reference::lookup(state.root_ext, label).is_some() { return None; }
+
+    Some((label.to_owned(), ref_end + 1))
+}
@@ -18,12 +18,20 @@
 // just a style choice that clippy has no business complaining about
 #![allow(clippy::uninlined_format_args)]
 
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod build;
 pub mod common;
 pub mod examples;
 pub mod generics;
 pub mod parser;
+pub mod plugin_api;
 pub mod plugins;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use parser::main::MarkdownIt;
 pub use parser::node::{Node, NodeValue};
-pub use parser::renderer::Renderer;
+pub use parser::renderer::{merge_attrs, AttrValue, EntityMode, EventRenderer, IdConflict, RenderEvent, RenderOptions, Renderer};
@@ -1,4 +1,7 @@
+use crate::common::sourcemap::SourcePos;
 use crate::parser::core::{CoreRule, Root};
+use crate::parser::diagnostics;
+use crate::parser::inline::{InlineRoot, Text};
 use crate::{MarkdownIt, Node};
 
 pub fn add(md: &mut MarkdownIt) {
@@ -6,18 +9,62 @@ pub fn add(md: &mut MarkdownIt) {
         .before_all();
 }
 
+// end of the last line that fits into `max_input_size`
+fn input_limit(src: &str, max_input_size: usize) -> usize {
+    if src.len() <= max_input_size { return src.len(); }
+    src.as_bytes()[..max_input_size].iter().rposition(|&ch| ch == b'\n').map_or(0, |pos| pos + 1)
+}
+
 pub struct BlockParserRule;
 impl CoreRule for BlockParserRule {
     fn run(root: &mut Node, md: &MarkdownIt) {
         let mut node = std::mem::take(root);
         let data = node.cast_mut::<Root>().unwrap();
+        let limit = input_limit(&data.content, md.max_input_size);
+
+        if data.fragment {
+            // fragments have no block structure, so all content is inline
+            let mut inline = Node::new(InlineRoot::new(data.content[..limit].to_owned(), vec![(0, 0)]));
+            inline.srcmap = Some(SourcePos::new(0, limit));
+            let tail = text_tail(&data.content, limit);
+            node.children.push(inline);
+            node.children.extend(tail);
+            *root = node;
+            return;
+        }
+
         let source = std::mem::take(&mut data.content);
         let mut ext = std::mem::take(&mut data.ext);
 
-        node = md.block.parse(source.as_str(), node, md, &mut ext);
+        node = md.block.parse(&source[..limit], node, md, &mut ext);
+        if let Some(tail) = text_tail(&source, limit) {
+            diagnostics::report_limit(md, &mut ext, "input is too large, the rest is kept as text", tail.srcmap);
+            node.children.push(tail);
+        }
         let data = node.cast_mut::<Root>().unwrap();
         data.content = source;
         data.ext = ext;
         *root = node;
     }
 }
+
+// input that doesn't fit into `max_input_size` is kept as plain text
+fn text_tail(src: &str, limit: usize) -> Option<Node> {
+    if limit == src.len() { return None; }
+    let mut text = Node::new(Text { content: src[limit..].to_owned() });
+    text.srcmap = Some(SourcePos::new(limit, src.len()));
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::input_limit;
+
+    #[test]
+    fn limit() {
+        assert_eq!(input_limit("abc", 3), 3);
+        assert_eq!(input_limit("a\nb\nc", 4), 4);
+        assert_eq!(input_limit("a\nb\nc", 3), 2);
+        assert_eq!(input_limit("abc", 2), 0);
+    }
+}
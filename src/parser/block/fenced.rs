@@ -0,0 +1,246 @@
+//! Block rules for contents between fences like `::: name` and `:::`,
+//! see [BlockParser::add_fenced](super::BlockParser::add_fenced).
+//!
+//! Contents are tokenized like in blockquotes, and [FencedCloseScanner] stops
+//! it at the closing fence, so fences inside of code blocks, html blocks or
+//! nested containers don't close the block.
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::node::NodeEmpty;
+use crate::Node;
+
+/// Function that creates a node from the info string after an opening fence,
+/// or returns `None` if it doesn't handle that block.
+pub type FencedBlockFn = dyn Fn(&str) -> Option<Node> + Send + Sync;
+
+pub(super) struct FencedRule {
+    pub(super) marker: char,
+    pub(super) min_len: usize,
+    pub(super) make: Box<FencedBlockFn>,
+}
+
+impl FencedRule {
+    // returns info string if line starts with a fence
+    fn parse_fence<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let len = line.chars().take_while(|ch| *ch == self.marker).count();
+        if len < self.min_len { return None; }
+        Some(line[len * self.marker.len_utf8()..].trim())
+    }
+
+    fn is_close(&self, state: &BlockState, line: usize) -> bool {
+        state.line_indent(line) < state.md.max_indent && self.parse_fence(state.get_line(line)) == Some("")
+    }
+}
+
+impl Debug for FencedRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FencedRule")
+            .field("marker", &self.marker)
+            .field("min_len", &self.min_len)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct OpenFence {
+    rule: usize,
+    // level and indent of the contents
+    level: u32,
+    blk_indent: usize,
+    close: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Candidate {
+    // first line that may close the block, searched from `from`
+    Found { from: usize, line: usize },
+    // there are no closing fences between `from` and `to`
+    Missing { from: usize, to: usize },
+}
+
+#[derive(Debug, Default)]
+/// Fenced blocks being parsed, and results of searches for closing fences.
+pub(super) struct FencedState {
+    open: Vec<OpenFence>,
+    // (rule, line_max) -> result of the last search for closing fence
+    candidates: HashMap<(usize, usize), Candidate>,
+}
+
+#[doc(hidden)]
+pub struct FencedBlockScanner;
+
+impl FencedBlockScanner {
+    // check that there is a line that may close the block, it doesn't know about
+    // other blocks, so it may turn out to be a part of them (e.g. of a code block)
+    fn has_close_candidate(state: &mut BlockState, rule_idx: usize) -> bool {
+        let key = (rule_idx, state.line_max);
+        let start = state.line + 1;
+        match state.fenced.candidates.get(&key) {
+            Some(&Candidate::Found { from, line }) if from <= start && start <= line => return true,
+            Some(&Candidate::Missing { from, to }) if from <= start && start <= to => return false,
+            _ => {}
+        }
+
+        let rule = &state.md.block.fenced_rules[rule_idx];
+        let mut line = start;
+        // stop at the end of the parent container (e.g. less indented than list item)
+        while line < state.line_max && state.line_indent(line) >= 0 {
+            if rule.is_close(state, line) {
+                state.fenced.candidates.insert(key, Candidate::Found { from: start, line });
+                return true;
+            }
+            line += 1;
+        }
+
+        state.fenced.candidates.insert(key, Candidate::Missing { from: start, to: line });
+        false
+    }
+
+    // returns rule index and node
+    fn find_block(state: &mut BlockState) -> Option<(usize, Node)> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        // contents would be kept as text, so closing fence can't be found
+        if state.level + 1 >= state.md.max_nesting { return None; }
+        let line = state.get_line(state.line);
+
+        let (rule_idx, node) = state.md.block.fenced_rules.iter().enumerate().find_map(|(idx, rule)| {
+            let info = rule.parse_fence(line).filter(|info| !info.is_empty())?;
+            Some((idx, (rule.make)(info)?))
+        })?;
+
+        Self::has_close_candidate(state, rule_idx).then_some((rule_idx, node))
+    }
+}
+
+impl BlockRule for FencedBlockScanner {
+    fn check(state: &mut BlockState) -> Option<()> {
+        Self::find_block(state).map(|_| ())
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        let (rule, node) = Self::find_block(state)?;
+
+        let start_line = state.line;
+        let old_line_max = state.line_max;
+        let old_node = std::mem::replace(&mut state.node, node);
+
+        state.line = start_line + 1;
+        state.level += 1;
+        state.fenced.open.push(OpenFence { rule, level: state.level, blk_indent: state.blk_indent, close: None });
+        state.md.block.tokenize(state);
+        let close = state.fenced.open.pop().unwrap().close;
+        state.level -= 1;
+
+        // closing fence was a part of another block, so the block
+        // ends together with its parent (like unclosed code blocks)
+        let end_line = close.map_or(state.line, |line| line + 1);
+        state.line = start_line;
+        state.line_max = old_line_max;
+
+        let node = std::mem::replace(&mut state.node, old_node);
+        Some((node, end_line - start_line))
+    }
+}
+
+#[doc(hidden)]
+/// Closing fence of the innermost fenced block, ends tokenizing of its contents.
+pub struct FencedCloseScanner;
+
+impl BlockRule for FencedCloseScanner {
+    fn check(state: &mut BlockState) -> Option<()> {
+        let open = state.fenced.open.last()?;
+        let rule = &state.md.block.fenced_rules[open.rule];
+
+        if open.level == state.level {
+            return rule.is_close(state, state.line).then_some(());
+        }
+
+        // fences inside of nested containers (e.g. `> :::`) don't count, but lines
+        // outside of them do (e.g. after a paragraph in a list item)
+        if state.line_indent(state.line) >= 0 { return None; }
+        let indent = state.line_offsets[state.line].indent_nonspace - open.blk_indent as i32;
+        if indent < 0 || indent >= state.md.max_indent { return None; }
+        (rule.parse_fence(state.get_line(state.line)) == Some("")).then_some(())
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        if state.fenced.open.last()?.level != state.level { return None; }
+        Self::check(state)?;
+        state.fenced.open.last_mut().unwrap().close = Some(state.line);
+        // stops the tokenizer, line_max is restored by FencedBlockScanner
+        state.line_max = state.line;
+        Some((Node::new(NodeEmpty), 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Container(String);
+
+    impl NodeValue for Container {
+        fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+            fmt.cr();
+            fmt.open("div", &[("class".into(), self.0.clone())]);
+            fmt.cr();
+            fmt.contents(&node.children);
+            fmt.cr();
+            fmt.close("div");
+            fmt.cr();
+        }
+    }
+
+    fn run(src: &str) -> String {
+        let md = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        md.block.add_fenced(":::", |info| (info != "skip").then(|| Container(info.to_owned())));
+        md.block.add_fenced("!!!", |info| Some(Container(format!("!{info}"))));
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn fenced() {
+        assert_eq!(run("::: a\n*b*\n:::"), "<div class=\"a\">\n<p><em>b</em></p>\n</div>\n");
+        assert_eq!(run(":::: a\n!!! b\nc\n!!!!\n::::::\nd"),
+            "<div class=\"a\">\n<div class=\"!b\">\n<p>c</p>\n</div>\n</div>\n<p>d</p>\n");
+        // interrupts a paragraph
+        assert_eq!(run("a\n::: b\n:::"), "<p>a</p>\n<div class=\"b\">\n</div>\n");
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(run("::: a\n::: b\nc\n:::\n:::"),
+            "<div class=\"a\">\n<div class=\"b\">\n<p>c</p>\n</div>\n</div>\n");
+        // fences of blocks not handled by any rule are text
+        assert_eq!(run("::: a\n::: skip\n:::\n:::"), "<div class=\"a\">\n<p>::: skip</p>\n</div>\n<p>:::</p>\n");
+    }
+
+    #[test]
+    fn other_blocks() {
+        assert_eq!(run("::: a\n```\n:::\n```\n:::"), "<div class=\"a\">\n<pre><code>:::\n</code></pre>\n</div>\n");
+        assert_eq!(run("::: a\n> :::\n:::"), "<div class=\"a\">\n<blockquote>\n<p>:::</p>\n</blockquote>\n</div>\n");
+        assert_eq!(run("::: a\n> b\n:::"), "<div class=\"a\">\n<blockquote>\n<p>b</p>\n</blockquote>\n</div>\n");
+        assert_eq!(run("::: a\n- b\n:::"), "<div class=\"a\">\n<ul>\n<li>b</li>\n</ul>\n</div>\n");
+        assert_eq!(run("- ::: a\n  - b\n  :::\n- c"),
+            "<ul>\n<li>\n<div class=\"a\">\n<ul>\n<li>b</li>\n</ul>\n</div>\n</li>\n<li>c</li>\n</ul>\n");
+        // closing fence is inside of the code block
+        assert_eq!(run("::: a\n```\n:::"), "<div class=\"a\">\n<pre><code>:::\n</code></pre>\n</div>\n");
+        assert_eq!(run("> ::: a\n> ```\n> :::\n\nb"),
+            "<blockquote>\n<div class=\"a\">\n<pre><code>:::\n</code></pre>\n</div>\n</blockquote>\n<p>b</p>\n");
+    }
+
+    #[test]
+    fn not_fenced() {
+        assert_eq!(run("::: a\nb"), "<p>::: a\nb</p>\n");
+        assert_eq!(run(":: a\n::"), "<p>:: a\n::</p>\n");
+        assert_eq!(run("::: skip\n:::"), "<p>::: skip\n:::</p>\n");
+        assert_eq!(run("    ::: a\n    :::"), "<pre><code>::: a\n:::\n</code></pre>\n");
+        // closing fence is outside of the list item
+        assert_eq!(run("- ::: a\n  b\n:::"), "<ul>\n<li>::: a\nb\n:::</li>\n</ul>\n");
+        assert_eq!(run("> ::: a\n> b\n\n:::"), "<blockquote>\n<p>::: a\nb</p>\n</blockquote>\n<p>:::</p>\n");
+    }
+}
@@ -5,15 +5,22 @@ pub use state::*;
 mod rule;
 pub use rule::*;
 
+mod fenced;
+pub use fenced::FencedBlockFn;
+#[doc(hidden)]
+pub use fenced::{FencedBlockScanner, FencedCloseScanner};
+use fenced::FencedRule;
+
 #[doc(hidden)]
 pub mod builtin;
 
-use crate::common::ruler::Ruler;
+use crate::common::ruler::{Ruler, RulerError};
 use crate::common::TypeKey;
+use crate::parser::diagnostics;
 use crate::parser::extset::RootExtSet;
-use crate::parser::inline::InlineRoot;
+use crate::parser::inline::{InlineRoot, Text};
 use crate::parser::node::NodeEmpty;
-use crate::{MarkdownIt, Node};
+use crate::{MarkdownIt, Node, NodeValue};
 
 type RuleFns = (
     fn (&mut BlockState) -> Option<()>,
@@ -24,6 +31,7 @@ type RuleFns = (
 /// Block-level tokenizer.
 pub struct BlockParser {
     ruler: Ruler<TypeKey, RuleFns>,
+    fenced_rules: Vec<FencedRule>,
 }
 
 impl BlockParser {
@@ -45,13 +53,29 @@ impl BlockParser {
                 // Nested calls currently used for blockquotes & lists
                 if state.line_indent(state.line) < 0 { break; }
 
-                // If nesting level exceeded - skip tail to the end. That's not ordinary
-                // situation and we should not care about content.
-                if state.level >= state.md.max_nesting {
+                // Parse was cancelled, result will be thrown away anyway,
+                // so skip everything (it also stops all outer containers).
+                if state.is_cancelled() {
                     state.line = state.line_max;
                     break;
                 }
 
+                // If nesting level exceeded - keep lines up to the next empty line as plain
+                // text. That's not ordinary situation and we should not care about structure.
+                if state.level >= state.md.max_nesting {
+                    let start_line = state.line;
+                    state.line += 1;
+                    while state.line < state.line_max && !state.is_empty(state.line) && state.line_indent(state.line) >= 0 {
+                        state.line += 1;
+                    }
+                    let (content, _) = state.get_lines(start_line, state.line, state.blk_indent, false);
+                    let mut node = Node::new(Text { content });
+                    node.srcmap = state.get_map(start_line, state.line - 1);
+                    diagnostics::report_limit(state.md, state.root_ext, "nesting is too deep, content is kept as text", node.srcmap);
+                    state.node.children.push(node);
+                    continue;
+                }
+
                 // Try all possible rules.
                 // On success, rule should:
                 //
@@ -117,11 +141,78 @@ impl BlockParser {
         RuleBuilder::new(item)
     }
 
-    pub fn has_rule<T: BlockRule>(&mut self) -> bool {
+    /// Add a rule for blocks between fences like `::: info` and `:::`, without
+    /// implementing [BlockRule].
+    ///
+    /// `fence` is a character repeated at least as many times as in the opening
+    /// fence (e.g. `:::`), it must be followed by an info string. `make` creates
+    /// a node from that info string, or returns `None` to leave the block to other
+    /// rules. Contents are parsed as blocks into children of the node.
+    ///
+    /// Blocks end at the first closing fence that isn't a part of another block
+    /// in the contents (e.g. a code block or a nested fenced block), they can be put
+    /// inside of lists and blockquotes. Blocks without closing fence are kept as text,
+    /// and if it turns out to be a part of another block, they end with their parent.
+    ///
+    /// ```rust
+    /// use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+    ///
+    /// #[derive(Debug)]
+    /// struct Note(String);
+    ///
+    /// impl NodeValue for Note {
+    ///     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+    ///         fmt.cr();
+    ///         fmt.open("aside", &[("title".into(), self.0.clone())]);
+    ///         fmt.contents(&node.children);
+    ///         fmt.close("aside");
+    ///         fmt.cr();
+    ///     }
+    /// }
+    ///
+    /// let md = &mut MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    /// md.block.add_fenced(":::", |info| info.strip_prefix("note ").map(|title| Note(title.into())));
+    ///
+    /// let html = md.parse("::: note Hi\n*text*\n:::").render();
+    /// assert_eq!(html, "<aside title=\"Hi\">\n<p><em>text</em></p>\n</aside>\n");
+    /// ```
+    pub fn add_fenced<T: NodeValue>(&mut self, fence: &str, make: impl Fn(&str) -> Option<T> + Send + Sync + 'static) {
+        let marker = fence.chars().next().expect("fence can't be empty");
+        assert!(fence.chars().all(|ch| ch == marker), "fence must be a repeated character");
+        assert!(!marker.is_whitespace(), "fence can't be whitespace");
+
+        if !self.has_rule::<FencedBlockScanner>() {
+            self.add_rule::<FencedBlockScanner>();
+            self.add_rule::<FencedCloseScanner>().before_all();
+        }
+
+        self.fenced_rules.push(FencedRule {
+            marker,
+            min_len: fence.chars().count(),
+            make: Box::new(move |info| make(info).map(Node::new)),
+        });
+    }
+
+    /// Check that block rules can be ordered, see [Ruler::validate].
+    pub fn validate_rules(&self) -> Result<(), RulerError<TypeKey>> {
+        self.ruler.validate()
+    }
+
+    pub fn has_rule<T: BlockRule>(&self) -> bool {
         self.ruler.contains(TypeKey::of::<T>())
     }
 
     pub fn remove_rule<T: BlockRule>(&mut self) {
         self.ruler.remove(TypeKey::of::<T>());
     }
+
+    // rules in the order they run, see [MarkdownIt::rules](crate::MarkdownIt::rules)
+    pub(crate) fn rule_marks(&self) -> Vec<(TypeKey, bool)> {
+        self.ruler.marks()
+    }
+
+    pub(crate) fn set_rule_enabled(&mut self, key: TypeKey, enabled: bool) -> bool {
+        self.ruler.set_enabled(key, enabled)
+    }
 }
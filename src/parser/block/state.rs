@@ -1,7 +1,9 @@
 // Parser state class
 //
 use crate::common::sourcemap::SourcePos;
-use crate::common::utils::calc_right_whitespace_with_tabstops;
+use crate::common::utils::calc_right_whitespace_with_tab_width;
+use crate::parser::cancel::{self, CancelToken};
+use crate::parser::block::fenced::FencedState;
 use crate::parser::extset::RootExtSet;
 use crate::{MarkdownIt, Node};
 
@@ -42,6 +44,14 @@ pub struct BlockState<'a, 'b> where 'b: 'a {
     pub list_indent: Option<u32>,
 
     pub level: u32,
+
+    /// Width of tab stops in this document ([MarkdownIt::tab_width], at least 1).
+    #[readonly]
+    pub tab_width: usize,
+
+    cancel: Option<CancelToken>,
+
+    pub(super) fenced: FencedState,
 }
 
 /// Holds start/end/etc. positions for a specific source text line.
@@ -100,6 +110,7 @@ pub struct LineOffset {
 
 impl<'a, 'b> BlockState<'a, 'b> {
     pub fn new(src: &'b str, md: &'a MarkdownIt, root_ext: &'b mut RootExtSet, node: Node) -> Self {
+        let cancel = cancel::token(root_ext).cloned();
         let mut result = Self {
             src,
             md,
@@ -112,12 +123,20 @@ impl<'a, 'b> BlockState<'a, 'b> {
             tight: false,
             list_indent: None,
             level: 0,
+            tab_width: if md.strict { 4 } else { md.tab_width.max(1) },
+            cancel,
+            fenced: FencedState::default(),
         };
 
         result.generate_caches();
         result
     }
 
+    /// True if parsing was cancelled (see [cancel](crate::parser::cancel)).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
     fn generate_caches(&mut self) {
         // Create caches
         // Generate markers.
@@ -132,7 +151,8 @@ impl<'a, 'b> BlockState<'a, 'b> {
             match chars.next() {
                 Some(ch @ (' ' | '\t')) if !indent_found => {
                     indent += 1;
-                    offset += if ch == '\t' { 4 - offset % 4 } else { 1 };
+                    let tab_width = self.tab_width as i32;
+                    offset += if ch == '\t' { tab_width - offset % tab_width } else { 1 };
                     pos += 1;
                 }
                 ch @ (Some('\n' | '\r') | None) => {
@@ -236,9 +256,10 @@ impl<'a, 'b> BlockState<'a, 'b> {
             let last = offsets.line_end;
             let add_last_lf = line + 1 < end || keep_last_lf;
 
-            let (num_spaces, first) = calc_right_whitespace_with_tabstops(
+            let (num_spaces, first) = calc_right_whitespace_with_tab_width(
                 &self.src[offsets.line_start..offsets.first_nonspace],
-                offsets.indent_nonspace - indent as i32
+                offsets.indent_nonspace - indent as i32,
+                self.tab_width,
             );
 
             mapping.push(( result.len(), offsets.line_start+first ));
@@ -0,0 +1,106 @@
+//! Programmatic construction of AST nodes.
+//!
+//! This allows you to generate parts of the document (or the entire document)
+//! without writing markdown source and parsing it:
+//!
+//! ```rust
+//! use markdown_it::parser::builder::NodeBuilder;
+//! use markdown_it::plugins::cmark::block::heading::ATXHeading;
+//! use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let mut ast = md.parse("hello world");
+//! ast.children.insert(0, NodeBuilder::new(ATXHeading { level: 1 })
+//!     .attr("id", "generated")
+//!     .text("Table of contents")
+//!     .build());
+//! ast.children.push(NodeBuilder::new(Paragraph).text("goodbye").build());
+//!
+//! assert_eq!(ast.render(), "<h1 id=\"generated\">Table of contents</h1>\n<p>hello world</p>\n<p>goodbye</p>\n");
+//! ```
+use crate::common::sourcemap::SourcePos;
+use crate::parser::inline::Text;
+use crate::{Node, NodeValue};
+
+#[derive(Debug)]
+#[must_use]
+/// Builder for [Node], use [NodeBuilder::build] to get the result.
+pub struct NodeBuilder {
+    node: Node,
+}
+
+impl NodeBuilder {
+    /// Start building a node with the given value.
+    pub fn new<T: NodeValue>(value: T) -> Self {
+        Self { node: Node::new(value) }
+    }
+
+    /// Add an attribute to be rendered in resulting html.
+    pub fn attr(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.node.attrs.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set source mapping info.
+    pub fn srcmap(mut self, srcmap: SourcePos) -> Self {
+        self.node.srcmap = Some(srcmap);
+        self
+    }
+
+    /// Append a child node, it can be either [Node] or another [NodeBuilder].
+    pub fn child(mut self, child: impl Into<Node>) -> Self {
+        self.node.children.push(child.into());
+        self
+    }
+
+    /// Append multiple child nodes.
+    pub fn children(mut self, children: impl IntoIterator<Item = Node>) -> Self {
+        self.node.children.extend(children);
+        self
+    }
+
+    /// Append a [Text] child node with given content.
+    pub fn text(self, content: impl Into<String>) -> Self {
+        self.child(Node::new(Text { content: content.into() }))
+    }
+
+    /// Finish building and return the node.
+    pub fn build(self) -> Node {
+        self.node
+    }
+}
+
+impl From<NodeBuilder> for Node {
+    fn from(builder: NodeBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NodeBuilder;
+    use crate::common::sourcemap::SourcePos;
+    use crate::plugins::cmark::block::paragraph::Paragraph;
+    use crate::plugins::cmark::inline::emphasis::Em;
+    use crate::plugins::cmark::inline::link::Link;
+
+    #[test]
+    fn nested() {
+        let node = NodeBuilder::new(Paragraph)
+            .text("hello ")
+            .child(NodeBuilder::new(Link { url: "/world".into(), title: None })
+                .attr("class", "internal")
+                .child(NodeBuilder::new(Em { marker: '*' }).text("world")))
+            .build();
+
+        assert_eq!(node.render(), "<p>hello <a class=\"internal\" href=\"/world\"><em>world</em></a></p>\n");
+    }
+
+    #[test]
+    fn srcmap() {
+        let node = NodeBuilder::new(Paragraph).srcmap(SourcePos::new(1, 2)).build();
+        assert_eq!(node.srcmap.unwrap().get_byte_offsets(), (1, 2));
+    }
+}
@@ -0,0 +1,113 @@
+//! Abort parsing of a document that takes too long.
+//!
+//! Pathological input may take a lot of time to parse. A web service can give each
+//! document a time budget: pass [CancelToken] in [ParseOptions::cancel], and cancel
+//! it from another thread (e.g. a timer) when the budget is exceeded. Block and inline
+//! rule chains check the token before each line and each character respectively,
+//! core rules are checked between each other.
+//!
+//! If the token was cancelled, the returned AST is incomplete and should be thrown away.
+//!
+//! ```rust
+//! use markdown_it::parser::cancel::CancelToken;
+//! use markdown_it::parser::options::ParseOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let token = CancelToken::new();
+//! let options = ParseOptions { cancel: Some(token.clone()), ..Default::default() };
+//! let ast = md.parse_with_options("*hello*", options);
+//! assert!(!token.is_cancelled());
+//! assert_eq!(ast.render(), "<p><em>hello</em></p>\n");
+//!
+//! // a timer thread would normally do this
+//! token.cancel();
+//! let options = ParseOptions { cancel: Some(token.clone()), ..Default::default() };
+//! let ast = md.parse_with_options("*hello*", options);
+//! assert!(ast.children.is_empty());
+//! ```
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::parser::extset::RootExtSet;
+use crate::parser::options;
+#[cfg(doc)]
+use crate::parser::options::ParseOptions;
+
+#[derive(Debug, Clone, Default)]
+/// Shared flag used to stop parsing, clones of it refer to the same flag.
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop all parses that use this token (can be called from any thread).
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Token the current document is parsed with, if any.
+pub fn token(root_ext: &RootExtSet) -> Option<&CancelToken> {
+    options::get(root_ext)?.cancel.as_ref()
+}
+
+/// Check if parse of the current document was cancelled, rules that loop over
+/// large input by themselves may use it to stop early.
+pub fn is_cancelled(root_ext: &RootExtSet) -> bool {
+    token(root_ext).is_some_and(CancelToken::is_cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CancelToken;
+    use crate::parser::inline::{InlineRule, InlineState};
+    use crate::parser::options::ParseOptions;
+    use crate::{MarkdownIt, Node};
+
+    // cancels the parse when it sees `!`, like a timer would do in the middle of parsing
+    struct CancelOnMarker;
+    impl InlineRule for CancelOnMarker {
+        const MARKER: char = '!';
+
+        fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+            super::token(state.root_ext)?.cancel();
+            None
+        }
+    }
+
+    fn parse(md: &MarkdownIt, src: &str, token: &CancelToken) -> Node {
+        md.parse_with_options(src, ParseOptions { cancel: Some(token.clone()), ..Default::default() })
+    }
+
+    #[test]
+    fn cancel_in_the_middle() {
+        let md = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        md.inline.add_rule::<CancelOnMarker>();
+
+        let token = CancelToken::new();
+        parse(md, "> - a\n> - b!\n\nc", &token);
+        assert!(token.is_cancelled());
+
+        // regular parse isn't affected
+        assert_eq!(md.parse("a!").render(), "<p>a!</p>\n");
+    }
+
+    #[test]
+    fn not_cancelled() {
+        let md = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let token = CancelToken::new();
+        let ast = parse(md, "- *a*", &token);
+        assert!(!token.is_cancelled());
+        assert_eq!(ast.render(), "<ul>\n<li><em>a</em></li>\n</ul>\n");
+    }
+}
@@ -6,11 +6,18 @@ use crate::{Node, NodeValue, Renderer};
 pub struct Root {
     pub content: String,
     pub ext: RootExtSet,
+    /// Document fragment (see [MarkdownIt::parse_fragment](crate::MarkdownIt::parse_fragment)),
+    /// its content is parsed as inline text without any block-level structure.
+    pub fragment: bool,
 }
 
 impl Root {
     pub fn new(content: String) -> Self {
-        Self { content, ext: RootExtSet::new() }
+        Self { content, ext: RootExtSet::new(), fragment: false }
+    }
+
+    pub fn fragment(content: String) -> Self {
+        Self { content, ext: RootExtSet::new(), fragment: true }
     }
 }
 
@@ -18,4 +25,9 @@ impl NodeValue for Root {
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.contents(&node.children);
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self::new(String::new()))
+    }
 }
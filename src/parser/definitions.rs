@@ -0,0 +1,211 @@
+//! Definitions collected while parsing a document, with references to them.
+//!
+//! Plugins that have definitions referenced by label record them in the root node:
+//! link references (`Definitions<Definition>`), footnotes (`Definitions<FootnoteDefinition>`)
+//! and substitutions (`Definitions<SubstitutionDefinition>`). Each kind is typed by
+//! the node of its definitions, so tools can find unused definitions and references
+//! to missing ones after parsing:
+//!
+//! ```rust
+//! use markdown_it::parser::definitions::Definitions;
+//! use markdown_it::plugins::cmark::block::reference::Definition;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("[a][used], [b][missing]\n\n[used]: /a\n[unused]: /b");
+//! let definitions = Definitions::<Definition>::of(&ast).unwrap();
+//!
+//! let unused = definitions.unused().map(|def| def.label.as_str()).collect::<Vec<_>>();
+//! assert_eq!(unused, ["unused"]);
+//! let missing = definitions.missing().iter().map(|r| r.label.as_str()).collect::<Vec<_>>();
+//! assert_eq!(missing, ["missing"]);
+//! ```
+//!
+//! Only references that can't be anything else are reported as missing: `[text][label]`
+//! and `[label][]` for links, `[^label]` for footnotes. Shortcut links (`[label]`) and
+//! unknown substitutions are usually meant as plain text.
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::Root;
+use crate::parser::extset::RootExt;
+use crate::{Node, NodeValue};
+
+#[derive(Debug, Clone)]
+/// Definition found in the document.
+pub struct CollectedDefinition {
+    /// Label as written in the definition.
+    pub label: String,
+    /// Position of the definition.
+    pub srcmap: Option<SourcePos>,
+    /// Positions of references to this definition, in order of parsing.
+    pub references: Vec<SourcePos>,
+}
+
+impl CollectedDefinition {
+    pub fn is_used(&self) -> bool {
+        !self.references.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Reference to a label that isn't defined in the document.
+pub struct MissingReference {
+    pub label: String,
+    pub srcmap: Option<SourcePos>,
+}
+
+/// Definitions of one kind (`T` is their node type) and references to them,
+/// stored in the root node.
+pub struct Definitions<T> {
+    definitions: Vec<CollectedDefinition>,
+    by_key: HashMap<String, usize>,
+    missing: Vec<MissingReference>,
+    // positions of recorded references, to skip repeated calls
+    seen: HashSet<(usize, usize)>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Definitions<T> {
+    fn default() -> Self {
+        Self {
+            definitions: Vec::new(),
+            by_key: HashMap::new(),
+            missing: Vec::new(),
+            seen: HashSet::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for Definitions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Definitions")
+            .field("definitions", &self.definitions)
+            .field("missing", &self.missing)
+            .finish()
+    }
+}
+
+impl<T: NodeValue> RootExt for Definitions<T> {}
+
+impl<T: NodeValue> Definitions<T> {
+    /// Definitions collected in the parsed document, `None` if it has none
+    /// and no references to them.
+    pub fn of(root: &Node) -> Option<&Self> {
+        root.cast::<Root>()?.ext.get::<Self>()
+    }
+
+    /// All definitions in order of appearance, duplicates are ignored.
+    pub fn iter(&self) -> impl Iterator<Item = &CollectedDefinition> {
+        self.definitions.iter()
+    }
+
+    /// Find a definition by its key (label normalized the way the plugin does it).
+    pub fn get(&self, key: &str) -> Option<&CollectedDefinition> {
+        self.by_key.get(key).map(|&idx| &self.definitions[idx])
+    }
+
+    /// Definitions that aren't referenced anywhere in the document.
+    pub fn unused(&self) -> impl Iterator<Item = &CollectedDefinition> {
+        self.definitions.iter().filter(|def| !def.is_used())
+    }
+
+    /// References to labels that aren't defined in the document.
+    pub fn missing(&self) -> &[MissingReference] {
+        &self.missing
+    }
+
+    /// Record a definition, `key` is used to match references to it.
+    /// Returns false if it's already defined.
+    pub fn define(&mut self, key: String, label: &str, srcmap: Option<SourcePos>) -> bool {
+        if self.by_key.contains_key(&key) { return false; }
+
+        self.by_key.insert(key, self.definitions.len());
+        self.definitions.push(CollectedDefinition {
+            label: label.to_owned(),
+            srcmap,
+            references: Vec::new(),
+        });
+        true
+    }
+
+    /// Record a reference, rules may call it several times for the same one
+    /// (e.g. when checking for a match and running), those are counted once.
+    pub fn reference(&mut self, key: &str, label: &str, srcmap: Option<SourcePos>) {
+        if let Some(srcmap) = srcmap {
+            if !self.seen.insert(srcmap.get_byte_offsets()) { return; }
+        }
+
+        match self.by_key.get(key) {
+            Some(&idx) => {
+                let Some(srcmap) = srcmap else { return; };
+                self.definitions[idx].references.push(srcmap);
+            }
+            None => {
+                self.missing.push(MissingReference { label: label.to_owned(), srcmap });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Definitions;
+    use crate::plugins::cmark::block::reference::Definition;
+    #[cfg(feature = "footnote")]
+    use crate::plugins::extra::footnote::definitions::FootnoteDefinition;
+    #[cfg(feature = "substitutions")]
+    use crate::plugins::extra::substitutions::SubstitutionDefinition;
+
+    fn labels<'a>(iter: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+        iter.collect()
+    }
+
+    #[test]
+    fn link_references() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let src = "[x][A], [A], ![B][], [c], [d][e] [[f][g]]\n\n[a]: /a\n[b]: /b\n[A]: /dup\n[h]: /h";
+        let ast = md.parse(src);
+        let definitions = Definitions::<Definition>::of(&ast).unwrap();
+
+        assert_eq!(labels(definitions.iter().map(|def| def.label.as_str())), ["a", "b", "h"]);
+        assert_eq!(definitions.iter().map(|def| def.references.len()).collect::<Vec<_>>(), [2, 1, 0]);
+        let (start, end) = definitions.get("A").unwrap().references[0].get_byte_offsets();
+        assert_eq!(&src[start..end], "[x][A]");
+        assert_eq!(labels(definitions.unused().map(|def| def.label.as_str())), ["h"]);
+        assert_eq!(labels(definitions.missing().iter().map(|r| r.label.as_str())), ["e", "g"]);
+    }
+
+    #[cfg(feature = "footnote")]
+    #[test]
+    fn footnotes() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::footnote::add(md);
+        let ast = md.parse("[^a] [^b] [^a] ^[inline]\n\n[^a]: A\n[^c]: C");
+        let definitions = Definitions::<FootnoteDefinition>::of(&ast).unwrap();
+
+        assert_eq!(definitions.get("a").unwrap().references.len(), 2);
+        assert_eq!(labels(definitions.unused().map(|def| def.label.as_str())), ["c"]);
+        assert_eq!(labels(definitions.missing().iter().map(|r| r.label.as_str())), ["b"]);
+    }
+
+    #[cfg(feature = "substitutions")]
+    #[test]
+    fn substitutions() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::substitutions::add(md);
+        let ast = md.parse("*[:a:]: A\n*[:b:]: B\n\n:a: :c:");
+        let definitions = Definitions::<SubstitutionDefinition>::of(&ast).unwrap();
+
+        assert_eq!(labels(definitions.unused().map(|def| def.label.as_str())), ["b"]);
+        assert!(definitions.missing().is_empty());
+        assert!(Definitions::<Definition>::of(&ast).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+//! Read AST back from the format produced by `serde::Serialize` implementation of [Node].
+//!
+//! Node values are trait objects, so deserializer needs to know all node types
+//! in advance. Built-in ones are registered by default, custom nodes need to implement
+//! [NodeValue::deserialize_data] and get registered with [NodeDeserializer::register].
+//!
+//! ```rust
+//! use markdown_it::parser::deserialize::NodeDeserializer;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let json = serde_json::to_string(&md.parse("# hello *world*")).unwrap();
+//! let ast = NodeDeserializer::new().from_json(&json).unwrap();
+//!
+//! assert_eq!(ast.render(), "<h1>hello <em>world</em></h1>\n");
+//! ```
+use serde::de::{DeserializeOwned, Error};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::common::sourcemap::SourcePos;
+use crate::{Node, NodeValue};
+
+type NodeConstructor = fn (&Value) -> Option<Node>;
+
+fn construct<T: NodeValue>(data: &Value) -> Option<Node> {
+    T::deserialize_data(data).map(Node::new)
+}
+
+/// Read a single field from node data, helper for [NodeValue::deserialize_data] implementations.
+pub fn field<T: DeserializeOwned>(data: &Value, key: &str) -> Option<T> {
+    T::deserialize(data.get(key)?).ok()
+}
+
+#[derive(Debug, Clone)]
+/// Registry of node types that can be deserialized.
+pub struct NodeDeserializer {
+    types: HashMap<&'static str, NodeConstructor>,
+}
+
+impl NodeDeserializer {
+    /// Create deserializer that knows about all node types from this crate.
+    pub fn new() -> Self {
+        use crate::parser::core::Root;
+        use crate::parser::inline::{Text, TextSpecial};
+        use crate::plugins::cmark::block::*;
+        use crate::plugins::cmark::inline::*;
+        use crate::plugins::html::{html_block, html_inline, markdown_in_html};
+
+        let mut this = Self::empty();
+        this.register::<Root>();
+        this.register::<Text>();
+        this.register::<TextSpecial>();
+        this.register::<crate::parser::node::Splice>();
+
+        this.register::<blockquote::Blockquote>();
+        this.register::<code::CodeBlock>();
+        this.register::<fence::CodeFence>();
+        this.register::<heading::ATXHeading>();
+        this.register::<hr::ThematicBreak>();
+        this.register::<lheading::SetextHeader>();
+        this.register::<list::BulletList>();
+        this.register::<list::OrderedList>();
+        this.register::<list::ListItem>();
+        this.register::<paragraph::Paragraph>();
+        this.register::<reference::Definition>();
+
+        this.register::<autolink::Autolink>();
+        this.register::<backticks::CodeInline>();
+        this.register::<emphasis::Em>();
+        this.register::<emphasis::Strong>();
+        this.register::<image::Image>();
+        this.register::<link::Link>();
+        this.register::<newline::Hardbreak>();
+        this.register::<newline::Softbreak>();
+
+        this.register::<html_block::HtmlBlock>();
+        this.register::<html_inline::HtmlInline>();
+        this.register::<markdown_in_html::HtmlMarkdownBlock>();
+
+        #[cfg(feature = "admonition")]
+        this.register::<crate::plugins::extra::admonition::Admonition>();
+        #[cfg(feature = "callouts")]
+        this.register::<crate::plugins::extra::callouts::Callout>();
+        #[cfg(feature = "conditional")]
+        this.register::<crate::plugins::extra::conditional::Conditional>();
+        #[cfg(feature = "critic")] {
+            use crate::plugins::extra::critic;
+            this.register::<critic::CriticComment>();
+            this.register::<critic::CriticDeletion>();
+            this.register::<critic::CriticHighlight>();
+            this.register::<critic::CriticInsertion>();
+            this.register::<critic::CriticSubstitution>();
+        }
+        #[cfg(feature = "dates")]
+        this.register::<crate::plugins::extra::dates::FormattedDate>();
+        #[cfg(feature = "details")]
+        this.register::<crate::plugins::extra::details::Details>();
+        #[cfg(feature = "footnote")] {
+            use crate::plugins::extra::footnote;
+            this.register::<footnote::back_refs::FootnoteRefAnchor>();
+            this.register::<footnote::collect::FootnotesContainerNode>();
+            this.register::<footnote::definitions::FootnoteDefinition>();
+            this.register::<footnote::inline::InlineFootnote>();
+            this.register::<footnote::references::FootnoteReference>();
+        }
+        #[cfg(feature = "images")]
+        this.register::<crate::plugins::extra::images::Figure>();
+        #[cfg(feature = "include")]
+        this.register::<crate::plugins::extra::include::Include>();
+        #[cfg(feature = "link_options")]
+        this.register::<crate::plugins::extra::link_options::ObfuscatedMailto>();
+        #[cfg(feature = "math")]
+        this.register::<crate::plugins::extra::math::Math>();
+        #[cfg(feature = "quote_attribution")]
+        this.register::<crate::plugins::extra::quote_attribution::QuoteHeader>();
+        #[cfg(feature = "raw_attribute")] {
+            use crate::plugins::extra::raw_attribute;
+            this.register::<raw_attribute::RawBlock>();
+            this.register::<raw_attribute::RawInline>();
+        }
+        #[cfg(feature = "ruby")]
+        this.register::<crate::plugins::extra::ruby::Ruby>();
+        #[cfg(feature = "spoiler")]
+        this.register::<crate::plugins::extra::spoiler::Spoiler>();
+        #[cfg(feature = "strikethrough")]
+        this.register::<crate::plugins::extra::strikethrough::Strikethrough>();
+        #[cfg(feature = "substitutions")]
+        this.register::<crate::plugins::extra::substitutions::SubstitutionDefinition>();
+        #[cfg(feature = "tables")] {
+            use crate::plugins::extra::tables;
+            this.register::<tables::Table>();
+            this.register::<tables::TableHead>();
+            this.register::<tables::TableBody>();
+            this.register::<tables::TableRow>();
+            this.register::<tables::TableCell>();
+        }
+        #[cfg(feature = "linkify")]
+        this.register::<crate::plugins::extra::linkify::Linkified>();
+        #[cfg(feature = "syntect")]
+        this.register::<crate::plugins::extra::syntect::SyntectSnippet>();
+
+        this
+    }
+
+    /// Create deserializer without any registered node types.
+    pub fn empty() -> Self {
+        Self { types: HashMap::new() }
+    }
+
+    /// Make node type known to this deserializer.
+    pub fn register<T: NodeValue>(&mut self) {
+        self.types.insert(std::any::type_name::<T>(), construct::<T>);
+    }
+
+    /// Check if node type is known to this deserializer.
+    pub fn has<T: NodeValue>(&self) -> bool {
+        self.types.contains_key(std::any::type_name::<T>())
+    }
+
+    /// Build AST from json string.
+    pub fn from_json(&self, json: &str) -> Result<Node, serde_json::Error> {
+        self.from_value(&serde_json::from_str(json)?)
+    }
+
+    /// Build AST from parsed json value.
+    pub fn from_value(&self, value: &Value) -> Result<Node, serde_json::Error> {
+        let Some(name) = value.get("type").and_then(Value::as_str) else {
+            return Err(Error::missing_field("type"));
+        };
+
+        let Some(constructor) = self.types.get(name) else {
+            return Err(Error::custom(format_args!("unknown node type: {}", name)));
+        };
+
+        let data = value.get("data").unwrap_or(&Value::Null);
+        let Some(mut node) = constructor(data) else {
+            return Err(Error::custom(format_args!("invalid data for node type: {}", name)));
+        };
+
+        if let Some(srcmap) = value.get("srcmap").filter(|v| !v.is_null()) {
+            let (start, end) = <(usize, usize)>::deserialize(srcmap)?;
+            node.srcmap = Some(SourcePos::new(start, end));
+        }
+
+        if let Some(attrs) = value.get("attrs") {
+            node.attrs = Vec::deserialize(attrs)?;
+        }
+
+        if let Some(attrs_raw) = value.get("attrs_raw") {
+            node.attrs_raw = Vec::deserialize(attrs_raw)?;
+        }
+
+        if let Some(children) = value.get("children") {
+            let Some(children) = children.as_array() else {
+                return Err(Error::custom("children must be an array"));
+            };
+            for child in children {
+                node.children.push(self.from_value(child)?);
+            }
+        }
+
+        Ok(node)
+    }
+}
+
+impl Default for NodeDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,114 @@
+//! Warnings about a document, reported by rules while parsing.
+//!
+//! Any rule can report a problem with [report] (block and inline rules pass
+//! `state.root_ext`, core rules pass extensions of the root node). Reports are
+//! stored in the root node and can be retrieved with [Diagnostics::of] after parsing.
+//! Built-in checks are in the [lint](crate::plugins::extra::lint) plugin.
+//!
+//! ```rust
+//! use markdown_it::parser::core::{CoreRule, Root};
+//! use markdown_it::parser::diagnostics::{self, Diagnostic, Diagnostics};
+//! use markdown_it::{MarkdownIt, Node};
+//!
+//! struct EmptyDocument;
+//! impl CoreRule for EmptyDocument {
+//!     fn run(root: &mut Node, _: &MarkdownIt) {
+//!         if !root.children.is_empty() { return; }
+//!         let root_data = root.cast_mut::<Root>().unwrap();
+//!         diagnostics::report(&mut root_data.ext, Diagnostic::warning("empty", "document is empty", None));
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! md.add_rule::<EmptyDocument>();
+//!
+//! let ast = md.parse("");
+//! assert_eq!(Diagnostics::of(&ast)[0].to_string(), "warning[empty]: document is empty");
+//! assert!(Diagnostics::of(&md.parse("text")).is_empty());
+//! ```
+use std::fmt::{self, Display};
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::Root;
+use crate::parser::extset::{RootExt, RootExtSet};
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Something that may be intended, but is worth a look.
+    Info,
+    #[default]
+    /// Likely a mistake in the document.
+    Warning,
+    /// Document doesn't render as it's supposed to.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Problem found in a document.
+pub struct Diagnostic {
+    /// Short identifier of the check, e.g. `heading-level`.
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Source position of the problem.
+    pub srcmap: Option<SourcePos>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, severity: Severity, message: impl Into<String>, srcmap: Option<SourcePos>) -> Self {
+        Self { code, severity, message: message.into(), srcmap }
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>, srcmap: Option<SourcePos>) -> Self {
+        Self::new(code, Severity::Warning, message, srcmap)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+        if let Some(map) = self.srcmap {
+            let (start, end) = map.get_byte_offsets();
+            write!(f, " ({start}..{end})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// All diagnostics reported for a document, in order of reporting.
+pub struct Diagnostics(pub Vec<Diagnostic>);
+impl RootExt for Diagnostics {}
+
+impl Diagnostics {
+    /// Diagnostics of the parsed document.
+    pub fn of(root: &Node) -> &[Diagnostic] {
+        root.cast::<Root>()
+            .and_then(|root| root.ext.get::<Self>())
+            .map_or(&[], |diagnostics| diagnostics.0.as_slice())
+    }
+}
+
+/// Report a problem in the document being parsed.
+pub fn report(root_ext: &mut RootExtSet, diagnostic: Diagnostic) {
+    root_ext.get_or_insert_default::<Diagnostics>().0.push(diagnostic);
+}
+
+/// Report output that differs from CommonMark because of a parser limit,
+/// does nothing unless [strict](MarkdownIt::strict) mode is enabled.
+pub(crate) fn report_limit(md: &MarkdownIt, root_ext: &mut RootExtSet, message: &str, srcmap: Option<SourcePos>) {
+    if !md.strict { return; }
+    report(root_ext, Diagnostic::warning("commonmark-limit", message, srcmap));
+}
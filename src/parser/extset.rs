@@ -57,6 +57,11 @@ macro_rules! extension_set {
                 self.0.clear();
             }
 
+            #[allow(dead_code)]
+            pub(crate) fn iter(&self) -> impl Iterator<Item = (&crate::common::TypeKey, &dyn $trait)> {
+                self.0.iter().map(|(key, value)| (key, value.as_ref()))
+            }
+
             #[must_use]
             pub fn contains<T: 'static>(&self) -> bool {
                 let key = crate::common::TypeKey::of::<T>();
@@ -114,7 +119,6 @@ pub(crate) use extension_set;
 
 #[cfg(test)]
 mod tests {
-    use super::extension_set;
     use downcast_rs::{Downcast, impl_downcast};
     use std::fmt::Debug;
 
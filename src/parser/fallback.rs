@@ -0,0 +1,363 @@
+//! Render documents with nodes that renderer can't (or shouldn't) handle.
+//!
+//! Nodes that don't support renderer's format are replaced with their children,
+//! and nodes that keep their content in node value (e.g. code blocks) are silently
+//! dropped this way. Some node types might also be unwanted in a specific output,
+//! e.g. tables in emails. [FallbackRegistry] renders such nodes in a simpler way
+//! instead, and reports each node it did that for.
+//!
+//! ```rust
+//! # #[cfg(feature = "strikethrough")] {
+//! use markdown_it::parser::fallback::{Fallback, FallbackRegistry};
+//! use markdown_it::plugins::cmark::block::blockquote::Blockquote;
+//! use markdown_it::plugins::extra::strikethrough::Strikethrough;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::strikethrough::add(md);
+//!
+//! let mut registry = FallbackRegistry::new();
+//! registry.disable::<Strikethrough>(Fallback::Code);
+//! registry.disable::<Blockquote>(Fallback::Children);
+//!
+//! let (html, warnings) = registry.render(&md.parse("> a ~~b~~"));
+//! assert_eq!(html, "<p>a <code>~~b~~</code></p>\n");
+//! assert_eq!(warnings.len(), 2);
+//! assert_eq!(warnings[1].to_string(), "Strikethrough (4..9) is disabled, fallback is used");
+//! # }
+//! ```
+use derivative::Derivative;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use crate::common::sourcemap::SourcePos;
+use crate::common::TypeKey;
+use crate::parser::core::Root;
+use crate::parser::extset::RenderExtSet;
+use crate::parser::renderer::HTMLRenderer;
+use crate::{AttrValue, Node, NodeValue, RenderOptions, Renderer};
+
+/// Custom fallback, called with the node and its source text.
+pub type FallbackFn = Box<dyn Fn(&Node, &str, &mut dyn Renderer) + Send + Sync>;
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+/// How to render a node that can't be rendered normally.
+///
+/// Source text is taken from the document using node's source map,
+/// or collected from its text nodes if there isn't one.
+pub enum Fallback {
+    /// Render node's children (this is what renderers do by default).
+    Children,
+    /// Render source text as plain text.
+    Text,
+    /// Render source text as inline code.
+    Code,
+    /// Render source text as code block.
+    CodeBlock,
+    /// Render node's children inside a blockquote.
+    Blockquote,
+    /// Render node using custom function.
+    Custom(#[derivative(Debug = "ignore")] FallbackFn),
+}
+
+impl Fallback {
+    fn render(&self, node: &Node, source: &str, fmt: &mut dyn Renderer) {
+        match self {
+            Self::Children => fmt.contents(&node.children),
+            Self::Text => fmt.text(source),
+            Self::Code => {
+                fmt.open("code", &[]);
+                fmt.text(source);
+                fmt.close("code");
+            }
+            Self::CodeBlock => {
+                fmt.cr();
+                fmt.open("pre", &[]);
+                fmt.open("code", &[]);
+                fmt.text(source);
+                if !source.ends_with('\n') { fmt.text("\n"); }
+                fmt.close("code");
+                fmt.close("pre");
+                fmt.cr();
+            }
+            Self::Blockquote => {
+                fmt.cr();
+                fmt.open("blockquote", &[]);
+                fmt.cr();
+                fmt.contents(&node.children);
+                fmt.cr();
+                fmt.close("blockquote");
+                fmt.cr();
+            }
+            Self::Custom(f) => f(node, source, fmt),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Why fallback was used for a node.
+pub enum FallbackReason {
+    /// Node type is disabled with [FallbackRegistry::disable].
+    Disabled,
+    /// Renderer doesn't support this node type.
+    Unsupported,
+}
+
+#[derive(Debug, Clone)]
+/// Warning emitted for each node rendered using a fallback.
+pub struct FallbackWarning {
+    /// Node type name, see [Node::name].
+    pub node_name: &'static str,
+    /// Source position of the node.
+    pub srcmap: Option<SourcePos>,
+    pub reason: FallbackReason,
+}
+
+impl Display for FallbackWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let short_name = self.node_name.rsplit("::").next().unwrap_or(self.node_name);
+        f.write_str(short_name)?;
+        if let Some(map) = self.srcmap {
+            let (start, end) = map.get_byte_offsets();
+            write!(f, " ({start}..{end})")?;
+        }
+        match self.reason {
+            FallbackReason::Disabled => f.write_str(" is disabled, fallback is used"),
+            FallbackReason::Unsupported => f.write_str(" is not supported by renderer, fallback is used"),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Registry of fallbacks for node types, see [module documentation](self).
+pub struct FallbackRegistry {
+    disabled: HashMap<TypeKey, Fallback>,
+    unsupported: HashMap<TypeKey, Fallback>,
+    default: Fallback,
+}
+
+impl FallbackRegistry {
+    /// Create registry that renders unsupported nodes without children as text.
+    pub fn new() -> Self {
+        Self {
+            disabled: HashMap::new(),
+            unsupported: HashMap::new(),
+            default: Fallback::Text,
+        }
+    }
+
+    /// Always render nodes of this type using fallback.
+    pub fn disable<T: NodeValue>(&mut self, fallback: Fallback) {
+        self.disabled.insert(TypeKey::of::<T>(), fallback);
+    }
+
+    /// Render nodes of this type using fallback if renderer doesn't support them.
+    pub fn set<T: NodeValue>(&mut self, fallback: Fallback) {
+        self.unsupported.insert(TypeKey::of::<T>(), fallback);
+    }
+
+    /// Set fallback for unsupported nodes without children and without their own fallback
+    /// ([Fallback::Text] by default). Unsupported nodes with children are rendered
+    /// as their children without any warnings, same as [Node::render_with] does.
+    pub fn set_default(&mut self, fallback: Fallback) {
+        self.default = fallback;
+    }
+
+    /// Render document to HTML, returns it along with the list of fallbacks used.
+    pub fn render(&self, root: &Node) -> (String, Vec<FallbackWarning>) {
+        let mut fmt = HTMLRenderer::new(&RenderOptions::default());
+        let warnings = self.render_with(root, &mut fmt);
+        (fmt.into(), warnings)
+    }
+
+    /// Render document using a custom renderer, returns the list of fallbacks used.
+    pub fn render_with(&self, root: &Node, fmt: &mut dyn Renderer) -> Vec<FallbackWarning> {
+        let source = root.cast::<Root>().map_or("", |root| root.content.as_str());
+        let mut fmt = FallbackRenderer { inner: fmt, registry: self, source, warnings: Vec::new() };
+        fmt.render(root);
+        fmt.warnings
+    }
+}
+
+impl Default for FallbackRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// wraps a renderer, and checks every node before rendering it
+struct FallbackRenderer<'a> {
+    inner: &'a mut dyn Renderer,
+    registry: &'a FallbackRegistry,
+    source: &'a str,
+    warnings: Vec<FallbackWarning>,
+}
+
+impl FallbackRenderer<'_> {
+    fn render(&mut self, node: &Node) {
+        if node.is_hidden() { return; }
+
+        let registry = self.registry;
+
+        if let Some(fallback) = registry.disabled.get(&node.node_type) {
+            self.render_fallback(node, fallback, FallbackReason::Disabled);
+            return;
+        }
+
+        if node.render_supported(self) { return; }
+
+        if let Some(fallback) = registry.unsupported.get(&node.node_type) {
+            self.render_fallback(node, fallback, FallbackReason::Unsupported);
+        } else if node.children.is_empty() {
+            self.render_fallback(node, &registry.default, FallbackReason::Unsupported);
+        } else {
+            self.contents(&node.children);
+        }
+    }
+
+    fn render_fallback(&mut self, node: &Node, fallback: &Fallback, reason: FallbackReason) {
+        self.warnings.push(FallbackWarning { node_name: node.name(), srcmap: node.srcmap, reason });
+
+        let source = node.srcmap.and_then(|map| {
+            let (start, end) = map.get_byte_offsets();
+            self.source.get(start..end)
+        });
+
+        match source {
+            Some(source) => fallback.render(node, source, self),
+            None => fallback.render(node, &node.collect_text(), self),
+        }
+    }
+}
+
+impl Renderer for FallbackRenderer<'_> {
+    fn open(&mut self, tag: &str, attrs: &[(String, String)]) {
+        self.inner.open(tag, attrs);
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.inner.close(tag);
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(String, String)]) {
+        self.inner.self_close(tag, attrs);
+    }
+
+    fn open_tag(&mut self, tag: &str, attrs: &[(&str, AttrValue<'_>)]) {
+        self.inner.open_tag(tag, attrs);
+    }
+
+    fn close_tag(&mut self, tag: &str) {
+        self.inner.close_tag(tag);
+    }
+
+    fn contents(&mut self, nodes: &[Node]) {
+        for node in nodes.iter() {
+            stacker::maybe_grow(64*1024, 1024*1024, || {
+                self.render(node);
+            });
+        }
+    }
+
+    fn cr(&mut self) {
+        self.inner.cr();
+    }
+
+    fn text(&mut self, text: &str) {
+        self.inner.text(text);
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        self.inner.text_raw(text);
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        self.inner.ext()
+    }
+
+    fn format(&self) -> &'static str {
+        self.inner.format()
+    }
+
+    fn fallback_formats(&self) -> &'static [&'static str] {
+        self.inner.fallback_formats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fallback, FallbackReason, FallbackRegistry};
+    use crate::parser::inline::Text;
+    use crate::plugins::cmark::block::fence::CodeFence;
+    use crate::plugins::cmark::inline::emphasis::Em;
+    use crate::{Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Math;
+    impl NodeValue for Math {
+        // supports some format that isn't html
+        fn render_format(&self, _: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+            if format != "latex" { return false; }
+            fmt.text_raw("$x$");
+            true
+        }
+    }
+
+    fn parse(src: &str) -> Node {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        md.parse(src)
+    }
+
+    #[test]
+    fn unsupported() {
+        let mut ast = parse("a *b* c");
+        let paragraph = &mut ast.children[0];
+        let srcmap = paragraph.children[1].srcmap;
+        paragraph.children[1].replace(Math);
+        let mut math = Node::new(Math);
+        math.srcmap = srcmap;
+        paragraph.children.push(math);
+
+        // node with children is rendered as its children, node without them as source text
+        let (html, warnings) = FallbackRegistry::new().render(&ast);
+        assert_eq!(html, "<p>a b c*b*</p>\n");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].reason, FallbackReason::Unsupported);
+        assert_eq!(warnings[0].to_string(), "Math (2..5) is not supported by renderer, fallback is used");
+
+        let mut registry = FallbackRegistry::new();
+        registry.set_default(Fallback::Custom(Box::new(|_, source, fmt| {
+            fmt.text_raw(&source.to_uppercase());
+        })));
+        assert_eq!(registry.render(&ast).0, "<p>a b c*B*</p>\n");
+
+        registry.set::<Math>(Fallback::Blockquote);
+        let (html, warnings) = registry.render(&ast);
+        assert_eq!(html, "<p>a \n<blockquote>\nb\n</blockquote>\n c\n<blockquote>\n</blockquote>\n</p>\n");
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn disabled() {
+        let ast = parse("```rust\nfn main() {}\n```\n\n*a* *b*");
+        let mut registry = FallbackRegistry::new();
+        registry.disable::<CodeFence>(Fallback::CodeBlock);
+        registry.disable::<Em>(Fallback::Text);
+        registry.disable::<Text>(Fallback::Children);
+
+        let (html, warnings) = registry.render(&ast);
+        assert_eq!(html, "<pre><code>```rust\nfn main() {}\n```\n</code></pre>\n<p>*a**b*</p>\n");
+        assert_eq!(warnings.iter().filter(|w| w.reason == FallbackReason::Disabled).count(), 4);
+    }
+
+    #[test]
+    fn without_source() {
+        let mut node = Node::new(Em { marker: '*' });
+        node.children.push(Node::new(Text { content: "<a>".into() }));
+        let mut registry = FallbackRegistry::new();
+        registry.disable::<Em>(Fallback::Code);
+        assert_eq!(registry.render(&node).0, "<code>&lt;a&gt;</code>");
+    }
+}
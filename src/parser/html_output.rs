@@ -0,0 +1,314 @@
+//! Whitespace of rendered html: pretty-printed or minified.
+//!
+//! Default output has one block element per line without indentation (same
+//! as markdown-it.js). Set [RenderOptions::output_style](crate::RenderOptions::output_style)
+//! to get html indented for reading, or without optional whitespace:
+//!
+//! ```rust
+//! use markdown_it::parser::html_output::OutputStyle;
+//! use markdown_it::RenderOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! let ast = md.parse("> - a\n>\n>   b\n\n```\n  code\n```");
+//!
+//! let options = RenderOptions::new().output_style(OutputStyle::Pretty { indent: 2 });
+//! assert_eq!(ast.render_with_options(&options), "\
+//! <blockquote>
+//!   <ul>
+//!     <li>
+//!       <p>a</p>
+//!       <p>b</p>
+//!     </li>
+//!   </ul>
+//! </blockquote>
+//! <pre><code>  code
+//! </code></pre>
+//! ");
+//!
+//! let options = RenderOptions::new().output_style(OutputStyle::Minified);
+//! assert_eq!(ast.render_with_options(&options),
+//!     "<blockquote><ul><li><p>a</p><p>b</p></li></ul></blockquote><pre><code>  code\n</code></pre>");
+//! ```
+//!
+//! Contents of `<pre>`, `<code>`, `<textarea>`, `<script>` and `<style>` are kept as is,
+//! whitespace inside paragraphs is collapsed (minified) or indented (pretty-printed),
+//! which doesn't change how html is displayed.
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// Whitespace between html elements, see [module documentation](self).
+pub enum OutputStyle {
+    #[default]
+    /// Line break after each block element.
+    Default,
+    /// Block elements on separate lines, indented by `indent` spaces per nesting level.
+    Pretty { indent: usize },
+    /// No whitespace between block elements, whitespace in text collapsed.
+    Minified,
+}
+
+/// Change whitespace of html according to given style.
+pub fn apply(html: String, style: OutputStyle) -> String {
+    match style {
+        OutputStyle::Default => html,
+        OutputStyle::Pretty { indent } => pretty_print(&html, indent),
+        OutputStyle::Minified => minify(&html),
+    }
+}
+
+// elements that start a new line
+const BLOCK_TAGS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "caption", "col", "colgroup", "dd",
+    "details", "dialog", "div", "dl", "dt", "fieldset", "figcaption", "figure", "footer",
+    "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hgroup", "hr", "li", "main",
+    "nav", "ol", "p", "pre", "section", "summary", "table", "tbody", "td", "tfoot", "th",
+    "thead", "tr", "ul",
+];
+
+// elements with contents that must be kept as is
+const RAW_TAGS: &[&str] = &["code", "pre", "script", "style", "textarea"];
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+    // tag name is lowercased
+    Open { raw: &'a str, name: String, void: bool },
+    Close { raw: &'a str, name: String },
+    Text(&'a str),
+    // comments, doctype, contents of raw elements
+    Raw(&'a str),
+}
+
+impl Token<'_> {
+    fn block_name(&self) -> Option<&str> {
+        match self {
+            Token::Open { name, .. } | Token::Close { name, .. } => {
+                BLOCK_TAGS.contains(&name.as_str()).then_some(name.as_str())
+            }
+            _ => None,
+        }
+    }
+}
+
+// length of a tag starting at the beginning of `src`, quoted attribute values may contain `>`
+fn tag_len(src: &str) -> Option<usize> {
+    let mut quote = None;
+    for (pos, ch) in src.char_indices().skip(1) {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), _) if q == ch => quote = None,
+            (None, '>') => return Some(pos + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches(['<', '/'])
+        .split(|ch: char| ch.is_ascii_whitespace() || ch == '>' || ch == '/')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut text_start = 0;
+
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &html[start..];
+        let next = rest[1..].chars().next().unwrap_or_default();
+
+        let len = if rest.starts_with("<!--") {
+            rest.find("-->").map_or(rest.len(), |end| end + 3)
+        } else if next.is_ascii_alphabetic() || next == '/' || next == '!' || next == '?' {
+            match tag_len(rest) {
+                Some(len) => len,
+                None => break,
+            }
+        } else {
+            pos = start + 1;
+            continue;
+        };
+
+        if text_start < start {
+            tokens.push(Token::Text(&html[text_start..start]));
+        }
+
+        let raw = &rest[..len];
+        pos = start + len;
+
+        if next == '/' {
+            tokens.push(Token::Close { raw, name: tag_name(raw) });
+        } else if next.is_ascii_alphabetic() {
+            let name = tag_name(raw);
+            let void = VOID_TAGS.contains(&name.as_str()) || raw.ends_with("/>");
+
+            if RAW_TAGS.contains(&name.as_str()) && !void {
+                // contents of raw elements (up to closing tag) are a single token
+                let end = html[pos..].to_ascii_lowercase().find(&format!("</{name}")).map_or(html.len(), |end| pos + end);
+                tokens.push(Token::Open { raw, name, void });
+                if pos < end {
+                    tokens.push(Token::Raw(&html[pos..end]));
+                }
+                pos = end;
+            } else {
+                tokens.push(Token::Open { raw, name, void });
+            }
+        } else {
+            tokens.push(Token::Raw(raw));
+        }
+
+        text_start = pos;
+    }
+
+    if text_start < html.len() {
+        tokens.push(Token::Text(&html[text_start..]));
+    }
+
+    tokens
+}
+
+fn collapse_whitespace(text: &str, result: &mut String) {
+    let mut prev_space = false;
+    for ch in text.chars() {
+        if ch.is_ascii_whitespace() {
+            if !prev_space { result.push(' '); }
+            prev_space = true;
+        } else {
+            result.push(ch);
+            prev_space = false;
+        }
+    }
+}
+
+/// Remove whitespace between block elements and at their edges, and collapse
+/// whitespace in text.
+pub fn minify(html: &str) -> String {
+    let tokens = tokenize(html);
+    let mut result = String::with_capacity(html.len());
+
+    for (idx, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Text(text) => {
+                // whitespace next to block tags and line breaks isn't displayed
+                let trim_before = idx == 0 || tokens[idx - 1].block_name().is_some()
+                    || matches!(&tokens[idx - 1], Token::Open { name, .. } if name == "br");
+                let trim_after = tokens.get(idx + 1).is_none_or(|next| next.block_name().is_some());
+
+                let mut text = *text;
+                if trim_before { text = text.trim_start_matches(|ch: char| ch.is_ascii_whitespace()); }
+                if trim_after { text = text.trim_end_matches(|ch: char| ch.is_ascii_whitespace()); }
+                collapse_whitespace(text, &mut result);
+            }
+            Token::Open { raw, .. } | Token::Close { raw, .. } | Token::Raw(raw) => result.push_str(raw),
+        }
+    }
+
+    result
+}
+
+/// Put block elements on separate lines, indented by `indent` spaces per level.
+pub fn pretty_print(html: &str, indent: usize) -> String {
+    let tokens = tokenize(html);
+    let mut result = String::with_capacity(html.len() * 2);
+    // open block elements, and whether they have block children
+    let mut stack : Vec<(&str, bool)> = Vec::new();
+
+    let new_line = |result: &mut String, depth: usize| {
+        if !result.is_empty() && !result.ends_with('\n') { result.push('\n'); }
+        result.push_str(&" ".repeat(depth * indent));
+    };
+
+    for (idx, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Open { raw, void, .. } if token.block_name().is_some() => {
+                if let Some(parent) = stack.last_mut() { parent.1 = true; }
+                new_line(&mut result, stack.len());
+                result.push_str(raw);
+                if !void {
+                    stack.push((token.block_name().unwrap(), false));
+                }
+            }
+            Token::Close { raw, name } if token.block_name().is_some() => {
+                if let Some(pos) = stack.iter().rposition(|(open, _)| open == name) {
+                    let (_, has_blocks) = stack[pos];
+                    stack.truncate(pos);
+                    if has_blocks { new_line(&mut result, stack.len()); }
+                }
+                result.push_str(raw);
+            }
+            Token::Text(text) => {
+                let prev_block = idx == 0 || tokens[idx - 1].block_name().is_some();
+                let next_block = tokens.get(idx + 1).is_none_or(|next| next.block_name().is_some());
+
+                let mut text = *text;
+                if prev_block { text = text.trim_start_matches(|ch: char| ch.is_ascii_whitespace()); }
+                if next_block { text = text.trim_end_matches(|ch: char| ch.is_ascii_whitespace()); }
+                if text.is_empty() { continue; }
+
+                // text after a nested block (e.g. `<li><p>a</p>b</li>`) starts a new line
+                if prev_block && matches!(tokens.get(idx.wrapping_sub(1)), Some(Token::Close { .. })) {
+                    new_line(&mut result, stack.len());
+                }
+
+                // continuation lines are indented, this whitespace is collapsed by browsers
+                let line_indent = format!("\n{}", " ".repeat(stack.len() * indent));
+                for (line_idx, line) in text.split('\n').enumerate() {
+                    if line_idx > 0 { result.push_str(&line_indent); }
+                    result.push_str(line.trim_start_matches(|ch: char| ch.is_ascii_whitespace() && line_idx > 0));
+                }
+            }
+            Token::Open { raw, .. } | Token::Close { raw, .. } | Token::Raw(raw) => result.push_str(raw),
+        }
+    }
+
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minify, pretty_print, tokenize, Token};
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize("a<b title='>'>c</B><!-- <p> --><pre>\n<i> </pre >");
+        assert_eq!(tokens, [
+            Token::Text("a"),
+            Token::Open { raw: "<b title='>'>", name: "b".into(), void: false },
+            Token::Text("c"),
+            Token::Close { raw: "</B>", name: "b".into() },
+            Token::Raw("<!-- <p> -->"),
+            Token::Open { raw: "<pre>", name: "pre".into(), void: false },
+            Token::Raw("\n<i> "),
+            Token::Close { raw: "</pre >", name: "pre".into() },
+        ]);
+        assert_eq!(tokenize("1 < 2 <"), [Token::Text("1 < 2 <")]);
+    }
+
+    #[test]
+    fn minified() {
+        let html = "<p>a\n<em>b</em>  c<br>\nd</p>\n<hr>\n<ul>\n<li> e </li>\n</ul>\n<textarea> f\n </textarea>\n";
+        assert_eq!(minify(html), "<p>a <em>b</em> c<br>d</p><hr><ul><li>e</li></ul><textarea> f\n </textarea>");
+        assert_eq!(minify("<p>a  <code>b  c\n</code>\n</p>"), "<p>a <code>b  c\n</code></p>");
+    }
+
+    #[test]
+    fn pretty() {
+        let html = "<ol>\n<li>a\n<ul>\n<li>b</li>\n</ul>\nc</li>\n</ol>\n<p>d\ne</p>\n<table>\n<tr>\n<td>f</td>\n</tr>\n</table>\n";
+        assert_eq!(pretty_print(html, 1), "<ol>\n <li>a\n  <ul>\n   <li>b</li>\n  </ul>\n  c\n </li>\n</ol>\n\
+            <p>d\n e</p>\n<table>\n <tr>\n  <td>f</td>\n </tr>\n</table>\n");
+        assert_eq!(pretty_print("", 2), "");
+        assert_eq!(pretty_print("<em>a</em>", 2), "<em>a</em>\n");
+    }
+}
@@ -0,0 +1,271 @@
+//! Re-parse a document after a small edit, reusing unchanged blocks (e.g. for live preview in editors).
+//!
+//! See [MarkdownIt::parse_incremental](crate::MarkdownIt::parse_incremental).
+//!
+//! Block structure is always parsed from scratch (it's cheap), then every new top-level
+//! block with the same type and source as a block in the previous AST is replaced
+//! with that block, so it isn't parsed by the inline parser again.
+//!
+//! Reused blocks are not passed to core rules that run after the block parser
+//! (e.g. typographer or heading anchors), they keep results of previous run instead.
+//! So it's best to do a full parse once editing is done, because rules working
+//! on the whole document (e.g. unique heading ids) only see re-parsed blocks.
+//!
+//! Blocks after the edit are only reused if it doesn't change the number of lines,
+//! so that line numbers stored by core rules (e.g. sourcepos) stay correct.
+//! If edit changes link, footnote or substitution definitions (or other nodes with
+//! [NodeValue::is_definition]), nothing is reused.
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::common::sourcemap::SourcePos;
+use crate::common::TypeKey;
+use crate::{Node, NodeValue, Renderer};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Single edit of the document source: replace bytes in `range` with `text`.
+pub struct TextEdit {
+    /// Byte range in the old source.
+    pub range: Range<usize>,
+    /// New text.
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        Self { range, text: text.into() }
+    }
+
+    /// Apply this edit to the source.
+    ///
+    /// Panics if range is out of bounds or doesn't lie on char boundaries.
+    pub fn apply(&self, src: &mut String) {
+        src.replace_range(self.range.clone(), &self.text);
+    }
+}
+
+#[derive(Debug)]
+// placeholder for a reused block, so other core rules don't see it
+struct ReusedBlock(usize);
+impl NodeValue for ReusedBlock {
+    fn render(&self, _: &Node, _: &mut dyn Renderer) {}
+}
+
+fn get_span(node: &Node) -> Option<(usize, usize)> {
+    node.srcmap.map(|map| map.get_byte_offsets())
+}
+
+pub(crate) struct BlockReuse {
+    // previous top-level blocks that weren't reused yet
+    prev: Vec<Option<Node>>,
+    // (span in new source, type) -> index in `prev`
+    index: HashMap<((usize, usize), TypeKey), usize>,
+    delta: isize,
+    reused: Vec<Node>,
+}
+
+impl BlockReuse {
+    // `prev_src` is the source before the edit
+    pub fn new(mut prev_root: Node, prev_src: &str, edit: &TextEdit) -> Self {
+        let removed = &prev_src[edit.range.clone()];
+        let delta = edit.text.len() as isize - removed.len() as isize;
+        let same_lines = removed.matches('\n').count() == edit.text.matches('\n').count();
+
+        let mut index = HashMap::new();
+        for (idx, node) in prev_root.children.iter().enumerate() {
+            let Some((start, end)) = get_span(node) else { continue; };
+            if end <= edit.range.start {
+                index.insert(((start, end), node.node_type), idx);
+            } else if start >= edit.range.end && same_lines {
+                let span = ((start as isize + delta) as usize, (end as isize + delta) as usize);
+                index.insert((span, node.node_type), idx);
+            }
+        }
+
+        Self {
+            prev: std::mem::take(&mut prev_root.children).into_iter().map(Some).collect(),
+            index,
+            delta,
+            reused: Vec::new(),
+        }
+    }
+
+    // replace matching blocks in the freshly parsed document with placeholders
+    pub fn take_blocks(&mut self, root: &mut Node) {
+        let matches : Vec<_> = root.children.iter().map(|node| {
+            get_span(node).and_then(|span| self.index.get(&(span, node.node_type)).copied())
+        }).collect();
+
+        let unmatched_new = root.children.iter().zip(matches.iter())
+            .filter(|(_, m)| m.is_none()).map(|(node, _)| node);
+        let unmatched_prev = self.prev.iter().enumerate()
+            .filter(|(idx, _)| !matches.contains(&Some(*idx)))
+            .filter_map(|(_, node)| node.as_ref());
+
+        if unmatched_new.chain(unmatched_prev).any(|node| node.node_value.is_definition()) { return; }
+
+        for (node, idx) in root.children.iter_mut().zip(matches) {
+            let Some(idx) = idx else { continue; };
+            let Some(mut prev) = self.prev[idx].take() else { continue; };
+            if self.delta != 0 && get_span(&prev) != get_span(node) {
+                let delta = self.delta;
+                prev.walk_mut(|node, _| {
+                    if let Some((start, end)) = get_span(node) {
+                        let start = (start as isize + delta) as usize;
+                        let end = (end as isize + delta) as usize;
+                        node.srcmap = Some(SourcePos::new(start, end));
+                    }
+                });
+            }
+
+            let mut placeholder = Node::new(ReusedBlock(self.reused.len()));
+            placeholder.srcmap = prev.srcmap;
+            self.reused.push(prev);
+            *node = placeholder;
+        }
+    }
+
+    // put reused blocks back in place of placeholders
+    pub fn restore_blocks(mut self, root: &mut Node) {
+        for node in root.children.iter_mut() {
+            if let Some(&ReusedBlock(idx)) = node.cast::<ReusedBlock>() {
+                *node = std::mem::take(&mut self.reused[idx]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextEdit;
+    use crate::parser::extset::NodeExt;
+
+    #[derive(Debug)]
+    struct Marker;
+    impl NodeExt for Marker {}
+
+    fn md() -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        crate::plugins::sourcepos::add(&mut md);
+        md
+    }
+
+    // returns new AST, checks it against full parse
+    fn edit(src: &str, edit: TextEdit) -> crate::Node {
+        let md = &md();
+        let mut prev = md.parse(src);
+        for node in prev.children.iter_mut() {
+            node.ext.insert(Marker);
+        }
+
+        let mut new_src = src.to_owned();
+        edit.apply(&mut new_src);
+        let ast = md.parse_incremental(prev, &edit);
+        assert_eq!(ast.render(), md.parse(&new_src).render());
+        ast
+    }
+
+    fn reused(ast: &crate::Node) -> Vec<bool> {
+        ast.children.iter().map(|node| node.ext.contains::<Marker>()).collect()
+    }
+
+    #[test]
+    fn apply() {
+        let mut src = "hello world".to_owned();
+        TextEdit::new(6..11, "there").apply(&mut src);
+        TextEdit::new(0..0, "> ").apply(&mut src);
+        assert_eq!(src, "> hello there");
+    }
+
+    #[test]
+    fn same_lines() {
+        let ast = edit("# a\n\nb *c*\n\n- d\n- e\n", TextEdit::new(8..9, "xyz"));
+        assert_eq!(reused(&ast), [true, false, true]);
+        assert_eq!(ast.children[2].srcmap.unwrap().get_byte_offsets(), (14, 21));
+    }
+
+    #[test]
+    fn new_lines() {
+        let ast = edit("# a\n\nb *c*\n\n- d\n- e\n", TextEdit::new(6..7, "\n\n"));
+        assert_eq!(reused(&ast), [true, false, false, false]);
+    }
+
+    #[test]
+    fn merged_blocks() {
+        let ast = edit("a\n\nb\n\nc\n\nd", TextEdit::new(5..5, "x"));
+        assert_eq!(reused(&ast), [true, false, true]);
+        let ast = edit("a\n\n```\nb\n\nc", TextEdit::new(8..8, "```"));
+        assert_eq!(reused(&ast), [true, false]);
+    }
+
+    #[test]
+    fn definitions() {
+        let ast = edit("[a]: /x\n\n[a]\n\ntext", TextEdit::new(6..7, "y"));
+        assert_eq!(reused(&ast), [false, false, false]);
+    }
+
+    #[test]
+    fn cancel() {
+        use crate::parser::cancel::{self, CancelToken};
+        use crate::parser::core::{CoreRule, Root};
+        use crate::parser::options::ParseOptions;
+        use crate::{MarkdownIt, Node};
+
+        struct CancelRule;
+        impl CoreRule for CancelRule {
+            fn run(root: &mut Node, _: &MarkdownIt) {
+                cancel::token(&root.cast::<Root>().unwrap().ext).unwrap().cancel();
+            }
+        }
+
+        struct MarkRule;
+        impl CoreRule for MarkRule {
+            fn run(root: &mut Node, _: &MarkdownIt) {
+                root.ext.insert(Marker);
+            }
+        }
+
+        let md = &mut md();
+        let token = CancelToken::new();
+        let ast = md.parse_with_options("a\n\nb", ParseOptions { cancel: Some(token.clone()), ..Default::default() });
+
+        md.add_rule::<CancelRule>();
+        md.add_rule::<MarkRule>().after::<CancelRule>();
+        let ast = md.parse_incremental(ast, &TextEdit::new(0..1, "c"));
+        assert!(token.is_cancelled());
+        assert!(!ast.ext.contains::<Marker>());
+    }
+
+    #[test]
+    fn fragment() {
+        let md = &md();
+        let ast = md.parse_incremental(md.parse_fragment("*a*"), &TextEdit::new(1..2, "b"));
+        assert_eq!(ast.render(), "<em data-sourcepos=\"1:1-1:3\">b</em>");
+    }
+
+    #[test]
+    fn not_root() {
+        let md = &md();
+        let mut ast = md.parse("*a*");
+        let para = ast.children.remove(0);
+        let ast = md.parse_incremental(para, &TextEdit::new(1..2, "b"));
+        assert_eq!(ast.render(), "<p data-sourcepos=\"1:1-1:3\"><em data-sourcepos=\"1:1-1:3\">a</em></p>\n");
+    }
+
+    #[cfg(feature = "images")]
+    #[test]
+    fn options() {
+        use crate::parser::options::ParseOptions;
+        use crate::plugins::extra::images::{self, ImagesOptions};
+
+        let md = &mut md();
+        images::add(md, ImagesOptions::default());
+        let mut options = ParseOptions::new();
+        options.ext.insert(ImagesOptions { lazy: true, ..Default::default() });
+
+        let ast = md.parse_with_options("![a](a.png)\n\nb", options);
+        let ast = md.parse_incremental(ast, &TextEdit::new(0..0, "x "));
+        assert!(ast.render().contains("loading=\"lazy\""));
+    }
+}
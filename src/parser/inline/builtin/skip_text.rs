@@ -4,7 +4,9 @@
 use regex::{self, Regex};
 
 use crate::parser::inline::{InlineRule, InlineState};
-use crate::{MarkdownIt, Node, NodeValue, Renderer};
+use crate::{EntityMode, MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 /// Plain text AST node.
@@ -16,6 +18,22 @@ impl NodeValue for Text {
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.text(&self.content);
     }
+
+    fn render_format(&self, _: &Node, fmt: &mut dyn Renderer, _: &str) -> bool {
+        // escaping is up to the renderer, so this works for any format
+        fmt.text(&self.content);
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "content": self.content })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { content: field(data, "content")? })
+    }
 }
 
 #[derive(Debug)]
@@ -26,9 +44,49 @@ pub struct TextSpecial {
     pub info: &'static str,
 }
 
+impl TextSpecial {
+    fn render_text(&self, fmt: &mut dyn Renderer) {
+        if self.info != "entity" {
+            fmt.text(&self.content);
+            return;
+        }
+
+        match fmt.ext().get::<EntityMode>().copied().unwrap_or_default() {
+            EntityMode::Decode => fmt.text(&self.content),
+            EntityMode::Raw => fmt.text_raw(&self.markup),
+            EntityMode::Escape => fmt.text(&self.markup),
+        }
+    }
+}
+
 impl NodeValue for TextSpecial {
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
-        fmt.text(&self.content);
+        self.render_text(fmt);
+    }
+
+    fn render_format(&self, _: &Node, fmt: &mut dyn Renderer, _: &str) -> bool {
+        // escaping is up to the renderer, so this works for any format
+        self.render_text(fmt);
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "content": self.content, "markup": self.markup, "info": self.info })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            content: field(data, "content")?,
+            markup: field(data, "markup")?,
+            info: match data.get("info")?.as_str()? {
+                "autolink" => "autolink",
+                "entity" => "entity",
+                "escape" => "escape",
+                _ => "",
+            },
+        })
     }
 }
 
@@ -8,6 +8,12 @@ pub use state::*;
 mod rule;
 pub use rule::*;
 
+mod simple;
+pub use simple::SimpleRuleFn;
+#[doc(hidden)]
+pub use simple::SimpleRuleScanner;
+use simple::SimpleRule;
+
 #[doc(hidden)]
 pub mod builtin;
 
@@ -15,9 +21,10 @@ pub use builtin::inline_parser::InlineRoot;
 pub use builtin::skip_text::{Text, TextSpecial};
 use builtin::skip_text::TextScannerImpl;
 
-use crate::{MarkdownIt, Node};
+use crate::{MarkdownIt, Node, NodeValue};
 use crate::common::TypeKey;
-use crate::common::ruler::Ruler;
+use crate::common::ruler::{Ruler, RulerError};
+use crate::parser::diagnostics;
 use crate::parser::extset::{InlineRootExtSet, RootExtSet};
 
 use super::node::NodeEmpty;
@@ -33,6 +40,7 @@ pub struct InlineParser {
     ruler: Ruler<TypeKey, RuleFns>,
     text_charmap: HashMap<char, Vec<TypeKey>>,
     text_impl: OnceCell<TextScannerImpl>,
+    simple_rules: Vec<SimpleRule>,
 }
 
 impl InlineParser {
@@ -84,7 +92,17 @@ impl InlineParser {
         stacker::maybe_grow(64*1024, 1024*1024, || {
             let end = state.pos_max;
 
+            if state.level >= state.md.max_nesting && state.pos < end {
+                let srcmap = state.get_map(state.pos, end);
+                diagnostics::report_limit(state.md, state.root_ext, "nesting is too deep, content is kept as text", srcmap);
+            }
+
             while state.pos < end {
+                if state.is_cancelled() {
+                    state.pos = end;
+                    break;
+                }
+
                 // Try all possible rules.
                 // On success, rule should:
                 //
@@ -146,7 +164,59 @@ impl InlineParser {
         RuleBuilder::new(item)
     }
 
-    pub fn has_rule<T: InlineRule>(&mut self) -> bool {
+    /// Add a rule for contents between `open` and `close` delimiters, e.g. `==text==`,
+    /// without implementing [InlineRule].
+    ///
+    /// `make` creates a node from the contents, which are also parsed as inline
+    /// markdown into children of that node. Rules are tried in the order they
+    /// were added, before other inline rules.
+    ///
+    /// ```rust
+    /// use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+    ///
+    /// #[derive(Debug)]
+    /// struct Mark(String);
+    ///
+    /// impl NodeValue for Mark {
+    ///     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+    ///         fmt.open("mark", &node.attrs);
+    ///         fmt.contents(&node.children);
+    ///         fmt.close("mark");
+    ///     }
+    /// }
+    ///
+    /// let md = &mut MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    /// md.inline.add_simple("==", "==", |content| Mark(content));
+    ///
+    /// assert_eq!(md.parse("==*marked*==").render(), "<p><mark><em>marked</em></mark></p>\n");
+    /// ```
+    pub fn add_simple<T: NodeValue>(&mut self, open: &str, close: &str, make: impl Fn(String) -> T + Send + Sync + 'static) {
+        assert!(!open.is_empty() && !close.is_empty(), "delimiters of inline rule can't be empty");
+
+        if !self.has_rule::<SimpleRuleScanner>() {
+            self.add_rule::<SimpleRuleScanner>().before_all();
+        }
+
+        let marker = open.chars().next().unwrap();
+        let charvec = self.text_charmap.entry(marker).or_default();
+        if !charvec.contains(&TypeKey::of::<SimpleRuleScanner>()) {
+            charvec.push(TypeKey::of::<SimpleRuleScanner>());
+        }
+
+        self.simple_rules.push(SimpleRule {
+            open: open.to_owned(),
+            close: close.to_owned(),
+            make: Box::new(move |content| Node::new(make(content))),
+        });
+    }
+
+    /// Check that inline rules can be ordered, see [Ruler::validate].
+    pub fn validate_rules(&self) -> Result<(), RulerError<TypeKey>> {
+        self.ruler.validate()
+    }
+
+    pub fn has_rule<T: InlineRule>(&self) -> bool {
         self.ruler.contains(TypeKey::of::<T>())
     }
 
@@ -159,4 +229,13 @@ impl InlineParser {
 
         self.ruler.remove(TypeKey::of::<T>());
     }
+
+    // rules in the order they run, see [MarkdownIt::rules](crate::MarkdownIt::rules)
+    pub(crate) fn rule_marks(&self) -> Vec<(TypeKey, bool)> {
+        self.ruler.marks()
+    }
+
+    pub(crate) fn set_rule_enabled(&mut self, key: TypeKey, enabled: bool) -> bool {
+        self.ruler.set_enabled(key, enabled)
+    }
 }
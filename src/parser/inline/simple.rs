@@ -0,0 +1,146 @@
+//! Inline rules defined by a pair of delimiters and a closure,
+//! see [InlineParser::add_simple](super::InlineParser::add_simple).
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::parser::extset::InlineRootExt;
+use crate::parser::inline::{InlineRule, InlineState};
+use crate::Node;
+
+/// Function that creates a node from contents between delimiters.
+pub type SimpleRuleFn = dyn Fn(String) -> Node + Send + Sync;
+
+pub(super) struct SimpleRule {
+    pub(super) open: String,
+    pub(super) close: String,
+    pub(super) make: Box<SimpleRuleFn>,
+}
+
+impl Debug for SimpleRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimpleRule")
+            .field("open", &self.open)
+            .field("close", &self.close)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+// rule index -> range of the source without closing delimiter
+struct SimpleRuleCache(HashMap<usize, (usize, usize)>);
+impl InlineRootExt for SimpleRuleCache {}
+
+#[doc(hidden)]
+pub struct SimpleRuleScanner;
+
+impl SimpleRuleScanner {
+    // returns rule and length of contents
+    fn find_rule<'a>(state: &mut InlineState<'a, '_>) -> Option<(&'a SimpleRule, usize)> {
+        if state.level >= state.md.max_nesting { return None; }
+
+        let rules = &state.md.inline.simple_rules;
+        rules.iter().enumerate().find_map(|(idx, rule)| {
+            if !state.src[state.pos..state.pos_max].starts_with(rule.open.as_str()) { return None; }
+            let start = state.pos + rule.open.len();
+
+            let cache = state.inline_ext.get_or_insert_default::<SimpleRuleCache>();
+            if let Some(&(from, to)) = cache.0.get(&idx) {
+                if from <= start && state.pos_max <= to { return None; }
+            }
+
+            let Some(content_len) = state.src[start..state.pos_max].find(rule.close.as_str()) else {
+                let cache = state.inline_ext.get_or_insert_default::<SimpleRuleCache>();
+                cache.0.insert(idx, (start, state.pos_max));
+                return None;
+            };
+            (content_len > 0).then_some((rule, content_len))
+        })
+    }
+}
+
+impl InlineRule for SimpleRuleScanner {
+    // markers are registered for each rule separately
+    const MARKER: char = '\0';
+
+    fn check(state: &mut InlineState) -> Option<usize> {
+        let (rule, content_len) = Self::find_rule(state)?;
+        Some(rule.open.len() + content_len + rule.close.len())
+    }
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        let (rule, content_len) = Self::find_rule(state)?;
+
+        let start = state.pos + rule.open.len();
+        let end = start + content_len;
+        let node = (rule.make)(state.src[start..end].to_owned());
+
+        // contents are parsed as children, node can render them or use the string
+        let old_node = std::mem::replace(&mut state.node, node);
+        let (old_pos, old_pos_max) = (state.pos, state.pos_max);
+        state.level += 1;
+        state.pos = start;
+        state.pos_max = end;
+        state.md.inline.tokenize(state);
+        state.pos = old_pos;
+        state.pos_max = old_pos_max;
+        state.level -= 1;
+        let node = std::mem::replace(&mut state.node, old_node);
+
+        Some((node, rule.open.len() + content_len + rule.close.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Mark(String);
+
+    impl NodeValue for Mark {
+        fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+            fmt.open("mark", &[("title".into(), self.0.clone())]);
+            fmt.contents(&node.children);
+            fmt.close("mark");
+        }
+    }
+
+    #[derive(Debug)]
+    struct Kbd(String);
+
+    impl NodeValue for Kbd {
+        fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+            fmt.open("kbd", &[]);
+            fmt.text(&self.0);
+            fmt.close("kbd");
+        }
+    }
+
+    #[test]
+    fn simple_rules() {
+        let md = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        md.inline.add_simple("==", "==", Mark);
+        md.inline.add_simple("[[", "]]", Kbd);
+
+        assert_eq!(md.parse("a ==*b*== [[*c*]]").render(),
+            "<p>a <mark title=\"*b*\"><em>b</em></mark> <kbd>*c*</kbd></p>\n");
+        // not closed or empty
+        assert_eq!(md.parse("==a\n\n==== [[]]").render(), "<p>==a</p>\n<p>==== [[]]</p>\n");
+        // links don't end inside of a rule
+        assert_eq!(md.parse("[a ==]==](/url)").render(), "<p><a href=\"/url\">a <mark title=\"]\">]</mark></a></p>\n");
+        assert_eq!(md.parse("==a ==b").render(), "<p><mark title=\"a \">a </mark>b</p>\n");
+        assert_eq!(md.parse("==a [[b ==c").render(), "<p><mark title=\"a [[b \">a [[b </mark>c</p>\n");
+    }
+
+    #[test]
+    fn max_nesting() {
+        let md = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        md.inline.add_simple("((", "))", Mark);
+        assert_eq!(md.parse("[((a))](/u)").render(), "<p><a href=\"/u\"><mark title=\"a\">a</mark></a></p>\n");
+
+        md.max_nesting = 1;
+        assert_eq!(md.parse("[((a))](/u)").render(), "<p><a href=\"/u\">((a))</a></p>\n");
+    }
+}
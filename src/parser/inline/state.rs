@@ -2,6 +2,7 @@
 //
 use crate::common::sourcemap::SourcePos;
 use crate::common::utils::is_punct_char;
+use crate::parser::cancel::{self, CancelToken};
 use crate::parser::extset::{InlineRootExtSet, RootExtSet};
 use crate::parser::inline::Text;
 use crate::{MarkdownIt, Node};
@@ -55,6 +56,8 @@ pub struct InlineState<'a, 'b> where 'b: 'a {
 
     /// Counter used to prevent recursion by image and link rules.
     pub level: u32,
+
+    cancel: Option<CancelToken>,
 }
 
 impl<'a, 'b> InlineState<'a, 'b> {
@@ -66,6 +69,7 @@ impl<'a, 'b> InlineState<'a, 'b> {
         inline_ext: &'b mut InlineRootExtSet,
         node: Node,
     ) -> Self {
+        let cancel = cancel::token(root_ext).cloned();
         let mut result = Self {
             pos:        0,
             pos_max:    src.len(),
@@ -77,6 +81,7 @@ impl<'a, 'b> InlineState<'a, 'b> {
             node,
             link_level: 0,
             level:      0,
+            cancel,
         };
 
         result.trim_src();
@@ -93,6 +98,11 @@ impl<'a, 'b> InlineState<'a, 'b> {
         }
     }
 
+    /// True if parsing was cancelled (see [cancel](crate::parser::cancel)).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
     pub fn trailing_text_push(&mut self, start: usize, end: usize) {
         if let Some(text) = self.node.children.last_mut()
                                        .and_then(|t| t.cast_mut::<Text>()) {
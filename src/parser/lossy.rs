@@ -0,0 +1,140 @@
+//! Parse input that isn't guaranteed to be valid UTF-8 (user uploads, legacy files).
+//!
+//! Invalid byte sequences are replaced according to [Utf8Policy], and positions
+//! of all replacements are kept in the document, so they can be reported to the user.
+//!
+//! ```rust
+//! use markdown_it::parser::lossy::{self, Utf8Policy};
+//! use markdown_it::parser::options::ParseOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse_with_options(b"*caf\xE9*", ParseOptions::new());
+//! assert_eq!(ast.render(), "<p><em>caf\u{FFFD}</em></p>\n");
+//!
+//! let replacements = lossy::replacements(&ast);
+//! assert_eq!(replacements.len(), 1);
+//! assert_eq!(replacements[0].to_string(), "invalid utf-8 sequence at byte 4");
+//!
+//! // legacy files are often in latin-1
+//! let options = ParseOptions { utf8_policy: Utf8Policy::Latin1, ..Default::default() };
+//! let ast = md.parse_with_options(b"*caf\xE9*", options);
+//! assert_eq!(ast.render(), "<p><em>café</em></p>\n");
+//! ```
+use std::fmt::{self, Display};
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::Root;
+use crate::parser::extset::RootExt;
+use crate::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What to do with invalid UTF-8 sequences.
+pub enum Utf8Policy {
+    /// Replace each invalid sequence with a character, U+FFFD by default
+    /// (same as [String::from_utf8_lossy]).
+    Replace(char),
+    /// Remove invalid sequences.
+    Skip,
+    /// Decode each invalid byte as ISO-8859-1 (Latin-1) character.
+    Latin1,
+}
+
+impl Default for Utf8Policy {
+    fn default() -> Self {
+        Self::Replace(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Invalid UTF-8 sequence that was replaced.
+pub struct Utf8Replacement {
+    /// Byte offset of the invalid sequence in the input.
+    pub offset: usize,
+    /// Length of the invalid sequence in bytes.
+    pub len: usize,
+    /// Position of the replacement in decoded source (empty if sequence was skipped).
+    pub srcmap: SourcePos,
+}
+
+impl Display for Utf8Replacement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid utf-8 sequence at byte {}", self.offset)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Utf8Replacements(Vec<Utf8Replacement>);
+impl RootExt for Utf8Replacements {}
+
+/// Decode bytes as UTF-8, replacing invalid sequences according to `policy`.
+pub fn decode(src: &[u8], policy: Utf8Policy) -> (String, Vec<Utf8Replacement>) {
+    let mut result = String::with_capacity(src.len());
+    let mut replacements = Vec::new();
+    let mut offset = 0;
+
+    for chunk in src.utf8_chunks() {
+        result.push_str(chunk.valid());
+        offset += chunk.valid().len();
+
+        let invalid = chunk.invalid();
+        if invalid.is_empty() { continue; }
+
+        let start = result.len();
+        match policy {
+            Utf8Policy::Replace(ch) => result.push(ch),
+            Utf8Policy::Skip => {}
+            Utf8Policy::Latin1 => result.extend(invalid.iter().map(|&byte| char::from(byte))),
+        }
+        replacements.push(Utf8Replacement {
+            offset,
+            len: invalid.len(),
+            srcmap: SourcePos::new(start, result.len()),
+        });
+        offset += invalid.len();
+    }
+
+    (result, replacements)
+}
+
+pub(crate) fn set_replacements(root: &mut Node, replacements: Vec<Utf8Replacement>) {
+    if replacements.is_empty() { return; }
+    if let Some(data) = root.cast_mut::<Root>() {
+        data.ext.insert(Utf8Replacements(replacements));
+    }
+}
+
+/// Invalid UTF-8 sequences replaced in a document parsed with
+/// [MarkdownIt::parse_with_options](crate::MarkdownIt::parse_with_options).
+pub fn replacements(root: &Node) -> &[Utf8Replacement] {
+    root.cast::<Root>()
+        .and_then(|root| root.ext.get::<Utf8Replacements>())
+        .map(|replacements| replacements.0.as_slice())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Utf8Policy};
+
+    #[test]
+    fn policies() {
+        let src = b"a\xF0\x9F\x98b\xFF\xFEc\xE2\x82\xAC";
+        assert_eq!(decode(src, Utf8Policy::default()).0, "a\u{FFFD}b\u{FFFD}\u{FFFD}c€");
+        assert_eq!(decode(src, Utf8Policy::Replace('?')).0, "a?b??c€");
+        assert_eq!(decode(src, Utf8Policy::Skip).0, "abc€");
+        assert_eq!(decode(src, Utf8Policy::Latin1).0, "a\u{F0}\u{9F}\u{98}b\u{FF}\u{FE}c€");
+
+        let (_, replacements) = decode(src, Utf8Policy::Replace('?'));
+        let positions : Vec<_> = replacements.iter().map(|r| (r.offset, r.len, r.srcmap.get_byte_offsets())).collect();
+        assert_eq!(positions, [(1, 3, (1, 2)), (5, 1, (3, 4)), (6, 1, (4, 5))]);
+    }
+
+    #[test]
+    fn valid() {
+        let (result, replacements) = decode("ä€😀".as_bytes(), Utf8Policy::Skip);
+        assert_eq!(result, "ä€😀");
+        assert!(replacements.is_empty());
+    }
+}
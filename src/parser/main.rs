@@ -1,13 +1,22 @@
 use derivative::Derivative;
 
-use crate::common::ruler::Ruler;
+use crate::common::ruler::{Ruler, RulerError};
 use crate::common::sourcemap::SourcePos;
 use crate::common::TypeKey;
+use crate::parser::block::builtin::BlockParserRule;
 use crate::parser::block::{self, BlockParser};
+use crate::parser::cancel;
 use crate::parser::core::{Root, *};
 use crate::parser::extset::MarkdownItExtSet;
+use crate::parser::incremental::{BlockReuse, TextEdit};
 use crate::parser::inline::{self, InlineParser};
 use crate::parser::linkfmt::{LinkFormatter, MDLinkFormatter};
+use crate::parser::lossy;
+use crate::parser::options::{self, ParseOptions};
+use crate::parser::rules::{self, RuleChain, RuleInfo, RuleNames, UnknownRule};
+use crate::parser::stream::ParserStream;
+use crate::plugins::preset::Preset;
+use crate::plugins::PluginId;
 use crate::Node;
 
 type RuleFn = fn (&mut Node, &MarkdownIt);
@@ -28,17 +37,30 @@ pub struct MarkdownIt {
     /// Storage for custom data used in plugins.
     pub ext: MarkdownItExtSet,
 
-    /// Maximum depth of the generated AST, exists to prevent recursion
-    /// (if markdown source reaches this depth, deeply nested structures
-    /// will be parsed as plain text).
-    /// TODO: doesn't work
-    #[doc(hidden)]
+    /// Maximum nesting depth of block containers (blockquotes, lists) and
+    /// inline containers (link labels), default 100. Deeper structures
+    /// are kept as plain text, this protects against pathological input.
     pub max_nesting: u32,
 
+    /// Maximum size of the input in bytes, default usize::MAX. Anything after
+    /// the last line that fits is kept as plain text without parsing.
+    pub max_input_size: usize,
+
     /// Maximum allowed indentation for syntax blocks
     /// default i32::MAX, indented code blocks will set this to 4
     pub max_indent: i32,
 
+    /// Width of tab stops used to calculate indentation of blocks, default 4
+    /// as CommonMark requires (ignored in [strict](Self::strict) mode).
+    pub tab_width: usize,
+
+    /// Follow CommonMark strictly, default false, set by [strict](crate::plugins::cmark::strict)
+    /// plugin. Plugins should ignore their options that aren't in the spec, and output
+    /// that differs from it because of parser limits ([max_nesting](Self::max_nesting),
+    /// [max_input_size](Self::max_input_size)) is reported as a `commonmark-limit`
+    /// [diagnostic](crate::parser::diagnostics).
+    pub strict: bool,
+
     ruler: Ruler<TypeKey, RuleFn>,
 }
 
@@ -47,13 +69,169 @@ impl MarkdownIt {
         Self::default()
     }
 
+    /// Create parser with built-in plugins selected at runtime
+    /// (see [registry](crate::plugins::registry)).
+    pub fn with_plugins(plugins: &[PluginId]) -> Self {
+        let mut md = Self::new();
+        for plugin in plugins {
+            plugin.add(&mut md);
+        }
+        md
+    }
+
+    /// Create parser with plugins of a [preset](crate::plugins::preset).
+    pub fn from_preset(preset: Preset) -> Self {
+        let mut md = Self::new();
+        preset.add(&mut md);
+        md
+    }
+
     pub fn parse(&self, src: &str) -> Node {
-        let mut node = Node::new(Root::new(src.to_owned()));
-        node.srcmap = Some(SourcePos::new(0, src.len()));
+        self.parse_root(Root::new(src.to_owned()))
+    }
+
+    /// Parse document with per-document settings (base url, locale, flags, plugin
+    /// options, shared link references, cancellation), see [options](crate::parser::options).
+    ///
+    /// Source may contain invalid UTF-8, it's replaced according to
+    /// [ParseOptions::utf8_policy] (see [lossy](crate::parser::lossy)),
+    /// source maps in the resulting AST refer to the decoded source.
+    pub fn parse_with_options(&self, src: impl AsRef<[u8]>, options: ParseOptions) -> Node {
+        let (src, replacements) = match std::str::from_utf8(src.as_ref()) {
+            Ok(src) => (src.to_owned(), Vec::new()),
+            Err(_) => lossy::decode(src.as_ref(), options.utf8_policy),
+        };
+        let mut root = Root::new(src);
+        options::set_options(&mut root.ext, options);
+        let mut node = self.parse_root(root);
+        lossy::set_replacements(&mut node, replacements);
+        node
+    }
+
+    /// Parse a snippet of text that shouldn't be treated as a document
+    /// (e.g. table cell content or UI string): no block-level structure is
+    /// inferred, so the result isn't wrapped in a paragraph.
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// assert_eq!(md.parse("# *hello*").render(), "<h1><em>hello</em></h1>\n");
+    /// assert_eq!(md.parse_fragment("# *hello*").render(), "# <em>hello</em>");
+    /// ```
+    pub fn parse_fragment(&self, src: &str) -> Node {
+        self.parse_root(Root::fragment(src.to_owned()))
+    }
+
+    /// Parse inline markup only (same as `parseInline` in markdown-it.js), e.g. for
+    /// titles and photo captions. Block parsing is skipped, so the children of
+    /// returned root are inline nodes, see [parse_fragment](Self::parse_fragment).
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let ast = md.parse_inline("1. *Sunset* > [Lisbon](/lisbon)");
+    /// assert_eq!(ast.render(), "1. <em>Sunset</em> &gt; <a href=\"/lisbon\">Lisbon</a>");
+    /// assert_eq!(ast.children[1].name(), "markdown_it::plugins::cmark::inline::emphasis::Em");
+    /// ```
+    pub fn parse_inline(&self, src: &str) -> Node {
+        self.parse_fragment(src)
+    }
+
+    /// Parse many documents on multiple threads using rayon (requires `rayon` feature),
+    /// results are returned in input order.
+    ///
+    /// Use [Build](crate::build::Build) to also render and check documents
+    /// without keeping all ASTs in memory.
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let pages = md.parse_many(&["# Home", "*About*"]);
+    /// assert_eq!(pages[1].render(), "<p><em>About</em></p>\n");
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn parse_many(&self, sources: &[&str]) -> Vec<Node> {
+        use rayon::prelude::*;
+        sources.par_iter().map(|src| self.parse(src)).collect()
+    }
+
+    /// Create a stream to feed document source in chunks, see [ParserStream].
+    pub fn stream(&self) -> ParserStream<'_> {
+        ParserStream::new(self)
+    }
+
+    /// Parse document after an edit, reusing unchanged blocks of the previous AST
+    /// (see [incremental](crate::parser::incremental) for details).
+    ///
+    /// If `prev` doesn't have [Root] node at the top, it is returned unchanged.
+    ///
+    /// ```rust
+    /// use markdown_it::parser::incremental::TextEdit;
+    ///
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let ast = md.parse("# title\n\nhello *world*");
+    /// let ast = md.parse_incremental(ast, &TextEdit::new(2..7, "heading"));
+    /// assert_eq!(ast.render(), "<h1>heading</h1>\n<p>hello <em>world</em></p>\n");
+    /// ```
+    pub fn parse_incremental(&self, mut prev: Node, edit: &TextEdit) -> Node {
+        let Some(data) = prev.cast_mut::<Root>() else {
+            return prev;
+        };
+
+        let prev_src = std::mem::take(&mut data.content);
+        let prev_ext = std::mem::take(&mut data.ext);
+        let mut src = prev_src.clone();
+        edit.apply(&mut src);
+
+        let mut root = if data.fragment { Root::fragment(src) } else { Root::new(src) };
+        // document is parsed with the same options (results of previous parse aren't kept)
+        options::share_options(&prev_ext, &mut root.ext);
+
+        if root.fragment {
+            return self.parse_root(root);
+        }
+
+        let mut reuse = BlockReuse::new(prev, &prev_src, edit);
+        let mut node = self.run_core_rules(root, |key, rule, node| {
+            rule(node, self);
+            if key == TypeKey::of::<BlockParserRule>() {
+                reuse.take_blocks(node);
+            }
+        });
+
+        reuse.restore_blocks(&mut node);
+        node
+    }
 
-        for rule in self.ruler.iter() {
-            rule(&mut node, self);
+    pub(crate) fn parse_root(&self, root: Root) -> Node {
+        self.run_core_rules(root, |_, rule, node| rule(node, self))
+    }
+
+    // same as parse_root, reporting time spent in each core rule
+    #[cfg(feature = "bench")]
+    pub(crate) fn parse_profiled(&self, src: &str, mut on_rule: impl FnMut(TypeKey, std::time::Duration)) -> Node {
+        self.run_core_rules(Root::new(src.to_owned()), |key, rule, node| {
+            let start = std::time::Instant::now();
+            rule(node, self);
+            on_rule(key, start.elapsed());
+        })
+    }
+
+    // run enabled core rules on a new document, `run_rule` is called for each of them
+    fn run_core_rules(&self, root: Root, mut run_rule: impl FnMut(TypeKey, RuleFn, &mut Node)) -> Node {
+        let src_len = root.content.len();
+        let mut node = Node::new(root);
+        node.srcmap = Some(SourcePos::new(0, src_len));
+
+        for (key, rule) in self.core_rules() {
+            run_rule(key, *rule, &mut node);
             debug_assert!(node.is::<Root>(), "root node of the AST must always be Root");
+            if cancel::is_cancelled(&node.cast::<Root>().unwrap().ext) { break; }
         }
         node
     }
@@ -63,15 +241,89 @@ impl MarkdownIt {
         RuleBuilder::new(item)
     }
 
-    pub fn has_rule<T: CoreRule>(&mut self) -> bool {
+    /// Check that core, block and inline rules can be ordered according to their
+    /// constraints (e.g. [before](RuleBuilder::before) and [after](RuleBuilder::after)),
+    /// so conflicting plugins are reported before the first document is parsed
+    /// (which would panic instead).
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    /// markdown_it::plugins::extra::add(md);
+    /// assert!(md.validate_rules().is_ok());
+    /// ```
+    pub fn validate_rules(&self) -> Result<(), RulerError<TypeKey>> {
+        self.ruler.validate()?;
+        self.block.validate_rules()?;
+        self.inline.validate_rules()
+    }
+
+    pub fn has_rule<T: CoreRule>(&self) -> bool {
         self.ruler.contains(TypeKey::of::<T>())
     }
 
     pub fn remove_rule<T: CoreRule>(&mut self) {
         self.ruler.remove(TypeKey::of::<T>());
     }
+
+    // enabled core rules in the order they run, along with their rule type
+    fn core_rules(&self) -> impl Iterator<Item = (TypeKey, &RuleFn)> {
+        let keys = self.ruler.marks().into_iter().filter(|(_, enabled)| *enabled).map(|(key, _)| key);
+        keys.zip(self.ruler.iter())
+    }
+
+    /// All core, block and inline rules in the order they run, see [rules](crate::parser::rules).
+    pub fn rules(&self) -> Vec<RuleInfo> {
+        let chains = [
+            (RuleChain::Core, self.ruler.marks()),
+            (RuleChain::Block, self.block.rule_marks()),
+            (RuleChain::Inline, self.inline.rule_marks()),
+        ];
+
+        chains.into_iter().flat_map(|(chain, marks)| {
+            marks.into_iter().map(move |(key, enabled)| RuleInfo { name: rules::rule_name_in(self, key), chain, key, enabled })
+        }).collect()
+    }
+
+    /// Enable rules with given names (e.g. `md.enable(&["image", "linkify"])`),
+    /// see [rules](crate::parser::rules).
+    pub fn enable(&mut self, names: impl RuleNames) -> Result<(), UnknownRule> {
+        self.set_rules_enabled(&names.rule_names(), true)
+    }
+
+    /// Disable rules with given names (e.g. `md.disable("html_block")`),
+    /// see [rules](crate::parser::rules).
+    pub fn disable(&mut self, names: impl RuleNames) -> Result<(), UnknownRule> {
+        self.set_rules_enabled(&names.rule_names(), false)
+    }
+
+    fn set_rules_enabled(&mut self, names: &[&str], enabled: bool) -> Result<(), UnknownRule> {
+        let rules = self.rules();
+
+        if let Some(name) = names.iter().find(|name| !rules.iter().any(|rule| rule.name == **name)) {
+            return Err(UnknownRule(name.to_string()));
+        }
+
+        for rule in rules.iter().filter(|rule| names.contains(&rule.name)) {
+            match rule.chain {
+                RuleChain::Core => self.ruler.set_enabled(rule.key, enabled),
+                RuleChain::Block => self.block.set_rule_enabled(rule.key, enabled),
+                RuleChain::Inline => self.inline.set_rule_enabled(rule.key, enabled),
+            };
+        }
+
+        Ok(())
+    }
 }
 
+// parser is configured once and shared between threads, documents
+// parsed on worker threads are sent back to the caller
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MarkdownIt>();
+    assert_send_sync::<Node>();
+};
+
 impl Default for MarkdownIt {
     fn default() -> Self {
         let mut md = Self {
@@ -82,9 +334,45 @@ impl Default for MarkdownIt {
             max_nesting: 100,
             ruler: Ruler::new(),
             max_indent: i32::MAX,
+            tab_width: 4,
+            max_input_size: usize::MAX,
+            strict: false,
         };
         block::builtin::add(&mut md);
         inline::builtin::add(&mut md);
         md
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parse_many() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let sources : Vec<_> = (0..50).map(|n| format!("*{n}*")).collect();
+        let sources : Vec<_> = sources.iter().map(String::as_str).collect();
+        let pages = md.parse_many(&sources);
+        assert_eq!(pages.len(), 50);
+        for (n, page) in pages.iter().enumerate() {
+            assert_eq!(page.render(), format!("<p><em>{n}</em></p>\n"));
+        }
+
+        assert!(md.parse_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn parse_inline() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        #[cfg(feature = "footnote")]
+        crate::plugins::extra::footnote::add(md);
+
+        // block syntax (including definitions) is kept as text
+        let ast = md.parse_inline("    a\n[b]: /c\n\n[b] `d`");
+        assert!(ast.children.iter().all(|node| node.srcmap.is_some()));
+        assert_eq!(ast.render(), "a\n[b]: /c\n[b] <code>d</code>");
+    }
+}
@@ -0,0 +1,415 @@
+//! Convert a document back to markdown, see [Node::render_markdown].
+//!
+//! Conversion is done from the html [events](crate::EventRenderer) of the nodes,
+//! so it works for any plugin that renders html elements with markdown
+//! equivalents (paragraphs, headings, lists, emphasis, links, tables, etc.).
+//! Other elements are replaced with their contents, raw html is kept as is.
+//! Result is normalized: `*` for emphasis, `-` for bullet lists, fenced code
+//! blocks, and so on.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("Title\n=====\n\n+ _one_\n+ __two__\n\ntext\n\n    code");
+//! assert_eq!(ast.render_markdown(), "# Title\n\n- *one*\n- **two**\n\ntext\n\n```\ncode\n```\n");
+//! ```
+use crate::{EventRenderer, Node, RenderEvent};
+
+const BLOCK_TAGS : &[&str] = &[
+    "address", "article", "aside", "blockquote", "dd", "details", "div", "dl", "dt",
+    "figcaption", "figure", "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr",
+    "li", "nav", "ol", "p", "pre", "section", "summary", "table", "tbody", "td", "tfoot",
+    "th", "thead", "tr", "ul",
+];
+
+#[derive(Debug)]
+enum Item {
+    Element { tag: String, attrs: Vec<(String, String)>, children: Vec<Item> },
+    Text(String),
+    Raw(String),
+    Cr,
+}
+
+impl Item {
+    fn is_block(&self) -> bool {
+        match self {
+            Item::Element { tag, .. } => BLOCK_TAGS.contains(&tag.as_str()),
+            Item::Raw(raw) => raw.ends_with('\n'),
+            _ => false,
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        let Item::Element { attrs, .. } = self else { return None; };
+        attrs.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn children(&self) -> &[Item] {
+        match self {
+            Item::Element { children, .. } => children,
+            _ => &[],
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Item::Element { children, .. } => children.iter().map(Item::text).collect(),
+            Item::Text(text) | Item::Raw(text) => text.clone(),
+            Item::Cr => String::new(),
+        }
+    }
+}
+
+fn build_tree(node: &Node) -> Vec<Item> {
+    // stack of open elements, the first one is a document
+    let mut stack : Vec<(String, Vec<(String, String)>, Vec<Item>)> = vec![(String::new(), Vec::new(), Vec::new())];
+
+    EventRenderer::new(|event| match event {
+        RenderEvent::Open { tag, attrs } => stack.push((tag, attrs, Vec::new())),
+        RenderEvent::Close { tag } => {
+            // unbalanced events are closed up to the matching tag
+            if !stack[1..].iter().any(|(open, _, _)| *open == tag) { return; }
+            loop {
+                let (open, attrs, children) = stack.pop().unwrap();
+                let done = open == tag;
+                stack.last_mut().unwrap().2.push(Item::Element { tag: open, attrs, children });
+                if done { break; }
+            }
+        }
+        RenderEvent::SelfClose { tag, attrs } => {
+            stack.last_mut().unwrap().2.push(Item::Element { tag, attrs, children: Vec::new() });
+        }
+        RenderEvent::Text(text) => {
+            // escaping depends on the next character, so text is merged
+            let children = &mut stack.last_mut().unwrap().2;
+            match children.last_mut() {
+                Some(Item::Text(last)) => last.push_str(&text),
+                _ => children.push(Item::Text(text)),
+            }
+        }
+        RenderEvent::TextRaw(text) => stack.last_mut().unwrap().2.push(Item::Raw(text)),
+        RenderEvent::Cr => stack.last_mut().unwrap().2.push(Item::Cr),
+    }).render(node);
+
+    while stack.len() > 1 {
+        let (tag, attrs, children) = stack.pop().unwrap();
+        stack.last_mut().unwrap().2.push(Item::Element { tag, attrs, children });
+    }
+    stack.pop().unwrap().2
+}
+
+// writes blocks separated by blank lines (or line breaks in tight lists),
+// inline content between blocks is written as a paragraph
+fn write_blocks(items: &[Item], tight: bool) -> String {
+    let mut blocks = Vec::new();
+    let mut inline = Vec::new();
+
+    let flush = |inline: &mut Vec<&Item>, blocks: &mut Vec<String>| {
+        let text = write_paragraph(inline.drain(..));
+        if !text.is_empty() { blocks.push(text); }
+    };
+
+    let mut prev_tag = None;
+    for item in items {
+        if item.is_block() {
+            flush(&mut inline, &mut blocks);
+            // adjacent lists of the same type need different markers, otherwise they are merged
+            let tag = match item {
+                Item::Element { tag, .. } if tag == "ul" || tag == "ol" => Some(tag.as_str()),
+                _ => None,
+            };
+            let alternate = tag.is_some() && tag == prev_tag && !alternate_prev(&blocks);
+            let block = write_block(item, alternate);
+            prev_tag = tag;
+            if !block.is_empty() { blocks.push(block); }
+        } else {
+            if !matches!(item, Item::Cr) { prev_tag = None; }
+            inline.push(item);
+        }
+    }
+    flush(&mut inline, &mut blocks);
+
+    blocks.join(if tight { "\n" } else { "\n\n" })
+}
+
+// whether the last written list uses alternative markers
+fn alternate_prev(blocks: &[String]) -> bool {
+    blocks.last().is_some_and(|block| {
+        let marker = block.trim_start_matches(|ch: char| ch.is_ascii_digit());
+        marker.starts_with('*') || marker.starts_with(')')
+    })
+}
+
+fn write_paragraph<'a>(items: impl Iterator<Item = &'a Item>) -> String {
+    let text : String = items.map(write_inline).collect();
+    let text = text.trim_matches('\n');
+    text.lines()
+        .map(|line| escape_line_start(line.trim_start_matches([' ', '\t'])))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// escape characters that would start a block at the beginning of a line
+fn escape_line_start(line: &str) -> String {
+    let digits = line.len() - line.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+    let rest = &line[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ") || rest == "." || rest == ")") {
+        return format!("{}\\{}", &line[..digits], rest);
+    }
+    if line.starts_with(['#', '+', '-', '=', '>']) {
+        return format!("\\{line}");
+    }
+    line.to_owned()
+}
+
+fn escape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '~' | '|' => result.push('\\'),
+            '\n' => { result.push_str("&#10;"); continue; }
+            '&' if chars.peek().is_some_and(|ch| ch.is_ascii_alphanumeric() || *ch == '#') => result.push('\\'),
+            _ => {}
+        }
+        result.push(ch);
+    }
+    result
+}
+
+fn prefix_lines(text: &str, first: &str, rest: &str) -> String {
+    text.lines().enumerate().map(|(idx, line)| {
+        let prefix = if idx == 0 { first } else { rest };
+        if line.is_empty() { prefix.trim_end().to_owned() } else { format!("{prefix}{line}") }
+    }).collect::<Vec<_>>().join("\n")
+}
+
+fn fence(content: &str, marker: char, min_len: usize) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in content.chars() {
+        current = if ch == marker { current + 1 } else { 0 };
+        longest = longest.max(current);
+    }
+    marker.to_string().repeat(min_len.max(longest + 1))
+}
+
+fn link_destination(url: &str) -> String {
+    if url.is_empty() || url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_owned()
+    }
+}
+
+fn link_title(item: &Item) -> String {
+    match item.attr("title") {
+        Some(title) => format!(" \"{}\"", title.replace('\\', "\\\\").replace('"', "\\\"")),
+        None => String::new(),
+    }
+}
+
+fn write_inline(item: &Item) -> String {
+    let contents = || item.children().iter().map(write_inline).collect::<String>();
+    match item {
+        Item::Text(text) => escape_text(text),
+        Item::Raw(raw) => raw.clone(),
+        Item::Cr => "\n".into(),
+        Item::Element { tag, .. } => match tag.as_str() {
+            "em" | "i" => {
+                // `*` next to another `*` would be parsed as strong emphasis
+                let contents = contents();
+                let marker = if contents.starts_with('*') || contents.ends_with('*') { '_' } else { '*' };
+                format!("{marker}{contents}{marker}")
+            }
+            "strong" | "b" => format!("**{}**", contents()),
+            "s" | "del" => format!("~~{}~~", contents()),
+            "br" => "\\".into(),
+            "code" => {
+                let code = item.text();
+                let marker = fence(&code, '`', 1);
+                let pad = if code.starts_with(['`', ' ']) || code.ends_with(['`', ' ']) { " " } else { "" };
+                format!("{marker}{pad}{code}{pad}{marker}")
+            }
+            "img" => format!(
+                "![{}]({}{})",
+                escape_text(item.attr("alt").unwrap_or_default()),
+                link_destination(item.attr("src").unwrap_or_default()),
+                link_title(item),
+            ),
+            "a" => {
+                let href = item.attr("href").unwrap_or_default();
+                let text = item.text();
+                if item.attr("title").is_none() && (href == text || href.strip_prefix("mailto:") == Some(&text)) && !text.is_empty() {
+                    format!("<{text}>")
+                } else {
+                    format!("[{}]({}{})", contents(), link_destination(href), link_title(item))
+                }
+            }
+            _ => contents(),
+        },
+    }
+}
+
+fn write_list_item(item: &Item, marker: &str, tight: bool) -> String {
+    let content = write_blocks(item.children(), tight);
+    let indent = " ".repeat(marker.len() + 1);
+    if content.is_empty() { return marker.to_owned(); }
+    prefix_lines(&content, &format!("{marker} "), &indent)
+}
+
+fn write_table(item: &Item) -> String {
+    let mut rows = Vec::new();
+    let mut alignments = Vec::new();
+    let mut collect_rows = |item: &Item| {
+        for row in item.children().iter().filter(|row| matches!(row, Item::Element { tag, .. } if tag == "tr")) {
+            let cells : Vec<_> = row.children().iter().filter(|cell| cell.is_block()).collect();
+            if rows.is_empty() {
+                alignments = cells.iter().map(|cell| match cell.attr("style") {
+                    Some("text-align:left") => ":--",
+                    Some("text-align:right") => "--:",
+                    Some("text-align:center") => ":-:",
+                    _ => "---",
+                }).collect();
+            }
+            rows.push(cells.iter().map(|cell| {
+                write_paragraph(cell.children().iter()).replace('\n', " ")
+            }).collect::<Vec<_>>());
+        }
+    };
+
+    for part in item.children() {
+        match part {
+            Item::Element { tag, .. } if tag == "tr" => collect_rows(item),
+            Item::Element { .. } => collect_rows(part),
+            _ => {}
+        }
+    }
+
+    let Some(head) = rows.first() else { return String::new(); };
+    let line = |cells: &[String]| format!("| {} |", cells.join(" | "));
+    let mut result = vec![line(head), format!("| {} |", alignments.join(" | "))];
+    result.extend(rows[1..].iter().map(|row| line(row)));
+    result.join("\n")
+}
+
+fn write_block(item: &Item, alternate: bool) -> String {
+    let Item::Element { tag, children, .. } = item else {
+        // raw html block
+        return item.text().trim_end_matches('\n').to_owned();
+    };
+
+    match tag.as_str() {
+        "p" => write_paragraph(children.iter()),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = tag[1..].parse().unwrap_or(1);
+            let mut text = write_paragraph(children.iter()).replace('\n', " ");
+            // trailing `#` would be parsed as a closing sequence
+            if let Some(pos) = text.strip_suffix('#').map(|rest| rest.trim_end_matches('#').len()) {
+                text.insert(pos, '\\');
+            }
+            format!("{} {}", "#".repeat(level), text)
+        }
+        "hr" => "***".into(),
+        "blockquote" => {
+            let content = write_blocks(children, false);
+            if content.is_empty() { ">".into() } else { prefix_lines(&content, "> ", "> ") }
+        }
+        "pre" => {
+            let code = children.iter().find(|child| matches!(child, Item::Element { tag, .. } if tag == "code"));
+            let lang = code.and_then(|code| code.attr("class"))
+                .and_then(|class| class.split_whitespace().find_map(|class| class.strip_prefix("language-")))
+                .unwrap_or_default();
+            let mut content = item.text();
+            if !content.is_empty() && !content.ends_with('\n') { content.push('\n'); }
+            let marker = fence(&content, '`', 3);
+            format!("{marker}{lang}\n{content}{marker}")
+        }
+        "ul" | "ol" => {
+            let items : Vec<_> = children.iter().filter(|child| child.is_block()).collect();
+            let tight = !items.iter().any(|item| item.children().iter().any(|child| {
+                matches!(child, Item::Element { tag, .. } if tag == "p")
+            }));
+            let mut number = item.attr("start").and_then(|start| start.parse::<u32>().ok()).unwrap_or(1);
+            let items : Vec<_> = items.iter().map(|item| {
+                let marker = match (tag.as_str(), alternate) {
+                    ("ol", false) => format!("{number}."),
+                    ("ol", true) => format!("{number})"),
+                    (_, false) => "-".to_owned(),
+                    (_, true) => "*".to_owned(),
+                };
+                number += 1;
+                write_list_item(item, &marker, tight)
+            }).collect();
+            items.join(if tight { "\n" } else { "\n\n" })
+        }
+        "table" => write_table(item),
+        _ => write_blocks(children, false),
+    }
+}
+
+pub(crate) fn render_markdown(node: &Node) -> String {
+    let result = write_blocks(&build_tree(node), false);
+    if result.is_empty() { result } else { result + "\n" }
+}
+
+#[cfg(all(test, feature = "strikethrough", feature = "tables"))]
+mod tests {
+    fn convert(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        crate::plugins::extra::tables::add(md);
+        crate::plugins::extra::strikethrough::add(md);
+        md.parse(src).render_markdown()
+    }
+
+    #[test]
+    fn inline() {
+        assert_eq!(convert("_a_ __b__ ~~c~~ `` d`e `` <span>x</span>"), "*a* **b** ~~c~~ ``d`e`` <span>x</span>\n");
+        assert_eq!(convert("[a *b*](/u \"t\") ![c](<d e.png>) <http://x.org>"), "[a *b*](/u \"t\") ![c](d%20e.png) <http://x.org>\n");
+        assert_eq!(convert("a\\*b\\_ &amp;c 1\\. x\\\ny\nz"), "a\\*b\\_ \\&c 1. x\\\ny\nz\n");
+        assert_eq!(convert("\\# a\n\\- b\n1\\. c"), "\\# a\n\\- b\n1\\. c\n");
+        assert_eq!(convert("*_a_* a&#10;b"), "_*a*_ a&#10;b\n");
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(convert("a\n***\n> b\n>\n> > c\n\n<div>\nd\n</div>"), "a\n\n***\n\n> b\n>\n> > c\n\n<div>\nd\n</div>\n");
+        assert_eq!(convert("~~~rust\nlet a = ```;\n~~~"), "````rust\nlet a = ```;\n````\n");
+        assert_eq!(convert("## a #"), "## a\n");
+        assert_eq!(convert("## a \\##\n>"), "## a \\##\n\n>\n");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(convert("* a\n* b\n  - c\n\n    d"), "- a\n- b\n  - c\n\n    d\n");
+        assert_eq!(convert("- a\n\n- b"), "- a\n\n- b\n");
+        assert_eq!(convert("- a\n+ b\n- c\n\n1. d\n1) e"), "- a\n\n* b\n\n- c\n\n1. d\n\n1) e\n");
+        assert_eq!(convert("3) a\n4) b\n   > c"), "3. a\n4. b\n   > c\n");
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(convert("a | b | c\n:-|-:|:-:\n1 | 2\\|3 |"), "| a | b | c |\n| :-- | --: | :-: |\n| 1 | 2\\|3 |  |\n");
+    }
+
+    #[test]
+    fn attrs_raw() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let mut ast = md.parse("[a](/b)");
+        ast.children[0].children[0].attrs_raw.push(("title".into(), "c &amp; d".into()));
+        assert_eq!(ast.render_markdown(), "[a](/b \"c & d\")\n");
+    }
+
+    #[test]
+    fn round_trip() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let src = "# a\n\n1. *b* [c](/d)\n2. e\n\n   > f\n\n```js\ng\n```\n";
+        let converted = md.parse(src).render_markdown();
+        assert_eq!(md.parse(&converted).render(), md.parse(src).render());
+    }
+}
@@ -35,10 +35,28 @@
 //!  - [core rule](crate::plugins::sourcepos) - source mapping
 //!
 pub mod block;
+pub mod builder;
+pub mod cancel;
 pub mod core;
+pub mod definitions;
+pub mod diagnostics;
+#[cfg(feature = "serde")]
+pub mod deserialize;
 pub mod extset;
+pub mod fallback;
+pub mod html_output;
+pub mod incremental;
 pub mod inline;
 pub mod linkfmt;
+pub mod lossy;
+pub mod markdown;
+pub mod options;
+pub mod postprocess;
+pub mod query;
+pub mod rules;
+pub mod render_cache;
+pub mod stream;
+pub mod tokens;
 
 pub(super) mod main;
 pub(super) mod node;
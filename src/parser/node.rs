@@ -1,12 +1,19 @@
 use downcast_rs::{impl_downcast, Downcast};
 use std::any::TypeId;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
 
 use crate::common::sourcemap::SourcePos;
 use crate::common::TypeKey;
-use crate::parser::extset::NodeExtSet;
+use crate::parser::core::Root;
+use crate::parser::extset::{NodeExt, NodeExtSet, RenderExtSet};
 use crate::parser::inline::Text;
-use crate::parser::renderer::HTMLRenderer;
+use crate::parser::postprocess::PostProcessors;
+use crate::parser::query::{self, Descendants, Select, Selector};
+use crate::parser::renderer::{EventRenderer, HTMLRenderer, RenderEvent, RenderOptions, RenderOverrides, SourceLine, TextRenderer};
+use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::plugins::cmark::block::reference::Definition;
 use crate::plugins::cmark::inline::newline::Softbreak;
 use crate::Renderer;
 
@@ -26,6 +33,11 @@ pub struct Node {
     /// Additional attributes to be added to resulting html.
     pub attrs: Vec<(String, String)>,
 
+    /// Additional attributes with values already encoded as html (e.g. with entities),
+    /// added to the first tag of this node by html renderer. Unlike [attrs](Node::attrs),
+    /// values aren't escaped (except for `"`), so they must not come from user input.
+    pub attrs_raw: Vec<(String, String)>,
+
     /// Type name, used for debugging.
     #[readonly]
     pub node_type: TypeKey,
@@ -42,6 +54,7 @@ impl Node {
             children:   Vec::new(),
             srcmap:     None,
             attrs:      Vec::new(),
+            attrs_raw:  Vec::new(),
             ext:        NodeExtSet::new(),
             node_type:  TypeKey::of::<T>(),
             node_value: Box::new(value),
@@ -84,20 +97,183 @@ impl Node {
 
     /// Render this node to HTML.
     pub fn render(&self) -> String {
-        let mut fmt = HTMLRenderer::<false>::new();
-        fmt.render(self);
-        fmt.into()
+        self.render_with_options(&RenderOptions::default())
     }
 
     /// Render this node to XHTML, it adds slash to self-closing tags like this: `<img />`.
     ///
     /// This mode exists for compatibility with CommonMark tests.
     pub fn xrender(&self) -> String {
-        let mut fmt = HTMLRenderer::<true>::new();
+        self.render_with_options(&RenderOptions { xhtml: true, ..Default::default() })
+    }
+
+    /// Render this node to HTML using given options.
+    ///
+    /// If this is a root of a document, [post-processing hooks](crate::parser::postprocess)
+    /// are applied to the result.
+    pub fn render_with_options(&self, options: &RenderOptions) -> String {
+        match PostProcessors::of(self) {
+            Some(hooks) => hooks.render(self, options),
+            None => self.render_html(options),
+        }
+    }
+
+    /// Render this node to HTML, writing it out block by block instead of
+    /// building the whole string in memory.
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let mut html = String::new();
+    /// md.parse("# hello\n\n*world*").render_to(&mut html).unwrap();
+    /// assert_eq!(html, "<h1>hello</h1>\n<p><em>world</em></p>\n");
+    /// ```
+    pub fn render_to(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        self.render_to_with_options(out, &RenderOptions::default())
+    }
+
+    /// Same as [render_to](Self::render_to), using given options.
+    pub fn render_to_with_options(&self, out: &mut impl std::fmt::Write, options: &RenderOptions) -> std::fmt::Result {
+        self.render_chunks(options, |chunk| out.write_str(chunk))
+    }
+
+    /// Same as [render_to](Self::render_to), for byte streams (files, sockets, etc.).
+    pub fn render_to_io(&self, out: &mut impl std::io::Write) -> std::io::Result<()> {
+        self.render_to_io_with_options(out, &RenderOptions::default())
+    }
+
+    /// Same as [render_to_io](Self::render_to_io), using given options.
+    pub fn render_to_io_with_options(&self, out: &mut impl std::io::Write, options: &RenderOptions) -> std::io::Result<()> {
+        self.render_chunks(options, |chunk| out.write_all(chunk.as_bytes()))
+    }
+
+    // render html and pass it to `write` in chunks, one per top-level block
+    // (post-processing hooks need the whole document, so there is one chunk if there are any)
+    fn render_chunks<E>(&self, options: &RenderOptions, mut write: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+        let streamed = PostProcessors::of(self).is_none()
+            && !self.is_hidden()
+            && !(options.unwrap_single_paragraph && self.single_paragraph().is_some());
+
+        let Some(root) = self.cast::<Root>().filter(|_| streamed) else {
+            return write(&self.render_with_options(options));
+        };
+
+        let mut fmt = HTMLRenderer::new(options);
+        fmt.set_source(&root.content);
+        for child in self.children.iter() {
+            fmt.render(child);
+            write(&fmt.take_output())?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn render_html(&self, options: &RenderOptions) -> String {
+        let mut fmt = HTMLRenderer::new(options);
+        if let Some(root) = self.cast::<Root>() {
+            fmt.set_source(&root.content);
+        }
+        match self.single_paragraph() {
+            Some(paragraph) if options.unwrap_single_paragraph => fmt.contents(&paragraph.children),
+            _ => fmt.render(self),
+        }
+        fmt.into()
+    }
+
+    // nodes rendered by `render_with_options`: children of a single paragraph if it's unwrapped
+    pub(crate) fn rendered_nodes(&self, options: &RenderOptions) -> &[Node] {
+        match self.single_paragraph() {
+            Some(paragraph) if options.unwrap_single_paragraph => &paragraph.children,
+            _ => std::slice::from_ref(self),
+        }
+    }
+
+    // returns the only child of the root node if it's a paragraph
+    // (reference definitions aren't rendered, so they are skipped)
+    fn single_paragraph(&self) -> Option<&Node> {
+        if !self.is::<Root>() { return None; }
+        let mut children = self.children.iter().filter(|child| !child.is::<Definition>());
+        let (Some(child), None) = (children.next(), children.next()) else { return None; };
+        child.is::<Paragraph>().then_some(child)
+    }
+
+    /// Render this node to plain text without any markup, e.g. for search indexing.
+    ///
+    /// Block elements are separated by newlines, list items are prefixed with
+    /// their markers, images are replaced with their alt text.
+    pub fn render_text(&self) -> String {
+        let mut fmt = TextRenderer::new();
         fmt.render(self);
         fmt.into()
     }
 
+    /// Convert this node back to markdown (normalized, e.g. setext headings
+    /// become ATX headings), see [markdown](crate::parser::markdown).
+    pub fn render_markdown(&self) -> String {
+        crate::parser::markdown::render_markdown(self)
+    }
+
+    /// Render this node into a list of structured events (open tag, text, etc.),
+    /// see [EventRenderer].
+    pub fn render_events(&self) -> Vec<RenderEvent> {
+        let mut events = Vec::new();
+        EventRenderer::new(|event| events.push(event)).render(self);
+        events
+    }
+
+    /// Render this node using a custom renderer.
+    ///
+    /// Node is asked to render itself in renderer's [format](Renderer::format) first,
+    /// then in each of its [fallback formats](Renderer::fallback_formats). If node supports
+    /// none of them, its children are rendered instead.
+    ///
+    /// If renderer has [overrides](crate::parser::renderer::RenderOverrides) for this
+    /// node type, they are used instead.
+    pub fn render_with(&self, fmt: &mut dyn Renderer) {
+        if self.is_hidden() { return; }
+
+        if let Some(f) = fmt.ext().get::<RenderOverrides>().and_then(|overrides| overrides.get(self)) {
+            return f(self, fmt);
+        }
+
+        self.render_default(fmt);
+    }
+
+    /// Render this node using a custom renderer, ignoring [overrides](crate::parser::renderer::RenderOverrides)
+    /// of this node type (so that overrides can wrap default output).
+    pub fn render_default(&self, fmt: &mut dyn Renderer) {
+        if !self.render_supported(fmt) {
+            fmt.contents(&self.children);
+        }
+    }
+
+    // render node in the first format it supports, returns false if there are none
+    pub(crate) fn render_supported(&self, fmt: &mut dyn Renderer) -> bool {
+        let format = fmt.format();
+        if self.node_value.render_format(self, fmt, format) { return true; }
+
+        for format in fmt.fallback_formats() {
+            if self.node_value.render_format(self, fmt, format) { return true; }
+        }
+
+        false
+    }
+
+    /// Exclude this node and its children from rendered output,
+    /// while keeping them in the AST (e.g. for drafts or conditional builds).
+    pub fn set_hidden(&mut self, hidden: bool) {
+        if hidden {
+            self.ext.insert(Hidden);
+        } else {
+            self.ext.remove::<Hidden>();
+        }
+    }
+
+    /// Check if this node is excluded from rendered output, see [Node::set_hidden].
+    pub fn is_hidden(&self) -> bool {
+        self.ext.contains::<Hidden>()
+    }
+
     /// Replace custom value with another value (this is roughly equivalent
     /// to replacing the entire node and copying children and sourcemaps).
     pub fn replace<T: NodeValue>(&mut self, value: T) {
@@ -105,6 +281,95 @@ impl Node {
         self.node_value = Box::new(value);
     }
 
+    /// Replace this node with a list of nodes (which may be empty, to remove it).
+    ///
+    /// This can be called from [walk_mut](Node::walk_mut) and other mutable walks,
+    /// which insert new nodes into the parent once the current level is visited
+    /// (preorder walks visit new nodes as well). Outside of walks, the node keeps
+    /// new nodes as its children and renders them transparently until its parent
+    /// is walked.
+    ///
+    /// ```rust
+    /// use markdown_it::plugins::cmark::inline::emphasis::Em;
+    ///
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let mut ast = md.parse("hello *world*");
+    /// ast.walk_mut(|node, _| {
+    ///     if node.is::<Em>() { node.unwrap_children(); }
+    /// });
+    /// assert_eq!(ast.render(), "<p>hello world</p>\n");
+    /// ```
+    pub fn replace_with(&mut self, nodes: Vec<Node>) {
+        let mut nodes = nodes;
+        if nodes.len() == 1 {
+            *self = nodes.pop().unwrap();
+        } else {
+            *self = Node::new(Splice);
+            self.children = nodes;
+        }
+    }
+
+    /// Replace this node with its children, see [replace_with](Node::replace_with).
+    pub fn unwrap_children(&mut self) {
+        let children = std::mem::take(&mut self.children);
+        self.replace_with(children);
+    }
+
+    /// Replace this node with a new node of value `T` containing it as the only child.
+    /// New node gets the same source mapping.
+    pub fn wrap_in<T: NodeValue>(&mut self, value: T) {
+        let inner = std::mem::replace(self, Node::new(value));
+        self.srcmap = inner.srcmap;
+        self.children.push(inner);
+    }
+
+    /// Replace children in `range` with a single node of value `T` containing them.
+    /// New node gets source mapping spanning all of them.
+    ///
+    /// Panics if `range` is out of bounds, same as [Vec::drain].
+    pub fn wrap_children_in<T: NodeValue>(&mut self, range: std::ops::Range<usize>, value: T) {
+        let index = range.start;
+        let mut node = Node::new(value);
+        node.children = self.children.drain(range).collect();
+        let first = node.children.first().and_then(|n| n.srcmap);
+        let last = node.children.last().and_then(|n| n.srcmap);
+        if let (Some(first), Some(last)) = (first, last) {
+            let (start, _) = first.get_byte_offsets();
+            let (_, end) = last.get_byte_offsets();
+            node.srcmap = Some(SourcePos::new(start, end));
+        }
+        self.children.insert(index, node);
+    }
+
+    /// Find the innermost node with source span containing given byte offset,
+    /// e.g. to show information about the node under cursor in an editor.
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let ast = md.parse("# hello *world*");
+    /// let node = ast.node_at_offset(10).unwrap();
+    /// assert_eq!(node.collect_text(), "world");
+    /// assert!(ast.node_at_offset(100).is_none());
+    /// ```
+    pub fn node_at_offset(&self, offset: usize) -> Option<&Node> {
+        let contains = |node: &Node| node.srcmap.is_some_and(|map| {
+            let (start, end) = map.get_byte_offsets();
+            start <= offset && offset < end
+        });
+
+        if !contains(self) { return None; }
+
+        let mut node = self;
+        while let Some(child) = node.children.iter().find(|child| contains(child)) {
+            node = child;
+        }
+        Some(node)
+    }
+
     /// Execute function `f` recursively on every member of AST tree
     /// (using preorder deep-first search).
     pub fn walk<'a>(&'a self, mut f: impl FnMut(&'a Node, u32)) {
@@ -132,6 +397,36 @@ impl Node {
                     walk_recursive(n, depth + 1, f);
                 });
             }
+            flatten_splices(&mut node.children);
+        }
+
+        walk_recursive(self, 0, &mut f);
+    }
+
+    /// Execute function `f` recursively on every descendant of this node
+    /// (using preorder deep-first search), passing its parent and index
+    /// (node itself is `parent.children[index]`), so `f` can look at and modify
+    /// its siblings.
+    ///
+    /// `f` may insert or remove siblings after `index`; they are visited
+    /// according to the updated list.
+    pub fn walk_children_mut(&mut self, mut f: impl FnMut(&mut Node, usize, u32)) {
+        fn walk_recursive(node: &mut Node, depth: u32, f: &mut impl FnMut(&mut Node, usize, u32)) {
+            let mut idx = 0;
+            while idx < node.children.len() {
+                f(node, idx, depth + 1);
+                if node.children.get(idx).is_some_and(|child| child.is::<Splice>()) {
+                    let nodes = std::mem::take(&mut node.children[idx].children);
+                    node.children.splice(idx..=idx, nodes);
+                    continue;
+                }
+                if let Some(child) = node.children.get_mut(idx) {
+                    stacker::maybe_grow(64*1024, 1024*1024, || {
+                        walk_recursive(child, depth + 1, f);
+                    });
+                }
+                idx += 1;
+            }
         }
 
         walk_recursive(self, 0, &mut f);
@@ -161,12 +456,50 @@ impl Node {
                     walk_recursive(n, depth + 1, f);
                 });
             }
+            flatten_splices(&mut node.children);
             f(node, depth);
         }
 
         walk_recursive(self, 0, &mut f);
     }
 
+    /// Iterate over this node and all its descendants (in preorder).
+    pub fn descendants(&self) -> Descendants<'_> {
+        Descendants::new(self)
+    }
+
+    /// Find this node and all descendants of type `T` (in preorder).
+    pub fn find_all<T: NodeValue>(&self) -> impl Iterator<Item = &Node> {
+        self.descendants().filter(|node| node.is::<T>())
+    }
+
+    /// Find the first node of type `T` (in preorder).
+    pub fn find_first<T: NodeValue>(&self) -> Option<&Node> {
+        self.find_all::<T>().next()
+    }
+
+    /// Same as [find_all](Node::find_all), but returns mutable references.
+    ///
+    /// Only the outermost matches are returned, nodes of type `T` nested
+    /// inside them are skipped.
+    pub fn find_all_mut<T: NodeValue>(&mut self) -> impl Iterator<Item = &mut Node> {
+        query::collect_mut(self, |node, _| node.is::<T>())
+    }
+
+    /// Find this node and all descendants matching a selector
+    /// (see [query](crate::parser::query) for syntax).
+    pub fn select<'a, 'b>(&'a self, selector: &'b Selector) -> Select<'a, 'b> {
+        Select::new(self, selector)
+    }
+
+    /// Same as [select](Node::select), but returns mutable references.
+    ///
+    /// Only the outermost matches are returned, matching nodes nested
+    /// inside them are skipped.
+    pub fn select_mut(&mut self, selector: &Selector) -> impl Iterator<Item = &mut Node> {
+        query::collect_mut(self, |node, ancestors| selector.matches(node, ancestors))
+    }
+
     /// Walk recursively through child nodes and collect all text nodes
     /// into a single string.
     pub fn collect_text(&self) -> String {
@@ -182,6 +515,26 @@ impl Node {
 
         result
     }
+
+    /// Hash of this node and all its descendants: node types, attributes and html
+    /// output of each node (source maps are ignored), so that nodes with the same hash
+    /// are rendered the same way (see [RenderCache](crate::parser::render_cache::RenderCache)).
+    ///
+    /// Render overrides and renderer extensions are not taken into account, since they
+    /// are the same for all nodes rendered with the same options.
+    pub fn content_hash(&self) -> u64 {
+        let mut fmt = HashRenderer { hasher: DefaultHasher::new(), ext: RenderExtSet::new() };
+        self.walk(|node, depth| {
+            depth.hash(&mut fmt.hasher);
+            node.node_type.hash(&mut fmt.hasher);
+            node.is_hidden().hash(&mut fmt.hasher);
+            node.attrs.hash(&mut fmt.hasher);
+            node.attrs_raw.hash(&mut fmt.hasher);
+            // children are hashed separately, HashRenderer doesn't render them
+            node.render_default(&mut fmt);
+        });
+        fmt.hasher.finish()
+    }
 }
 
 impl Drop for Node {
@@ -192,6 +545,68 @@ impl Drop for Node {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Node {
+    /// Serialize node as a map with `type`, `srcmap`, `attrs`, `data` and `children` fields
+    /// (and `attrs_raw` if there are any).
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Node", 6)?;
+        state.serialize_field("type", self.name())?;
+        state.serialize_field("srcmap", &self.srcmap)?;
+        state.serialize_field("attrs", &self.attrs)?;
+        if self.attrs_raw.is_empty() {
+            state.skip_field("attrs_raw")?;
+        } else {
+            state.serialize_field("attrs_raw", &self.attrs_raw)?;
+        }
+        state.serialize_field("data", &self.node_value.serialize_data())?;
+        state.serialize_field("children", &self.children)?;
+        state.end()
+    }
+}
+
+// renderer writing html output of a node (without its children) into a hasher
+struct HashRenderer {
+    hasher: DefaultHasher,
+    ext: RenderExtSet,
+}
+
+impl Renderer for HashRenderer {
+    fn open(&mut self, tag: &str, attrs: &[(String, String)]) {
+        (0u8, tag, attrs).hash(&mut self.hasher);
+    }
+
+    fn close(&mut self, tag: &str) {
+        (1u8, tag).hash(&mut self.hasher);
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(String, String)]) {
+        (2u8, tag, attrs).hash(&mut self.hasher);
+    }
+
+    fn contents(&mut self, _: &[Node]) {
+        3u8.hash(&mut self.hasher);
+    }
+
+    fn cr(&mut self) {
+        4u8.hash(&mut self.hasher);
+    }
+
+    fn text(&mut self, text: &str) {
+        (5u8, text).hash(&mut self.hasher);
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        (6u8, text).hash(&mut self.hasher);
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        &mut self.ext
+    }
+}
+
 #[derive(Debug)]
 #[doc(hidden)]
 pub struct NodeEmpty;
@@ -206,7 +621,11 @@ impl Default for Node {
 }
 
 /// Contents of the specific AST node.
-pub trait NodeValue : Debug + Downcast {
+///
+/// Node values must be `Send + Sync`, so that documents can be parsed and rendered
+/// on multiple threads. Values that use `Rc` or `RefCell` need to switch to `Arc`
+/// and `Mutex` (or `OnceLock`).
+pub trait NodeValue : Debug + Downcast + Send + Sync {
     /// Output HTML corresponding to this node using Renderer API.
     ///
     /// Example implementation looks like this:
@@ -224,6 +643,260 @@ pub trait NodeValue : Debug + Downcast {
         let _ = fmt;
         unimplemented!("{} doesn't implement render", node.name());
     }
+
+    /// Output this node in a given format, return `false` if this node doesn't
+    /// support it (so that renderer can try its fallback formats).
+    ///
+    /// Default implementation uses [render](NodeValue::render) for `"html"` format,
+    /// and doesn't support anything else. Nodes that can be represented in other
+    /// formats should override it like this:
+    /// ```rust
+    /// # const IGNORE : &str = stringify! {
+    /// fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+    ///     match format {
+    ///         "html" => self.render(node, fmt),
+    ///         "latex" => {
+    ///             fmt.text_raw("\\emph{");
+    ///             fmt.contents(&node.children);
+    ///             fmt.text_raw("}");
+    ///         }
+    ///         _ => return false,
+    ///     }
+    ///     true
+    /// }
+    /// # };
+    /// ```
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        if format == "html" {
+            self.render(node, fmt);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Return data specific to this node type (e.g. url of a link), it is
+    /// stored in `data` field when AST is serialized.
+    ///
+    /// Nodes that don't carry any data besides their children can leave this as is.
+    /// ```rust
+    /// # const IGNORE : &str = stringify! {
+    /// fn serialize_data(&self) -> serde_json::Value {
+    ///     serde_json::json!({ "level": self.level })
+    /// }
+    /// # };
+    /// ```
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Construct node value from `data` field of serialized AST, this is
+    /// the reverse of [serialize_data](NodeValue::serialize_data).
+    ///
+    /// Return `None` if data is malformed. Node types that don't implement this
+    /// cannot be deserialized.
+    /// ```rust
+    /// # const IGNORE : &str = stringify! {
+    /// fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+    ///     Some(Self { level: field(data, "level")? })
+    /// }
+    /// # };
+    /// ```
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> where Self: Sized {
+        let _ = data;
+        None
+    }
+
+    /// How this node is split into source lines when html output is annotated
+    /// with them (see [RenderOptions::source_lines]).
+    fn source_line(&self) -> SourceLine {
+        SourceLine::Block
+    }
+
+    /// Return `true` for blocks that change how other blocks are parsed (e.g. link
+    /// reference definitions), so that [MarkdownIt::parse_incremental](crate::MarkdownIt::parse_incremental)
+    /// doesn't reuse any blocks when one of them is added, removed or edited.
+    fn is_definition(&self) -> bool {
+        false
+    }
 }
 
 impl_downcast!(NodeValue);
+
+#[derive(Debug)]
+/// Placeholder for a list of nodes, see [Node::replace_with].
+pub(crate) struct Splice;
+
+impl NodeValue for Splice {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.contents(&node.children);
+    }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, _format: &str) -> bool {
+        self.render(node, fmt);
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+// Insert children of splice placeholders in their place.
+fn flatten_splices(nodes: &mut Vec<Node>) {
+    if !nodes.iter().any(|node| node.is::<Splice>()) { return; }
+
+    let mut result = Vec::with_capacity(nodes.len());
+    for mut node in std::mem::take(nodes) {
+        if node.is::<Splice>() {
+            result.append(&mut node.children);
+        } else {
+            result.push(node);
+        }
+    }
+    *nodes = result;
+}
+
+#[derive(Debug)]
+/// Marker for nodes excluded from rendering, see [Node::set_hidden].
+struct Hidden;
+impl NodeExt for Hidden {}
+
+#[cfg(test)]
+mod tests {
+    use super::Node;
+    use crate::common::sourcemap::SourcePos;
+    use crate::parser::inline::Text;
+    use crate::plugins::cmark::block::paragraph::Paragraph;
+    use crate::plugins::cmark::inline::emphasis::{Em, Strong};
+
+    fn text(s: &str) -> Node {
+        Node::new(Text { content: s.into() })
+    }
+
+    fn para(children: Vec<Node>) -> Node {
+        let mut node = Node::new(Paragraph);
+        node.children = children;
+        node
+    }
+
+    #[test]
+    fn replace_with_in_walk() {
+        let mut root = para(vec![text("a"), text("b"), text("c")]);
+
+        root.walk_mut(|node, _| {
+            if node.cast::<Text>().is_some_and(|t| t.content == "b") {
+                node.replace_with(vec![text("x"), text("y")]);
+            } else if node.cast::<Text>().is_some_and(|t| t.content == "c") {
+                node.replace_with(vec![]);
+            }
+        });
+
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(root.render(), "<p>axy</p>\n");
+    }
+
+    #[test]
+    fn replace_with_outside_walk() {
+        let mut node = para(vec![text("a")]);
+        node.children[0].replace_with(vec![text("b"), text("c")]);
+        assert_eq!(node.render(), "<p>bc</p>\n");
+    }
+
+    #[test]
+    fn new_nodes_are_visited() {
+        let mut root = para(vec![text("a")]);
+        let mut visited = Vec::new();
+        root.walk_mut(|node, _| {
+            if let Some(t) = node.cast::<Text>() {
+                visited.push(t.content.clone());
+                if t.content == "a" {
+                    node.replace_with(vec![text("b"), text("c")]);
+                }
+            }
+        });
+        assert_eq!(visited, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn unwrap_in_walk_children() {
+        let mut em = Node::new(Em { marker: '*' });
+        em.children = vec![text("b"), text("c")];
+        let mut root = para(vec![text("a"), em, text("d")]);
+
+        let mut visited = Vec::new();
+        root.walk_children_mut(|parent, idx, _| {
+            if parent.children[idx].is::<Em>() {
+                parent.children[idx].unwrap_children();
+            } else if let Some(t) = parent.children[idx].cast::<Text>() {
+                visited.push(t.content.clone());
+            }
+        });
+        assert_eq!(visited, ["a", "b", "c", "d"]);
+        assert_eq!(root.children.len(), 4);
+    }
+
+    #[test]
+    fn wrap_in() {
+        let mut root = para(vec![text("a")]);
+        root.children[0].srcmap = Some(SourcePos::new(0, 1));
+        root.children[0].wrap_in(Strong { marker: '*' });
+        assert_eq!(root.children[0].srcmap.unwrap().get_byte_offsets(), (0, 1));
+        assert_eq!(root.render(), "<p><strong>a</strong></p>\n");
+    }
+
+    #[test]
+    fn wrap_children_in() {
+        let mut root = para(vec![text("a"), text("b"), text("c"), text("d")]);
+        root.children[1].srcmap = Some(SourcePos::new(1, 2));
+        root.children[2].srcmap = Some(SourcePos::new(2, 3));
+        root.wrap_children_in(1..3, Em { marker: '_' });
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(root.children[1].srcmap.unwrap().get_byte_offsets(), (1, 3));
+        assert_eq!(root.render(), "<p>a<em>bc</em>d</p>\n");
+    }
+
+    #[test]
+    fn render_to() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+
+        let options = crate::RenderOptions { source_lines: true, ..Default::default() };
+        for example in crate::testing::spec_examples() {
+            let ast = md.parse(&example.markdown);
+            let mut html = String::new();
+            ast.render_to_with_options(&mut html, &options).unwrap();
+            assert_eq!(html, ast.render_with_options(&options), "example {}", example.number);
+
+            let mut bytes = Vec::new();
+            ast.render_to_io(&mut bytes).unwrap();
+            assert_eq!(String::from_utf8(bytes).unwrap(), ast.render());
+        }
+    }
+
+    #[test]
+    fn render_to_chunks() {
+        struct Chunks(Vec<String>);
+        impl std::fmt::Write for Chunks {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                self.0.push(s.to_owned());
+                Ok(())
+            }
+        }
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let mut chunks = Chunks(Vec::new());
+        md.parse("a\0\n\n---").render_to(&mut chunks).unwrap();
+        assert_eq!(chunks.0, ["<p>a\u{FFFD}</p>\n", "<hr>\n"]);
+
+        let options = crate::RenderOptions { unwrap_single_paragraph: true, ..Default::default() };
+        let mut html = String::new();
+        md.parse("*a*").render_to_with_options(&mut html, &options).unwrap();
+        assert_eq!(html, "<em>a</em>");
+    }
+}
@@ -0,0 +1,151 @@
+//! Per-document settings passed to rules.
+//!
+//! [MarkdownIt] is usually created once and shared between documents. Settings
+//! that differ per document (base url, locale, flags, plugin options, link references
+//! shared with other documents, cancellation, handling of invalid UTF-8) are passed
+//! with [MarkdownIt::parse_with_options] instead of building a new parser for
+//! each configuration.
+//!
+//! Rules get these settings with [ParseOptions::of] (block and inline rules,
+//! from `state.root_ext`) or [ParseOptions::of_root] (core rules). Plugin options
+//! stored in [ParseOptions::ext] override the ones set in the parser, see
+//! [ParseOptions::ext_or].
+//!
+//! ```rust
+//! # #[cfg(feature = "images")] {
+//! use markdown_it::parser::options::ParseOptions;
+//! use markdown_it::plugins::extra::images::{self, ImagesOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! images::add(md, ImagesOptions::default());
+//!
+//! let mut options = ParseOptions::new();
+//! options.ext.insert(ImagesOptions { lazy: true, ..Default::default() });
+//!
+//! assert_eq!(md.parse("![a](a.png)").render(), "<p><img src=\"a.png\" alt=\"a\"></p>\n");
+//! assert_eq!(
+//!     md.parse_with_options("![a](a.png)", options).render(),
+//!     "<p><img loading=\"lazy\" decoding=\"async\" src=\"a.png\" alt=\"a\"></p>\n"
+//! );
+//! # }
+//! ```
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::parser::cancel::CancelToken;
+use crate::parser::core::Root;
+use crate::parser::extset::{MarkdownItExt, MarkdownItExtSet, RootExt, RootExtSet};
+use crate::parser::lossy::Utf8Policy;
+use crate::{MarkdownIt, Node};
+
+static DEFAULT : Lazy<Arc<ParseOptions>> = Lazy::new(Default::default);
+
+#[derive(Debug, Default)]
+/// Settings of a single document, see [module documentation](self).
+pub struct ParseOptions {
+    /// Url of the document, used to resolve relative links
+    /// (e.g. passed to [link_resolver](crate::plugins::extra::link_resolver) callback).
+    pub base_url: Option<String>,
+
+    /// Language of the document (e.g. `"de-DE"`), for rules that produce localized text.
+    pub locale: Option<String>,
+
+    /// Named flags (feature toggles), merged over the ones set in the parser
+    /// (e.g. with [conditional::set_flags](crate::plugins::extra::conditional::set_flags)).
+    pub flags: HashMap<String, String>,
+
+    /// Plugin options overriding the ones stored in [MarkdownIt::ext].
+    ///
+    /// Link references defined elsewhere (e.g. in other files of the same documentation)
+    /// are passed here as [ReferenceMap](crate::plugins::cmark::block::reference::ReferenceMap).
+    pub ext: MarkdownItExtSet,
+
+    /// Stop parsing when this token is cancelled, see [cancel](crate::parser::cancel).
+    pub cancel: Option<CancelToken>,
+
+    /// What to do with invalid UTF-8 in the source, see [lossy](crate::parser::lossy).
+    pub utf8_policy: Utf8Policy,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get plugin options of type `T` for this document, falling back to the ones
+    /// stored in the parser.
+    pub fn ext_or<'a, T: MarkdownItExt>(&'a self, md: &'a MarkdownIt) -> Option<&'a T> {
+        self.ext.get::<T>().or_else(|| md.ext.get::<T>())
+    }
+
+    /// Get options of the document being parsed (default ones if document
+    /// was parsed without options).
+    pub fn of(root_ext: &RootExtSet) -> Arc<ParseOptions> {
+        root_ext.get::<SharedParseOptions>().map_or_else(|| DEFAULT.clone(), |options| options.0.clone())
+    }
+
+    /// Same as [of](ParseOptions::of), for core rules that have access to the root node.
+    pub fn of_root(root: &Node) -> Arc<ParseOptions> {
+        root.cast::<Root>().map_or_else(|| DEFAULT.clone(), |root| Self::of(&root.ext))
+    }
+}
+
+#[derive(Debug)]
+struct SharedParseOptions(Arc<ParseOptions>);
+impl RootExt for SharedParseOptions {}
+
+// same as ParseOptions::of, borrowing from the document
+pub(crate) fn get(root_ext: &RootExtSet) -> Option<&ParseOptions> {
+    root_ext.get::<SharedParseOptions>().map(|options| &*options.0)
+}
+
+pub(crate) fn set_options(root_ext: &mut RootExtSet, options: ParseOptions) {
+    root_ext.insert(SharedParseOptions(Arc::new(options)));
+}
+
+// parse another document (e.g. included file) with the same options
+pub(crate) fn share_options(from: &RootExtSet, to: &mut RootExtSet) {
+    if let Some(options) = from.get::<SharedParseOptions>() {
+        to.insert(SharedParseOptions(options.0.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseOptions;
+    use crate::parser::core::CoreRule;
+    use crate::parser::extset::MarkdownItExt;
+    use crate::{MarkdownIt, Node};
+
+    #[derive(Debug)]
+    struct Setting(&'static str);
+    impl MarkdownItExt for Setting {}
+
+    struct CheckRule;
+    impl CoreRule for CheckRule {
+        fn run(root: &mut Node, md: &MarkdownIt) {
+            let options = ParseOptions::of_root(root);
+            let setting = options.ext_or::<Setting>(md).map_or("none", |s| s.0);
+            let locale = options.locale.clone().unwrap_or_default();
+            root.attrs.push((setting.into(), locale));
+        }
+    }
+
+    #[test]
+    fn ext_override() {
+        let md = &mut MarkdownIt::new();
+        md.add_rule::<CheckRule>();
+
+        assert_eq!(md.parse("").attrs, [("none".into(), "".into())]);
+
+        md.ext.insert(Setting("parser"));
+        assert_eq!(md.parse("").attrs, [("parser".into(), "".into())]);
+
+        let mut options = ParseOptions::new();
+        options.ext.insert(Setting("document"));
+        options.locale = Some("fr".into());
+        assert_eq!(md.parse_with_options("", options).attrs, [("document".into(), "fr".into())]);
+    }
+}
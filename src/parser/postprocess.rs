@@ -0,0 +1,190 @@
+//! Hooks that change rendered output of a document.
+//!
+//! Cross-cutting changes of the output (minification, nonce injection, link rewriting)
+//! don't need a custom renderer. Hooks are registered on [MarkdownIt], stored in the
+//! root of every parsed document, and applied when the root is rendered to html
+//! with [Node::render] and friends. There are two kinds of them, each kind runs in
+//! the order hooks were added:
+//!
+//!  - [add_event_hook] receives [structured events](RenderEvent) of the whole document,
+//!    it runs first, so tags and attributes can be changed without parsing html;
+//!  - [add_output_hook] receives final html string.
+//!
+//! ```rust
+//! use markdown_it::parser::postprocess;
+//! use markdown_it::RenderEvent;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! postprocess::add_event_hook(md, |events| {
+//!     for event in events.iter_mut() {
+//!         if let RenderEvent::Open { tag, attrs } = event {
+//!             if tag == "a" { attrs.push(("target".into(), "_blank".into())); }
+//!         }
+//!     }
+//! });
+//! postprocess::add_output_hook(md, |html| html.replace('\n', ""));
+//!
+//! let html = md.parse("# title\n\n[link](/)").render();
+//! assert_eq!(html, "<h1>title</h1><p><a href=\"/\" target=\"_blank\">link</a></p>");
+//! ```
+//!
+//! Hooks are not applied when a node other than root is rendered, or when
+//! a document is rendered to events or text. If there are event hooks,
+//! [RenderOptions::source_lines] is ignored, since lines are attached by
+//! html renderer to nodes.
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::extset::{MarkdownItExt, RootExt};
+use crate::parser::renderer::{EventRenderer, HTMLRenderer, RenderEvent, RenderOptions};
+use crate::{MarkdownIt, Node, Renderer};
+
+/// Function that changes structured events of a rendered document.
+pub type EventHook = dyn Fn(&mut Vec<RenderEvent>) + Send + Sync;
+
+/// Function that changes rendered html of a document.
+pub type OutputHook = dyn Fn(String) -> String + Send + Sync;
+
+#[derive(Default, Clone)]
+/// Hooks registered on a parser, also stored in roots of parsed documents.
+pub struct PostProcessors {
+    events: Vec<Arc<EventHook>>,
+    output: Vec<Arc<OutputHook>>,
+}
+
+impl PostProcessors {
+    /// Hooks stored in a document, if any were registered on the parser.
+    pub fn of(root: &Node) -> Option<&Self> {
+        root.cast::<Root>()?.ext.get::<Self>()
+    }
+
+    /// Apply event hooks to events of a document.
+    pub fn process_events(&self, events: &mut Vec<RenderEvent>) {
+        for hook in self.events.iter() {
+            hook(events);
+        }
+    }
+
+    /// Apply output hooks to rendered html.
+    pub fn process_output(&self, html: String) -> String {
+        self.output.iter().fold(html, |html, hook| hook(html))
+    }
+
+    // render document to html, passing it through all hooks
+    pub(crate) fn render(&self, root: &Node, options: &RenderOptions) -> String {
+        if self.events.is_empty() {
+            return self.process_output(root.render_html(options));
+        }
+
+        let mut events = Vec::new();
+        let mut fmt = EventRenderer::new(|event| events.push(event));
+        options.overrides.install(fmt.ext());
+        for node in root.rendered_nodes(options) {
+            fmt.render(node);
+        }
+        drop(fmt);
+        self.process_events(&mut events);
+
+        let mut fmt = HTMLRenderer::new(options);
+        replay(&events, &mut fmt);
+        self.process_output(fmt.into())
+    }
+}
+
+impl Debug for PostProcessors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostProcessors")
+            .field("events", &self.events.len())
+            .field("output", &self.output.len())
+            .finish()
+    }
+}
+
+impl MarkdownItExt for PostProcessors {}
+impl RootExt for PostProcessors {}
+
+/// Add a hook that changes structured events of rendered documents.
+pub fn add_event_hook(md: &mut MarkdownIt, hook: impl Fn(&mut Vec<RenderEvent>) + Send + Sync + 'static) {
+    add_rule(md);
+    md.ext.get_or_insert_default::<PostProcessors>().events.push(Arc::new(hook));
+}
+
+/// Add a hook that changes rendered html of documents.
+pub fn add_output_hook(md: &mut MarkdownIt, hook: impl Fn(String) -> String + Send + Sync + 'static) {
+    add_rule(md);
+    md.ext.get_or_insert_default::<PostProcessors>().output.push(Arc::new(hook));
+}
+
+fn add_rule(md: &mut MarkdownIt) {
+    if !md.has_rule::<PostProcessRule>() {
+        md.add_rule::<PostProcessRule>();
+    }
+}
+
+/// Send events to a renderer (e.g. to write events produced by [EventRenderer](crate::EventRenderer)
+/// as html after changing them).
+pub fn replay(events: &[RenderEvent], fmt: &mut dyn Renderer) {
+    for event in events {
+        match event {
+            RenderEvent::Open { tag, attrs } => fmt.open(tag, attrs),
+            RenderEvent::Close { tag } => fmt.close(tag),
+            RenderEvent::SelfClose { tag, attrs } => fmt.self_close(tag, attrs),
+            RenderEvent::Text(text) => fmt.text(text),
+            RenderEvent::TextRaw(text) => fmt.text_raw(text),
+            RenderEvent::Cr => fmt.cr(),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct PostProcessRule;
+impl CoreRule for PostProcessRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let Some(hooks) = md.ext.get::<PostProcessors>() else { return; };
+        let Some(data) = root.cast_mut::<Root>() else { return; };
+        data.ext.insert(hooks.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::postprocess;
+    use crate::{RenderEvent, RenderOptions};
+
+    #[test]
+    fn hooks_order() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        postprocess::add_output_hook(md, |html| html + "a");
+        postprocess::add_event_hook(md, |events| events.retain(|event| !matches!(event, RenderEvent::Cr)));
+        postprocess::add_output_hook(md, |html| html + "b");
+        postprocess::add_event_hook(md, |events| events.push(RenderEvent::Text("<".into())));
+
+        let ast = md.parse("*x*\n\n---");
+        assert_eq!(ast.render(), "<p><em>x</em></p><hr>&lt;ab");
+        assert_eq!(ast.xrender(), "<p><em>x</em></p><hr />&lt;ab");
+        // only documents are post-processed
+        assert_eq!(ast.children[0].render(), "<p><em>x</em></p>\n");
+    }
+
+    #[test]
+    fn unwrap_single_paragraph() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        postprocess::add_output_hook(md, |html| html.to_uppercase());
+
+        let options = RenderOptions { unwrap_single_paragraph: true, ..Default::default() };
+        assert_eq!(md.parse("*x*").render_with_options(&options), "<EM>X</EM>");
+    }
+
+    #[test]
+    fn no_hooks() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let ast = md.parse("x");
+        assert!(postprocess::PostProcessors::of(&ast).is_none());
+    }
+}
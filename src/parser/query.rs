@@ -0,0 +1,531 @@
+//! Find nodes in the AST with CSS-like selectors or by type.
+//!
+//! Selectors match nodes by the last segment of their type name (case-insensitive,
+//! `*` matches any node) and by html attributes in [Node::attrs], combined with
+//! descendant (`a b`) and child (`a > b`) combinators, and grouped with `,`.
+//!
+//! With `serde` feature, `[name=value]` also matches fields of node values
+//! (from [NodeValue::serialize_data](crate::NodeValue::serialize_data)) if there's
+//! no attribute with that name, e.g. `atxheading[level=2]`.
+//!
+//! ```rust
+//! use markdown_it::parser::query::Selector;
+//! use markdown_it::plugins::cmark::block::heading::ATXHeading;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("# hello\n\n## *world*\n\n*foo*");
+//!
+//! let headings : Vec<_> = ast.find_all::<ATXHeading>().map(|node| node.collect_text()).collect();
+//! assert_eq!(headings, ["hello", "world"]);
+//!
+//! let selector = Selector::parse("atxheading em > text").unwrap();
+//! let text : Vec<_> = ast.select(&selector).map(|node| node.collect_text()).collect();
+//! assert_eq!(text, ["world"]);
+//! ```
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::Node;
+
+/// Iterator over a node and all its descendants in preorder,
+/// created by [Node::descendants].
+///
+/// Besides the nodes themselves, it gives access to parent and siblings
+/// of the node returned last:
+///
+/// ```rust
+/// use markdown_it::plugins::cmark::block::heading::ATXHeading;
+/// use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+///
+/// let ast = md.parse("# a\n\nb\n\n# c\n\n- d");
+/// let mut walk = ast.descendants();
+/// let mut intros = Vec::new();
+/// while let Some(node) = walk.next() {
+///     if node.is::<ATXHeading>() {
+///         if let Some(next) = walk.next_sibling().filter(|next| next.is::<Paragraph>()) {
+///             intros.push(next.collect_text());
+///         }
+///     }
+/// }
+/// assert_eq!(intros, ["b"]);
+/// ```
+#[derive(Debug)]
+pub struct Descendants<'a> {
+    start: Option<&'a Node>,
+    stack: Vec<(&'a Node, std::slice::Iter<'a, Node>)>,
+}
+
+impl<'a> Descendants<'a> {
+    pub(crate) fn new(node: &'a Node) -> Self {
+        Self { start: Some(node), stack: Vec::new() }
+    }
+
+    /// Ancestors of the node returned last, starting from the root of iteration.
+    pub fn ancestors(&self) -> impl DoubleEndedIterator<Item = &'a Node> + '_ {
+        let len = self.stack.len().saturating_sub(1);
+        self.stack[..len].iter().map(|(node, _)| *node)
+    }
+
+    /// Parent of the node returned last (`None` for the root of iteration).
+    pub fn parent(&self) -> Option<&'a Node> {
+        self.ancestors().next_back()
+    }
+
+    /// Index of the node returned last in its parent's children.
+    pub fn index(&self) -> Option<usize> {
+        let (parent, children) = self.stack.len().checked_sub(2).map(|idx| &self.stack[idx])?;
+        Some(parent.children.len() - children.len() - 1)
+    }
+
+    /// Sibling that precedes the node returned last.
+    pub fn prev_sibling(&self) -> Option<&'a Node> {
+        let index = self.index()?.checked_sub(1)?;
+        self.parent()?.children.get(index)
+    }
+
+    /// Sibling that follows the node returned last.
+    pub fn next_sibling(&self) -> Option<&'a Node> {
+        let index = self.index()? + 1;
+        self.parent()?.children.get(index)
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(node) = self.start.take() {
+            self.stack.push((node, node.children.iter()));
+            return Some(node);
+        }
+
+        loop {
+            let (_, children) = self.stack.last_mut()?;
+            if let Some(child) = children.next() {
+                self.stack.push((child, child.children.iter()));
+                return Some(child);
+            }
+            self.stack.pop();
+        }
+    }
+}
+
+/// Iterator over nodes matching a selector, created by [Node::select].
+#[derive(Debug)]
+pub struct Select<'a, 'b> {
+    walk: Descendants<'a>,
+    selector: &'b Selector,
+}
+
+impl<'a, 'b> Select<'a, 'b> {
+    pub(crate) fn new(node: &'a Node, selector: &'b Selector) -> Self {
+        Self { walk: Descendants::new(node), selector }
+    }
+}
+
+impl<'a> Iterator for Select<'a, '_> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.walk.next()?;
+            let ancestors : Vec<_> = self.walk.ancestors().collect();
+            if self.selector.matches(node, &ancestors) {
+                return Some(node);
+            }
+        }
+    }
+}
+
+// Collect mutable references to nodes selected by `matches` predicate.
+//
+// Nested matches can't be borrowed mutably at the same time,
+// so only the outermost ones are returned.
+pub(crate) fn collect_mut(
+    root: &mut Node,
+    matches: impl Fn(&Node, &[&Node]) -> bool,
+) -> std::vec::IntoIter<&mut Node> {
+    let mut selected = HashSet::new();
+    let mut walk = Descendants::new(root);
+    while let Some(node) = walk.next() {
+        let ancestors : Vec<_> = walk.ancestors().collect();
+        if matches(node, &ancestors) {
+            selected.insert(node as *const Node);
+        }
+    }
+
+    fn collect<'a>(node: &'a mut Node, selected: &HashSet<*const Node>, result: &mut Vec<&'a mut Node>) {
+        if selected.contains(&(node as *const Node)) {
+            result.push(node);
+        } else {
+            for child in node.children.iter_mut() {
+                stacker::maybe_grow(64*1024, 1024*1024, || {
+                    collect(child, selected, result);
+                });
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    if !selected.is_empty() {
+        collect(root, &selected, &mut result);
+    }
+    result.into_iter()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Error returned when selector can't be parsed.
+pub struct SelectorError {
+    /// Byte offset of the unexpected character (or end of input).
+    pub offset: usize,
+}
+
+impl Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid selector syntax at offset {}", self.offset)
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Compound {
+    // lowercased type name, `None` for `*`
+    name: Option<String>,
+    attrs: Vec<(String, Option<String>)>,
+}
+
+impl Compound {
+    fn matches(&self, node: &Node) -> bool {
+        if let Some(name) = &self.name {
+            let short_name = node.name().rsplit("::").next().unwrap_or_default();
+            if !short_name.eq_ignore_ascii_case(name) { return false; }
+        }
+
+        self.attrs.iter().all(|(name, value)| {
+            match node.attrs.iter().find(|(k, _)| k == name) {
+                Some((_, v)) => value.as_ref().is_none_or(|value| v == value),
+                None => Self::matches_field(node, name, value.as_deref()),
+            }
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    fn matches_field(node: &Node, name: &str, value: Option<&str>) -> bool {
+        use serde_json::Value;
+
+        let data = node.node_value.serialize_data();
+        match (data.get(name), value) {
+            (None | Some(Value::Null), _) => false,
+            (Some(_), None) => true,
+            (Some(Value::String(field)), Some(value)) => field == value,
+            (Some(field @ (Value::Bool(_) | Value::Number(_))), Some(value)) => {
+                serde_json::from_str::<Value>(value).is_ok_and(|value| &value == field)
+            }
+            (Some(_), Some(_)) => false,
+        }
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn matches_field(_: &Node, _: &str, _: Option<&str>) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Parsed selector, see [module documentation](self) for syntax.
+pub struct Selector {
+    // each alternative is a list of compound selectors, with combinator
+    // linking it to the previous one
+    alternatives: Vec<Vec<(Combinator, Compound)>>,
+}
+
+impl Selector {
+    /// Parse selector string.
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        SelectorParser { src: selector, pos: 0 }.parse()
+    }
+
+    /// Check whether node matches this selector, `ancestors` are listed
+    /// starting from the root.
+    pub fn matches(&self, node: &Node, ancestors: &[&Node]) -> bool {
+        fn matches_parts(parts: &[(Combinator, Compound)], node: &Node, ancestors: &[&Node]) -> bool {
+            let Some(((combinator, compound), rest)) = parts.split_last() else { return true; };
+            if !compound.matches(node) { return false; }
+            if rest.is_empty() { return true; }
+
+            match combinator {
+                Combinator::Child => {
+                    let Some((parent, ancestors)) = ancestors.split_last() else { return false; };
+                    matches_parts(rest, parent, ancestors)
+                }
+                Combinator::Descendant => {
+                    (0..ancestors.len()).rev().any(|idx| matches_parts(rest, ancestors[idx], &ancestors[..idx]))
+                }
+            }
+        }
+
+        self.alternatives.iter().any(|parts| matches_parts(parts, node, ancestors))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+struct SelectorParser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl SelectorParser<'_> {
+    fn parse(mut self) -> Result<Selector, SelectorError> {
+        let mut alternatives = Vec::new();
+
+        loop {
+            alternatives.push(self.parse_complex()?);
+            match self.peek() {
+                None => break,
+                Some(',') => self.pos += 1,
+                Some(_) => return Err(self.error()),
+            }
+        }
+
+        Ok(Selector { alternatives })
+    }
+
+    fn parse_complex(&mut self) -> Result<Vec<(Combinator, Compound)>, SelectorError> {
+        let mut parts = Vec::new();
+        let mut combinator = Combinator::Descendant;
+
+        self.skip_whitespace();
+        loop {
+            parts.push((combinator, self.parse_compound()?));
+
+            let has_whitespace = self.skip_whitespace();
+            combinator = match self.peek() {
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(',') | None => break,
+                Some(_) if has_whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.error()),
+            };
+        }
+
+        Ok(parts)
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, SelectorError> {
+        let start = self.pos;
+        let name = if self.peek() == Some('*') {
+            self.pos += 1;
+            None
+        } else {
+            let name = self.parse_ident();
+            (!name.is_empty()).then(|| name.to_ascii_lowercase())
+        };
+
+        let mut attrs = Vec::new();
+        while self.peek() == Some('[') {
+            self.pos += 1;
+            self.skip_whitespace();
+            let attr = self.parse_ident().to_owned();
+            if attr.is_empty() { return Err(self.error()); }
+            self.skip_whitespace();
+
+            let value = if self.peek() == Some('=') {
+                self.pos += 1;
+                self.skip_whitespace();
+                let value = self.parse_value()?;
+                self.skip_whitespace();
+                Some(value)
+            } else {
+                None
+            };
+
+            if self.peek() != Some(']') { return Err(self.error()); }
+            self.pos += 1;
+            attrs.push((attr, value));
+        }
+
+        if self.pos == start { return Err(self.error()); }
+        Ok(Compound { name, attrs })
+    }
+
+    fn parse_ident(&mut self) -> &str {
+        let start = self.pos;
+        let len = self.src[start..]
+            .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '-'))
+            .unwrap_or(self.src.len() - start);
+        self.pos += len;
+        &self.src[start..self.pos]
+    }
+
+    fn parse_value(&mut self) -> Result<String, SelectorError> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                let start = self.pos + 1;
+                let Some(len) = self.src[start..].find(quote) else {
+                    self.pos = self.src.len();
+                    return Err(self.error());
+                };
+                self.pos = start + len + 1;
+                Ok(self.src[start..start + len].to_owned())
+            }
+            _ => {
+                let value = self.parse_ident();
+                if value.is_empty() { return Err(self.error()); }
+                Ok(value.to_owned())
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        let len = self.src[start..].len() - self.src[start..].trim_start().len();
+        self.pos += len;
+        len > 0
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn error(&self) -> SelectorError {
+        SelectorError { offset: self.pos }
+    }
+}
+
+#[cfg(all(test, feature = "heading_anchors"))]
+mod tests {
+    use super::{Selector, SelectorError};
+    use crate::plugins::cmark::block::heading::ATXHeading;
+    use crate::plugins::cmark::block::hr::ThematicBreak;
+    use crate::plugins::cmark::inline::emphasis::Em;
+    use crate::MarkdownIt;
+
+    fn md() -> MarkdownIt {
+        let mut md = MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        crate::plugins::extra::heading_anchors::add(&mut md, |s| s.to_lowercase());
+        md
+    }
+
+    fn select(src: &str, selector: &str) -> Vec<String> {
+        let ast = md().parse(src);
+        let selector = Selector::parse(selector).unwrap();
+        ast.select(&selector).map(|node| node.collect_text()).collect()
+    }
+
+    #[test]
+    fn combinators() {
+        let src = "> *a* **b *c***\n\n*d*";
+        assert_eq!(select(src, "em"), ["a", "c", "d"]);
+        assert_eq!(select(src, "blockquote em"), ["a", "c"]);
+        assert_eq!(select(src, "paragraph > em"), ["a", "d"]);
+        assert_eq!(select(src, "blockquote > * > em"), ["a"]);
+        assert_eq!(select(src, "strong em, root > paragraph em"), ["c", "d"]);
+    }
+
+    #[test]
+    fn attrs() {
+        let src = "# Foo\n\n## Bar";
+        assert_eq!(select(src, "[id]"), ["Foo", "Bar"]);
+        assert_eq!(select(src, "atxheading[id=bar]"), ["Bar"]);
+        assert_eq!(select(src, "*[ id = 'foo' ]"), ["Foo"]);
+        assert!(select(src, "[id=baz]").is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn fields() {
+        let src = "# Foo\n\n## Bar *baz*\n\n```rust\nx\n```";
+        assert_eq!(select(src, "atxheading[level=2] > text"), ["Bar "]);
+        assert_eq!(select(src, "[level]"), ["Foo", "Bar baz"]);
+        assert_eq!(select(src, "codefence[info=rust]").len(), 1);
+        // attributes take precedence over fields
+        assert!(select(src, "atxheading[id=foo][level=2]").is_empty());
+        assert!(select(src, "atxheading[level=3]").is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Selector::parse("").unwrap_err(), SelectorError { offset: 0 });
+        assert_eq!(Selector::parse("em >").unwrap_err(), SelectorError { offset: 4 });
+        assert_eq!(Selector::parse("em[id").unwrap_err(), SelectorError { offset: 5 });
+        assert_eq!(Selector::parse("em[id=\"x]").unwrap_err(), SelectorError { offset: 9 });
+        assert_eq!(Selector::parse("em,").unwrap_err(), SelectorError { offset: 3 });
+        assert_eq!(Selector::parse("em!").unwrap_err(), SelectorError { offset: 2 });
+    }
+
+    #[test]
+    fn find_all_mut() {
+        let mut ast = md().parse("*a* _b **c** *d*_");
+        for node in ast.find_all_mut::<Em>() {
+            node.attrs.push(("class".into(), "x".into()));
+        }
+        assert_eq!(ast.render(), "<p><em class=\"x\">a</em> <em class=\"x\">b <strong>c</strong> <em>d</em></em></p>\n");
+
+        let selector = Selector::parse("em > em").unwrap();
+        for node in ast.select_mut(&selector) {
+            node.attrs.push(("class".into(), "y".into()));
+        }
+        assert!(ast.render().contains("<em class=\"y\">d</em>"));
+    }
+
+    #[test]
+    fn navigation() {
+        let ast = md().parse("a *b* c\n\nd");
+        let mut walk = ast.descendants();
+        assert!(walk.next().unwrap().is::<crate::parser::core::Root>());
+        assert!(walk.parent().is_none() && walk.index().is_none());
+
+        let mut found = Vec::new();
+        while let Some(node) = walk.next() {
+            let text = |node: Option<&crate::Node>| node.map(|node| node.collect_text());
+            found.push((node.collect_text(), walk.index(), text(walk.prev_sibling()), text(walk.next_sibling()), text(walk.parent())));
+        }
+        assert_eq!(found[2], ("b".into(), Some(1), Some("a ".into()), Some(" c".into()), Some("a b c".into())));
+        assert_eq!(found[5], ("d".into(), Some(1), Some("a b c".into()), None, Some("a b cd".into())));
+    }
+
+    #[test]
+    fn walk_children_mut() {
+        let mut ast = md().parse("# a\n\nb\n\nc");
+        ast.walk_children_mut(|parent, index, _| {
+            if parent.children[index].is::<ATXHeading>() {
+                if let Some(next) = parent.children.get_mut(index + 1) {
+                    next.attrs.push(("class".into(), "lead".into()));
+                }
+                parent.children.insert(index + 1, crate::Node::new(ThematicBreak::new('-', 3)));
+            }
+        });
+        assert_eq!(ast.render(), "<h1 id=\"a\">a</h1>\n<hr>\n<p class=\"lead\">b</p>\n<p>c</p>\n");
+    }
+
+    #[test]
+    fn find_first() {
+        let ast = md().parse("text *a* *b*");
+        assert_eq!(ast.find_first::<Em>().unwrap().collect_text(), "a");
+        assert_eq!(ast.descendants().count(), 8);
+    }
+}
@@ -0,0 +1,189 @@
+//! Reuse html of unchanged blocks when a document is rendered again after an edit.
+//!
+//! [RenderCache] keeps html of top-level blocks of the last rendered document,
+//! keyed by [Node::content_hash]. Together with [incremental parsing](super::incremental)
+//! it keeps live preview of very large documents fast, since only edited blocks
+//! are parsed and rendered again.
+//!
+//! ```rust
+//! use markdown_it::parser::incremental::TextEdit;
+//! use markdown_it::parser::render_cache::RenderCache;
+//! use markdown_it::RenderOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! let mut cache = RenderCache::new(RenderOptions::default());
+//!
+//! let ast = md.parse("# title\n\nhello *world*");
+//! assert_eq!(cache.render(&ast), "<h1>title</h1>\n<p>hello <em>world</em></p>\n");
+//!
+//! let ast = md.parse_incremental(ast, &TextEdit::new(2..7, "heading"));
+//! assert_eq!(cache.render(&ast), "<h1>heading</h1>\n<p>hello <em>world</em></p>\n");
+//! assert_eq!((cache.reused(), cache.rendered()), (1, 1));
+//! ```
+use std::collections::HashMap;
+
+use crate::parser::core::Root;
+use crate::parser::renderer::{HTMLRenderer, RenderOptions};
+use crate::{Node, Renderer};
+
+#[derive(Debug, Default)]
+/// Html of top-level blocks of the last rendered document, see [module documentation](self).
+pub struct RenderCache {
+    options: RenderOptions,
+    entries: HashMap<u64, String>,
+    reused: usize,
+    rendered: usize,
+}
+
+impl RenderCache {
+    pub fn new(options: RenderOptions) -> Self {
+        Self { options, ..Default::default() }
+    }
+
+    /// Render document same as [Node::render_with_options], reusing html of
+    /// blocks that didn't change since the previous call.
+    ///
+    /// Nothing is cached for nodes other than [Root], and with options that depend
+    /// on the whole document ([RenderOptions::source_lines] and
+    /// [RenderOptions::unwrap_single_paragraph]).
+    pub fn render(&mut self, node: &Node) -> String {
+        self.reused = 0;
+        self.rendered = 0;
+
+        if !node.is::<Root>() || self.options.source_lines || self.options.unwrap_single_paragraph {
+            self.entries.clear();
+            return node.render_with_options(&self.options);
+        }
+
+        let mut previous = std::mem::take(&mut self.entries);
+        let mut result = String::new();
+
+        for child in node.children.iter() {
+            let hash = child.content_hash();
+            if !self.entries.contains_key(&hash) {
+                let html = match previous.remove(&hash) {
+                    Some(html) => html,
+                    None => {
+                        self.rendered += 1;
+                        render_block(child, &self.options)
+                    }
+                };
+                self.entries.insert(hash, html);
+            }
+            push_block(&mut result, &self.entries[&hash]);
+        }
+
+        self.reused = node.children.len() - self.rendered;
+        result
+    }
+
+    /// Number of blocks reused in the last [render](RenderCache::render) call.
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// Number of blocks rendered in the last [render](RenderCache::render) call.
+    pub fn rendered(&self) -> usize {
+        self.rendered
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// blocks usually start with `Renderer::cr`, which only writes a newline if output
+// doesn't end with one, so it's kept in cached html and written if needed
+fn render_block(node: &Node, options: &RenderOptions) -> String {
+    let mut fmt = HTMLRenderer::new(options);
+    fmt.text_raw("-");
+    fmt.render(node);
+    let mut html : String = fmt.into();
+    html.remove(0);
+    html
+}
+
+fn push_block(result: &mut String, html: &str) {
+    match html.strip_prefix('\n') {
+        Some(html) if result.is_empty() || result.ends_with('\n') => result.push_str(html),
+        _ => result.push_str(html),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenderCache;
+    use crate::RenderOptions;
+
+    #[test]
+    fn same_as_render() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        let mut cache = RenderCache::new(RenderOptions { xhtml: true, ..Default::default() });
+
+        for src in ["a\n\n---\n\n> b\n\n---", "x <b>y</b>\n***\n\n- a\n- b", ""] {
+            let ast = md.parse(src);
+            assert_eq!(cache.render(&ast), ast.xrender());
+            assert_eq!(cache.render(&ast), ast.xrender());
+            assert_eq!(cache.rendered(), 0);
+        }
+
+        let ast = md.parse_fragment("*a*\nb");
+        assert_eq!(cache.render(&ast), ast.render());
+    }
+
+    #[test]
+    fn reuse() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let mut cache = RenderCache::default();
+
+        cache.render(&md.parse("# a\n\nb\n\nb\n\nc"));
+        assert_eq!((cache.reused(), cache.rendered()), (1, 3));
+
+        // blocks are matched by content, not position
+        assert_eq!(cache.render(&md.parse("c\n\n# a\n\nd")), "<p>c</p>\n<h1>a</h1>\n<p>d</p>\n");
+        assert_eq!((cache.reused(), cache.rendered()), (2, 1));
+
+        // attributes are part of the hash
+        let mut ast = md.parse("c");
+        ast.children[0].attrs.push(("class".into(), "x".into()));
+        assert_eq!(cache.render(&ast), "<p class=\"x\">c</p>\n");
+        assert_eq!(cache.rendered(), 1);
+    }
+
+    #[test]
+    fn content_hash() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let a = md.parse("*a*\n\n*a*\n\n**a**\n\n*b*");
+        let hashes : Vec<_> = a.children.iter().map(|node| node.content_hash()).collect();
+        assert_eq!(hashes[0], hashes[1]);
+        assert_ne!(hashes[0], hashes[2]);
+        assert_ne!(hashes[0], hashes[3]);
+    }
+
+    #[test]
+    fn content_hash_output() {
+        use crate::{Node, NodeValue, Renderer};
+
+        // same `Debug` output, different html
+        struct Value(&'static str);
+        impl std::fmt::Debug for Value {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Value")
+            }
+        }
+        impl NodeValue for Value {
+            fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+                fmt.text(self.0);
+            }
+        }
+
+        assert_ne!(Node::new(Value("a")).content_hash(), Node::new(Value("b")).content_hash());
+        assert_eq!(Node::new(Value("a")).content_hash(), Node::new(Value("a")).content_hash());
+    }
+}
@@ -1,9 +1,14 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::sync::Arc;
 
-use crate::common::utils::escape_html;
-use crate::parser::extset::RenderExtSet;
-use crate::Node;
+use crate::common::sourcemap::SourceWithLineStarts;
+use crate::common::utils::{escape_html, html_to_text, is_valid_attr_name};
+use crate::common::TypeKey;
+use crate::parser::extset::{RenderExt, RenderExtSet};
+use crate::parser::html_output::{self, OutputStyle};
+use crate::{Node, NodeValue};
 
 /// Each node outputs its HTML using this API.
 ///
@@ -16,6 +21,22 @@ pub trait Renderer {
     fn close(&mut self, tag: &str);
     /// Write self-closing html tag with attributes, e.g. `<img src="url"/>`.
     fn self_close(&mut self, tag: &str, attrs: &[(String, String)]);
+    /// Write opening tag with structured attributes, values of which may be
+    /// already encoded as html (see [AttrValue]). Nodes should use it instead of
+    /// writing tags with [text_raw](Renderer::text_raw), so that renderers
+    /// still see element structure.
+    ///
+    /// Default implementation decodes html values and calls [open](Renderer::open).
+    fn open_tag(&mut self, tag: &str, attrs: &[(&str, AttrValue<'_>)]) {
+        let attrs : Vec<_> = attrs.iter()
+            .map(|(name, value)| (name.to_string(), value.to_text().into_owned()))
+            .collect();
+        self.open(tag, &attrs);
+    }
+    /// Write closing tag for [open_tag](Renderer::open_tag), calls [close](Renderer::close) by default.
+    fn close_tag(&mut self, tag: &str) {
+        self.close(tag);
+    }
     /// Loop through child nodes and render each one.
     fn contents(&mut self, nodes: &[Node]);
     /// Write line break (`\n`). Default renderer ignores it if last char in the buffer is `\n` already.
@@ -26,85 +47,591 @@ pub trait Renderer {
     fn text_raw(&mut self, text: &str);
     /// Extension set to store custom stuff.
     fn ext(&mut self) -> &mut RenderExtSet;
+    /// Name of the output format (`"html"` for default renderer), nodes may use it
+    /// to emit content that only makes sense for a specific format.
+    fn format(&self) -> &'static str {
+        "html"
+    }
+    /// Formats to try, in order, for nodes that can't render themselves in [format](Renderer::format).
+    /// Nodes that support none of those get their children rendered instead.
+    ///
+    /// For example, LaTeX renderer might use `&["text"]` here, so that nodes without
+    /// LaTeX support are rendered as plain text instead of being skipped.
+    fn fallback_formats(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Attribute value for [Renderer::open_tag].
+pub enum AttrValue<'a> {
+    /// Plain text, escaped by html renderer.
+    Text(&'a str),
+    /// Value already encoded as html (e.g. with entities), written as is by html
+    /// renderer (except for `"`, which would end the value) and decoded into text by others.
+    Html(&'a str),
+}
+
+impl AttrValue<'_> {
+    /// Value as plain text, with html entities decoded.
+    pub fn to_text(&self) -> Cow<'_, str> {
+        match self {
+            Self::Text(text) => Cow::Borrowed(text),
+            Self::Html(html) => html_to_text(html),
+        }
+    }
+
+    /// Value encoded as html, safe to put into a quoted attribute.
+    pub fn to_html(&self) -> Cow<'_, str> {
+        match self {
+            Self::Text(text) => escape_html(text),
+            Self::Html(html) if html.contains('"') => Cow::Owned(html.replace('"', "&quot;")),
+            Self::Html(html) => Cow::Borrowed(html),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+/// Options for the default HTML renderer, see [Node::render_with_options].
+///
+/// New options can be added in minor releases, so it's built with setters
+/// (`RenderOptions::new().xhtml(true)`) outside of this crate.
+pub struct RenderOptions {
+    /// Use XHTML-style self-closing tags, e.g. `<br />` instead of `<br>`.
+    pub xhtml: bool,
+    /// Omit wrapping `<p>` tag if the document consists of a single paragraph
+    /// (e.g. for comments, captions or form fields).
+    pub unwrap_single_paragraph: bool,
+    /// Annotate output with source line numbers, so that code review tools can
+    /// attach comments to rendered markdown: block elements get `data-source-line`
+    /// attribute, and contents of paragraphs and headings are split into
+    /// `<span data-source-line="N">` elements, one per source line (elements like
+    /// emphasis that span several lines are split into spans inside of them).
+    /// Node types choose how they are annotated with [NodeValue::source_line].
+    ///
+    /// Only applies when rendering the root node of a document.
+    pub source_lines: bool,
+    /// Custom output of specific node types.
+    pub overrides: RenderOverrides,
+    /// Functions that change attributes of every element.
+    pub attr_hooks: AttrHooks,
+    /// Pretty-print or minify the output, see [html_output](crate::parser::html_output).
+    pub output_style: OutputStyle,
+    /// Which `id` to keep if an element has several of them (e.g. one from source
+    /// and one added by a plugin), the first one by default.
+    pub id_conflict: IdConflict,
+    /// How to output entities and numeric character references from the source.
+    pub entities: EntityMode,
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// See [RenderOptions::xhtml](#structfield.xhtml).
+    pub fn xhtml(mut self, xhtml: bool) -> Self {
+        self.xhtml = xhtml;
+        self
+    }
+
+    /// See [RenderOptions::unwrap_single_paragraph](#structfield.unwrap_single_paragraph).
+    pub fn unwrap_single_paragraph(mut self, unwrap: bool) -> Self {
+        self.unwrap_single_paragraph = unwrap;
+        self
+    }
+
+    /// See [RenderOptions::source_lines](#structfield.source_lines).
+    pub fn source_lines(mut self, source_lines: bool) -> Self {
+        self.source_lines = source_lines;
+        self
+    }
+
+    /// See [RenderOptions::overrides](#structfield.overrides).
+    pub fn overrides(mut self, overrides: RenderOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// See [RenderOptions::attr_hooks](#structfield.attr_hooks).
+    pub fn attr_hooks(mut self, attr_hooks: AttrHooks) -> Self {
+        self.attr_hooks = attr_hooks;
+        self
+    }
+
+    /// See [RenderOptions::output_style](#structfield.output_style).
+    pub fn output_style(mut self, output_style: OutputStyle) -> Self {
+        self.output_style = output_style;
+        self
+    }
+
+    /// See [RenderOptions::id_conflict](#structfield.id_conflict).
+    pub fn id_conflict(mut self, id_conflict: IdConflict) -> Self {
+        self.id_conflict = id_conflict;
+        self
+    }
+
+    /// See [RenderOptions::entities](#structfield.entities).
+    pub fn entities(mut self, entities: EntityMode) -> Self {
+        self.entities = entities;
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// How to output entities and numeric character references (`&copy;`, `&#169;`)
+/// written in the source, see [RenderOptions::entities].
+///
+/// Other renderers can use it by inserting it into [Renderer::ext].
+///
+/// ```rust
+/// use markdown_it::{EntityMode, RenderOptions};
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+/// let ast = md.parse("&copy; &#65; &amp;");
+///
+/// let render = |entities| ast.render_with_options(&RenderOptions::new().entities(entities));
+/// assert_eq!(render(EntityMode::Decode), "<p>© A &amp;</p>\n");
+/// assert_eq!(render(EntityMode::Raw), "<p>&copy; &#65; &amp;</p>\n");
+/// assert_eq!(render(EntityMode::Escape), "<p>&amp;copy; &amp;#65; &amp;amp;</p>\n");
+/// ```
+pub enum EntityMode {
+    #[default]
+    /// Output characters they represent (escaped if needed).
+    Decode,
+    /// Output them as written, for html that should keep them.
+    Raw,
+    /// Escape the ampersand, so they are displayed as written.
+    Escape,
+}
+
+impl RenderExt for EntityMode {}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// Role of a node in html output annotated with source lines, see
+/// [RenderOptions::source_lines] and [NodeValue::source_line].
+pub enum SourceLine {
+    #[default]
+    /// Element gets `data-source-line` attribute with its first line.
+    Block,
+    /// Block with inline content (e.g. paragraph or heading), its contents are
+    /// split into `<span data-source-line="N">` elements.
+    Text,
+    /// Line break inside of inline content, it starts next line.
+    Break,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// How to resolve several `id` attributes of one element, see [RenderOptions::id_conflict].
+pub enum IdConflict {
+    #[default]
+    KeepFirst,
+    KeepLast,
+}
+
+/// Merge repeated attributes of an element the same way html renderer does:
+///
+///  - `class` values are joined with spaces, each class is kept once;
+///  - `style` values are joined with `;`;
+///  - only one `id` is kept, see [IdConflict];
+///  - other attributes are kept, and written next to the first one with the same name.
+///
+/// ```rust
+/// use markdown_it::{merge_attrs, IdConflict};
+///
+/// let attrs = [("id", "a"), ("class", "x y"), ("id", "b"), ("class", "y z")]
+///     .map(|(name, value)| (name.to_owned(), value.to_owned()));
+/// assert_eq!(merge_attrs(&attrs, IdConflict::KeepLast), [
+///     ("id".to_owned(), "b".to_owned()),
+///     ("class".to_owned(), "x y z".to_owned()),
+/// ]);
+/// ```
+pub fn merge_attrs(attrs: &[(String, String)], id_conflict: IdConflict) -> Vec<(String, String)> {
+    let attrs = attrs.iter().map(|(name, value)| (name.as_str(), Cow::Borrowed(value.as_str())));
+    merge_attr_values(attrs, id_conflict).into_iter()
+        .map(|(name, value)| (name.to_owned(), value.into_owned()))
+        .collect()
+}
+
+fn merge_attr_values<'a>(
+    attrs: impl IntoIterator<Item = (&'a str, Cow<'a, str>)>,
+    id_conflict: IdConflict,
+) -> Vec<(&'a str, Cow<'a, str>)> {
+    // values grouped by name, in order of first occurrence
+    let mut groups : Vec<(&str, Vec<Cow<str>>)> = Vec::new();
+    for (name, value) in attrs {
+        match groups.iter_mut().find(|(group, _)| *group == name) {
+            Some((_, values)) => values.push(value),
+            None => groups.push((name, vec![value])),
+        }
+    }
+
+    let mut result = Vec::with_capacity(groups.len());
+    for (name, mut values) in groups {
+        if values.len() == 1 {
+            result.push((name, values.pop().unwrap()));
+            continue;
+        }
+
+        match name {
+            "class" => {
+                let mut classes : Vec<&str> = Vec::new();
+                for class in values.iter().flat_map(|value| value.split_ascii_whitespace()) {
+                    if !classes.contains(&class) { classes.push(class); }
+                }
+                result.push((name, Cow::Owned(classes.join(" "))));
+            }
+            "style" => result.push((name, Cow::Owned(values.join(";")))),
+            "id" => {
+                let value = match id_conflict {
+                    IdConflict::KeepFirst => values.swap_remove(0),
+                    IdConflict::KeepLast => values.pop().unwrap(),
+                };
+                result.push((name, value));
+            }
+            _ => result.extend(values.into_iter().map(|value| (name, value))),
+        }
+    }
+    result
+}
+
+/// Function that changes attributes of an element with given tag, see [AttrHooks].
+pub type AttrHookFn = dyn Fn(&str, &mut Vec<(String, String)>) + Send + Sync;
+
+#[derive(Default, Clone)]
+/// Functions called by html renderer before each opening or self-closing tag is
+/// written, they can add, change or remove its attributes (e.g. to add classes
+/// for css frameworks) without an extra pass over the AST.
+///
+/// Attribute values are plain text, escaped when written. Multiple `class`
+/// attributes are merged into one.
+///
+/// ```rust
+/// use markdown_it::RenderOptions;
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+///
+/// let mut options = RenderOptions::default();
+/// options.attr_hooks.add(|tag, attrs| {
+///     if tag == "p" { attrs.push(("class".into(), "prose-p".into())); }
+/// });
+///
+/// let html = md.parse("hello").render_with_options(&options);
+/// assert_eq!(html, "<p class=\"prose-p\">hello</p>\n");
+/// ```
+pub struct AttrHooks(Vec<Arc<AttrHookFn>>);
+
+impl AttrHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a function, functions are called in the order they were added.
+    pub fn add(&mut self, f: impl Fn(&str, &mut Vec<(String, String)>) + Send + Sync + 'static) {
+        self.0.push(Arc::new(f));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Call all functions with attributes of an element.
+    pub fn apply(&self, tag: &str, attrs: &mut Vec<(String, String)>) {
+        for f in self.0.iter() {
+            f(tag, attrs);
+        }
+    }
+}
+
+impl Debug for AttrHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AttrHooks").field(&self.0.len()).finish()
+    }
+}
+
+/// Function that renders a node instead of its [NodeValue::render], see [RenderOverrides].
+pub type RenderOverrideFn = dyn Fn(&Node, &mut dyn Renderer) + Send + Sync;
+
+#[derive(Default, Clone)]
+/// Render functions replacing output of specific node types (like `renderer.rules`
+/// in markdown-it.js), so that output of nodes defined by plugins can be changed
+/// without new node types.
+///
+/// They are applied by any renderer that has them in its [ext](Renderer::ext), html
+/// renderer takes them from [RenderOptions]. Override can call [Node::render_default]
+/// to get the original output, and [Renderer::contents] to render children.
+///
+/// ```rust
+/// use markdown_it::plugins::cmark::block::fence::CodeFence;
+/// use markdown_it::RenderOptions;
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+///
+/// let mut options = RenderOptions::default();
+/// options.overrides.set::<CodeFence>(|node, fmt| {
+///     fmt.open("figure", &[]);
+///     node.render_default(fmt);
+///     fmt.close("figure");
+///     fmt.cr();
+/// });
+///
+/// let html = md.parse("```\nfoo\n```").render_with_options(&options);
+/// assert_eq!(html, "<figure>\n<pre><code>foo\n</code></pre>\n</figure>\n");
+/// ```
+pub struct RenderOverrides(HashMap<TypeKey, Arc<RenderOverrideFn>>);
+
+impl RenderOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render nodes of type `T` with a custom function.
+    pub fn set<T: NodeValue>(&mut self, f: impl Fn(&Node, &mut dyn Renderer) + Send + Sync + 'static) {
+        self.0.insert(TypeKey::of::<T>(), Arc::new(f));
+    }
+
+    /// Render nodes of type `T` with their own [NodeValue::render] again.
+    pub fn remove<T: NodeValue>(&mut self) {
+        self.0.remove(&TypeKey::of::<T>());
+    }
+
+    /// Function that renders given node, if any.
+    pub fn get(&self, node: &Node) -> Option<Arc<RenderOverrideFn>> {
+        self.0.get(&node.node_type).cloned()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Add overrides to extensions of a renderer, so that nodes use them.
+    pub fn install(&self, ext: &mut RenderExtSet) {
+        if !self.is_empty() {
+            ext.insert(self.clone());
+        }
+    }
+}
+
+impl Debug for RenderOverrides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys().map(|key| key.name)).finish()
+    }
 }
 
+impl RenderExt for RenderOverrides {}
+
 #[derive(Debug, Default)]
 /// Default HTML/XHTML renderer.
-pub(crate) struct HTMLRenderer<const XHTML: bool> {
+pub(crate) struct HTMLRenderer {
     result: String,
     ext: RenderExtSet,
+    options: RenderOptions,
+    // source of the document, set if lines should be annotated
+    lines: Option<SourceWithLineStarts>,
+    // line of the block being rendered, added to its first tag
+    pending_line: Option<u32>,
+    // line of the paragraph or heading being rendered, its contents are split into lines
+    leaf_line: Option<u32>,
+    in_leaf: bool,
+    // last byte of the output taken with `take_output`
+    last_taken: Option<u8>,
+    // `Node::attrs_raw` of the node being rendered, added to its first tag
+    pending_raw_attrs: Vec<(String, String)>,
 }
 
-impl<const XHTML: bool> HTMLRenderer<XHTML> {
-    pub fn new() -> Self {
+impl HTMLRenderer {
+    pub fn new(options: &RenderOptions) -> Self {
+        let mut ext = RenderExtSet::new();
+        options.overrides.install(&mut ext);
+        ext.insert(options.entities);
+
         Self {
             result: String::new(),
-            ext: RenderExtSet::new(),
+            ext,
+            options: options.clone(),
+            lines: None,
+            pending_line: None,
+            leaf_line: None,
+            in_leaf: false,
+            last_taken: None,
+            pending_raw_attrs: Vec::new(),
+        }
+    }
+
+    /// Take html rendered so far, so that it can be written out before
+    /// the rest of the document is rendered.
+    pub fn take_output(&mut self) -> String {
+        if let Some(last) = self.result.as_bytes().last() {
+            self.last_taken = Some(*last);
+        }
+        let output = replace_null(std::mem::take(&mut self.result));
+        html_output::apply(output, self.options.output_style)
+    }
+
+    /// Set document source used to annotate output with line numbers
+    /// (if enabled in [RenderOptions::source_lines]).
+    pub fn set_source(&mut self, source: &str) {
+        if self.options.source_lines {
+            self.lines = Some(SourceWithLineStarts::new(source));
         }
     }
 
     pub fn render(&mut self, node: &Node) {
-        node.node_value.render(node, self);
+        let Some(lines) = self.lines.as_ref().filter(|_| !self.in_leaf) else {
+            return self.render_node(node);
+        };
+
+        let line = node.srcmap.map(|srcmap| srcmap.get_positions(lines).0.0);
+        if node.node_value.source_line() == SourceLine::Text {
+            self.leaf_line = line;
+        }
+        self.pending_line = line;
+        self.render_node(node);
+        self.pending_line = None;
+        self.leaf_line = None;
+    }
+
+    // wraps inline nodes of each line into spans, spans are closed before
+    // nodes containing line breaks (e.g. emphasis), whose contents are split
+    // into their own spans, so that elements are nested properly
+    fn render_lines(&mut self, nodes: &[Node], mut line: u32) {
+        let in_leaf = std::mem::replace(&mut self.in_leaf, true);
+        let mut span_open = false;
+
+        for node in nodes {
+            let (start, end) = self.lines.as_ref().zip(node.srcmap)
+                .map(|(lines, srcmap)| srcmap.get_positions(lines))
+                .map_or((None, None), |(start, end)| (Some(start.0), Some(end.0)));
+
+            if node.node_value.source_line() == SourceLine::Break {
+                if std::mem::take(&mut span_open) { self.close("span"); }
+                self.render_node(node);
+                line += 1;
+            } else if has_line_break(node) {
+                if std::mem::take(&mut span_open) { self.close("span"); }
+                let start = start.unwrap_or(line);
+                self.leaf_line = Some(start);
+                self.render_node(node);
+                self.leaf_line = None;
+                line = end.unwrap_or(start + count_line_breaks(node));
+            } else {
+                if !span_open {
+                    line = start.unwrap_or(line);
+                    self.open_line(line);
+                    span_open = true;
+                }
+                self.render_node(node);
+            }
+        }
+
+        if span_open { self.close("span"); }
+        self.in_leaf = in_leaf;
+    }
+
+    fn render_node(&mut self, node: &Node) {
+        self.pending_raw_attrs.clone_from(&node.attrs_raw);
+        node.render_with(self);
+        // nodes without tags don't pass their raw attributes to the next element
+        self.pending_raw_attrs.clear();
+    }
+
+    fn open_line(&mut self, line: u32) {
+        self.open_tag("span", &[("data-source-line", AttrValue::Text(&line.to_string()))]);
+    }
+
+    fn line_attr(&mut self) {
+        if let Some(line) = self.pending_line.take() {
+            self.make_attr("data-source-line", &line.to_string());
+        }
     }
 
     fn make_attr(&mut self, name: &str, value: &str) {
+        if !is_valid_attr_name(name) { return; }
+
         self.result.push(' ');
         self.result.push_str(&escape_html(name));
         self.result.push('=');
         self.result.push('"');
-        self.result.push_str(&escape_html(value));
+        self.result.push_str(value);
         self.result.push('"');
     }
 
-    fn make_attrs(&mut self, attrs: &[(String, String)]) {
-        let mut attr_hash = HashMap::new();
-        let mut attr_order = Vec::with_capacity(attrs.len());
+    // write attributes of a tag, passing them through attribute hooks
+    fn tag_attrs(&mut self, tag: &str, attrs: &[(&str, AttrValue<'_>)]) {
+        if !self.pending_raw_attrs.is_empty() {
+            let raw_attrs = std::mem::take(&mut self.pending_raw_attrs);
+            let mut attrs = attrs.to_vec();
+            attrs.extend(raw_attrs.iter().map(|(name, value)| (name.as_str(), AttrValue::Html(value))));
+            return self.tag_attrs(tag, &attrs);
+        }
 
-        for (name, value) in attrs {
-            let entry = attr_hash.entry(name).or_insert(Vec::new());
-            entry.push(value.as_str());
-            attr_order.push(name);
+        if self.options.attr_hooks.is_empty() {
+            return self.make_attrs(attrs);
         }
 
-        for name in attr_order {
-            let Some(value) = attr_hash.remove(name) else { continue; };
+        let mut owned = attrs.iter().map(|(name, value)| (name.to_string(), value.to_text().into_owned())).collect();
+        self.options.attr_hooks.apply(tag, &mut owned);
+        self.make_attrs(&text_attrs(&owned));
+    }
 
-            if name == "class" {
-                self.make_attr(name, &value.join(" "));
-            } else if name == "style" {
-                self.make_attr(name, &value.join(";"));
-            } else {
-                for v in value {
-                    self.make_attr(name, v);
-                }
-            }
+    fn make_attrs(&mut self, attrs: &[(&str, AttrValue<'_>)]) {
+        let values = attrs.iter().map(|(name, value)| (*name, value.to_html()));
+        for (name, value) in merge_attr_values(values, self.options.id_conflict) {
+            self.make_attr(name, &value);
         }
     }
 }
 
-impl<const XHTML: bool> From<HTMLRenderer<XHTML>> for String {
-    fn from(f: HTMLRenderer<XHTML>) -> Self {
-        #[cold]
-        fn replace_null(input: String) -> String {
-            input.replace('\0', "\u{FFFD}")
-        }
+impl From<HTMLRenderer> for String {
+    fn from(f: HTMLRenderer) -> Self {
+        html_output::apply(replace_null(f.result), f.options.output_style)
+    }
+}
 
-        if f.result.contains('\0') {
-            // U+0000 must be replaced with U+FFFD as per commonmark spec,
-            // we do it at the very end in order to avoid messing with byte offsets
-            // for source maps (since "\0".len() != "\u{FFFD}".len())
-            replace_null(f.result)
-        } else {
-            f.result
-        }
+fn has_line_break(node: &Node) -> bool {
+    node.children.iter().any(|child| child.node_value.source_line() == SourceLine::Break || has_line_break(child))
+}
+
+fn count_line_breaks(node: &Node) -> u32 {
+    node.children.iter().map(|child| {
+        u32::from(child.node_value.source_line() == SourceLine::Break) + count_line_breaks(child)
+    }).sum()
+}
+
+fn replace_null(input: String) -> String {
+    #[cold]
+    fn replace(input: String) -> String {
+        input.replace('\0', "\u{FFFD}")
     }
+
+    if input.contains('\0') {
+        // U+0000 must be replaced with U+FFFD as per commonmark spec,
+        // we do it at the very end in order to avoid messing with byte offsets
+        // for source maps (since "\0".len() != "\u{FFFD}".len())
+        replace(input)
+    } else {
+        input
+    }
+}
+
+fn text_attrs(attrs: &[(String, String)]) -> Vec<(&str, AttrValue<'_>)> {
+    attrs.iter().map(|(name, value)| (name.as_str(), AttrValue::Text(value))).collect()
 }
 
-impl<const XHTML: bool> Renderer for HTMLRenderer<XHTML> {
+impl Renderer for HTMLRenderer {
     fn open(&mut self, tag: &str, attrs: &[(String, String)]) {
+        self.open_tag(tag, &text_attrs(attrs));
+    }
+
+    fn open_tag(&mut self, tag: &str, attrs: &[(&str, AttrValue<'_>)]) {
         self.result.push('<');
         self.result.push_str(tag);
-        self.make_attrs(attrs);
+        self.line_attr();
+        self.tag_attrs(tag, attrs);
         self.result.push('>');
     }
 
@@ -118,8 +645,9 @@ impl<const XHTML: bool> Renderer for HTMLRenderer<XHTML> {
     fn self_close(&mut self, tag: &str, attrs: &[(String, String)]) {
         self.result.push('<');
         self.result.push_str(tag);
-        self.make_attrs(attrs);
-        if XHTML {
+        self.line_attr();
+        self.tag_attrs(tag, &text_attrs(attrs));
+        if self.options.xhtml {
             self.result.push(' ');
             self.result.push('/');
         }
@@ -127,6 +655,9 @@ impl<const XHTML: bool> Renderer for HTMLRenderer<XHTML> {
     }
 
     fn contents(&mut self, nodes: &[Node]) {
+        if let Some(line) = self.leaf_line.take() {
+            return self.render_lines(nodes, line);
+        }
         for node in nodes.iter() {
             self.render(node);
         }
@@ -134,17 +665,19 @@ impl<const XHTML: bool> Renderer for HTMLRenderer<XHTML> {
 
     fn cr(&mut self) {
         // only push '\n' if last character isn't it
-        match self.result.as_bytes().last() {
+        match self.result.as_bytes().last().or(self.last_taken.as_ref()) {
             Some(b'\n') | None => {}
             Some(_) => self.result.push('\n')
         }
     }
 
     fn text(&mut self, text: &str) {
+        self.pending_line = None;
         self.result.push_str(&escape_html(text));
     }
 
     fn text_raw(&mut self, text: &str) {
+        self.pending_line = None;
         self.result.push_str(text);
     }
 
@@ -152,3 +685,516 @@ impl<const XHTML: bool> Renderer for HTMLRenderer<XHTML> {
         &mut self.ext
     }
 }
+
+#[derive(Debug, Default)]
+/// Plain text renderer, all markup is stripped.
+pub(crate) struct TextRenderer {
+    result: String,
+    ext: RenderExtSet,
+}
+
+#[derive(Debug, Default, Clone)]
+/// Indentation added to each line of plain text output by [TextRenderer],
+/// set by nodes with nested blocks (e.g. list items) while their children are rendered.
+pub(crate) struct TextIndent(pub String);
+impl RenderExt for TextIndent {}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn render(&mut self, node: &Node) {
+        node.render_with(self);
+    }
+
+    // push text, indenting lines that start in it
+    fn push(&mut self, text: &str) {
+        let Some(TextIndent(indent)) = self.ext.get::<TextIndent>().filter(|indent| !indent.0.is_empty()) else {
+            self.result.push_str(text);
+            return;
+        };
+
+        for line in text.split_inclusive('\n') {
+            if matches!(self.result.as_bytes().last(), Some(b'\n') | None) && line != "\n" {
+                self.result.push_str(indent);
+            }
+            self.result.push_str(line);
+        }
+    }
+}
+
+impl From<TextRenderer> for String {
+    fn from(f: TextRenderer) -> Self {
+        f.result
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn open(&mut self, _: &str, _: &[(String, String)]) {}
+
+    fn close(&mut self, _: &str) {}
+
+    fn self_close(&mut self, _: &str, _: &[(String, String)]) {}
+
+    fn contents(&mut self, nodes: &[Node]) {
+        for node in nodes.iter() {
+            self.render(node);
+        }
+    }
+
+    fn cr(&mut self) {
+        // only push '\n' if last character isn't it
+        match self.result.as_bytes().last() {
+            Some(b'\n') | None => {}
+            Some(_) => self.result.push('\n')
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        self.push(text);
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        self.push(text);
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        &mut self.ext
+    }
+
+    fn format(&self) -> &'static str {
+        "text"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Single output event emitted by [EventRenderer].
+pub enum RenderEvent {
+    /// Opening tag with attributes, see [Renderer::open].
+    Open { tag: String, attrs: Vec<(String, String)> },
+    /// Closing tag, see [Renderer::close].
+    Close { tag: String },
+    /// Self-closing tag with attributes, see [Renderer::self_close].
+    SelfClose { tag: String, attrs: Vec<(String, String)> },
+    /// Plain text (unescaped), see [Renderer::text].
+    Text(String),
+    /// Raw content that is supposed to be written as is, see [Renderer::text_raw].
+    TextRaw(String),
+    /// Line break requested by a block node, see [Renderer::cr].
+    Cr,
+}
+
+/// Renderer that passes structured events to a callback instead of building a string.
+///
+/// This is the simplest way to create a new output backend (LaTeX, ANSI terminal, etc.)
+/// outside of this crate: nodes emit the same events they use for html, and callback
+/// decides what to do with each of them.
+///
+/// ```rust
+/// use markdown_it::{EventRenderer, RenderEvent};
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+/// let ast = md.parse("*hello*");
+///
+/// let mut ansi = String::new();
+/// EventRenderer::new(|event| match event {
+///     RenderEvent::Open { tag, .. } if tag == "em" => ansi.push_str("\x1b[3m"),
+///     RenderEvent::Close { tag } if tag == "em" => ansi.push_str("\x1b[0m"),
+///     RenderEvent::Text(text) => ansi.push_str(&text),
+///     _ => {}
+/// }).render(&ast);
+///
+/// assert_eq!(ansi, "\x1b[3mhello\x1b[0m");
+/// ```
+pub struct EventRenderer<'a> {
+    callback: Box<dyn FnMut(RenderEvent) + 'a>,
+    ext: RenderExtSet,
+    // `Node::attrs_raw` of the node being rendered, added to its first tag as text
+    pending_raw_attrs: Vec<(String, String)>,
+}
+
+impl<'a> EventRenderer<'a> {
+    pub fn new(callback: impl FnMut(RenderEvent) + 'a) -> Self {
+        Self {
+            callback: Box::new(callback),
+            ext: RenderExtSet::new(),
+            pending_raw_attrs: Vec::new(),
+        }
+    }
+
+    pub fn render(&mut self, node: &Node) {
+        self.pending_raw_attrs.clone_from(&node.attrs_raw);
+        node.render_with(self);
+        self.pending_raw_attrs.clear();
+    }
+
+    fn tag_attrs(&mut self, attrs: &[(String, String)]) -> Vec<(String, String)> {
+        let mut attrs = attrs.to_vec();
+        for (name, value) in std::mem::take(&mut self.pending_raw_attrs) {
+            attrs.push((name, html_to_text(&value).into_owned()));
+        }
+        merge_attrs(&attrs, IdConflict::default())
+    }
+}
+
+impl Debug for EventRenderer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventRenderer").field("ext", &self.ext).finish_non_exhaustive()
+    }
+}
+
+impl Renderer for EventRenderer<'_> {
+    fn open(&mut self, tag: &str, attrs: &[(String, String)]) {
+        let attrs = self.tag_attrs(attrs);
+        (self.callback)(RenderEvent::Open { tag: tag.to_owned(), attrs });
+    }
+
+    fn close(&mut self, tag: &str) {
+        (self.callback)(RenderEvent::Close { tag: tag.to_owned() });
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(String, String)]) {
+        let attrs = self.tag_attrs(attrs);
+        (self.callback)(RenderEvent::SelfClose { tag: tag.to_owned(), attrs });
+    }
+
+    fn contents(&mut self, nodes: &[Node]) {
+        for node in nodes.iter() {
+            self.render(node);
+        }
+    }
+
+    fn cr(&mut self) {
+        (self.callback)(RenderEvent::Cr);
+    }
+
+    fn text(&mut self, text: &str) {
+        (self.callback)(RenderEvent::Text(text.to_owned()));
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        (self.callback)(RenderEvent::TextRaw(text.to_owned()));
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        &mut self.ext
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use crate::parser::builder::NodeBuilder;
+    use crate::parser::extset::RenderExtSet;
+    use crate::plugins::cmark::block::paragraph::Paragraph;
+    #[cfg(feature = "raw_attribute")]
+    use crate::plugins::extra::raw_attribute::RawInline;
+    use crate::{Node, NodeValue};
+
+    #[derive(Default)]
+    struct LatexRenderer {
+        result: String,
+        ext: RenderExtSet,
+    }
+
+    impl Renderer for LatexRenderer {
+        fn open(&mut self, _: &str, _: &[(String, String)]) {}
+        fn close(&mut self, _: &str) {}
+        fn self_close(&mut self, _: &str, _: &[(String, String)]) {}
+        fn contents(&mut self, nodes: &[Node]) {
+            for node in nodes {
+                node.render_with(self);
+            }
+        }
+        fn cr(&mut self) {}
+        fn text(&mut self, text: &str) { self.result.push_str(text); }
+        fn text_raw(&mut self, text: &str) { self.result.push_str(text); }
+        fn ext(&mut self) -> &mut RenderExtSet { &mut self.ext }
+        fn format(&self) -> &'static str { "latex" }
+        fn fallback_formats(&self) -> &'static [&'static str] { &["text"] }
+    }
+
+    #[derive(Debug)]
+    struct Emph;
+    impl NodeValue for Emph {
+        fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+            if format != "latex" { return false; }
+            fmt.text_raw("\\emph{");
+            fmt.contents(&node.children);
+            fmt.text_raw("}");
+            true
+        }
+    }
+
+    #[derive(Debug)]
+    struct Word(&'static str);
+    impl NodeValue for Word {
+        fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+            fmt.text_raw("<b>");
+            fmt.text(self.0);
+            fmt.text_raw("</b>");
+        }
+
+        fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+            match format {
+                "html" => self.render(node, fmt),
+                "text" => fmt.text(self.0),
+                _ => return false,
+            }
+            true
+        }
+    }
+
+    fn render_latex(node: &Node) -> String {
+        let mut fmt = LatexRenderer::default();
+        node.render_with(&mut fmt);
+        fmt.result
+    }
+
+    #[test]
+    fn fallback_chain() {
+        let node = NodeBuilder::new(Paragraph)
+            .child(NodeBuilder::new(Emph).text("a"))
+            .child(NodeBuilder::new(Word("b")))
+            .build();
+
+        // Emph: latex; Word: text fallback; Paragraph: html only, so children are rendered
+        assert_eq!(render_latex(&node), "\\emph{a}b");
+        assert_eq!(node.render(), "<p>a<b>b</b></p>\n");
+    }
+
+    #[cfg(feature = "raw_attribute")]
+    #[test]
+    fn fallback_chain_raw() {
+        let node = NodeBuilder::new(Paragraph)
+            .child(NodeBuilder::new(Word("b")))
+            .child(NodeBuilder::new(RawInline { format: "html".into(), content: "<br>".into() }))
+            .child(NodeBuilder::new(RawInline { format: "latex".into(), content: "\\\\".into() }))
+            .build();
+
+        assert_eq!(render_latex(&node), "b\\\\");
+        assert_eq!(node.render(), "<p><b>b</b><br></p>\n");
+    }
+
+    #[test]
+    fn events() {
+        use super::RenderEvent::*;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let events = md.parse("[a](/b)  \n![c](d)").render_events();
+
+        assert_eq!(events, vec![
+            Cr,
+            Open { tag: "p".into(), attrs: vec![] },
+            Open { tag: "a".into(), attrs: vec![("href".into(), "/b".into())] },
+            Text("a".into()),
+            Close { tag: "a".into() },
+            SelfClose { tag: "br".into(), attrs: vec![] },
+            Cr,
+            SelfClose { tag: "img".into(), attrs: vec![("src".into(), "d".into()), ("alt".into(), "c".into())] },
+            Close { tag: "p".into() },
+            Cr,
+        ]);
+    }
+
+    #[test]
+    fn events_merge_attrs() {
+        use super::RenderEvent::*;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let mut ast = md.parse("a");
+        let para = &mut ast.children[0];
+        para.attrs.push(("class".into(), "x y".into()));
+        para.attrs.push(("id".into(), "b".into()));
+        para.attrs.push(("class".into(), "y z".into()));
+        para.attrs.push(("id".into(), "c".into()));
+
+        assert_eq!(ast.render_events()[1], Open {
+            tag: "p".into(),
+            attrs: vec![("class".into(), "x y z".into()), ("id".into(), "b".into())],
+        });
+    }
+
+    #[test]
+    fn open_tag() {
+        use super::AttrValue::Html;
+        use super::RenderEvent::*;
+
+        #[derive(Debug)]
+        struct Abbr;
+        impl NodeValue for Abbr {
+            fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+                fmt.open_tag("abbr", &[("title", Html("&lt;b&gt; &amp; &#99;")), ("class", super::AttrValue::Text("a&b"))]);
+                fmt.contents(&node.children);
+                fmt.close_tag("abbr");
+            }
+        }
+
+        let node = NodeBuilder::new(Abbr).text("x").build();
+        assert_eq!(node.render(), "<abbr title=\"&lt;b&gt; &amp; &#99;\" class=\"a&amp;b\">x</abbr>");
+        assert_eq!(node.render_events(), vec![
+            Open { tag: "abbr".into(), attrs: vec![("title".into(), "<b> & c".into()), ("class".into(), "a&b".into())] },
+            Text("x".into()),
+            Close { tag: "abbr".into() },
+        ]);
+    }
+
+    #[test]
+    fn source_lines() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let options = super::RenderOptions { source_lines: true, ..Default::default() };
+
+        let html = md.parse("# Title\n\nfoo *bar\nbaz* quux  \nend\n\n> a\n> b\n\n---\n").render_with_options(&options);
+        assert_eq!(html, "\
+<h1 data-source-line=\"1\"><span data-source-line=\"1\">Title</span></h1>
+<p data-source-line=\"3\"><span data-source-line=\"3\">foo </span><em><span data-source-line=\"3\">bar</span>
+<span data-source-line=\"4\">baz</span></em><span data-source-line=\"4\"> quux</span><br>
+<span data-source-line=\"5\">end</span></p>
+<blockquote data-source-line=\"7\">
+<p data-source-line=\"7\"><span data-source-line=\"7\">a</span>
+<span data-source-line=\"8\">b</span></p>
+</blockquote>
+<hr data-source-line=\"10\">
+");
+
+        let html = md.parse("**a [b\nc](/u)**").render_with_options(&options);
+        assert_eq!(html, "<p data-source-line=\"1\"><strong><span data-source-line=\"1\">a </span>\
+            <a href=\"/u\"><span data-source-line=\"1\">b</span>\n<span data-source-line=\"2\">c</span></a></strong></p>\n");
+    }
+
+    #[test]
+    fn source_lines_disabled() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let ast = md.parse("a\nb");
+        assert_eq!(ast.render(), "<p>a\nb</p>\n");
+        let options = super::RenderOptions { source_lines: true, ..Default::default() };
+        assert_eq!(ast.children[0].render_with_options(&options), "<p>a\nb</p>\n");
+    }
+
+    #[test]
+    fn overrides() {
+        use crate::parser::postprocess;
+        use crate::plugins::cmark::inline::emphasis::Em;
+        use crate::plugins::cmark::inline::image::Image;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let mut options = super::RenderOptions::default();
+        options.overrides.set::<Em>(|node, fmt| {
+            fmt.open("i", &[]);
+            fmt.contents(&node.children);
+            fmt.close("i");
+        });
+        options.overrides.set::<Image>(|_, fmt| fmt.text("[image]"));
+
+        let ast = md.parse("*a ![b](c)* **d**");
+        assert_eq!(ast.render_with_options(&options), "<p><i>a [image]</i> <strong>d</strong></p>\n");
+
+        // overrides are applied to events passed to post-processing hooks as well
+        postprocess::add_event_hook(md, |_| {});
+        options.overrides.remove::<Image>();
+        let ast = md.parse("*a ![b](c)*");
+        assert_eq!(ast.render_with_options(&options), "<p><i>a <img src=\"c\" alt=\"b\"></i></p>\n");
+
+        // custom renderers use overrides from their extensions
+        let mut fmt = LatexRenderer::default();
+        options.overrides.set::<Image>(|_, fmt| fmt.text("[image]"));
+        options.overrides.install(&mut fmt.ext);
+        ast.render_with(&mut fmt);
+        assert_eq!(fmt.result, "a [image]");
+    }
+
+    #[test]
+    fn attr_hooks() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let mut options = super::RenderOptions { xhtml: true, ..Default::default() };
+        options.attr_hooks.add(|tag, attrs| {
+            if tag == "img" { attrs.retain(|(name, _)| name != "alt"); }
+            attrs.push(("class".into(), format!("x-{tag}")));
+        });
+        options.attr_hooks.add(|_, attrs| {
+            // hooks run in order, so this one sees classes added by the first one
+            for (_, value) in attrs.iter_mut().filter(|(name, _)| name == "class") { value.make_ascii_uppercase(); }
+        });
+
+        let ast = md.parse("# a\n\n[b](/\"c\") ![d](e)");
+        assert_eq!(ast.render_with_options(&options), "<h1 class=\"X-H1\">a</h1>\n\
+            <p class=\"X-P\"><a href=\"/%22c%22\" class=\"X-A\">b</a> <img src=\"e\" class=\"X-IMG\" /></p>\n");
+    }
+
+    #[cfg(feature = "attrs")]
+    #[test]
+    fn attr_escaping() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::attrs::add(md);
+
+        let ast = md.parse("# a {key=\"<script>\" b=x\">}");
+        assert_eq!(ast.render(), "<h1 key=\"&lt;script&gt;\" b=\"x&quot;&gt;\">a</h1>\n");
+
+        let mut ast = md.parse("a");
+        let paragraph = &mut ast.children[0];
+        paragraph.attrs.push(("x onclick".into(), "1".into()));
+        paragraph.attrs.push(("y\"".into(), "2".into()));
+        paragraph.attrs_raw.push(("title".into(), "&lt;b&gt;\" onclick=\"x".into()));
+        assert_eq!(ast.render(), "<p title=\"&lt;b&gt;&quot; onclick=&quot;x\">a</p>\n");
+    }
+
+    #[test]
+    fn attrs_raw() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let mut ast = md.parse("*a* b");
+        ast.children[0].children[0].attrs_raw.push(("title".into(), "&#x263A;".into()));
+        ast.children[0].children[0].attrs.push(("class".into(), "c".into()));
+        assert_eq!(ast.render(), "<p><em class=\"c\" title=\"&#x263A;\">a</em> b</p>\n");
+        // decoded into text for event renderer
+        assert!(ast.render_events().contains(&super::RenderEvent::Open {
+            tag: "em".into(),
+            attrs: vec![("class".into(), "c".into()), ("title".into(), "☺".into())],
+        }));
+
+        // text doesn't have tags, its raw attributes are dropped
+        #[derive(Debug)]
+        struct Trailer;
+        impl NodeValue for Trailer {
+            fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+                fmt.contents(&node.children);
+                fmt.self_close("hr", &[]);
+            }
+        }
+
+        let mut ast = md.parse("a");
+        ast.children[0].children[0].attrs_raw.push(("title".into(), "x".into()));
+        let mut trailer = Node::new(Trailer);
+        trailer.children = std::mem::take(&mut ast.children[0].children);
+        assert_eq!(trailer.render(), "a<hr>");
+        assert!(trailer.render_events().contains(&super::RenderEvent::SelfClose { tag: "hr".into(), attrs: vec![] }));
+    }
+
+    #[cfg(feature = "attrs")]
+    #[test]
+    fn merged_attrs() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::attrs::add(md);
+
+        let mut ast = md.parse("# a {#x .b .c}");
+        ast.children[0].attrs.push(("class".into(), "c d".into()));
+        ast.children[0].attrs.push(("id".into(), "y".into()));
+        assert_eq!(ast.render(), "<h1 id=\"x\" class=\"b c d\">a</h1>\n");
+
+        let options = super::RenderOptions { id_conflict: super::IdConflict::KeepLast, ..Default::default() };
+        assert_eq!(ast.render_with_options(&options), "<h1 id=\"y\" class=\"b c d\">a</h1>\n");
+    }
+}
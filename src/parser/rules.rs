@@ -0,0 +1,177 @@
+//! Rules enabled and disabled by name at runtime.
+//!
+//! Each rule is named after the module it's defined in (e.g. `html_block`, `image`
+//! or `linkify`), same as rule names in markdown-it.js. Several rules can share
+//! a name (e.g. `linkify` has both inline and core rules), they are enabled and
+//! disabled together.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::html::add(md);
+//!
+//! md.disable(&["html_block", "html_inline", "image"]).unwrap();
+//! assert_eq!(md.parse("<div>\n\n![a](a.png)").render(), "<p>&lt;div&gt;</p>\n<p>!<a href=\"a.png\">a</a></p>\n");
+//!
+//! md.enable("image").unwrap();
+//! assert_eq!(md.parse("![a](a.png)").render(), "<p><img src=\"a.png\" alt=\"a\"></p>\n");
+//!
+//! assert!(md.disable("foo").is_err());
+//! assert!(md.rules().iter().any(|rule| rule.name == "html_block" && !rule.enabled));
+//! ```
+//!
+//! Disabled rules still take part in rule ordering, so plugins that depend on
+//! them keep working.
+//!
+//! Rules defined in [generic](crate::generics) modules are named by plugins that
+//! use them with [set_rule_name] (e.g. `image` and `link` both use `full_link`).
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use crate::common::TypeKey;
+use crate::parser::extset::MarkdownItExt;
+use crate::MarkdownIt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Rule chain a rule belongs to, see [parser](crate::parser).
+pub enum RuleChain {
+    Core,
+    Block,
+    Inline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Rule registered in a parser, returned by [MarkdownIt::rules](crate::MarkdownIt::rules).
+pub struct RuleInfo {
+    /// Name of the rule, see [module documentation](self).
+    pub name: &'static str,
+    pub chain: RuleChain,
+    /// Type of the rule.
+    pub key: TypeKey,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Default)]
+struct RuleNameMap(HashMap<TypeKey, &'static str>);
+impl MarkdownItExt for RuleNameMap {}
+
+/// Set name of a rule (of any chain) instead of its module name.
+pub fn set_rule_name<T: 'static>(md: &mut MarkdownIt, name: &'static str) {
+    md.ext.get_or_insert_default::<RuleNameMap>().0.insert(TypeKey::of::<T>(), name);
+}
+
+// name set with `set_rule_name`, or default one
+pub(crate) fn rule_name_in(md: &MarkdownIt, key: TypeKey) -> &'static str {
+    md.ext.get::<RuleNameMap>()
+        .and_then(|map| map.0.get(&key).copied())
+        .unwrap_or_else(|| rule_name(key))
+}
+
+/// Default name of a rule: name of the module its type is defined in.
+pub fn rule_name(key: TypeKey) -> &'static str {
+    // strip generic parameters, e.g. `smartquotes::SmartQuotesRule<'‘', ...>`
+    let path = key.name.split('<').next().unwrap_or_default();
+    let mut segments = path.rsplit("::");
+    let type_name = segments.next().unwrap_or_default();
+    segments.next().unwrap_or(type_name)
+}
+
+/// One or several rule names, accepted by [MarkdownIt::enable](crate::MarkdownIt::enable)
+/// and [MarkdownIt::disable](crate::MarkdownIt::disable).
+pub trait RuleNames {
+    fn rule_names(&self) -> Vec<&str>;
+}
+
+impl RuleNames for &str {
+    fn rule_names(&self) -> Vec<&str> {
+        vec![self]
+    }
+}
+
+impl RuleNames for &[&str] {
+    fn rule_names(&self) -> Vec<&str> {
+        self.to_vec()
+    }
+}
+
+impl<const N: usize> RuleNames for &[&str; N] {
+    fn rule_names(&self) -> Vec<&str> {
+        self.to_vec()
+    }
+}
+
+impl RuleNames for &[String] {
+    fn rule_names(&self) -> Vec<&str> {
+        self.iter().map(String::as_str).collect()
+    }
+}
+
+impl RuleNames for &Vec<String> {
+    fn rule_names(&self) -> Vec<&str> {
+        self.iter().map(String::as_str).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Error returned when there is no rule with given name.
+pub struct UnknownRule(pub String);
+
+impl Display for UnknownRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown rule: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownRule {}
+
+#[cfg(test)]
+mod tests {
+    use super::rule_name;
+    use crate::common::TypeKey;
+    #[cfg(feature = "smartquotes")]
+    use crate::plugins::extra::smartquotes::SmartQuotesRule;
+
+    #[test]
+    fn names() {
+        assert_eq!(rule_name(TypeKey::of::<crate::plugins::cmark::block::hr::HrScanner>()), "hr");
+        #[cfg(feature = "smartquotes")]
+        assert_eq!(rule_name(TypeKey::of::<SmartQuotesRule<'a', 'b', 'c', 'd'>>()), "smartquotes");
+        assert_eq!(rule_name(TypeKey::of::<u32>()), "u32");
+    }
+
+    #[cfg(all(feature = "smartquotes", feature = "strikethrough"))]
+    #[test]
+    fn list() {
+        use super::RuleChain;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::add(md);
+
+        let rules = md.rules();
+        let names = |chain| rules.iter().filter(|rule| rule.chain == chain).map(|rule| rule.name).collect::<Vec<_>>();
+        assert!(names(RuleChain::Block).starts_with(&["code", "fence", "blockquote", "hr"]));
+        assert!(names(RuleChain::Inline).starts_with(&["skip_text", "newline", "escape", "backticks", "emphasis"]));
+        assert!(names(RuleChain::Inline).contains(&"strikethrough"));
+        assert!(names(RuleChain::Core).contains(&"smartquotes"));
+        assert!(rules.iter().all(|rule| rule.enabled));
+    }
+
+    #[cfg(all(feature = "strikethrough", feature = "typographer"))]
+    #[test]
+    fn toggle() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::add(md);
+
+        md.disable(&["emphasis", "strikethrough", "typographer"]).unwrap();
+        assert_eq!(md.parse("*a* ~~b~~ (c)").render(), "<p>*a* ~~b~~ (c)</p>\n");
+
+        // nothing is changed if any of the names is unknown
+        assert_eq!(md.enable(&["emphasis", "foo"]).unwrap_err().to_string(), "unknown rule: foo");
+        assert_eq!(md.parse("*a*").render(), "<p>*a*</p>\n");
+
+        md.enable(&vec!["emphasis".to_owned(), "typographer".to_owned()]).unwrap();
+        assert_eq!(md.parse("*a* ~~b~~ (c)").render(), "<p><em>a</em> ~~b~~ ©</p>\n");
+    }
+}
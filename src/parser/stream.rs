@@ -0,0 +1,314 @@
+//! Feed markdown source to the parser in chunks (e.g. from a reader or a network stream).
+//!
+//! Top-level blocks are parsed as soon as a next block starts, so that they can
+//! be rendered and dropped with [ParserStream::take_blocks] before the whole
+//! document is received, and only the source of the last block is kept. It happens
+//! when enough input is buffered or on [ParserStream::flush]. Remaining blocks are
+//! parsed when [ParserStream::finish] is called. Chunks may be split in the middle
+//! of a UTF-8 character.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let mut stream = md.stream();
+//! stream.push_str("[link]: /url\n\n# hello ");
+//! stream.push_bytes(b"\xE2\x9C").unwrap();
+//! stream.push_bytes(b"\x93\n\n[link]").unwrap();
+//! stream.flush();
+//!
+//! let html : String = stream.take_blocks().iter().map(|node| node.render()).collect();
+//! assert_eq!(html, "<h1>hello ✓</h1>\n");
+//!
+//! let ast = stream.finish().unwrap();
+//! assert_eq!(ast.render(), "<p><a href=\"/url\">link</a></p>\n");
+//! ```
+//!
+//! Parsed blocks can't see input received after them, so unlike in a document parsed
+//! at once, links in them can only use references defined earlier, and core rules
+//! that work on the whole document (e.g. footnotes or unique heading ids) only see
+//! blocks parsed together. Blocks that are only recognized once their closing line
+//! is found (e.g. `:::` containers) should be received in the same flush.
+//!
+//! It also implements [std::io::Write], so any reader can be copied into it:
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let mut stream = md.stream();
+//! std::io::copy(&mut "*hello*".as_bytes(), &mut stream).unwrap();
+//! assert_eq!(stream.finish().unwrap().render(), "<p><em>hello</em></p>\n");
+//! ```
+use std::fmt::{self, Display};
+use std::io;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::block::reference::{CustomReferenceMap, DefaultReferenceMap, ReferenceMap};
+use crate::{MarkdownIt, Node};
+
+// buffered source that is parsed to find completed blocks
+const FLUSH_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Error returned if stream input isn't valid UTF-8.
+pub struct InvalidUtf8 {
+    /// Byte offset of invalid sequence from the start of the stream.
+    pub offset: usize,
+}
+
+impl Display for InvalidUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid utf-8 sequence at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for InvalidUtf8 {}
+
+#[derive(Debug)]
+/// Markdown source collected in chunks, see [module documentation](self).
+pub struct ParserStream<'a> {
+    md: &'a MarkdownIt,
+    // source of blocks that aren't parsed yet
+    source: String,
+    // length of the input before `source`
+    offset: usize,
+    // size of `source` at which it's parsed again
+    flush_at: usize,
+    // incomplete utf-8 character at the end of the last chunk
+    pending: Vec<u8>,
+    // parsed blocks that weren't taken yet
+    blocks: Vec<Node>,
+    // link references defined in parsed blocks
+    references: DefaultReferenceMap,
+}
+
+impl<'a> ParserStream<'a> {
+    pub fn new(md: &'a MarkdownIt) -> Self {
+        Self {
+            md,
+            source: String::new(),
+            offset: 0,
+            flush_at: FLUSH_SIZE,
+            pending: Vec::new(),
+            blocks: Vec::new(),
+            references: DefaultReferenceMap::new(),
+        }
+    }
+
+    /// Add a chunk of text. If previous chunk of bytes ended with an incomplete
+    /// character, that character is replaced with U+FFFD.
+    pub fn push_str(&mut self, chunk: &str) {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.source.push(char::REPLACEMENT_CHARACTER);
+        }
+        self.source.push_str(chunk);
+        self.flush_if_needed();
+    }
+
+    /// Add a chunk of UTF-8 encoded bytes.
+    pub fn push_bytes(&mut self, chunk: &[u8]) -> Result<(), InvalidUtf8> {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(chunk);
+
+        match std::str::from_utf8(&bytes) {
+            Ok(str) => self.source.push_str(str),
+            Err(err) => {
+                let valid = err.valid_up_to();
+                if err.error_len().is_some() {
+                    return Err(InvalidUtf8 { offset: self.source.len() + valid });
+                }
+                self.source.push_str(std::str::from_utf8(&bytes[..valid]).unwrap());
+                self.pending = bytes[valid..].to_vec();
+            }
+        }
+        self.flush_if_needed();
+        Ok(())
+    }
+
+    /// Number of bytes received so far.
+    pub fn len(&self) -> usize {
+        self.offset + self.source.len() + self.pending.len()
+    }
+
+    /// Check if nothing was received yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parse top-level blocks received so far, except for the last one
+    /// (which may continue in the next chunk).
+    pub fn flush(&mut self) {
+        let mut root = self.parse_source();
+        let Some(last) = root.children.iter().rposition(|node| node.srcmap.is_some()) else { return; };
+        let (start, _) = root.children[last].srcmap.unwrap().get_byte_offsets();
+        let start = self.source[..start].rfind('\n').map_or(0, |pos| pos + 1);
+        if start == 0 { return; }
+
+        // blocks after the last one are added by core rules, and are added again later
+        root.children.truncate(last);
+        shift_srcmaps(&mut root.children, self.offset);
+        self.blocks.append(&mut root.children);
+
+        if let Some(map) = ReferenceMap::of(&root).and_then(|map| map.downcast_ref::<DefaultReferenceMap>()) {
+            for (label, destination, title) in map.iter() {
+                self.references.insert(label.to_owned(), destination.to_owned(), title.map(str::to_owned));
+            }
+        }
+
+        self.source.drain(..start);
+        self.offset += start;
+        self.flush_at = FLUSH_SIZE.max(self.source.len() * 2);
+    }
+
+    /// Take blocks parsed so far, so that they can be rendered before
+    /// the rest of the document is received.
+    pub fn take_blocks(&mut self) -> Vec<Node> {
+        std::mem::take(&mut self.blocks)
+    }
+
+    /// Parse the rest of the source, returns an error if input ends in the middle
+    /// of a character. Returned document contains blocks that weren't taken,
+    /// its [Root](crate::parser::core::Root) content is the source of the blocks
+    /// parsed last.
+    pub fn finish(self) -> Result<Node, InvalidUtf8> {
+        if !self.pending.is_empty() {
+            return Err(InvalidUtf8 { offset: self.len() - self.pending.len() });
+        }
+
+        let mut root = self.parse_source();
+        shift_srcmaps(&mut root.children, self.offset);
+        root.children.splice(0..0, self.blocks);
+        if root.srcmap.is_some() {
+            root.srcmap = Some(SourcePos::new(0, self.offset + self.source.len()));
+        }
+        Ok(root)
+    }
+
+    fn flush_if_needed(&mut self) {
+        if self.source.len() >= self.flush_at {
+            // if nothing was parsed, wait until the buffer doubles
+            self.flush_at = self.source.len() * 2;
+            self.flush();
+        }
+    }
+
+    fn parse_source(&self) -> Node {
+        let mut options = ParseOptions::new();
+        options.ext.insert(ReferenceMap::new(self.references.clone()));
+        self.md.parse_with_options(&self.source, options)
+    }
+}
+
+fn shift_srcmaps(nodes: &mut [Node], offset: usize) {
+    if offset == 0 { return; }
+    for node in nodes {
+        node.walk_mut(|node, _| {
+            if let Some(map) = node.srcmap {
+                let (start, end) = map.get_byte_offsets();
+                node.srcmap = Some(SourcePos::new(start + offset, end + offset));
+            }
+        });
+    }
+}
+
+impl io::Write for ParserStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.push_bytes(buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        ParserStream::flush(self);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InvalidUtf8;
+
+    fn md() -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        md
+    }
+
+    #[test]
+    fn split_chars() {
+        let md = &md();
+        let src = "ä€😀 *x*".as_bytes();
+        for size in 1..=4 {
+            let mut stream = md.stream();
+            for chunk in src.chunks(size) {
+                stream.push_bytes(chunk).unwrap();
+            }
+            assert_eq!(stream.len(), src.len());
+            assert_eq!(stream.finish().unwrap().render(), "<p>ä€😀 <em>x</em></p>\n");
+        }
+    }
+
+    #[test]
+    fn flush() {
+        let md = &md();
+        let src = "[a]: /a\n\n# h\n\n- b\n\n- c\n\n```\nd\n\ne\n```\n\nf\n===\n\n  [a] g";
+        for size in [1, 3, 8] {
+            let mut stream = md.stream();
+            let mut blocks = Vec::new();
+            for chunk in src.as_bytes().chunks(size) {
+                stream.push_bytes(chunk).unwrap();
+                stream.flush();
+                blocks.append(&mut stream.take_blocks());
+            }
+            let ast = stream.finish().unwrap();
+            let html : String = blocks.iter().chain(ast.children.iter()).map(|node| node.render()).collect();
+            assert_eq!(html, md.parse(src).render());
+
+            let srcmap = |node: &crate::Node| node.srcmap.map(|map| map.get_byte_offsets());
+            let srcmaps : Vec<_> = blocks.iter().chain(ast.children.iter()).map(srcmap).collect();
+            let expected : Vec<_> = md.parse(src).children.iter().map(srcmap).collect();
+            assert_eq!(srcmaps, expected);
+        }
+    }
+
+    #[test]
+    fn flush_size() {
+        let md = &md();
+        let mut stream = md.stream();
+        let count = super::FLUSH_SIZE / 3 + 1;
+        for _ in 0..count {
+            stream.push_str("a\n\n");
+        }
+        // blocks are parsed once enough input is buffered
+        assert_eq!(stream.take_blocks().len(), count - 1);
+        assert_eq!(stream.len(), count * 3);
+        assert_eq!(stream.finish().unwrap().children.len(), 1);
+    }
+
+    #[test]
+    fn invalid() {
+        let md = &md();
+        let mut stream = md.stream();
+        stream.push_str("ab");
+        assert_eq!(stream.push_bytes(b"c\xFFd"), Err(InvalidUtf8 { offset: 3 }));
+
+        let mut stream = md.stream();
+        stream.push_bytes(b"ab\xE2\x9C").unwrap();
+        assert_eq!(stream.finish().unwrap_err(), InvalidUtf8 { offset: 2 });
+
+        let mut stream = md.stream();
+        stream.push_bytes(b"a\xE2").unwrap();
+        stream.push_str("b");
+        assert_eq!(stream.finish().unwrap().render(), "<p>a\u{FFFD}b</p>\n");
+    }
+
+    #[test]
+    fn io_error() {
+        let md = &md();
+        let mut stream = md.stream();
+        let err = std::io::copy(&mut &b"\xFF"[..], &mut stream).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
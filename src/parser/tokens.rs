@@ -0,0 +1,374 @@
+//! Flat token stream view of the AST, as used by markdown-it.js.
+//!
+//! JS plugins work with a list of tokens (`paragraph_open`, `inline`, `paragraph_close`,
+//! ...) instead of a tree. This module converts the AST into the same representation,
+//! so that behavior of a JS plugin can be compared token by token with its port,
+//! and tests from JS plugins can be reused as is.
+//!
+//! Token types of the built-in nodes follow markdown-it.js naming; other nodes get
+//! a type from their struct name in snake case (`Kbd` -> `kbd_open`, `kbd_close`).
+//! Tag and attributes are the ones node renders as html (outermost tag, if there are several).
+//!
+//! ```rust
+//! use markdown_it::parser::tokens;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let tokens = tokens::from_ast(&md.parse("# *hello*"));
+//! let types : Vec<_> = tokens.iter().map(|t| t.kind.as_str()).collect();
+//! assert_eq!(types, ["heading_open", "inline", "heading_close"]);
+//! assert_eq!(tokens[0].tag, "h1");
+//!
+//! let types : Vec<_> = tokens[1].children.iter().map(|t| t.kind.as_str()).collect();
+//! assert_eq!(types, ["em_open", "text", "em_close"]);
+//! ```
+use crate::common::sourcemap::SourcePos;
+use crate::common::utils::html_to_text;
+use crate::parser::core::Root;
+use crate::parser::extset::RenderExtSet;
+use crate::parser::inline::{Text, TextSpecial};
+use crate::plugins::cmark::block::code::CodeBlock;
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::plugins::cmark::block::list::ListItem;
+use crate::plugins::cmark::inline::backticks::CodeInline;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::html::html_block::HtmlBlock;
+use crate::plugins::html::html_inline::HtmlInline;
+use crate::{merge_attrs, IdConflict, Node, Renderer};
+
+#[derive(Debug, Clone, Default)]
+/// Single token, same as `Token` class in markdown-it.js.
+pub struct Token {
+    /// Token type, e.g. `paragraph_open` (`type` in JS).
+    pub kind: String,
+    /// Html tag name, e.g. `p`.
+    pub tag: String,
+    /// Level change: `1` means the tag is opening, `0` is self-closing, `-1` is closing.
+    pub nesting: i8,
+    pub attrs: Vec<(String, String)>,
+    /// Source position of the node this token was created from
+    /// (byte offsets, unlike line numbers in `map` in JS).
+    pub srcmap: Option<SourcePos>,
+    /// Contents of self-closing tokens: text, code, html or image alt text.
+    pub content: String,
+    /// Fence info string.
+    pub info: String,
+    /// True for block-level tokens, false for inline tokens.
+    pub block: bool,
+    /// If true, token isn't rendered (used for paragraphs in tight lists).
+    pub hidden: bool,
+    /// Inline tokens, for `inline` and `image` tokens.
+    pub children: Vec<Token>,
+}
+
+/// Convert AST into a list of block tokens.
+pub fn from_ast(root: &Node) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    if root.is::<Root>() {
+        push_blocks(&root.children, &mut tokens);
+    } else {
+        push_blocks(std::slice::from_ref(root), &mut tokens);
+    }
+    tokens
+}
+
+// markdown-it.js types for built-in nodes that don't match their struct names
+const KNOWN_TYPES : [(&str, &str); 10] = [
+    ("ATXHeading", "heading"),
+    ("SetextHeader", "heading"),
+    ("ThematicBreak", "hr"),
+    ("CodeFence", "fence"),
+    ("CodeBlock", "code_block"),
+    ("Strikethrough", "s"),
+    ("Autolink", "link"),
+    ("Linkified", "link"),
+    ("TextSpecial", "text_special"),
+    ("HtmlMarkdownBlock", "html_block"),
+];
+
+// nodes whose type in markdown-it.js is the same as their tag
+const TAG_TYPES : [&str; 4] = ["TableHead", "TableBody", "TableRow", "TableCell"];
+
+fn short_name(node: &Node) -> &'static str {
+    let name = node.name();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (idx, ch) in name.char_indices() {
+        if ch.is_ascii_uppercase() && idx > 0 {
+            result.push('_');
+        }
+        result.push(ch.to_ascii_lowercase());
+    }
+    result
+}
+
+// tag, attributes, and whether the node is a block, found by rendering node itself (without children)
+//
+// Block nodes write a line break before their output (`<p>`) or after their closing tag (`</li>`),
+// inline nodes don't, except for line breaks themselves (`<br>` + newline, or just a newline).
+#[derive(Debug, Default)]
+struct NodeShape {
+    tag: Option<String>,
+    attrs: Vec<(String, String)>,
+    self_closing: bool,
+    block: bool,
+    started: bool,
+    has_output: bool,
+    after_close: bool,
+    ext: RenderExtSet,
+}
+
+impl NodeShape {
+    fn new(node: &Node) -> Self {
+        let mut shape = Self::default();
+        node.render_with(&mut shape);
+        shape.block &= shape.has_output;
+        // html renderer adds raw attributes to the first tag of the node
+        if shape.tag.is_some() {
+            shape.attrs.extend(raw_attrs(node));
+        }
+        shape
+    }
+
+    fn output(&mut self) {
+        self.started = true;
+        self.has_output = true;
+        self.after_close = false;
+    }
+
+    fn tag(&mut self, tag: &str, attrs: &[(String, String)], self_closing: bool) {
+        if self.tag.is_none() {
+            self.tag = Some(tag.to_owned());
+            self.attrs = attrs.to_vec();
+            self.self_closing = self_closing;
+        }
+        self.output();
+    }
+}
+
+impl Renderer for NodeShape {
+    fn open(&mut self, tag: &str, attrs: &[(String, String)]) {
+        self.tag(tag, attrs, false);
+    }
+
+    fn close(&mut self, _: &str) {
+        self.output();
+        self.after_close = true;
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(String, String)]) {
+        self.tag(tag, attrs, true);
+    }
+
+    fn contents(&mut self, _: &[Node]) {
+        self.started = true;
+    }
+
+    fn cr(&mut self) {
+        if !self.started || self.after_close { self.block = true; }
+    }
+
+    fn text(&mut self, _: &str) {
+        self.output();
+    }
+
+    fn text_raw(&mut self, _: &str) {
+        self.output();
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        &mut self.ext
+    }
+}
+
+// `Node::attrs_raw` decoded to text, as in other attributes of tokens
+fn raw_attrs(node: &Node) -> impl Iterator<Item = (String, String)> + '_ {
+    node.attrs_raw.iter().map(|(name, value)| (name.clone(), html_to_text(value).into_owned()))
+}
+
+fn make_token(node: &Node, shape: &NodeShape, nesting: i8) -> Token {
+    let name = short_name(node);
+    let tag = shape.tag.clone().unwrap_or_default();
+    let mut kind = match KNOWN_TYPES.iter().find(|(known, _)| *known == name) {
+        Some((_, kind)) => kind.to_string(),
+        None if TAG_TYPES.contains(&name) => tag.clone(),
+        None => snake_case(name),
+    };
+    match nesting {
+        1 => kind.push_str("_open"),
+        -1 => kind.push_str("_close"),
+        _ => {}
+    }
+
+    Token {
+        kind,
+        tag,
+        nesting,
+        // repeated attributes are merged, like `attrJoin` does in JS
+        attrs: if nesting == -1 { Vec::new() } else { merge_attrs(&shape.attrs, IdConflict::default()) },
+        srcmap: node.srcmap,
+        block: shape.block,
+        ..Default::default()
+    }
+}
+
+// nodes that are represented by a single token in markdown-it.js, even if they have children
+fn is_leaf(node: &Node, shape: &NodeShape) -> bool {
+    node.is::<CodeInline>() || node.is::<Image>() || node.is::<CodeFence>() || node.is::<CodeBlock>()
+        || (node.children.is_empty() && (shape.tag.is_none() || shape.self_closing))
+}
+
+fn leaf_token(node: &Node, shape: &NodeShape) -> Token {
+    let mut token = make_token(node, shape, 0);
+
+    if let Some(data) = node.cast::<Text>() {
+        token.content = data.content.clone();
+    } else if let Some(data) = node.cast::<TextSpecial>() {
+        token.content = data.content.clone();
+    } else if let Some(data) = node.cast::<CodeFence>() {
+        token.content = data.content.clone();
+        token.info = data.info.clone();
+        token.tag = "code".into();
+        let attrs : Vec<_> = node.attrs.iter().cloned().chain(raw_attrs(node)).collect();
+        token.attrs = merge_attrs(&attrs, IdConflict::default());
+    } else if let Some(data) = node.cast::<CodeBlock>() {
+        token.content = data.content.clone();
+        token.tag = "code".into();
+        let attrs : Vec<_> = node.attrs.iter().cloned().chain(raw_attrs(node)).collect();
+        token.attrs = merge_attrs(&attrs, IdConflict::default());
+    } else if let Some(data) = node.cast::<HtmlBlock>() {
+        token.content = data.content.clone();
+    } else if let Some(data) = node.cast::<HtmlInline>() {
+        token.content = data.content.clone();
+    } else {
+        token.content = node.collect_text();
+    }
+
+    if node.is::<Image>() {
+        push_inlines(&node.children, &mut token.children);
+    }
+    token
+}
+
+fn push_blocks(nodes: &[Node], tokens: &mut Vec<Token>) {
+    let mut idx = 0;
+    while idx < nodes.len() {
+        let node = &nodes[idx];
+        let shape = NodeShape::new(node);
+
+        if !shape.block {
+            // inline content without a paragraph (e.g. in tight lists)
+            let start = idx;
+            while idx < nodes.len() && !NodeShape::new(&nodes[idx]).block { idx += 1; }
+            let open = Token { kind: "paragraph_open".into(), tag: "p".into(), nesting: 1, block: true, hidden: true, ..Default::default() };
+            let close = Token { kind: "paragraph_close".into(), nesting: -1, ..open.clone() };
+            tokens.push(open);
+            tokens.push(inline_token(&nodes[start..idx]));
+            tokens.push(close);
+            continue;
+        }
+
+        if is_leaf(node, &shape) {
+            tokens.push(leaf_token(node, &shape));
+        } else {
+            tokens.push(make_token(node, &shape, 1));
+            // list items may contain blocks, so their inline content is put into hidden paragraphs
+            if !node.is::<ListItem>() && !node.children.is_empty() && node.children.iter().all(|child| !NodeShape::new(child).block) {
+                tokens.push(inline_token(&node.children));
+            } else {
+                push_blocks(&node.children, tokens);
+            }
+            tokens.push(make_token(node, &shape, -1));
+        }
+        idx += 1;
+    }
+}
+
+fn inline_token(nodes: &[Node]) -> Token {
+    let mut token = Token { kind: "inline".into(), block: true, ..Default::default() };
+    if let (Some(first), Some(last)) = (nodes.first().and_then(|n| n.srcmap), nodes.last().and_then(|n| n.srcmap)) {
+        token.srcmap = Some(SourcePos::new(first.get_byte_offsets().0, last.get_byte_offsets().1));
+    }
+    for node in nodes {
+        token.content.push_str(&node.collect_text());
+    }
+    push_inlines(nodes, &mut token.children);
+    token
+}
+
+fn push_inlines(nodes: &[Node], tokens: &mut Vec<Token>) {
+    for node in nodes {
+        let shape = NodeShape::new(node);
+        if is_leaf(node, &shape) {
+            tokens.push(leaf_token(node, &shape));
+        } else {
+            tokens.push(make_token(node, &shape, 1));
+            push_inlines(&node.children, tokens);
+            tokens.push(make_token(node, &shape, -1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_ast, Token};
+
+    fn kinds(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|t| t.kind.as_str()).collect()
+    }
+
+    #[test]
+    fn blocks() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let tokens = from_ast(&md.parse("- a\n- b\n\n```rs\nx\n```\n\n---"));
+        assert_eq!(kinds(&tokens), [
+            "bullet_list_open",
+            "list_item_open", "paragraph_open", "inline", "paragraph_close", "list_item_close",
+            "list_item_open", "paragraph_open", "inline", "paragraph_close", "list_item_close",
+            "bullet_list_close",
+            "fence",
+            "hr",
+        ]);
+        assert!(tokens[2].hidden);
+        assert_eq!(tokens[3].content, "a");
+        assert_eq!((tokens[12].tag.as_str(), tokens[12].info.as_str(), tokens[12].content.as_str()), ("code", "rs", "x\n"));
+        assert_eq!(tokens[13].tag, "hr");
+    }
+
+    #[test]
+    fn inlines() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let tokens = from_ast(&md.parse("[a `b`](/c) ![*d*](/e)  \nf"));
+        assert_eq!(kinds(&tokens), ["paragraph_open", "inline", "paragraph_close"]);
+        assert!(!tokens[0].hidden);
+
+        let inline = &tokens[1].children;
+        assert_eq!(kinds(inline), ["link_open", "text", "code_inline", "link_close", "text", "image", "hardbreak", "text"]);
+        assert_eq!(inline[0].attrs, [("href".to_owned(), "/c".to_owned())]);
+        assert_eq!(inline[2].content, "b");
+        assert_eq!(inline[5].content, "d");
+        assert_eq!(kinds(&inline[5].children), ["em_open", "text", "em_close"]);
+        assert!(inline.iter().all(|t| !t.block));
+    }
+
+    #[test]
+    fn raw_attrs() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let mut ast = md.parse("a\n\n    b");
+        for node in ast.children.iter_mut() {
+            node.attrs_raw.push(("title".into(), "x &amp; y".into()));
+        }
+
+        let tokens = from_ast(&ast);
+        assert_eq!(tokens[0].attrs, [("title".to_owned(), "x & y".to_owned())]);
+        assert_eq!(tokens[3].attrs, [("title".to_owned(), "x & y".to_owned())]);
+    }
+}
@@ -0,0 +1,85 @@
+//! Everything a plugin crate needs, in one place.
+//!
+//! Parser internals are reorganized from time to time, and plugins importing types
+//! by their full paths (`markdown_it::parser::inline::builtin::...`) break when that
+//! happens. Items re-exported here are the stable part of the API: they will stay
+//! available at these paths, and their signatures will only change in a semver-major
+//! release. Anything not listed here may change in a minor release.
+//!
+//! ```rust
+//! use markdown_it::plugin_api::*;
+//!
+//! #[derive(Debug)]
+//! struct Kbd;
+//! impl NodeValue for Kbd {
+//!     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+//!         fmt.open("kbd", &node.attrs);
+//!         fmt.contents(&node.children);
+//!         fmt.close("kbd");
+//!     }
+//! }
+//!
+//! // `||Ctrl||` -> `<kbd>Ctrl</kbd>`
+//! struct KbdScanner;
+//! impl InlineRule for KbdScanner {
+//!     const MARKER: char = '|';
+//!
+//!     fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+//!         let input = state.src[state.pos..state.pos_max].strip_prefix("||")?;
+//!         let end = input.find("||")?;
+//!         let mut node = Node::new(Kbd);
+//!         node.children.push(Node::new(Text { content: input[..end].to_owned() }));
+//!         Some((node, end + 4))
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! md.inline.add_rule::<KbdScanner>();
+//!
+//! assert_eq!(md.parse("press ||Ctrl||").render(), "<p>press <kbd>Ctrl</kbd></p>\n");
+//! ```
+
+// parser and AST
+pub use crate::{MarkdownIt, Node, NodeValue};
+pub use crate::parser::core::Root;
+pub use crate::parser::options::ParseOptions;
+pub use crate::parser::inline::{InlineRoot, Text, TextSpecial};
+pub use crate::parser::node::NodeEmpty;
+pub use crate::parser::builder::NodeBuilder;
+pub use crate::common::sourcemap::SourcePos;
+
+// rule chains
+pub use crate::parser::core::CoreRule;
+pub use crate::parser::block::{BlockRule, BlockState};
+pub use crate::parser::inline::{DelimiterRun, InlineRule, InlineState};
+pub use crate::common::ruler::RulerError;
+
+/// Rules that other rules are usually positioned against,
+/// e.g. `md.add_rule::<MyRule>().after::<InlineParserRule>()`.
+pub mod rules {
+    pub use crate::parser::block::builtin::BlockParserRule;
+    pub use crate::parser::inline::builtin::InlineParserRule;
+}
+
+// plugin storage
+pub use crate::parser::extset::{InlineRootExt, MarkdownItExt, NodeExt, RenderExt, RootExt};
+
+// rendering
+pub use crate::{AttrValue, Renderer};
+
+/// Generic syntax helpers (see [generics](crate::generics)).
+pub mod delimiters {
+    /// Emphasis-like pairs: `*a*`, `~~a~~`, `==a==`.
+    pub use crate::generics::inline::emph_pair::add_with as add_emph_pair;
+    /// Code-like pairs: `` `a` ``, `$a$`.
+    pub use crate::generics::inline::code_pair::add_with as add_code_pair;
+    /// Link-like structures: `[a](b)`, `![a](b)`.
+    pub use crate::generics::inline::full_link::{add as add_link, add_prefix as add_link_with_prefix};
+    pub use crate::generics::inline::full_link::{parse_link_destination, parse_link_title};
+}
+
+/// Text utilities commonly needed in rules and renderers.
+pub mod utils {
+    pub use crate::common::utils::{escape_html, normalize_reference, unescape_all};
+}
@@ -3,7 +3,7 @@
 //! `> looks like this`
 //!
 //! <https://spec.commonmark.org/0.30/#block-quotes>
-use crate::common::utils::find_indent_of;
+use crate::common::utils::find_indent_with_tab_width;
 use crate::parser::block::{BlockRule, BlockState};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
@@ -20,6 +20,24 @@ impl NodeValue for Blockquote {
         fmt.close("blockquote");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.cr();
+                fmt.contents(&node.children);
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
@@ -92,9 +110,9 @@ impl BlockRule for BlockquoteScanner {
 
                     old_line_offsets.push(state.line_offsets[next_line].clone());
 
-                    let ( mut indent_after_marker, first_nonspace ) = find_indent_of(
+                    let ( mut indent_after_marker, first_nonspace ) = find_indent_with_tab_width(
                         &state.src[offsets.line_start..offsets.line_end],
-                        pos_after_marker - offsets.line_start);
+                        pos_after_marker - offsets.line_start, state.tab_width);
 
                     last_line_empty = first_nonspace == offsets.line_end - offsets.line_start;
 
@@ -150,7 +168,9 @@ impl BlockRule for BlockquoteScanner {
         let old_line_max = state.line_max;
         state.line = start_line;
         state.line_max = next_line;
+        state.level += 1;
         state.md.block.tokenize(state);
+        state.level -= 1;
         next_line = state.line;
         state.line = start_line;
         state.line_max = old_line_max;
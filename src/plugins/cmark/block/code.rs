@@ -3,8 +3,13 @@
 //! Parses anything indented with 4 spaces.
 //!
 //! <https://spec.commonmark.org/0.30/#indented-code-block>
+use crate::common::utils::html_to_text;
 use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::extset::MarkdownItExt;
+use crate::plugins::cmark::strict;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 const CODE_INDENT: i32 = 4;
 
@@ -28,13 +33,75 @@ impl NodeValue for CodeBlock {
         fmt.close("pre");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.cr();
+                fmt.text(&if self.raw { html_to_text(&self.content) } else { self.content.as_str().into() });
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "content": self.content, "raw": self.raw })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { content: field(data, "content")?, raw: field(data, "raw")? })
+    }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct CodeSettings { expand_tabs: bool }
+impl MarkdownItExt for CodeSettings {}
+
 pub fn add(md: &mut MarkdownIt) {
     md.block.add_rule::<CodeScanner>();
     md.max_indent = CODE_INDENT;
 }
 
+/// Replace tabs in indented and fenced code blocks with spaces up to the next
+/// tab stop ([MarkdownIt::tab_width]), for content written with other tab width
+/// in mind. Tabs are kept as written by default (and in strict mode).
+pub fn set_expand_tabs(md: &mut MarkdownIt, expand_tabs: bool) {
+    md.ext.insert(CodeSettings { expand_tabs });
+}
+
+// content of code blocks created by cmark plugins
+pub(crate) fn expand_tabs(state: &BlockState, content: String) -> String {
+    if strict::is_strict(state.md) { return content; }
+    let settings = state.md.ext.get::<CodeSettings>().copied().unwrap_or_default();
+    if !settings.expand_tabs || !content.contains('\t') { return content; }
+
+    let mut result = String::with_capacity(content.len());
+    let mut column = 0;
+    for ch in content.chars() {
+        match ch {
+            '\t' => {
+                let width = state.tab_width - column % state.tab_width;
+                result.push_str(&" ".repeat(width));
+                column += width;
+            }
+            '\n' => {
+                result.push(ch);
+                column = 0;
+            }
+            _ => {
+                result.push(ch);
+                column += 1;
+            }
+        }
+    }
+    result
+}
+
 #[doc(hidden)]
 pub struct CodeScanner;
 impl BlockRule for CodeScanner {
@@ -65,6 +132,7 @@ impl BlockRule for CodeScanner {
 
         let (mut content, _mapping) = state.get_lines(state.line, last, CODE_INDENT as usize + state.blk_indent, false);
         content += "\n";
+        let content = expand_tabs(state, content);
 
         let node = Node::new(CodeBlock { content, raw: false });
         //node.srcmap = state.get_map_from_offsets(mapping[0].1, state.line_offsets[last - 1].line_end);
@@ -72,3 +140,42 @@ impl BlockRule for CodeScanner {
         Some((node, last - state.line))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    fn make_md() -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        md
+    }
+
+    #[test]
+    fn tab_width() {
+        let md = &mut make_md();
+        assert_eq!(md.parse(" \tfoo").render(), "<pre><code>foo\n</code></pre>\n");
+        assert_eq!(md.parse(">\tfoo").render(), "<blockquote>\n<p>foo</p>\n</blockquote>\n");
+
+        md.tab_width = 8;
+        assert_eq!(md.parse(" \tfoo").render(), "<pre><code>    foo\n</code></pre>\n");
+        assert_eq!(md.parse(">\tfoo").render(), "<blockquote>\n<pre><code>  foo\n</code></pre>\n</blockquote>\n");
+    }
+
+    #[test]
+    fn expand_tabs() {
+        let md = &mut make_md();
+        let src = "```\na\tb\n```\n\n    c\t\td";
+        assert_eq!(md.parse(src).render(), "<pre><code>a\tb\n</code></pre>\n<pre><code>c\t\td\n</code></pre>\n");
+
+        super::set_expand_tabs(md, true);
+        assert_eq!(md.parse(src).render(), "<pre><code>a   b\n</code></pre>\n<pre><code>c       d\n</code></pre>\n");
+    }
+
+    #[test]
+    fn strict() {
+        let md = &mut make_md();
+        md.tab_width = 8;
+        super::set_expand_tabs(md, true);
+        crate::plugins::cmark::strict::add(md);
+        assert_eq!(md.parse(" \tfoo\tbar").render(), "<pre><code>foo\tbar\n</code></pre>\n");
+    }
+}
@@ -3,10 +3,14 @@
 //! ` ```lang ` or `~~~lang`
 //!
 //! <https://spec.commonmark.org/0.30/#code-fence>
-use crate::common::utils::unescape_all;
+use crate::common::utils::{html_to_text, unescape_all};
 use crate::parser::block::{BlockRule, BlockState};
 use crate::parser::extset::MarkdownItExt;
+use crate::plugins::cmark::block::code;
+use crate::plugins::cmark::strict;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct CodeFence {
@@ -43,6 +47,42 @@ impl NodeValue for CodeFence {
         fmt.close("pre");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.cr();
+                fmt.text(&if self.raw { html_to_text(&self.content) } else { self.content.as_str().into() });
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "info": self.info,
+            "marker": self.marker,
+            "marker_len": self.marker_len,
+            "content": self.content,
+            "raw": self.raw,
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            info: field(data, "info")?,
+            marker: field(data, "marker")?,
+            marker_len: field(data, "marker_len")?,
+            content: field(data, "content")?,
+            lang_prefix: FenceSettings::default().0,
+            raw: field(data, "raw")?,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -63,6 +103,12 @@ pub fn set_lang_prefix(md: &mut MarkdownIt, lang_prefix: &'static str) {
     md.ext.insert(FenceSettings(lang_prefix));
 }
 
+// prefix for fences created by other plugins
+pub(crate) fn lang_prefix(md: &MarkdownIt) -> &'static str {
+    if strict::is_strict(md) { return FenceSettings::default().0; }
+    md.ext.get::<FenceSettings>().copied().unwrap_or_default().0
+}
+
 #[doc(hidden)]
 pub struct FenceScanner;
 
@@ -155,8 +201,9 @@ impl BlockRule for FenceScanner {
         // If a fence has heading spaces, they should be removed from its inner block
         let indent = state.line_offsets[state.line].indent_nonspace;
         let (content, _) = state.get_lines(state.line + 1, next_line, indent as usize, true);
+        let content = code::expand_tabs(state, content);
 
-        let lang_prefix = state.md.ext.get::<FenceSettings>().copied().unwrap_or_default().0;
+        let lang_prefix = lang_prefix(state.md);
         let node = Node::new(CodeFence {
             info: params,
             marker,
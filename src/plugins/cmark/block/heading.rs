@@ -1,11 +1,17 @@
 //! ATX heading
 //!
-//! `# h1`, `## h2`, etc.
+//! `# h1`, `## h2`, etc. Space after `#` can be made optional with [HeadingOptions].
 //!
 //! <https://spec.commonmark.org/0.30/#atx-heading>
 use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::extset::MarkdownItExt;
 use crate::parser::inline::InlineRoot;
+use crate::parser::options::ParseOptions;
+use crate::parser::renderer::SourceLine;
+use crate::plugins::cmark::strict;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct ATXHeading {
@@ -23,12 +29,75 @@ impl NodeValue for ATXHeading {
         fmt.close(TAG[self.level as usize - 1]);
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.contents(&node.children);
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "level": self.level })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        let level = field(data, "level")?;
+        // rendered as h1-h6
+        (1..=6).contains(&level).then_some(Self { level })
+    }
+
+    fn source_line(&self) -> SourceLine {
+        SourceLine::Text
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [set_options], can also be set in [ParseOptions] of a document.
+/// Ignored in [strict](crate::plugins::cmark::strict) mode.
+pub struct HeadingOptions {
+    /// Parse setext headings (paragraph underlined with `===` or `---`),
+    /// enabled by default.
+    pub setext: bool,
+    /// Require a space after `#` in ATX headings, enabled by default. If disabled,
+    /// `#foo` is a heading as in original markdown (GFM and CommonMark require a space).
+    pub require_space: bool,
 }
 
+impl Default for HeadingOptions {
+    fn default() -> Self {
+        Self {
+            setext: true,
+            require_space: true,
+        }
+    }
+}
+
+impl MarkdownItExt for HeadingOptions {}
+
 pub fn add(md: &mut MarkdownIt) {
     md.block.add_rule::<HeadingScanner>();
 }
 
+/// Set options of ATX and setext headings.
+pub fn set_options(md: &mut MarkdownIt, options: HeadingOptions) {
+    md.ext.insert(options);
+}
+
+// options for the document being parsed
+pub(crate) fn options(state: &BlockState) -> HeadingOptions {
+    if strict::is_strict(state.md) { return HeadingOptions::default(); }
+    let parse_options = ParseOptions::of(state.root_ext);
+    parse_options.ext_or::<HeadingOptions>(state.md).copied().unwrap_or_default()
+}
+
 #[doc(hidden)]
 pub struct HeadingScanner;
 impl BlockRule for HeadingScanner {
@@ -40,17 +109,20 @@ impl BlockRule for HeadingScanner {
         let Some('#') = line.chars().next() else { return None; };
 
         let text_pos;
+        let require_space = options(state).require_space;
 
         // count heading level
         let mut level = 0u8;
-        let mut chars = line.char_indices();
+        let mut chars = line.char_indices().peekable();
         loop {
-            match chars.next() {
+            match chars.peek().copied() {
                 Some((_, '#')) => {
+                    chars.next();
                     level += 1;
                     if level > 6 { return None; }
                 }
                 Some((x, ' ' | '\t')) => {
+                    chars.next();
                     text_pos = x;
                     break;
                 }
@@ -58,6 +130,11 @@ impl BlockRule for HeadingScanner {
                     text_pos = level as usize;
                     break;
                 }
+                // #foo (text is kept in `chars`)
+                Some((x, _)) if !require_space => {
+                    text_pos = x;
+                    break;
+                }
                 Some(_) => return None,
             }
         }
@@ -85,3 +162,44 @@ impl BlockRule for HeadingScanner {
         Some((node, 1))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::HeadingOptions;
+    use crate::parser::options::ParseOptions;
+
+    fn make_md() -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        md
+    }
+
+    #[test]
+    fn require_space() {
+        let md = &mut make_md();
+        let src = "#foo\n\n##bar ##\n\n#b\n\n#######c";
+        assert_eq!(md.parse(src).render(), "<p>#foo</p>\n<p>##bar ##</p>\n<p>#b</p>\n<p>#######c</p>\n");
+
+        super::set_options(md, HeadingOptions { require_space: false, ..HeadingOptions::default() });
+        assert_eq!(md.parse(src).render(), "<h1>foo</h1>\n<h2>bar</h2>\n<h1>b</h1>\n<p>#######c</p>\n");
+    }
+
+    #[test]
+    fn setext() {
+        let md = &mut make_md();
+        let src = "a\n===\n\nb\n---";
+        assert_eq!(md.parse(src).render(), "<h1>a</h1>\n<h2>b</h2>\n");
+
+        let mut options = ParseOptions::new();
+        options.ext.insert(HeadingOptions { setext: false, ..HeadingOptions::default() });
+        assert_eq!(md.parse_with_options(src, options).render(), "<p>a\n===</p>\n<p>b</p>\n<hr>\n");
+    }
+
+    #[test]
+    fn strict() {
+        let md = &mut make_md();
+        super::set_options(md, HeadingOptions { setext: false, require_space: false });
+        crate::plugins::cmark::strict::add(md);
+        assert_eq!(md.parse("#a\n\nb\n=").render(), "<p>#a</p>\n<h1>b</h1>\n");
+    }
+}
@@ -5,11 +5,41 @@
 //! <https://spec.commonmark.org/0.30/#thematic-breaks>
 use crate::parser::block::{BlockRule, BlockState};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct ThematicBreak {
     pub marker: char,
     pub marker_len: usize,
+    /// Markers are separated with spaces (`- - -`).
+    pub spaced: bool,
+}
+
+impl ThematicBreak {
+    /// Break of `marker_len` markers without spaces (e.g. `---`).
+    pub fn new(marker: char, marker_len: usize) -> Self {
+        Self { marker, marker_len, spaced: false }
+    }
+
+    /// Markdown source of the break, without indentation.
+    ///
+    /// ```rust
+    /// use markdown_it::plugins::cmark::block::hr::ThematicBreak;
+    ///
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let ast = md.parse("  * * *  \n\n____");
+    /// assert_eq!(ast.children[0].cast::<ThematicBreak>().unwrap().markdown(), "* * *");
+    /// assert_eq!(ast.children[1].cast::<ThematicBreak>().unwrap().markdown(), "____");
+    /// ```
+    pub fn markdown(&self) -> String {
+        let marker = self.marker.to_string();
+        let separator = if self.spaced { " " } else { "" };
+        vec![marker; self.marker_len].join(separator)
+    }
 }
 
 impl NodeValue for ThematicBreak {
@@ -18,6 +48,29 @@ impl NodeValue for ThematicBreak {
         fmt.self_close("hr", &node.attrs);
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => fmt.cr(),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "marker": self.marker, "marker_len": self.marker_len, "spaced": self.spaced })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            marker: field(data, "marker")?,
+            marker_len: field(data, "marker_len")?,
+            spaced: field(data, "spaced").unwrap_or_default(),
+        })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
@@ -39,17 +92,23 @@ impl BlockRule for HrScanner {
 
         // markers can be mixed with spaces, but there should be at least 3 of them
         let mut cnt = 1;
+        let mut spaced = false;
+        let mut after_space = false;
         for ch in chars {
             if ch == marker {
                 cnt += 1;
-            } else if ch != ' ' && ch != '\t' {
+                spaced |= after_space;
+                after_space = false;
+            } else if ch == ' ' || ch == '\t' {
+                after_space = true;
+            } else {
                 return None;
             }
         }
 
         if cnt < 3 { return None; }
 
-        let node = Node::new(ThematicBreak { marker, marker_len: cnt });
+        let node = Node::new(ThematicBreak { marker, marker_len: cnt, spaced });
         Some((node, 1))
     }
 }
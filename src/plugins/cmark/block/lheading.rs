@@ -1,12 +1,17 @@
 //! Setext headings
 //!
-//! Paragraph underlined with `===` or `---`.
+//! Paragraph underlined with `===` or `---`, can be disabled with
+//! [HeadingOptions](super::heading::HeadingOptions).
 //!
 //! <https://spec.commonmark.org/0.30/#setext-headings>
 use crate::parser::block::{BlockRule, BlockState};
 use crate::parser::inline::InlineRoot;
+use crate::parser::renderer::SourceLine;
+use crate::plugins::cmark::block::heading;
 use crate::plugins::cmark::block::paragraph::ParagraphScanner;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct SetextHeader {
@@ -25,6 +30,34 @@ impl NodeValue for SetextHeader {
         fmt.close(TAG[self.level as usize - 1]);
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.contents(&node.children);
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "level": self.level, "marker": self.marker })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        let level = field(data, "level")?;
+        // rendered as h1-h2
+        (1..=2).contains(&level).then_some(Self { level, marker: field(data, "marker")? })
+    }
+
+    fn source_line(&self) -> SourceLine {
+        SourceLine::Text
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
@@ -43,6 +76,7 @@ impl BlockRule for LHeadingScanner {
     fn run(state: &mut BlockState) -> Option<(Node, usize)> {
 
         if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        if !heading::options(state).setext { return None; }
 
         let start_line = state.line;
         let mut next_line = start_line;
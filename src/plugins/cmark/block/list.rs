@@ -6,23 +6,64 @@
 //!
 //!  - <https://spec.commonmark.org/0.30/#lists>
 //!  - <https://spec.commonmark.org/0.30/#list-items>
-use crate::common::utils::find_indent_of;
+//!
+//! Numbering type of ordered lists isn't in markdown syntax, plugins can set it
+//! with [set_list_type]:
+//!
+//! ```rust
+//! use markdown_it::plugins::cmark::block::list::{self, ListType, OrderedList};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let mut ast = md.parse("3. c\n4. d");
+//! let list = &mut ast.children[0];
+//! assert_eq!(list.cast::<OrderedList>().unwrap().start, 3);
+//! list::set_list_type(list, ListType::LowerAlpha);
+//! assert_eq!(ast.render(), "<ol type=\"a\" start=\"3\">\n<li>c</li>\n<li>d</li>\n</ol>\n");
+//! ```
+use crate::common::utils::find_indent_with_tab_width;
+use crate::common::sourcemap::SourcePos;
+use crate::parser::block::builtin::BlockParserRule;
 use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::core::CoreRule;
+use crate::parser::extset::{MarkdownItExt, NodeExt};
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::InlineRoot;
+use crate::parser::options::ParseOptions;
+use crate::parser::renderer::TextIndent;
 use crate::plugins::cmark::block::hr::HrScanner;
 use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::plugins::cmark::inline::newline::Hardbreak;
+use crate::plugins::cmark::strict;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct OrderedList {
     pub start: u32,
     pub marker: char,
+    /// List is tight: paragraphs in its items aren't wrapped in `<p>`
+    /// (their contents are children of list items).
+    pub tight: bool,
+}
+
+impl OrderedList {
+    /// Loose list starting from `start`.
+    pub fn new(start: u32, marker: char) -> Self {
+        Self { start, marker, tight: false }
+    }
 }
 
 impl NodeValue for OrderedList {
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
         let start;
-        if self.start != 1 {
+        // `start` set in attributes takes precedence
+        if (self.start != 1 || node.ext.contains::<AlwaysStart>())
+            && !attrs.iter().any(|(name, _)| name == "start") {
             start = self.start.to_string();
             attrs.push(("start".into(), start));
         }
@@ -34,11 +75,105 @@ impl NodeValue for OrderedList {
         fmt.close("ol");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                let mut num = self.start;
+                render_text_items(node, fmt, || {
+                    let marker = format!("{}{} ", num, self.marker);
+                    num += 1;
+                    marker
+                });
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "start": self.start, "marker": self.marker, "tight": self.tight })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            start: field(data, "start")?,
+            marker: field(data, "marker")?,
+            tight: field(data, "tight").unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Numbering of ordered list items, `type` attribute of `<ol>`.
+pub enum ListType {
+    /// `1, 2, 3`
+    Decimal,
+    /// `a, b, c`
+    LowerAlpha,
+    /// `A, B, C`
+    UpperAlpha,
+    /// `i, ii, iii`
+    LowerRoman,
+    /// `I, II, III`
+    UpperRoman,
+}
+
+impl ListType {
+    /// Value of `type` attribute.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Decimal => "1",
+            Self::LowerAlpha => "a",
+            Self::UpperAlpha => "A",
+            Self::LowerRoman => "i",
+            Self::UpperRoman => "I",
+        }
+    }
+
+    /// Parse value of `type` attribute.
+    pub fn from_attr(value: &str) -> Option<Self> {
+        match value {
+            "1" => Some(Self::Decimal),
+            "a" => Some(Self::LowerAlpha),
+            "A" => Some(Self::UpperAlpha),
+            "i" => Some(Self::LowerRoman),
+            "I" => Some(Self::UpperRoman),
+            _ => None,
+        }
+    }
+}
+
+/// Numbering type of an ordered list, stored in its `type` attribute
+/// (so it can also be set by other plugins and attribute hooks).
+pub fn list_type(node: &Node) -> Option<ListType> {
+    node.attrs.iter().find(|(name, _)| name == "type").and_then(|(_, value)| ListType::from_attr(value))
+}
+
+/// Set numbering type of an ordered list, replacing its `type` attribute.
+/// Does nothing if `node` isn't an [OrderedList].
+pub fn set_list_type(node: &mut Node, list_type: ListType) {
+    if !node.is::<OrderedList>() { return; }
+    node.attrs.retain(|(name, _)| name != "type");
+    node.attrs.push(("type".into(), list_type.as_str().into()));
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct BulletList {
     pub marker: char,
+    /// List is tight, see [OrderedList::tight].
+    pub tight: bool,
+}
+
+impl BulletList {
+    /// Loose list with given marker.
+    pub fn new(marker: char) -> Self {
+        Self { marker, tight: false }
+    }
 }
 
 impl NodeValue for BulletList {
@@ -51,6 +186,41 @@ impl NodeValue for BulletList {
         fmt.close("ul");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => render_text_items(node, fmt, || format!("{} ", self.marker)),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "marker": self.marker, "tight": self.tight })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { marker: field(data, "marker")?, tight: field(data, "tight").unwrap_or_default() })
+    }
+}
+
+// contents of items are indented by marker width, so nested lists and
+// paragraphs are aligned with the text after the marker
+fn render_text_items(node: &Node, fmt: &mut dyn Renderer, mut marker: impl FnMut() -> String) {
+    let indent = fmt.ext().get_or_insert_default::<TextIndent>().0.clone();
+
+    fmt.cr();
+    for item in node.children.iter() {
+        let marker = marker();
+        fmt.text(&marker);
+        fmt.ext().insert(TextIndent(format!("{}{}", indent, " ".repeat(marker.len()))));
+        item.render_with(fmt);
+        fmt.ext().insert(TextIndent(indent.clone()));
+        fmt.cr();
+    }
 }
 
 #[derive(Debug)]
@@ -63,11 +233,144 @@ impl NodeValue for ListItem {
         fmt.close("li");
         fmt.cr();
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Whether paragraphs in list items are wrapped in `<p>`, see [ListOptions::spacing].
+pub enum ListSpacing {
+    #[default]
+    /// Loose or tight depending on blank lines between items, as CommonMark requires.
+    Source,
+    /// All lists are tight.
+    Tight,
+    /// All lists are loose.
+    Loose,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Options for [set_options], can also be set in [ParseOptions] of a document.
+/// Ignored in [strict](crate::plugins::cmark::strict) mode.
+pub struct ListOptions {
+    /// Add `start` attribute to every ordered list, including ones starting
+    /// at 1, so that numbering is kept when html is converted back to markdown.
+    pub always_start: bool,
+    /// Make all lists in the document tight or loose, for styles that need
+    /// consistent paragraphs in list items.
+    pub spacing: ListSpacing,
 }
 
+impl MarkdownItExt for ListOptions {}
+
+#[derive(Debug)]
+// set on ordered lists parsed with `always_start`, so they output `start` even if it's 1
+struct AlwaysStart;
+impl NodeExt for AlwaysStart {}
+
 pub fn add(md: &mut MarkdownIt) {
     md.block.add_rule::<ListScanner>()
         .after::<HrScanner>();
+
+    md.add_rule::<ListSpacingRule>()
+        .after::<BlockParserRule>()
+        .before::<InlineParserRule>();
+}
+
+/// Set options of lists.
+pub fn set_options(md: &mut MarkdownIt, options: ListOptions) {
+    md.ext.insert(options);
+}
+
+/// Check if a list node is tight, `None` if it isn't a list.
+pub fn is_tight(node: &Node) -> Option<bool> {
+    node.cast::<OrderedList>().map(|list| list.tight)
+        .or_else(|| node.cast::<BulletList>().map(|list| list.tight))
+}
+
+/// Make a list tight (unwrap paragraphs in its items) or loose (wrap their
+/// inline content in paragraphs). Must be called before inline parsing
+/// (rule that runs after [InlineParserRule] can't tell which nodes were paragraphs).
+/// Does nothing if `node` isn't a list.
+pub fn set_tight(node: &mut Node, tight: bool) {
+    if let Some(list) = node.cast_mut::<OrderedList>() {
+        list.tight = tight;
+    } else if let Some(list) = node.cast_mut::<BulletList>() {
+        list.tight = tight;
+    } else {
+        return;
+    }
+
+    for item in node.children.iter_mut() {
+        debug_assert!(item.is::<ListItem>());
+        if tight {
+            unwrap_paragraphs(&mut item.children);
+        } else {
+            wrap_paragraphs(&mut item.children);
+        }
+    }
+}
+
+fn unwrap_paragraphs(nodes: &mut Vec<Node>) {
+    let mut idx = 0;
+    let mut after_paragraph = false;
+    while idx < nodes.len() {
+        if nodes[idx].is::<Paragraph>() {
+            let mut children = std::mem::take(&mut nodes[idx].children);
+            // paragraphs separated by a blank line (only in lists made tight by
+            // ListSpacingRule) are kept on separate lines
+            if after_paragraph { children.insert(0, Node::new(Hardbreak)); }
+            let len = children.len();
+            nodes.splice(idx..idx+1, children);
+            idx += len;
+            after_paragraph = true;
+        } else {
+            idx += 1;
+            after_paragraph = false;
+        }
+    }
+}
+
+fn wrap_paragraphs(nodes: &mut [Node]) {
+    for node in nodes.iter_mut() {
+        let Some(inline) = node.cast::<InlineRoot>() else { continue; };
+
+        // paragraph position is lost when it's unwrapped, restore it from the content
+        let srcmap = match (inline.mapping.first(), inline.mapping.last()) {
+            (Some(&(_, start)), Some(&(offset, last_line))) => {
+                Some(SourcePos::new(start, last_line + inline.content.len() - offset))
+            }
+            _ => None,
+        };
+
+        let mut paragraph = Node::new(Paragraph);
+        paragraph.srcmap = srcmap;
+        paragraph.children.push(std::mem::take(node));
+        *node = paragraph;
+    }
+}
+
+#[doc(hidden)]
+pub struct ListSpacingRule;
+impl CoreRule for ListSpacingRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        if strict::is_strict(md) { return; }
+        let parse_options = ParseOptions::of_root(root);
+        let tight = match parse_options.ext_or::<ListOptions>(md).map(|options| options.spacing) {
+            Some(ListSpacing::Tight) => true,
+            Some(ListSpacing::Loose) => false,
+            Some(ListSpacing::Source) | None => return,
+        };
+
+        root.walk_mut(|node, _| {
+            if is_tight(node).is_some_and(|is_tight| is_tight != tight) {
+                set_tight(node, tight);
+            }
+        });
+    }
 }
 
 #[doc(hidden)]
@@ -116,20 +419,6 @@ impl ListScanner {
         }
     }
 
-    fn mark_tight_paragraphs(nodes: &mut Vec<Node>) {
-        let mut idx = 0;
-        while idx < nodes.len() {
-            if nodes[idx].is::<Paragraph>() {
-                let children = std::mem::take(&mut nodes[idx].children);
-                let len = children.len();
-                nodes.splice(idx..idx+1, children);
-                idx += len;
-            } else {
-                idx += 1;
-            }
-        }
-    }
-
     fn find_marker(state: &mut BlockState, silent: bool) -> Option<(usize, Option<u32>, char)> {
 
         if state.line_indent(state.line) >= state.md.max_indent { return None; }
@@ -216,14 +505,15 @@ impl BlockRule for ListScanner {
         let (mut pos_after_marker, marker_value, marker_char) = Self::find_marker(state, false)?;
 
         let new_node = if let Some(int) = marker_value {
-            Node::new(OrderedList {
-                start: int,
-                marker: marker_char
-            })
+            let mut node = Node::new(OrderedList::new(int, marker_char));
+            let parse_options = ParseOptions::of(state.root_ext);
+            let options = parse_options.ext_or::<ListOptions>(state.md).copied().unwrap_or_default();
+            if options.always_start && !strict::is_strict(state.md) {
+                node.ext.insert(AlwaysStart);
+            }
+            node
         } else {
-            Node::new(BulletList {
-                marker: marker_char
-            })
+            Node::new(BulletList::new(marker_char))
         };
 
         let old_node = std::mem::replace(&mut state.node, new_node);
@@ -242,9 +532,9 @@ impl BlockRule for ListScanner {
             let offsets = &state.line_offsets[next_line];
             let initial = offsets.indent_nonspace as usize + pos_after_marker;
 
-            let ( mut indent_after_marker, first_nonspace ) = find_indent_of(
+            let ( mut indent_after_marker, first_nonspace ) = find_indent_with_tab_width(
                 &state.src[offsets.line_start..offsets.line_end],
-                pos_after_marker + offsets.first_nonspace - offsets.line_start);
+                pos_after_marker + offsets.first_nonspace - offsets.line_start, state.tab_width);
 
             let reached_end_of_line = first_nonspace == offsets.line_end - offsets.line_start;
             let indent_nonspace = initial + indent_after_marker;
@@ -297,7 +587,9 @@ impl BlockRule for ListScanner {
                 }
             } else {
                 state.line = next_line;
+                state.level += 1;
                 state.md.block.tokenize(state);
+                state.level -= 1;
             }
 
             // If any of list item is tight, mark list as tight
@@ -356,10 +648,7 @@ impl BlockRule for ListScanner {
 
         // mark paragraphs tight if needed
         if tight {
-            for child in state.node.children.iter_mut() {
-                debug_assert!(child.is::<ListItem>());
-                Self::mark_tight_paragraphs(&mut child.children);
-            }
+            set_tight(&mut state.node, true);
         }
 
         // Finalize list
@@ -368,3 +657,75 @@ impl BlockRule for ListScanner {
         Some((node, next_line - state.line))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ListOptions, ListSpacing, ListType, OrderedList};
+    use crate::parser::options::ParseOptions;
+
+    fn make_md() -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        md
+    }
+
+    #[test]
+    fn list_type() {
+        let md = &mut make_md();
+        let mut ast = md.parse("1. a\n\n- b");
+        assert_eq!(super::list_type(&ast.children[0]), None);
+
+        super::set_list_type(&mut ast.children[0], ListType::UpperRoman);
+        super::set_list_type(&mut ast.children[0], ListType::LowerRoman);
+        super::set_list_type(&mut ast.children[1], ListType::LowerRoman);
+        assert_eq!(super::list_type(&ast.children[0]), Some(ListType::LowerRoman));
+        assert_eq!(ast.render(), "<ol type=\"i\">\n<li>a</li>\n</ol>\n<ul>\n<li>b</li>\n</ul>\n");
+    }
+
+    #[test]
+    fn always_start() {
+        let md = &mut make_md();
+        super::set_options(md, ListOptions { always_start: true, ..ListOptions::default() });
+        assert_eq!(md.parse("1. a\n\n- b\n\n0) c").render(),
+            "<ol start=\"1\">\n<li>a</li>\n</ol>\n<ul>\n<li>b</li>\n</ul>\n<ol start=\"0\">\n<li>c</li>\n</ol>\n");
+
+        // output follows the number changed after parsing
+        let mut ast = md.parse("1. a");
+        ast.children[0].cast_mut::<OrderedList>().unwrap().start = 5;
+        assert_eq!(ast.render(), "<ol start=\"5\">\n<li>a</li>\n</ol>\n");
+
+        crate::plugins::cmark::strict::add(md);
+        assert_eq!(md.parse("1. a").render(), "<ol>\n<li>a</li>\n</ol>\n");
+    }
+
+    #[test]
+    fn tight() {
+        let md = &mut make_md();
+        let ast = md.parse("- a\n- b\n\n1. c\n\n2. d");
+        assert_eq!(super::is_tight(&ast.children[0]), Some(true));
+        assert_eq!(super::is_tight(&ast.children[1]), Some(false));
+        assert_eq!(super::is_tight(&ast), None);
+    }
+
+    #[test]
+    fn spacing() {
+        let md = &mut make_md();
+        let src = "- a\n- b\n  - c\n\n    d\n\n1. e\n\n   f\n2. # g";
+        let parse = |spacing| {
+            let mut options = ParseOptions::new();
+            options.ext.insert(ListOptions { spacing, ..ListOptions::default() });
+            md.parse_with_options(src, options)
+        };
+
+        assert_eq!(parse(ListSpacing::Source).render(), "<ul>\n<li>a</li>\n<li>b\n<ul>\n<li>\n<p>c</p>\n<p>d</p>\n</li>\n</ul>\n</li>\n</ul>\n\
+            <ol>\n<li>\n<p>e</p>\n<p>f</p>\n</li>\n<li>\n<h1>g</h1>\n</li>\n</ol>\n");
+        assert_eq!(parse(ListSpacing::Tight).render(), "<ul>\n<li>a</li>\n<li>b\n<ul>\n<li>c<br>\nd</li>\n</ul>\n</li>\n</ul>\n\
+            <ol>\n<li>e<br>\nf</li>\n<li>\n<h1>g</h1>\n</li>\n</ol>\n");
+
+        let ast = parse(ListSpacing::Loose);
+        assert_eq!(ast.render(), "<ul>\n<li>\n<p>a</p>\n</li>\n<li>\n<p>b</p>\n<ul>\n<li>\n<p>c</p>\n<p>d</p>\n</li>\n</ul>\n</li>\n</ul>\n\
+            <ol>\n<li>\n<p>e</p>\n<p>f</p>\n</li>\n<li>\n<h1>g</h1>\n</li>\n</ol>\n");
+        assert_eq!(ast.children[0].children[1].children[0].srcmap.unwrap().get_byte_offsets(), (6, 7));
+        assert_eq!(super::is_tight(&ast.children[0]), Some(false));
+    }
+}
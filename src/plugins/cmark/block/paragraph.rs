@@ -5,6 +5,7 @@
 //! <https://spec.commonmark.org/0.30/#paragraph>
 use crate::parser::block::{BlockRule, BlockState};
 use crate::parser::inline::InlineRoot;
+use crate::parser::renderer::SourceLine;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
 pub fn add(md: &mut MarkdownIt) {
@@ -23,6 +24,27 @@ impl NodeValue for Paragraph {
         fmt.close("p");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.contents(&node.children);
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn source_line(&self) -> SourceLine {
+        SourceLine::Text
+    }
 }
 
 #[doc(hidden)]
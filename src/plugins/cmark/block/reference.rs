@@ -13,11 +13,17 @@ use downcast_rs::{impl_downcast, Downcast};
 use std::collections::HashMap;
 use std::fmt::Debug;
 
+use crate::common::sourcemap::SourcePos;
 use crate::common::utils::normalize_reference;
 use crate::generics::inline::full_link;
 use crate::parser::block::{BlockRule, BlockState};
-use crate::parser::extset::RootExt;
+use crate::parser::core::Root;
+use crate::parser::definitions::Definitions;
+use crate::parser::extset::{MarkdownItExt, NodeExt, RootExt, RootExtSet};
+use crate::parser::options;
 use crate::{MarkdownIt, Node, NodeValue};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 /// Storage for parsed references
 ///
@@ -95,6 +101,35 @@ use crate::{MarkdownIt, Node, NodeValue};
 /// assert_eq!(labels, ["hello"]);
 /// ```
 ///
+/// References collected from one document can be used in others, e.g. to share
+/// link definitions between files of multi-file documentation, by passing them
+/// in [ParseOptions::ext](crate::parser::options::ParseOptions::ext) (definitions in the document itself take precedence):
+///
+/// ```rust
+/// use markdown_it::parser::options::ParseOptions;
+/// use markdown_it::plugins::cmark::block::reference::{ReferenceMap, DefaultReferenceMap, CustomReferenceMap};
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+///
+/// let mut shared = DefaultReferenceMap::new();
+/// shared.insert("home".into(), "/index.html".into(), None);
+///
+/// let ast = md.parse("[rust]: https://www.rust-lang.org/");
+/// let refmap = ReferenceMap::of(&ast).unwrap().downcast_ref::<DefaultReferenceMap>().unwrap();
+/// for (label, destination, title) in refmap.iter() {
+///     shared.insert(label.into(), destination.into(), title.map(String::from));
+/// }
+///
+/// let mut options = ParseOptions::new();
+/// options.ext.insert(ReferenceMap::new(shared));
+/// let html = md.parse_with_options("[home], [rust]", options).render();
+/// assert_eq!(
+///     html.trim(),
+///     r#"<p><a href="/index.html">home</a>, <a href="https://www.rust-lang.org/">rust</a></p>"#
+/// );
+/// ```
+///
 #[derive(Debug, Deref, DerefMut)]
 #[deref(forward)]
 #[deref_mut(forward)]
@@ -104,6 +139,12 @@ impl ReferenceMap {
     pub fn new(custom_map: impl CustomReferenceMap + 'static) -> Self {
         Self(Box::new(custom_map))
     }
+
+    /// References defined in the parsed document (not including ones
+    /// given in [ParseOptions::ext](crate::parser::options::ParseOptions::ext)).
+    pub fn of(root: &Node) -> Option<&Self> {
+        root.cast::<Root>()?.ext.get::<Self>()
+    }
 }
 
 impl Default for ReferenceMap {
@@ -114,6 +155,16 @@ impl Default for ReferenceMap {
 
 impl RootExt for ReferenceMap {}
 
+// references shared with other documents, given in ParseOptions::ext
+impl MarkdownItExt for ReferenceMap {}
+
+/// Find destination and title for the given label, in the document first, and then
+/// in references shared with other documents.
+pub(crate) fn lookup<'a>(root_ext: &'a RootExtSet, label: &str) -> Option<(&'a str, Option<&'a str>)> {
+    root_ext.get::<ReferenceMap>().and_then(|map| map.get(label))
+        .or_else(|| options::get(root_ext)?.ext.get::<ReferenceMap>()?.get(label))
+}
+
 pub trait CustomReferenceMap : Debug + Downcast + Send + Sync {
     /// Insert new element to the reference map. You may return false if it's not a valid label to stop parsing.
     fn insert(&mut self, label: String, destination: String, title: Option<String>) -> bool;
@@ -124,7 +175,7 @@ pub trait CustomReferenceMap : Debug + Downcast + Send + Sync {
 
 impl_downcast!(CustomReferenceMap);
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct DefaultReferenceMap(HashMap<ReferenceMapKey, ReferenceMapEntry>);
 
 impl DefaultReferenceMap {
@@ -154,7 +205,7 @@ impl CustomReferenceMap for DefaultReferenceMap {
     }
 }
 
-#[derive(Derivative)]
+#[derive(Derivative, Clone)]
 #[derivative(Debug, Default, Hash, PartialEq, Eq)]
 /// Reference label
 struct ReferenceMapKey {
@@ -177,7 +228,7 @@ impl ReferenceMapKey {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 /// Reference value
 struct ReferenceMapEntry {
     pub destination: String,
@@ -190,6 +241,47 @@ impl ReferenceMapEntry {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+/// Source position of the definition a node refers to, so that editors can
+/// jump from a reference to its definition.
+///
+/// It is added to links and images created from link references (`[foo]` with
+/// `[foo]: /url` elsewhere in the document) and to footnote references.
+///
+/// ```rust
+/// use markdown_it::plugins::cmark::block::reference::DefinitionSrcmap;
+/// use markdown_it::plugins::cmark::inline::link::Link;
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+///
+/// let src = "[foo]\n\n[foo]: /url";
+/// let ast = md.parse(src);
+/// let mut definition = None;
+/// ast.walk(|node, _| {
+///     if node.is::<Link>() {
+///         definition = node.ext.get::<DefinitionSrcmap>().copied();
+///     }
+/// });
+///
+/// let (start, end) = definition.unwrap().0.get_byte_offsets();
+/// assert_eq!(&src[start..end], "[foo]: /url");
+/// ```
+pub struct DefinitionSrcmap(pub SourcePos);
+impl NodeExt for DefinitionSrcmap {}
+
+#[derive(Debug, Default)]
+/// Source positions of reference definitions found in the document,
+/// indexed by normalized label.
+struct ReferenceSrcmaps(HashMap<String, SourcePos>);
+impl RootExt for ReferenceSrcmaps {}
+
+/// Find source position of the reference definition for the given label,
+/// if it was defined in the document.
+pub(crate) fn definition_srcmap(root_ext: &RootExtSet, label: &str) -> Option<SourcePos> {
+    root_ext.get::<ReferenceSrcmaps>()?.0.get(&normalize_reference(label)).copied()
+}
+
 /// Add plugin that parses markdown link references
 pub fn add(md: &mut MarkdownIt) {
     md.block.add_rule::<ReferenceScanner>();
@@ -203,6 +295,20 @@ pub struct Definition {
 }
 impl NodeValue for Definition {
     fn render(&self, _: &Node, _: &mut dyn crate::Renderer) {}
+
+    fn is_definition(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "label": self.label, "destination": self.destination, "title": self.title })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { label: field(data, "label")?, destination: field(data, "destination")?, title: field(data, "title")? })
+    }
 }
 
 #[doc(hidden)]
@@ -342,7 +448,7 @@ impl BlockRule for ReferenceScanner {
         let mut chars = str[pos..].chars();
         loop {
             match chars.next() {
-                Some(' ' | '\t') => pos += 1,
+                Some(' ' | '\t') => {}
                 Some('\n') | None => break,
                 Some(_) if title.is_some() => {
                     // garbage at the end of the line after title,
@@ -362,6 +468,15 @@ impl BlockRule for ReferenceScanner {
         let references = state.root_ext.get_or_insert_default::<ReferenceMap>();
         if !references.insert(str[1..label_end].to_owned(), href.clone(), title.clone()) { return None; }
 
+        let srcmap = state.get_map(state.line, state.line + lines);
+        if let Some(srcmap) = srcmap {
+            let srcmaps = state.root_ext.get_or_insert_default::<ReferenceSrcmaps>();
+            srcmaps.0.entry(normalize_reference(&str[1..label_end])).or_insert(srcmap);
+        }
+
+        let definitions = state.root_ext.get_or_insert_default::<Definitions<Definition>>();
+        definitions.define(normalize_reference(&str[1..label_end]), &str[1..label_end], srcmap);
+
         Some((Node::new(
             Definition { 
                 label: str[1..label_end].to_owned(), 
@@ -372,3 +487,30 @@ impl BlockRule for ReferenceScanner {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CustomReferenceMap, DefaultReferenceMap, ReferenceMap};
+    use crate::parser::incremental::TextEdit;
+    use crate::parser::options::ParseOptions;
+
+    #[test]
+    fn shared_refs() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+
+        let mut refs = DefaultReferenceMap::new();
+        refs.insert("a".into(), "/shared".into(), None);
+        refs.insert("b".into(), "/shared".into(), None);
+
+        let mut options = ParseOptions::new();
+        options.ext.insert(ReferenceMap::new(refs));
+        let ast = md.parse_with_options("[a] [b]\n\n[a]: /local", options);
+        assert_eq!(ast.render(), "<p><a href=\"/local\">a</a> <a href=\"/shared\">b</a></p>\n");
+        let local = ReferenceMap::of(&ast).unwrap();
+        assert!(local.get("a").is_some() && local.get("b").is_none());
+
+        let ast = md.parse_incremental(ast, &TextEdit::new(0..0, "x "));
+        assert_eq!(ast.render(), "<p>x <a href=\"/local\">a</a> <a href=\"/shared\">b</a></p>\n");
+    }
+}
@@ -8,6 +8,8 @@ use regex::Regex;
 
 use crate::parser::inline::{InlineRule, InlineState, TextSpecial};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct Autolink {
@@ -23,6 +25,16 @@ impl NodeValue for Autolink {
         fmt.contents(&node.children);
         fmt.close("a");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "url": self.url })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { url: field(data, "url")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
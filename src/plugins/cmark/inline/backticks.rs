@@ -4,7 +4,10 @@
 //!
 //! <https://spec.commonmark.org/0.30/#code-span>
 use crate::generics::inline::code_pair;
+use crate::parser::rules;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct CodeInline {
@@ -18,9 +21,20 @@ impl NodeValue for CodeInline {
         fmt.contents(&node.children);
         fmt.close("code");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "marker": self.marker, "marker_len": self.marker_len })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { marker: field(data, "marker")?, marker_len: field(data, "marker_len")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
+    rules::set_rule_name::<code_pair::CodePairScanner<'`'>>(md, "backticks");
     code_pair::add_with::<'`'>(md, |len| Node::new(CodeInline {
         marker: '`',
         marker_len: len,
@@ -4,7 +4,10 @@
 //!
 //! <https://spec.commonmark.org/0.30/#emphasis-and-strong-emphasis>
 use crate::generics::inline::emph_pair;
+use crate::parser::rules;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct Em {
@@ -17,6 +20,16 @@ impl NodeValue for Em {
         fmt.contents(&node.children);
         fmt.close("em");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "marker": self.marker })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { marker: field(data, "marker")? })
+    }
 }
 
 #[derive(Debug)]
@@ -30,9 +43,21 @@ impl NodeValue for Strong {
         fmt.contents(&node.children);
         fmt.close("strong");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "marker": self.marker })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { marker: field(data, "marker")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
+    rules::set_rule_name::<emph_pair::EmphPairScanner<'*', true>>(md, "emphasis");
+    rules::set_rule_name::<emph_pair::EmphPairScanner<'_', false>>(md, "emphasis");
     emph_pair::add_with::<'*', 1, true>  (md, || Node::new(Em     { marker: '*' }));
     emph_pair::add_with::<'_', 1, false> (md, || Node::new(Em     { marker: '_' }));
     emph_pair::add_with::<'*', 2, true>  (md, || Node::new(Strong { marker: '*' }));
@@ -4,7 +4,10 @@
 //!
 //! <https://spec.commonmark.org/0.30/#links>
 use crate::generics::inline::full_link;
+use crate::parser::rules;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct Link {
@@ -25,9 +28,20 @@ impl NodeValue for Link {
         fmt.contents(&node.children);
         fmt.close("a");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "url": self.url, "title": self.title })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { url: field(data, "url")?, title: field(data, "title")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
+    rules::set_rule_name::<full_link::LinkScanner<false>>(md, "link");
     full_link::add::<false>(md, |href, title| Node::new(Link {
         url: href.unwrap_or_default(),
         title,
@@ -4,7 +4,10 @@
 //!
 //!  - <https://spec.commonmark.org/0.30/#hard-line-breaks>
 //!  - <https://spec.commonmark.org/0.30/#soft-line-breaks>
+use crate::parser::extset::MarkdownItExt;
 use crate::parser::inline::{InlineRule, InlineState};
+use crate::parser::renderer::SourceLine;
+use crate::plugins::cmark::strict;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
 #[derive(Debug)]
@@ -15,6 +18,24 @@ impl NodeValue for Hardbreak {
         fmt.self_close("br", &[]);
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => fmt.text("\n"),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn source_line(&self) -> SourceLine {
+        SourceLine::Break
+    }
 }
 
 #[derive(Debug)]
@@ -24,12 +45,42 @@ impl NodeValue for Softbreak {
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => fmt.text("\n"),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn source_line(&self) -> SourceLine {
+        SourceLine::Break
+    }
 }
 
+#[derive(Debug, Default, Clone, Copy)]
+struct NewlineSettings {
+    breaks: bool,
+}
+impl MarkdownItExt for NewlineSettings {}
+
 pub fn add(md: &mut MarkdownIt) {
     md.inline.add_rule::<NewlineScanner>();
 }
 
+/// Convert all newlines in paragraphs into hard breaks (`<br>`),
+/// same as `breaks` option in markdown-it.
+pub fn set_breaks(md: &mut MarkdownIt, breaks: bool) {
+    md.ext.insert(NewlineSettings { breaks });
+}
+
 #[doc(hidden)]
 pub struct NewlineScanner;
 impl InlineRule for NewlineScanner {
@@ -69,7 +120,9 @@ impl InlineRule for NewlineScanner {
 
         state.trailing_text_pop(tail_size);
 
-        let node = if tail_size >= 2 {
+        let breaks = state.md.ext.get::<NewlineSettings>().is_some_and(|s| s.breaks) && !strict::is_strict(state.md);
+
+        let node = if tail_size >= 2 || breaks {
             Node::new(Hardbreak)
         } else {
             Node::new(Softbreak)
@@ -9,6 +9,7 @@
 //! by invoking `add` function of the respective module.
 pub mod block;
 pub mod inline;
+pub mod strict;
 
 use crate::MarkdownIt;
 
@@ -0,0 +1,124 @@
+//! Strict CommonMark mode, for applications that need the same output
+//! as other CommonMark implementations.
+//!
+//! By default, this crate differs from the spec in a few ways:
+//!  - links with `javascript:`, `vbscript:`, `file:` and `data:` urls are not
+//!    recognized (except images in `data:`), to prevent XSS
+//!  - newlines can be rendered as `<br>` ([set_breaks](super::inline::newline::set_breaks))
+//!  - prefix of code block languages can be changed ([set_lang_prefix](super::block::fence::set_lang_prefix))
+//!  - tab stops can be wider than 4 ([MarkdownIt::tab_width]) and tabs in code can be
+//!    expanded ([set_expand_tabs](super::block::code::set_expand_tabs))
+//!  - setext headings can be disabled and ATX headings can be written without a space
+//!    ([HeadingOptions](super::block::heading::HeadingOptions))
+//!  - ordered lists can always have `start` attribute, and all lists can be made tight
+//!    or loose ([ListOptions](super::block::list::ListOptions))
+//!
+//! [add] sets [MarkdownIt::strict], which turns all of these off, so output of the cmark plugins matches the spec
+//! byte-for-byte (urls aren't validated anymore, so sanitize the output if it comes
+//! from untrusted users). Limits of [MarkdownIt::max_nesting] and [MarkdownIt::max_input_size]
+//! are kept to protect against pathological input, but if they change the output
+//! of a document, it's reported as a [diagnostic](crate::parser::diagnostics)
+//! with `commonmark-limit` code.
+//!
+//! ```rust
+//! use markdown_it::parser::diagnostics::Diagnostics;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::cmark::inline::newline::set_breaks(md, true);
+//! markdown_it::plugins::cmark::strict::add(md);
+//! md.max_nesting = 2;
+//!
+//! let html = md.parse("[a](javascript:void(0))\nb").render();
+//! assert_eq!(html, "<p><a href=\"javascript:void(0)\">a</a>\nb</p>\n");
+//!
+//! let ast = md.parse("> > > a");
+//! assert_eq!(Diagnostics::of(&ast)[0].to_string(),
+//!     "warning[commonmark-limit]: nesting is too deep, content is kept as text (4..7)");
+//! ```
+use crate::parser::linkfmt::{LinkFormatter, MDLinkFormatter};
+use crate::MarkdownIt;
+
+pub fn add(md: &mut MarkdownIt) {
+    if is_strict(md) { return; }
+    md.strict = true;
+
+    let parent = std::mem::replace(&mut md.link_formatter, Box::new(MDLinkFormatter::new()));
+    md.link_formatter = Box::new(StrictLinkFormatter { parent });
+}
+
+/// Check if strict mode is enabled (same as [MarkdownIt::strict]), plugins with
+/// options that aren't in CommonMark should ignore them in this case.
+pub fn is_strict(md: &MarkdownIt) -> bool {
+    md.strict
+}
+
+#[derive(Debug)]
+struct StrictLinkFormatter {
+    parent: Box<dyn LinkFormatter>,
+}
+
+impl LinkFormatter for StrictLinkFormatter {
+    fn validate_link(&self, _: &str) -> Option<()> {
+        Some(())
+    }
+
+    fn normalize_link(&self, url: &str) -> String {
+        self.parent.normalize_link(url)
+    }
+
+    fn normalize_link_text(&self, url: &str) -> String {
+        self.parent.normalize_link_text(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::diagnostics::Diagnostics;
+
+    fn make_md() -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        crate::plugins::html::add(&mut md);
+        md
+    }
+
+    #[test]
+    fn spec() {
+        let md = &mut make_md();
+        crate::plugins::cmark::inline::newline::set_breaks(md, true);
+        crate::plugins::cmark::block::fence::set_lang_prefix(md, "lang-");
+        super::add(md);
+        let report = crate::testing::run(md);
+        assert_eq!(report.failed().count(), 0, "{report}");
+    }
+
+    #[test]
+    fn options_ignored() {
+        let md = &mut make_md();
+        crate::plugins::cmark::inline::newline::set_breaks(md, true);
+        crate::plugins::cmark::block::fence::set_lang_prefix(md, "lang-");
+        let src = "a\nb\n\n```rs\n```\n\n[c](javascript:x)";
+        assert_eq!(md.parse(src).render(),
+            "<p>a<br>\nb</p>\n<pre><code class=\"lang-rs\"></code></pre>\n<p>[c](javascript:x)</p>\n");
+        super::add(md);
+        assert_eq!(md.parse(src).render(),
+            "<p>a\nb</p>\n<pre><code class=\"language-rs\"></code></pre>\n<p><a href=\"javascript:x\">c</a></p>\n");
+    }
+
+    #[test]
+    fn limits() {
+        let md = &mut make_md();
+        md.max_nesting = 1;
+        md.max_input_size = 12;
+        let src = "x [*a*](/)\n\nbcdef";
+        assert!(Diagnostics::of(&md.parse(src)).is_empty());
+
+        super::add(md);
+        let diagnostics = Diagnostics::of(&md.parse(src)).iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        assert_eq!(diagnostics, [
+            "warning[commonmark-limit]: input is too large, the rest is kept as text (12..17)",
+            "warning[commonmark-limit]: nesting is too deep, content is kept as text (3..6)",
+        ]);
+    }
+}
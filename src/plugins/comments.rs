@@ -0,0 +1,121 @@
+//! Preset for comment systems, forums and chat apps (untrusted input from many users).
+//!
+//!  - CommonMark syntax, without raw html (it's escaped even if html plugin was added)
+//!  - images are removed (keeping their alt text), or turned into links
+//!    (see [CommentsOptions::images_as_links])
+//!  - autolinking (with `linkify` feature), strikethrough and `||spoilers||`
+//!  - nesting is limited to 16 levels
+//!  - links to other websites get `rel="nofollow ugc noopener"` and `target="_blank"`
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::comments::add(md);
+//!
+//! let html = md.parse("~~first~~ ||spoiler|| <b>hi</b> ![cat](cat.png) [site](https://example.org)").render();
+//! assert_eq!(html.trim(), "<p><s>first</s> <span class=\"spoiler\">spoiler</span> &lt;b&gt;hi&lt;/b&gt; cat \
+//!     <a target=\"_blank\" rel=\"nofollow ugc noopener\" href=\"https://example.org\">site</a></p>");
+//! ```
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::cmark::inline::link::Link;
+use crate::plugins::extra::external_links::{self, ExternalLinksOptions, ExternalLinksRule};
+use crate::plugins::html::policy::{self, HtmlPolicy};
+use crate::plugins::{cmark, extra};
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Default)]
+/// Options for [add_with_options].
+pub struct CommentsOptions {
+    /// Render images as links to them, instead of only keeping their alt text.
+    pub images_as_links: bool,
+    /// Hosts of the site, links to them are not decorated (see [external_links]).
+    pub internal_hosts: Vec<String>,
+}
+
+impl MarkdownItExt for CommentsOptions {}
+
+/// Maximum nesting of blocks and inlines in comments.
+pub const MAX_NESTING: u32 = 16;
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, CommentsOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: CommentsOptions) {
+    cmark::add(md);
+    extra::strikethrough::add(md);
+    extra::spoiler::add(md);
+    #[cfg(feature = "linkify")]
+    extra::linkify::add(md);
+    policy::set_policy(md, HtmlPolicy::Escape);
+    external_links::add(md, ExternalLinksOptions {
+        internal_hosts: options.internal_hosts.clone(),
+        rel: Some("nofollow ugc noopener".into()),
+        ..Default::default()
+    });
+    md.max_nesting = md.max_nesting.min(MAX_NESTING);
+
+    md.ext.insert(options);
+    if !md.has_rule::<CommentImagesRule>() {
+        // links made from images are decorated as external links too
+        md.add_rule::<CommentImagesRule>()
+            .after::<InlineParserRule>()
+            .before::<ExternalLinksRule>();
+    }
+}
+
+#[doc(hidden)]
+pub struct CommentImagesRule;
+impl CoreRule for CommentImagesRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let images_as_links = ParseOptions::of_root(root).ext_or::<CommentsOptions>(md)
+            .is_some_and(|options| options.images_as_links);
+
+        root.walk_mut(|node, _| {
+            let Some(image) = node.cast::<Image>() else { return; };
+            if images_as_links {
+                let mut link = Node::new(Link { url: image.url.clone(), title: image.title.clone() });
+                link.srcmap = node.srcmap;
+                link.children = std::mem::take(&mut node.children);
+                *node = link;
+            } else {
+                node.unwrap_children();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommentsOptions;
+
+    #[test]
+    fn images_as_links() {
+        let md = &mut crate::MarkdownIt::new();
+        super::add_with_options(md, CommentsOptions {
+            images_as_links: true,
+            internal_hosts: vec!["example.org".into()],
+        });
+        assert_eq!(
+            md.parse("![*cat*](https://example.org/cat.png \"Cat\")").render(),
+            "<p><a href=\"https://example.org/cat.png\" title=\"Cat\"><em>cat</em></a></p>\n"
+        );
+        assert_eq!(
+            md.parse("![dog](https://example.com/dog.png)").render(),
+            "<p><a target=\"_blank\" rel=\"nofollow ugc noopener\" href=\"https://example.com/dog.png\">dog</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn html_and_nesting() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::html::add(md);
+        super::add(md);
+        assert_eq!(md.max_nesting, super::MAX_NESTING);
+        assert_eq!(md.parse("<div>\nx\n</div>").render(), "&lt;div&gt;\nx\n&lt;/div&gt;\n");
+        assert_eq!(md.parse(&">".repeat(100)).render().matches("<blockquote>").count(), 16);
+    }
+}
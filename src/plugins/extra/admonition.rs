@@ -0,0 +1,237 @@
+//! Admonitions (call-outs) in MkDocs / Material syntax.
+//!
+//! ```text
+//! !!! note "Optional title"
+//!     Contents indented by 4 spaces.
+//!
+//! ??? warning
+//!     Collapsible, `???+` makes it expanded by default.
+//! ```
+//!
+//! Kind of an admonition (`note`, `warning`, ...) can be followed by extra classes
+//! (`!!! tip inline end`). Title defaults to the capitalized kind, and is omitted
+//! if it's set to `""`. Output is the same as produced by MkDocs, so its themes apply:
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::admonition::add(md);
+//!
+//! let html = md.parse("!!! note\n    *Text*").render();
+//! assert_eq!(html, "<div class=\"admonition note\">\n<p class=\"admonition-title\">Note</p>\n\
+//!     <p><em>Text</em></p>\n</div>\n");
+//!
+//! let html = md.parse("???+ tip \"Hint\"\n    Text").render();
+//! assert_eq!(html, "<details class=\"tip\" open=\"\">\n<summary>Hint</summary>\n<p>Text</p>\n</details>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::parser::block::{BlockRule, BlockState};
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static OPEN_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(!!!|\?\?\?\+?)[ \t]+([\w-]+(?:[ \t]+[\w-]+)*)(?:[ \t]+"([^"]*)")?[ \t]*$"#).unwrap()
+});
+
+// indent of admonition contents
+const CONTENT_INDENT: usize = 4;
+
+#[derive(Debug)]
+/// Admonition block, its children are contents.
+pub struct Admonition {
+    /// Kind of an admonition, e.g. `note` or `warning`.
+    pub kind: String,
+    /// Extra classes given after the kind.
+    pub classes: Vec<String>,
+    /// Title, `None` if it was set to `""`.
+    pub title: Option<String>,
+    /// `None` for `!!!`, collapsible `???` blocks are expanded if `Some(true)`.
+    pub collapsible: Option<bool>,
+}
+
+impl NodeValue for Admonition {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        let classes = std::iter::once(&self.kind).chain(self.classes.iter()).cloned().collect::<Vec<_>>().join(" ");
+
+        fmt.cr();
+        if let Some(open) = self.collapsible {
+            attrs.push(("class".into(), classes));
+            if open { attrs.push(("open".into(), String::new())); }
+            fmt.open("details", &attrs);
+            fmt.cr();
+            if let Some(title) = &self.title {
+                fmt.open("summary", &[]);
+                fmt.text(title);
+                fmt.close("summary");
+                fmt.cr();
+            }
+            fmt.contents(&node.children);
+            fmt.cr();
+            fmt.close("details");
+        } else {
+            attrs.push(("class".into(), format!("admonition {classes}")));
+            fmt.open("div", &attrs);
+            fmt.cr();
+            if let Some(title) = &self.title {
+                fmt.open("p", &[("class".into(), "admonition-title".into())]);
+                fmt.text(title);
+                fmt.close("p");
+                fmt.cr();
+            }
+            fmt.contents(&node.children);
+            fmt.cr();
+            fmt.close("div");
+        }
+        fmt.cr();
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "kind": self.kind,
+            "classes": self.classes,
+            "title": self.title,
+            "collapsible": self.collapsible,
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            kind: field(data, "kind")?,
+            classes: field(data, "classes")?,
+            title: field(data, "title")?,
+            collapsible: field(data, "collapsible")?,
+        })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    if !md.block.has_rule::<AdmonitionScanner>() {
+        md.block.add_rule::<AdmonitionScanner>();
+    }
+}
+
+#[doc(hidden)]
+pub struct AdmonitionScanner;
+
+impl AdmonitionScanner {
+    fn parse_open(line: &str) -> Option<Admonition> {
+        let captures = OPEN_RE.captures(line)?;
+        let mut words = captures[2].split_whitespace().map(str::to_owned);
+        let kind = words.next()?;
+
+        let title = match captures.get(3) {
+            Some(title) if title.as_str().is_empty() => None,
+            Some(title) => Some(title.as_str().to_owned()),
+            None => {
+                let mut chars = kind.chars();
+                chars.next().map(|first| first.to_uppercase().chain(chars).collect())
+            }
+        };
+
+        let collapsible = match &captures[1] {
+            "!!!" => None,
+            marker => Some(marker.ends_with('+')),
+        };
+
+        Some(Admonition { kind, classes: words.collect(), title, collapsible })
+    }
+
+    // returns first line after the contents (trailing empty lines excluded)
+    fn find_end(state: &BlockState) -> usize {
+        let mut end = state.line + 1;
+        let mut next_line = end;
+
+        while next_line < state.line_max {
+            if !state.is_empty(next_line) {
+                if state.line_indent(next_line) < CONTENT_INDENT as i32 { break; }
+                end = next_line + 1;
+            }
+            next_line += 1;
+        }
+
+        end
+    }
+}
+
+impl BlockRule for AdmonitionScanner {
+    fn check(state: &mut BlockState) -> Option<()> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        Self::parse_open(state.get_line(state.line)).map(|_| ())
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        let admonition = Self::parse_open(state.get_line(state.line))?;
+        let end = Self::find_end(state);
+
+        let start_line = state.line;
+        let old_line_max = state.line_max;
+        let old_blk_indent = state.blk_indent;
+        let old_node = std::mem::replace(&mut state.node, Node::new(admonition));
+
+        state.line = start_line + 1;
+        state.line_max = end;
+        state.blk_indent += CONTENT_INDENT;
+        state.level += 1;
+        state.md.block.tokenize(state);
+        state.level -= 1;
+
+        state.line = start_line;
+        state.line_max = old_line_max;
+        state.blk_indent = old_blk_indent;
+
+        let node = std::mem::replace(&mut state.node, old_node);
+        Some((node, end - start_line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdmonitionScanner;
+
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn parse_open() {
+        let admonition = AdmonitionScanner::parse_open("!!! tip inline end \"A b\"").unwrap();
+        assert_eq!(admonition.kind, "tip");
+        assert_eq!(admonition.classes, ["inline", "end"]);
+        assert_eq!(admonition.title.as_deref(), Some("A b"));
+        assert_eq!(admonition.collapsible, None);
+        assert_eq!(AdmonitionScanner::parse_open("??? danger \"\"").unwrap().collapsible, Some(false));
+        assert!(AdmonitionScanner::parse_open("!!!").is_none());
+        assert!(AdmonitionScanner::parse_open("!!!note").is_none());
+        assert!(AdmonitionScanner::parse_open("!!! \"title\"").is_none());
+    }
+
+    #[test]
+    fn contents() {
+        assert_eq!(run("!!! note \"\"\n    a\n\n        b\n\n    - c\n\nd"),
+            "<div class=\"admonition note\">\n<p>a</p>\n<pre><code>b\n</code></pre>\n<ul>\n<li>c</li>\n</ul>\n</div>\n<p>d</p>\n");
+        assert_eq!(run("!!! note\n!!! warning\n    a"),
+            "<div class=\"admonition note\">\n<p class=\"admonition-title\">Note</p>\n</div>\n\
+            <div class=\"admonition warning\">\n<p class=\"admonition-title\">Warning</p>\n<p>a</p>\n</div>\n");
+        // contents end at the first line with less indentation
+        assert_eq!(run("??? note \"<x>\"\n    a\nb"),
+            "<details class=\"note\">\n<summary>&lt;x&gt;</summary>\n<p>a</p>\n</details>\n<p>b</p>\n");
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(run("- !!! a\n      !!! b\n          c\n      d"),
+            "<ul>\n<li>\n<div class=\"admonition a\">\n<p class=\"admonition-title\">A</p>\n\
+            <div class=\"admonition b\">\n<p class=\"admonition-title\">B</p>\n<p>c</p>\n</div>\n\
+            <p>d</p>\n</div>\n</li>\n</ul>\n");
+    }
+}
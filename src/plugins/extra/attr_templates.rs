@@ -0,0 +1,112 @@
+//! Document metadata in attribute values, written as `{{meta.name}}`.
+//!
+//! Values are taken from the [front matter](super::front_matter) of the document,
+//! or from metadata passed to the parser with [set_metadata] (e.g. site config or
+//! build environment). Templates with unknown names are left as is.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::front_matter::add(md);
+//! markdown_it::plugins::extra::attrs::add(md);
+//! markdown_it::plugins::extra::attr_templates::add(md);
+//!
+//! let html = md.parse("---\nversion: 2.1\n---\n# Changes {#v{{meta.version}} data-version={{meta.version}}}").render();
+//! assert_eq!(html, "<h1 id=\"v2.1\" data-version=\"2.1\">Changes</h1>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::extset::MarkdownItExt;
+use crate::plugins::extra::front_matter::DocumentMetadata;
+use crate::{MarkdownIt, Node};
+
+static TEMPLATE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{\{\s*meta\.([A-Za-z0-9_.-]+)\s*\}\}"#).unwrap()
+});
+
+#[derive(Debug, Default)]
+struct DefaultMetadata(HashMap<String, String>);
+impl MarkdownItExt for DefaultMetadata {}
+
+pub fn add(md: &mut MarkdownIt) {
+    // runs last, so attributes added by other plugins are resolved as well
+    md.add_rule::<AttrTemplatesRule>()
+        .after_all();
+}
+
+/// Set metadata available in all documents, front matter values override it.
+pub fn set_metadata(md: &mut MarkdownIt, metadata: HashMap<String, String>) {
+    md.ext.insert(DefaultMetadata(metadata));
+}
+
+/// Replace `{{meta.name}}` templates in attribute values of all nodes.
+pub fn apply_metadata(root: &mut Node, metadata: &HashMap<String, String>) {
+    root.walk_mut(|node, _| {
+        for (_, value) in node.attrs.iter_mut() {
+            if !value.contains("{{") { continue; }
+            let result = TEMPLATE_RE.replace_all(value, |captures: &Captures| {
+                metadata.get(&captures[1]).cloned().unwrap_or_else(|| captures[0].to_owned())
+            });
+            *value = result.into_owned();
+        }
+    });
+}
+
+#[doc(hidden)]
+pub struct AttrTemplatesRule;
+impl CoreRule for AttrTemplatesRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let mut metadata = md.ext.get::<DefaultMetadata>().map(|m| m.0.clone()).unwrap_or_default();
+        if let Some(document) = root.cast::<Root>().and_then(|root| root.ext.get::<DocumentMetadata>()) {
+            metadata.extend(document.0.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        if metadata.is_empty() { return; }
+
+        apply_metadata(root, &metadata);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::front_matter::add(md);
+        crate::plugins::extra::attrs::add(md);
+        super::add(md);
+        super::set_metadata(md, HashMap::from([
+            ("version".to_owned(), "1.0".to_owned()),
+            ("site.name".to_owned(), "<docs>".to_owned()),
+        ]));
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn env_metadata() {
+        assert_eq!(
+            run("# A {data-v={{meta.version}} title=\"{{ meta.site.name }} v{{meta.version}}\"}"),
+            "<h1 data-v=\"1.0\" title=\"&lt;docs&gt; v1.0\">A</h1>\n"
+        );
+    }
+
+    #[test]
+    fn front_matter_overrides_env() {
+        assert_eq!(
+            run("---\nversion: 2.0\n---\n```rust {data-v={{meta.version}}}\n```"),
+            "<pre><code data-v=\"2.0\" class=\"language-rust\"></code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn unknown_and_text() {
+        assert_eq!(
+            run("# {{meta.version}} {data-x={{meta.unknown}}}"),
+            "<h1 data-x=\"{{meta.unknown}}\">{{meta.version}}</h1>\n"
+        );
+    }
+}
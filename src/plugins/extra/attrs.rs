@@ -1,20 +1,99 @@
 //! Add identifiers, classes and attributes with the syntax `{#id .class key=value}`.
+//!
+//! Special attribute `render=false` is not added to the output, instead it excludes
+//! the node from rendering (it's still available in the AST), see [Node::set_hidden].
+//!
+//! Keys must be valid html attribute names, otherwise `{...}` is kept as text.
+//! Event handlers (`onclick`, ...) are dropped by default, other names can be
+//! restricted for untrusted content with [AttrsOptions]:
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::attrs::{self, AllowedNames, AttrsOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! attrs::add_with_options(md, AttrsOptions { allowed_names: AllowedNames::Safe });
+//!
+//! let html = md.parse("# Title {#a .b data-x=1 onclick=alert(1) href=/}").render();
+//! assert_eq!(html, "<h1 id=\"a\" class=\"b\" data-x=\"1\">Title</h1>\n");
+//! ```
 
 use crate::{
     MarkdownIt, Node,
-    parser::{core::CoreRule, inline::Text},
+    common::utils::is_valid_attr_name,
+    parser::{core::CoreRule, extset::MarkdownItExt, inline::Text, options::ParseOptions},
     plugins::cmark::block::{fence::CodeFence, heading::ATXHeading, lheading::SetextHeader},
 };
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// Attribute names kept by the plugin, others are dropped.
+pub enum AllowedNames {
+    /// Any valid attribute name.
+    Any,
+    #[default]
+    /// Any valid attribute name except event handlers (`on*`).
+    NoEventHandlers,
+    /// Only `id`, `class`, `data-*`, `aria-*` and names from [SAFE_NAMES],
+    /// for untrusted content.
+    Safe,
+}
+
+/// Attribute names allowed by [AllowedNames::Safe] in addition to `id`, `class`,
+/// `data-*` and `aria-*`: they can't run scripts, load resources or change links.
+pub const SAFE_NAMES: &[&str] = &[
+    "abbr", "colspan", "dir", "height", "hidden", "lang", "reversed", "role",
+    "rowspan", "scope", "start", "title", "translate", "width",
+];
+
+impl AllowedNames {
+    /// Check whether attribute with given name is kept.
+    pub fn allows(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        match self {
+            Self::Any => true,
+            Self::NoEventHandlers => !name.starts_with("on"),
+            Self::Safe => {
+                name == "id" || name == "class" || name.starts_with("data-") || name.starts_with("aria-")
+                    || SAFE_NAMES.contains(&name.as_str())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct AttrsOptions {
+    pub allowed_names: AllowedNames,
+}
+
+impl MarkdownItExt for AttrsOptions {}
+
 /// Add identifiers, classes and attributes with the syntax `{#id .class key=value}`.
 pub fn add(md: &mut MarkdownIt) {
-    md.add_rule::<AttrsRule>();
+    add_with_options(md, AttrsOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: AttrsOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<AttrsRule>() {
+        md.add_rule::<AttrsRule>();
+    }
 }
 
-struct AttrsRule;
+#[doc(hidden)]
+pub struct AttrsRule;
 
 impl CoreRule for AttrsRule {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let options = parse_options.ext_or::<AttrsOptions>(md).copied().unwrap_or_default();
+        // `{{meta.name}}` in unquoted values is only kept together if it's going to be replaced
+        #[cfg(feature = "attr_templates")]
+        let templates = md.has_rule::<crate::plugins::extra::attr_templates::AttrTemplatesRule>();
+        #[cfg(not(feature = "attr_templates"))]
+        let templates = false;
+
         root.walk_mut(|node, _| {
             if node.is::<ATXHeading>() || node.is::<SetextHeader>() {
                 // # Header {#foo}
@@ -26,33 +105,50 @@ impl CoreRule for AttrsRule {
                     return;
                 };
 
-                let (content, attrs) = parse_attrs(&text.content);
+                let (content, attrs) = parse_attrs(&text.content, templates);
 
                 if attrs.is_empty() {
                     return;
                 }
 
                 text.content = content.to_string();
-                node.attrs.extend(attrs);
+                add_attrs(node, attrs, &options);
             } else if let Some(code_fence) = node.cast_mut::<CodeFence>() {
                 // ```rust {#foo}
                 // println!("Hello world");
                 // ```
-                let (info, attrs) = parse_attrs(&code_fence.info);
+                let (info, attrs) = parse_attrs(&code_fence.info, templates);
 
                 if attrs.is_empty() {
                     return;
                 }
 
                 code_fence.info = info.to_string();
-                node.attrs.extend(attrs);
+                add_attrs(node, attrs, &options);
             }
         });
     }
 }
 
-/// Parse attributes including the curly braces.
-fn parse_attrs(s: &str) -> (&str, Vec<(String, String)>) {
+fn add_attrs(node: &mut Node, attrs: Vec<(String, String)>, options: &AttrsOptions) {
+    for (key, value) in attrs {
+        if key == "render" && value == "false" {
+            node.set_hidden(true);
+        } else if options.allowed_names.allows(&key) {
+            node.attrs.push((key, value));
+        }
+    }
+}
+
+// Check if unquoted value (parsed backwards) is inside a `{{template}}`,
+// where special characters are kept as is.
+fn in_template(value: &str) -> bool {
+    value.matches("}}").count() > value.matches("{{").count()
+}
+
+/// Parse attributes including the curly braces, `templates` allows `{{...}}`
+/// in unquoted values.
+fn parse_attrs(s: &str, templates: bool) -> (&str, Vec<(String, String)>) {
     enum State {
         Start,
         Blank,
@@ -152,7 +248,19 @@ fn parse_attrs(s: &str) -> (&str, Vec<(String, String)>) {
                 _ => return fail,
             },
             State::Unquoted => match index_char {
-                Some((_, c)) => match c {
+                Some((i, c)) => match c {
+                    // {key={{meta.val}}}
+                    //            ^
+                    c if templates && in_template(&value) => {
+                        if c == '{' {
+                            if !s[..i].ends_with('{') { return fail; }
+                            char_indices.next();
+                            value.insert_str(0, "{{");
+                        } else {
+                            value.insert(0, c);
+                        }
+                        State::Unquoted
+                    }
                     // {val}
                     // ^
                     '{' => return fail,
@@ -195,6 +303,7 @@ fn parse_attrs(s: &str) -> (&str, Vec<(String, String)>) {
                     // { key=val }
                     //  ^
                     c if c == '{' || c.is_ascii_whitespace() => {
+                        if !is_valid_attr_name(&key) { return fail; }
                         attrs.insert(0, (key.clone(), value.clone()));
                         if c == '{' {
                             end = i;
@@ -239,7 +348,7 @@ mod tests {
     #[test]
     fn parse_attrs_id() {
         assert_eq!(
-            parse_attrs("{#foo}"),
+            parse_attrs("{#foo}", false),
             ("", vec![("id".into(), "foo".into())])
         );
     }
@@ -247,7 +356,7 @@ mod tests {
     #[test]
     fn parse_attrs_class() {
         assert_eq!(
-            parse_attrs("{.haskell}"),
+            parse_attrs("{.haskell}", false),
             ("", vec![("class".into(), "haskell".into())])
         );
     }
@@ -255,7 +364,7 @@ mod tests {
     #[test]
     fn parse_attrs_key_value() {
         assert_eq!(
-            parse_attrs("{key=val}"),
+            parse_attrs("{key=val}", false),
             ("", vec![("key".into(), "val".into())])
         );
     }
@@ -263,31 +372,52 @@ mod tests {
     #[test]
     fn parse_attrs_key_value_quoted() {
         assert_eq!(
-            parse_attrs(r#"{key2="val 2"}"#),
+            parse_attrs(r#"{key2="val 2"}"#, false),
             ("", vec![("key2".into(), "val 2".into())]),
         );
         assert_eq!(
-            parse_attrs(r#"{key2="val\"2"}"#),
+            parse_attrs(r#"{key2="val\"2"}"#, false),
             ("", vec![("key2".into(), r#"val"2"#.into())]),
         );
     }
 
+    #[test]
+    fn parse_attrs_template() {
+        assert_eq!(
+            parse_attrs("{#v{{meta.v}} key={{meta.v}}}", true),
+            ("", vec![("id".into(), "v{{meta.v}}".into()), ("key".into(), "{{meta.v}}".into())])
+        );
+        assert_eq!(parse_attrs("{{meta.v}}", true), ("{{meta.v}}", vec![]));
+        assert_eq!(parse_attrs("{key={val}", true), ("{key={val}", vec![]));
+        assert_eq!(parse_attrs("{key=x{meta.v}}}", true), ("{key=x{meta.v}}}", vec![]));
+        assert_eq!(parse_attrs("{key={{meta.v}}}", false), ("{key={{meta.v}}}", vec![]));
+    }
+
+    #[test]
+    fn templates_off() {
+        assert_eq!(run("# a {a=\"{{x}}\"}"), "<h1 a=\"{{x}}\">a</h1>\n");
+        assert_eq!(run("# a {a={{x}}}"), "<h1>a {a={{x}}}</h1>\n");
+    }
+
     #[test]
     fn parse_attrs_fail() {
-        assert_eq!(parse_attrs("{#foo"), ("{#foo", vec![]));
-        assert_eq!(parse_attrs("{}"), ("{}", vec![]));
-        assert_eq!(parse_attrs("#foo}"), ("#foo}", vec![]));
-        assert_eq!(parse_attrs(r#"val" #foo}"#), (r#"val" #foo}"#, vec![]));
-        assert_eq!(parse_attrs(r#""val" #foo}"#), (r#""val" #foo}"#, vec![]));
-        assert_eq!(parse_attrs("{val #foo}"), ("{val #foo}", vec![]));
-        assert_eq!(parse_attrs("{ val #foo}"), ("{ val #foo}", vec![]));
-        assert_eq!(parse_attrs("key=val #foo}"), ("key=val #foo}", vec![]));
+        assert_eq!(parse_attrs("{#foo", false), ("{#foo", vec![]));
+        assert_eq!(parse_attrs("{}", false), ("{}", vec![]));
+        assert_eq!(parse_attrs("#foo}", false), ("#foo}", vec![]));
+        assert_eq!(parse_attrs(r#"val" #foo}"#, false), (r#"val" #foo}"#, vec![]));
+        assert_eq!(parse_attrs(r#""val" #foo}"#, false), (r#""val" #foo}"#, vec![]));
+        assert_eq!(parse_attrs("{val #foo}", false), ("{val #foo}", vec![]));
+        assert_eq!(parse_attrs("{ val #foo}", false), ("{ val #foo}", vec![]));
+        assert_eq!(parse_attrs("key=val #foo}", false), ("key=val #foo}", vec![]));
+        assert_eq!(parse_attrs("{=val}", false), ("{=val}", vec![]));
+        assert_eq!(parse_attrs(r#"{a"b=val}"#, false), (r#"{a"b=val}"#, vec![]));
+        assert_eq!(parse_attrs("{a<b=val}", false), ("{a<b=val}", vec![]));
     }
 
     #[test]
     fn parse_attrs_multiple() {
         assert_eq!(
-            parse_attrs(r#"{#mycode .haskell .numberLines startFrom="100"}"#),
+            parse_attrs(r#"{#mycode .haskell .numberLines startFrom="100"}"#, false),
             (
                 "",
                 vec![
@@ -300,7 +430,7 @@ mod tests {
         );
 
         assert_eq!(
-            parse_attrs(r#"{#id .class key=val key2="val 2"}"#),
+            parse_attrs(r#"{#id .class key=val key2="val 2"}"#, false),
             (
                 "",
                 vec![
@@ -329,6 +459,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_false() {
+        assert_eq!(
+            run("# Draft {.x render=false}\n\n```rust {render=false}\nfn main() {}\n```\n\ntext"),
+            "<p>text</p>\n"
+        );
+        assert_eq!(
+            run("# Heading {render=true}"),
+            "<h1 render=\"true\">Heading</h1>\n"
+        );
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        let mut ast = md.parse("# Draft {render=false}");
+        assert!(ast.children[0].is_hidden());
+        assert_eq!(ast.children[0].collect_text(), "Draft");
+
+        ast.children[0].set_hidden(false);
+        assert_eq!(ast.render(), "<h1>Draft</h1>\n");
+    }
+
+    #[test]
+    fn allowed_names() {
+        assert_eq!(run("# a {onclick=x OnMouseOver=y title=z}"), "<h1 title=\"z\">a</h1>\n");
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, AttrsOptions { allowed_names: AllowedNames::Any });
+        assert_eq!(md.parse("# a {onclick=x}").render(), "<h1 onclick=\"x\">a</h1>\n");
+
+        let mut options = crate::parser::options::ParseOptions::new();
+        options.ext.insert(AttrsOptions { allowed_names: AllowedNames::Safe });
+        assert_eq!(
+            md.parse_with_options("# a {#x aria-label=y style=z render=false}", options).children[0].attrs,
+            [("id".into(), "x".into()), ("aria-label".into(), "y".into())]
+        );
+    }
+
     #[test]
     fn fenced_code_attrs() {
         assert_eq!(
@@ -358,6 +527,33 @@ bar
         );
     }
 
+    #[test]
+    fn heading_anchors_added_first() {
+        use crate::plugins::extra::heading_anchors;
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        heading_anchors::add(md, heading_anchors::simple_slugify_fn);
+        super::add(md);
+        assert!(md.validate_rules().is_ok());
+        assert_eq!(
+            md.parse("# My heading {#foo}").render(),
+            "<h1 id=\"foo\">My heading</h1>\n"
+        );
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn syntect_added_first() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::syntect::add(md);
+        super::add(md);
+        assert_eq!(
+            md.parse("``` {#foo}\nbar\n```").render(),
+            "<pre><code id=\"foo\" class=\"code\"><span class=\"text plain\">bar\n</span></code></pre>\n"
+        );
+    }
+
     #[cfg(feature = "syntect")]
     #[test]
     fn syntect_attrs() {
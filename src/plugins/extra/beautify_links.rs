@@ -1,14 +1,90 @@
 //! Pretty-print all urls and fit them into N characters
+//!
+//! This only affects displayed text of autolinks (`<https://example.org>`) and
+//! linkified urls, link destination is kept as is.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::beautify_links::BeautifyOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::beautify_links::add_with_options(md, BeautifyOptions {
+//!     keep_scheme: true,
+//!     max_path_segments: Some(2),
+//!     ..Default::default()
+//! });
+//!
+//! let html = md.parse("<https://example.org/a/b/c/d?q=1>").render();
+//! assert_eq!(html.trim(), r#"<p><a href="https://example.org/a/b/c/d?q=1">https://example.org/a/…/d?q=1</a></p>"#);
+//! ```
 
 use crate::parser::linkfmt::{LinkFormatter, MDLinkFormatter};
 use crate::MarkdownIt;
 
+#[derive(Debug, Clone)]
+/// Options that control how displayed urls are shortened.
+pub struct BeautifyOptions {
+    /// Maximum length of displayed url, longer urls are elided with `…`.
+    pub max_length: usize,
+    /// Keep `http://`, `https://` and `mailto:` prefixes, which are removed by default.
+    pub keep_scheme: bool,
+    /// Maximum number of path segments to display, segments in the middle are
+    /// replaced with `…` (e.g. `/a/b/c/d` -> `/a/…/d` for 2 segments).
+    pub max_path_segments: Option<usize>,
+    /// Maximum length of query string (without leading `?`), the rest is replaced with `…`.
+    pub max_query_length: Option<usize>,
+}
+
+impl Default for BeautifyOptions {
+    fn default() -> Self {
+        Self {
+            max_length: 50,
+            keep_scheme: false,
+            max_path_segments: None,
+            max_query_length: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct LinkBeautifier {
-    max_length: usize,
+    options: BeautifyOptions,
     parent: Box<dyn LinkFormatter>,
 }
 
+impl LinkBeautifier {
+    fn shorten_url(&self, url: &str) -> String {
+        let mut parsed = mdurl::parse_url(url);
+
+        if let (Some(max), Some(pathname)) = (self.options.max_path_segments, parsed.pathname.as_ref()) {
+            let segments = pathname.split('/').skip(1).collect::<Vec<_>>();
+            if segments.len() > max.max(1) {
+                let mut shortened = segments[..max.max(1) - 1].to_vec();
+                shortened.push("…");
+                shortened.push(segments[segments.len() - 1]);
+                parsed.pathname = Some(format!("/{}", shortened.join("/")));
+            }
+        }
+
+        if let (Some(max), Some(search)) = (self.options.max_query_length, parsed.search.as_ref()) {
+            if let Some((idx, _)) = search.char_indices().nth(max + 1) {
+                parsed.search = Some(format!("{}…", &search[..idx]));
+            }
+        }
+
+        let scheme = match parsed.protocol.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            Some("http:") | Some("https:") if parsed.slashes && self.options.keep_scheme => {
+                format!("{}//", parsed.protocol.as_ref().unwrap())
+            }
+            Some("mailto:") if self.options.keep_scheme => parsed.protocol.clone().unwrap(),
+            _ => String::new(),
+        };
+
+        let max_length = self.options.max_length.saturating_sub(scheme.chars().count());
+        scheme + &mdurl::format_url_for_humans(&parsed.to_string(), max_length)
+    }
+}
+
 impl LinkFormatter for LinkBeautifier {
     fn validate_link(&self, url: &str) -> Option<()> {
         self.parent.as_ref().validate_link(url)
@@ -19,21 +95,69 @@ impl LinkFormatter for LinkBeautifier {
     }
 
     fn normalize_link_text(&self, url: &str) -> String {
-        mdurl::format_url_for_humans(url, self.max_length)
+        self.shorten_url(url)
     }
 }
 
 
 /// Add beautifier plugin, limiting urls to default 50 characters
 pub fn add(md: &mut MarkdownIt) {
-    add_with_char_limit(md, 50);
+    add_with_options(md, BeautifyOptions::default());
 }
 
 /// Add beautifier plugin, limiting urls to `max_length` characters
 pub fn add_with_char_limit(md: &mut MarkdownIt, max_length: usize) {
+    add_with_options(md, BeautifyOptions { max_length, ..Default::default() });
+}
+
+/// Add beautifier plugin with custom options
+pub fn add_with_options(md: &mut MarkdownIt, options: BeautifyOptions) {
     let parent = std::mem::replace(&mut md.link_formatter, Box::new(MDLinkFormatter::new()));
     md.link_formatter = Box::new(LinkBeautifier {
-        max_length,
+        options,
         parent,
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BeautifyOptions, LinkBeautifier};
+    use crate::parser::linkfmt::MDLinkFormatter;
+
+    fn shorten(url: &str, options: BeautifyOptions) -> String {
+        LinkBeautifier { options, parent: Box::new(MDLinkFormatter::new()) }.shorten_url(url)
+    }
+
+    #[test]
+    fn default_options() {
+        let url = "https://example.org/a/b?q=1";
+        assert_eq!(shorten(url, BeautifyOptions::default()), mdurl::format_url_for_humans(url, 50));
+        assert_eq!(shorten("http://example.org/", BeautifyOptions::default()), "example.org");
+    }
+
+    #[test]
+    fn keep_scheme() {
+        let options = BeautifyOptions { keep_scheme: true, ..Default::default() };
+        assert_eq!(shorten("https://example.org/a", options.clone()), "https://example.org/a");
+        assert_eq!(shorten("mailto:foo@example.org", options.clone()), "mailto:foo@example.org");
+        assert_eq!(shorten("example.org/a", options), "example.org/a");
+
+        let options = BeautifyOptions { keep_scheme: true, max_length: 20, ..Default::default() };
+        assert_eq!(shorten("https://example.org/abcdef", options), "https://example.org…");
+    }
+
+    #[test]
+    fn path_segments() {
+        let options = BeautifyOptions { max_path_segments: Some(2), ..Default::default() };
+        assert_eq!(shorten("https://example.org/a/b/c/d", options.clone()), "example.org/a/…/d");
+        assert_eq!(shorten("https://example.org/a/b", options.clone()), "example.org/a/b");
+        assert_eq!(shorten("https://example.org/a/b/c/", options), "example.org/a/…/");
+    }
+
+    #[test]
+    fn query_length() {
+        let options = BeautifyOptions { max_query_length: Some(3), ..Default::default() };
+        assert_eq!(shorten("https://example.org/?q=123456", options.clone()), "example.org/?q=1…");
+        assert_eq!(shorten("https://example.org/?q=1", options), "example.org/?q=1");
+    }
+}
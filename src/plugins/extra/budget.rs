@@ -0,0 +1,160 @@
+//! Report of heavy constructs in a document, to find pages that slow down site builds
+//! or produce too large output.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::budget::DocumentReport;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::html::add(md);
+//!
+//! let ast = md.parse("![a](a.png)\n\n```rust\nfn main() {}\n```\n\n<div>x</div>\n\n> - b");
+//! let report = DocumentReport::new(&ast);
+//! assert_eq!(report.image_count, 1);
+//! assert_eq!(report.code_lines, 1);
+//! assert_eq!(report.highlighted_code_lines, 1);
+//! assert_eq!(report.html_size, 13);
+//! assert_eq!(report.max_depth, 4);
+//!
+//! let budget = DocumentReport { image_count: 0, ..DocumentReport::unlimited() };
+//! assert_eq!(report.over_budget(&budget), ["image_count"]);
+//! ```
+use crate::plugins::cmark::block::code::CodeBlock;
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::html::html_block::HtmlBlock;
+use crate::plugins::html::html_inline::HtmlInline;
+use crate::Node;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Statistics of a single document, also used as a budget with limits for each value.
+pub struct DocumentReport {
+    /// Total number of nodes in the AST.
+    pub node_count: usize,
+    /// Lines in all code blocks.
+    pub code_lines: usize,
+    /// Lines in code blocks that are (or will be) highlighted: ones with a language
+    /// specified, or already highlighted by a plugin.
+    pub highlighted_code_lines: usize,
+    /// Size of embedded html blocks and inline html in bytes.
+    pub html_size: usize,
+    /// Number of images.
+    pub image_count: usize,
+    /// Depth of the deepest node (children of the root node have depth 1).
+    pub max_depth: usize,
+    /// Size of rendered html in bytes.
+    pub output_size: usize,
+}
+
+impl DocumentReport {
+    /// Analyze document (this renders it to measure output size).
+    pub fn new(root: &Node) -> Self {
+        let mut report = Self::default();
+
+        root.walk(|node, depth| {
+            report.node_count += 1;
+            report.max_depth = report.max_depth.max(depth as usize);
+
+            if let Some(data) = node.cast::<CodeFence>() {
+                let lines = data.content.lines().count();
+                report.code_lines += lines;
+                if data.raw || !data.info.trim().is_empty() {
+                    report.highlighted_code_lines += lines;
+                }
+            } else if let Some(data) = node.cast::<CodeBlock>() {
+                let lines = data.content.lines().count();
+                report.code_lines += lines;
+                if data.raw {
+                    report.highlighted_code_lines += lines;
+                }
+            } else if let Some(data) = node.cast::<HtmlBlock>() {
+                report.html_size += data.content.len();
+            } else if let Some(data) = node.cast::<HtmlInline>() {
+                report.html_size += data.content.len();
+            } else if node.is::<Image>() {
+                report.image_count += 1;
+            }
+        });
+
+        report.output_size = root.render().len();
+        report
+    }
+
+    /// Budget without any limits, use it as a base for your own budget.
+    pub fn unlimited() -> Self {
+        Self {
+            node_count: usize::MAX,
+            code_lines: usize::MAX,
+            highlighted_code_lines: usize::MAX,
+            html_size: usize::MAX,
+            image_count: usize::MAX,
+            max_depth: usize::MAX,
+            output_size: usize::MAX,
+        }
+    }
+
+    /// Names of the values that exceed given budget.
+    pub fn over_budget(&self, budget: &DocumentReport) -> Vec<&'static str> {
+        let checks = [
+            ("node_count", self.node_count, budget.node_count),
+            ("code_lines", self.code_lines, budget.code_lines),
+            ("highlighted_code_lines", self.highlighted_code_lines, budget.highlighted_code_lines),
+            ("html_size", self.html_size, budget.html_size),
+            ("image_count", self.image_count, budget.image_count),
+            ("max_depth", self.max_depth, budget.max_depth),
+            ("output_size", self.output_size, budget.output_size),
+        ];
+        checks.into_iter().filter(|(_, value, limit)| value > limit).map(|(name, _, _)| name).collect()
+    }
+
+    #[cfg(feature = "serde")]
+    /// Convert report to JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "node_count": self.node_count,
+            "code_lines": self.code_lines,
+            "highlighted_code_lines": self.highlighted_code_lines,
+            "html_size": self.html_size,
+            "image_count": self.image_count,
+            "max_depth": self.max_depth,
+            "output_size": self.output_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DocumentReport;
+
+    #[test]
+    fn report() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        let ast = md.parse("    a\n    b\n\n~~~\nc\n~~~\n\n- <b>*d*</b> ![e](e) ![f](f)");
+        assert_eq!(DocumentReport::new(&ast), DocumentReport {
+            node_count: 15,
+            code_lines: 3,
+            highlighted_code_lines: 0,
+            html_size: 7,
+            image_count: 2,
+            max_depth: 4,
+            output_size: 138,
+        });
+    }
+
+    #[test]
+    fn over_budget() {
+        let report = DocumentReport { code_lines: 10, max_depth: 10, ..Default::default() };
+        let budget = DocumentReport { code_lines: 10, max_depth: 9, output_size: 0, ..DocumentReport::unlimited() };
+        assert_eq!(report.over_budget(&budget), ["max_depth"]);
+        assert!(report.over_budget(&DocumentReport::unlimited()).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        let report = DocumentReport { image_count: 3, ..Default::default() };
+        assert_eq!(report.to_json()["image_count"], 3);
+    }
+}
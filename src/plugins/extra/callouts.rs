@@ -0,0 +1,234 @@
+//! Callouts (alerts) in GitHub syntax: blockquote starting with `[!NOTE]`.
+//!
+//! ```text
+//! > [!WARNING]
+//! > Contents of the callout.
+//! ```
+//!
+//! Kinds are `NOTE`, `TIP`, `IMPORTANT`, `WARNING` and `CAUTION` (case-insensitive),
+//! the marker must be alone on the first line. Other blockquotes are kept as is.
+//! Output is the same as GitHub's (without icons), so its stylesheets apply:
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::callouts::add(md);
+//!
+//! let html = md.parse("> [!tip]\n> *Text*").render();
+//! assert_eq!(html, "<div class=\"markdown-alert markdown-alert-tip\">\n\
+//!     <p class=\"markdown-alert-title\">Tip</p>\n<p><em>Text</em></p>\n</div>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::block::builtin::BlockParserRule;
+use crate::parser::core::CoreRule;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::InlineRoot;
+use crate::plugins::cmark::block::blockquote::Blockquote;
+use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static MARKER_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\[!([A-Za-z]+)\][ \t]*$"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalloutKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl CalloutKind {
+    /// Name used in classes, e.g. `note`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Tip => "tip",
+            Self::Important => "important",
+            Self::Warning => "warning",
+            Self::Caution => "caution",
+        }
+    }
+
+    /// Parse kind as written in the marker (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "note" => Some(Self::Note),
+            "tip" => Some(Self::Tip),
+            "important" => Some(Self::Important),
+            "warning" => Some(Self::Warning),
+            "caution" => Some(Self::Caution),
+            _ => None,
+        }
+    }
+
+    /// Default title, e.g. `Note`.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Callout block replacing a blockquote, its children are contents.
+pub struct Callout {
+    pub kind: CalloutKind,
+    /// Title, [CalloutKind::title] by default (applications may translate it).
+    pub title: String,
+}
+
+impl NodeValue for Callout {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), format!("markdown-alert markdown-alert-{}", self.kind.name())));
+
+        fmt.cr();
+        fmt.open("div", &attrs);
+        fmt.cr();
+        fmt.open("p", &[("class".into(), "markdown-alert-title".into())]);
+        fmt.text(&self.title);
+        fmt.close("p");
+        fmt.cr();
+        fmt.contents(&node.children);
+        fmt.cr();
+        fmt.close("div");
+        fmt.cr();
+    }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.cr();
+                fmt.text(&self.title);
+                fmt.cr();
+                fmt.contents(&node.children);
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "kind": self.kind.name(), "title": self.title })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            kind: CalloutKind::from_name(&field::<String>(data, "kind")?)?,
+            title: field(data, "title")?,
+        })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    // markers are read before inline parsing, so `[!NOTE]` can't become a link
+    if !md.has_rule::<CalloutRule>() {
+        md.add_rule::<CalloutRule>()
+            .after::<BlockParserRule>()
+            .before::<InlineParserRule>();
+    }
+}
+
+// removes the marker line from the first paragraph of a blockquote, returns its kind
+fn take_marker(blockquote: &mut Node) -> Option<CalloutKind> {
+    let paragraph = blockquote.children.first_mut().filter(|child| child.is::<Paragraph>())?;
+    let inline = paragraph.children.first_mut()?.cast_mut::<InlineRoot>()?;
+
+    let line_end = inline.content.find('\n').unwrap_or(inline.content.len());
+    let captures = MARKER_RE.captures(&inline.content[..line_end])?;
+    let kind = CalloutKind::from_name(&captures[1])?;
+
+    let cut = (line_end + 1).min(inline.content.len());
+    if cut == inline.content.len() {
+        blockquote.children.remove(0);
+        return Some(kind);
+    }
+
+    inline.content.drain(..cut);
+    inline.mapping = inline.mapping.iter()
+        .filter(|(offset, _)| *offset >= cut)
+        .map(|&(offset, source)| (offset - cut, source))
+        .collect();
+
+    let start = inline.mapping.first().map(|&(_, source)| source);
+    if let (Some(start), Some(srcmap)) = (start, paragraph.srcmap) {
+        paragraph.srcmap = Some(SourcePos::new(start, srcmap.get_byte_offsets().1));
+    }
+    Some(kind)
+}
+
+#[doc(hidden)]
+pub struct CalloutRule;
+impl CoreRule for CalloutRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        root.walk_mut(|node, _| {
+            if !node.is::<Blockquote>() { return; }
+            let Some(kind) = take_marker(node) else { return; };
+            node.replace(Callout { kind, title: kind.title().to_owned() });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Callout, CalloutKind};
+
+    fn parse(src: &str) -> crate::Node {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src)
+    }
+
+    #[test]
+    fn kinds() {
+        let ast = parse("> [!NOTE]\n> a\n\n> [!Caution]  \n> b\n\n> [!TODO]\n> c");
+        assert_eq!(ast.children[0].cast::<Callout>().unwrap().kind, CalloutKind::Note);
+        assert_eq!(ast.children[1].cast::<Callout>().unwrap().kind, CalloutKind::Caution);
+        assert!(ast.children[2].is::<crate::plugins::cmark::block::blockquote::Blockquote>());
+        assert_eq!(ast.render_text(), "Note\na\nCaution\nb\n[!TODO]\nc\n");
+    }
+
+    #[test]
+    fn not_callouts() {
+        assert_eq!(parse("> [!NOTE] text").render(), "<blockquote>\n<p>[!NOTE] text</p>\n</blockquote>\n");
+        assert_eq!(parse("> text\n> [!NOTE]").render(), "<blockquote>\n<p>text\n[!NOTE]</p>\n</blockquote>\n");
+        assert_eq!(parse("[!NOTE]\n> text").render(), "<p>[!NOTE]</p>\n<blockquote>\n<p>text</p>\n</blockquote>\n");
+    }
+
+    #[test]
+    fn contents() {
+        let src = "- > [!important]\n  >\n  > # a\n  > > [!warning]\n  > > **b**\n  > > c";
+        let ast = parse(src);
+        assert_eq!(ast.render(), "<ul>\n<li>\n<div class=\"markdown-alert markdown-alert-important\">\n\
+            <p class=\"markdown-alert-title\">Important</p>\n<h1>a</h1>\n\
+            <div class=\"markdown-alert markdown-alert-warning\">\n<p class=\"markdown-alert-title\">Warning</p>\n\
+            <p><strong>b</strong>\nc</p>\n</div>\n</div>\n</li>\n</ul>\n");
+
+        let paragraph = &ast.children[0].children[0].children[0].children[1].children[0];
+        let (start, end) = paragraph.srcmap.unwrap().get_byte_offsets();
+        assert_eq!(&src[start..end], "**b**\n  > > c");
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(parse("> [!NOTE]").render(), "<div class=\"markdown-alert markdown-alert-note\">\n\
+            <p class=\"markdown-alert-title\">Note</p>\n</div>\n");
+    }
+}
@@ -0,0 +1,244 @@
+//! Conditional content, so one source can produce multiple documentation variants.
+//!
+//! ```text
+//! ::: if edition="enterprise"
+//! Contact your account manager.
+//! :::
+//!
+//! ::: unless beta
+//! This feature is stable.
+//! :::
+//! ```
+//!
+//! Condition is either a flag name (true if flag is set to any value), or flag name
+//! with a value (true if flag is set to that value). `unless` negates the condition.
+//! Containers are [fenced blocks](crate::parser::block::BlockParser::add_fenced),
+//! so they can be nested with other containers using `:::` fences.
+//!
+//! Contents of all containers are kept in the AST, the ones with false conditions
+//! are [hidden](Node::set_hidden) when parsing with flags from [set_flags]. Use
+//! [apply_flags] to re-evaluate conditions on already parsed document.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::conditional::add(md);
+//!
+//! let src = "::: if edition=\"enterprise\"\n*a*\n:::\n\n::: unless edition\nb\n:::";
+//! let mut ast = md.parse(src);
+//! assert_eq!(ast.render(), "<p>b</p>\n");
+//!
+//! let flags = HashMap::from([("edition".into(), "enterprise".into())]);
+//! markdown_it::plugins::extra::conditional::apply_flags(&mut ast, &flags);
+//! assert_eq!(ast.render(), "<p><em>a</em></p>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::options::ParseOptions;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static CONDITION_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(if|unless)\s+([A-Za-z0-9_.-]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"']+)))?$"#).unwrap()
+});
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Condition of a [Conditional] container.
+pub struct Condition {
+    /// Flag name.
+    pub flag: String,
+    /// Expected flag value, or `None` if flag only needs to be set.
+    pub value: Option<String>,
+    /// True for `unless` condition.
+    pub negate: bool,
+}
+
+impl Condition {
+    // parses info string of the opening fence, e.g. `if a="b"`
+    fn parse(info: &str) -> Option<Self> {
+        let captures = CONDITION_RE.captures(info)?;
+        let value = captures.get(3).or(captures.get(4)).or(captures.get(5));
+        Some(Self {
+            flag: captures[2].to_owned(),
+            value: value.map(|m| m.as_str().to_owned()),
+            negate: &captures[1] == "unless",
+        })
+    }
+
+    /// Evaluate condition against a set of flags.
+    pub fn eval(&self, flags: &HashMap<String, String>) -> bool {
+        let result = match (flags.get(&self.flag), &self.value) {
+            (Some(actual), Some(expected)) => actual == expected,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        result != self.negate
+    }
+}
+
+#[derive(Debug)]
+/// Container with content that is only rendered if condition is true.
+pub struct Conditional {
+    pub condition: Condition,
+}
+
+impl NodeValue for Conditional {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.contents(&node.children);
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        let Condition { flag, value, negate } = &self.condition;
+        serde_json::json!({ "flag": flag, "value": value, "negate": negate })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { condition: Condition {
+            flag: field(data, "flag")?,
+            value: field(data, "value")?,
+            negate: field(data, "negate")?,
+        } })
+    }
+}
+
+#[derive(Debug, Default)]
+struct ConditionalFlags(HashMap<String, String>);
+impl MarkdownItExt for ConditionalFlags {}
+
+pub fn add(md: &mut MarkdownIt) {
+    if !md.has_rule::<ConditionalRule>() {
+        md.block.add_fenced(":::", |info| {
+            Condition::parse(info).map(|condition| Conditional { condition })
+        });
+
+        md.add_rule::<ConditionalRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+/// Set flags used to evaluate conditions when parsing, flags passed in
+/// [ParseOptions::flags] override them.
+pub fn set_flags(md: &mut MarkdownIt, flags: HashMap<String, String>) {
+    md.ext.insert(ConditionalFlags(flags));
+}
+
+/// Evaluate all conditions in the document against a set of flags,
+/// hiding or showing contents of each [Conditional] container.
+pub fn apply_flags(root: &mut Node, flags: &HashMap<String, String>) {
+    root.walk_mut(|node, _| {
+        if let Some(data) = node.cast::<Conditional>() {
+            let hidden = !data.condition.eval(flags);
+            node.set_hidden(hidden);
+        }
+    });
+}
+
+#[doc(hidden)]
+pub struct ConditionalRule;
+impl CoreRule for ConditionalRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let options = ParseOptions::of_root(root);
+        let mut flags = md.ext.get::<ConditionalFlags>().map(|f| f.0.clone()).unwrap_or_default();
+        flags.extend(options.flags.iter().map(|(k, v)| (k.clone(), v.clone())));
+        apply_flags(root, &flags);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Condition;
+    use std::collections::HashMap;
+
+    fn run(src: &str, flags: &[(&str, &str)]) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        super::set_flags(md, flags.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect());
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn parse() {
+        let cond = |flag: &str, value: Option<&str>, negate| Some(Condition {
+            flag: flag.into(), value: value.map(Into::into), negate
+        });
+        assert_eq!(Condition::parse("if a"), cond("a", None, false));
+        assert_eq!(Condition::parse("unless a.b = 'x y'"), cond("a.b", Some("x y"), true));
+        assert_eq!(Condition::parse("if a=\"\""), cond("a", Some(""), false));
+        assert_eq!(Condition::parse("if a=b"), cond("a", Some("b"), false));
+        assert_eq!(Condition::parse("when a"), None);
+        assert_eq!(Condition::parse("if a b"), None);
+    }
+
+    #[test]
+    fn parse_options_flags() {
+        use crate::parser::options::ParseOptions;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        super::set_flags(md, HashMap::from([("a".to_owned(), "1".to_owned())]));
+
+        let src = "::: if a=\"2\"\nx\n:::\n\n::: if b\ny\n:::";
+        assert_eq!(md.parse(src).render(), "");
+
+        let mut options = ParseOptions::new();
+        options.flags.insert("a".into(), "2".into());
+        options.flags.insert("b".into(), "".into());
+        assert_eq!(md.parse_with_options(src, options).render(), "<p>x</p>\n<p>y</p>\n");
+    }
+
+    #[test]
+    fn eval() {
+        let flags = HashMap::from([("a".to_owned(), "1".to_owned())]);
+        let cond = |flag: &str, value: Option<&str>, negate| Condition {
+            flag: flag.into(), value: value.map(Into::into), negate
+        };
+        assert!(cond("a", None, false).eval(&flags));
+        assert!(cond("a", Some("1"), false).eval(&flags));
+        assert!(!cond("a", Some("2"), false).eval(&flags));
+        assert!(!cond("b", None, false).eval(&flags));
+        assert!(cond("b", None, true).eval(&flags));
+        assert!(!cond("a", Some("1"), true).eval(&flags));
+    }
+
+    #[test]
+    fn nested() {
+        let src = "::: if a\na\n\n::: if b\nb\n:::\n\n- c\n:::\nd";
+        assert_eq!(run(src, &[]), "<p>d</p>\n");
+        assert_eq!(run(src, &[("a", "")]), "<p>a</p>\n<ul>\n<li>c</li>\n</ul>\n<p>d</p>\n");
+        assert_eq!(run(src, &[("a", ""), ("b", "")]), "<p>a</p>\n<p>b</p>\n<ul>\n<li>c</li>\n</ul>\n<p>d</p>\n");
+    }
+
+    #[cfg(feature = "details")]
+    #[test]
+    fn nested_containers() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::details::add(md);
+        super::add(md);
+
+        // closing fence of another container doesn't close the conditional block
+        let src = "::: if a\n::: details\nb\n:::\n:::\nc";
+        assert_eq!(md.parse(src).render(), "<p>c</p>\n");
+        // and fences in code blocks don't count
+        assert_eq!(run("::: if a\n```\n:::\n```\n:::\nb", &[]), "<p>b</p>\n");
+    }
+
+    #[test]
+    fn not_closed() {
+        assert_eq!(run("::: if a\nb", &[]), "<p>::: if a\nb</p>\n");
+        assert_eq!(run(":: if a\n::", &[]), "<p>:: if a\n::</p>\n");
+        assert_eq!(run("    ::: if a\n    :::", &[]), "<pre><code>::: if a\n:::\n</code></pre>\n");
+    }
+}
@@ -0,0 +1,265 @@
+//! [Critic Markup](https://github.com/CriticMarkup/CriticMarkup-toolkit) for
+//! editorial review: `{++insertion++}`, `{--deletion--}`, `{~~old~>new~~}`,
+//! `{==highlight==}` and `{>>comment<<}`.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::critic;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! critic::add(md);
+//!
+//! let src = "Hello {--big --}{++*small*++} {~~word~>world~~}{>>typo<<}";
+//! let mut ast = md.parse(src);
+//! assert_eq!(ast.render(), "<p>Hello <del>big </del><ins><em>small</em></ins> \
+//!     <del>word</del><ins>world</ins><span class=\"critic comment\">typo</span></p>\n");
+//!
+//! critic::accept_all(&mut ast);
+//! assert_eq!(ast.render(), "<p>Hello <em>small</em> world</p>\n");
+//!
+//! let mut ast = md.parse(src);
+//! critic::reject_all(&mut ast);
+//! assert_eq!(ast.render(), "<p>Hello big  word</p>\n");
+//! ```
+use std::collections::HashMap;
+
+use crate::parser::extset::InlineRootExt;
+use crate::parser::inline::{InlineRule, InlineState};
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+
+#[derive(Debug)]
+/// Text added by an editor, `{++text++}`.
+pub struct CriticInsertion;
+
+impl NodeValue for CriticInsertion {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.open("ins", &node.attrs);
+        fmt.contents(&node.children);
+        fmt.close("ins");
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+#[derive(Debug)]
+/// Text removed by an editor, `{--text--}`.
+pub struct CriticDeletion;
+
+impl NodeValue for CriticDeletion {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.open("del", &node.attrs);
+        fmt.contents(&node.children);
+        fmt.close("del");
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+#[derive(Debug)]
+/// Text replaced by an editor, `{~~old~>new~~}`, its children are
+/// [CriticDeletion] and [CriticInsertion].
+pub struct CriticSubstitution;
+
+impl NodeValue for CriticSubstitution {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.contents(&node.children);
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+#[derive(Debug)]
+/// Text highlighted by an editor, `{==text==}`.
+pub struct CriticHighlight;
+
+impl NodeValue for CriticHighlight {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.open("mark", &node.attrs);
+        fmt.contents(&node.children);
+        fmt.close("mark");
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+#[derive(Debug)]
+/// Comment of an editor, `{>>text<<}`.
+pub struct CriticComment;
+
+impl NodeValue for CriticComment {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), "critic comment".into()));
+        fmt.open("span", &attrs);
+        fmt.contents(&node.children);
+        fmt.close("span");
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    if !md.inline.has_rule::<CriticScanner>() {
+        // before other rules starting with `{` (e.g. ruby)
+        md.inline.add_rule::<CriticScanner>().before_all();
+    }
+}
+
+/// Apply all suggested changes: keep insertions and new text of substitutions,
+/// drop deletions and comments, remove highlighting.
+pub fn accept_all(root: &mut Node) {
+    resolve(root, true);
+}
+
+/// Discard all suggested changes: keep deletions and old text of substitutions,
+/// drop insertions and comments, remove highlighting.
+pub fn reject_all(root: &mut Node) {
+    resolve(root, false);
+}
+
+fn resolve(root: &mut Node, accept: bool) {
+    root.walk_mut(|node, _| {
+        if node.is::<CriticSubstitution>() {
+            let kept = std::mem::take(&mut node.children).into_iter()
+                .find(|child| if accept { child.is::<CriticInsertion>() } else { child.is::<CriticDeletion>() })
+                .map(|mut child| std::mem::take(&mut child.children))
+                .unwrap_or_default();
+            node.replace_with(kept);
+        } else if node.is::<CriticInsertion>() || node.is::<CriticDeletion>() {
+            if node.is::<CriticInsertion>() == accept {
+                node.unwrap_children();
+            } else {
+                node.replace_with(Vec::new());
+            }
+        } else if node.is::<CriticHighlight>() {
+            node.unwrap_children();
+        } else if node.is::<CriticComment>() {
+            node.replace_with(Vec::new());
+        }
+    });
+}
+
+// tokenize `state.src[start..end]` into children of a new node
+fn parse_range(state: &mut InlineState, start: usize, end: usize, node: Node) -> Node {
+    let old_node = std::mem::replace(&mut state.node, node);
+    let (old_pos, old_pos_max) = (state.pos, state.pos_max);
+
+    state.level += 1;
+    state.pos = start;
+    state.pos_max = end;
+    state.md.inline.tokenize(state);
+    state.pos = old_pos;
+    state.pos_max = old_pos_max;
+    state.level -= 1;
+
+    std::mem::replace(&mut state.node, old_node)
+}
+
+#[derive(Debug, Default)]
+// closing markup -> range of the source without it
+struct CriticScanCache(HashMap<&'static str, (usize, usize)>);
+impl InlineRootExt for CriticScanCache {}
+
+// returns length of contents
+fn find_closing(state: &mut InlineState, start: usize, closing: &'static str) -> Option<usize> {
+    let cache = state.inline_ext.get_or_insert_default::<CriticScanCache>();
+    if let Some(&(from, to)) = cache.0.get(closing) {
+        if from <= start && state.pos_max <= to { return None; }
+    }
+
+    let found = state.src[start..state.pos_max].find(closing);
+    if found.is_none() {
+        let cache = state.inline_ext.get_or_insert_default::<CriticScanCache>();
+        cache.0.insert(closing, (start, state.pos_max));
+    }
+    found
+}
+
+#[doc(hidden)]
+pub struct CriticScanner;
+impl InlineRule for CriticScanner {
+    const MARKER: char = '{';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        if state.level >= state.md.max_nesting { return None; }
+
+        let (opening, closing) = match state.src[state.pos..state.pos_max].get(..3)? {
+            "{++" => ("{++", "++}"),
+            "{--" => ("{--", "--}"),
+            "{~~" => ("{~~", "~~}"),
+            "{==" => ("{==", "==}"),
+            "{>>" => ("{>>", "<<}"),
+            _ => return None,
+        };
+
+        let start = state.pos + 3;
+        let content_len = find_closing(state, start, closing)?;
+        if content_len == 0 { return None; }
+        let end = start + content_len;
+
+        let node = match opening {
+            "{++" => parse_range(state, start, end, Node::new(CriticInsertion)),
+            "{--" => parse_range(state, start, end, Node::new(CriticDeletion)),
+            "{==" => parse_range(state, start, end, Node::new(CriticHighlight)),
+            "{>>" => parse_range(state, start, end, Node::new(CriticComment)),
+            _ => {
+                let separator = start + state.src[start..end].find("~>")?;
+                let mut node = Node::new(CriticSubstitution);
+                node.children.push(parse_range(state, start, separator, Node::new(CriticDeletion)));
+                node.children.push(parse_range(state, separator + 2, end, Node::new(CriticInsertion)));
+                node
+            }
+        };
+
+        Some((node, content_len + 6))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn markup() {
+        assert_eq!(run("{==**a**==}{>>b<<}"), "<p><mark><strong>a</strong></mark><span class=\"critic comment\">b</span></p>\n");
+        assert_eq!(run("{~~~>b~~}"), "<p><del></del><ins>b</ins></p>\n");
+        // not critic markup
+        assert_eq!(run("{++++} {++a} {~~a~~} {<<a>>}"), "<p>{++++} {++a} {~~a~~} {&lt;&lt;a&gt;&gt;}</p>\n");
+        assert_eq!(run("{++a {++b ++}"), "<p><ins>a {++b </ins></p>\n");
+    }
+
+    #[test]
+    fn resolve() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+
+        let mut ast = md.parse("a{++b {--c--}++}{==d==}");
+        super::accept_all(&mut ast);
+        assert_eq!(ast.render(), "<p>ab d</p>\n");
+
+        let mut ast = md.parse("a{++b {--c--}++}{==d==}");
+        super::reject_all(&mut ast);
+        assert_eq!(ast.render(), "<p>ad</p>\n");
+    }
+}
@@ -0,0 +1,298 @@
+//! Localized dates, written as `{{date 2024-05-01}}` or `{{date 2024-05-01T14:30+02:00}}`.
+//!
+//! Dates are rendered as `<time>` elements with text produced by a formatter
+//! callback (see [set_formatter]) at render time, so the same document can show dates
+//! in the reader's locale or timezone. [format_dates] changes the formatter of already
+//! parsed document, e.g. to render a cached AST for a different locale.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::dates::add(md);
+//!
+//! let mut ast = md.parse("Released on {{date 2024-05-01}}.");
+//! assert_eq!(ast.render(), "<p>Released on <time datetime=\"2024-05-01\">May 1, 2024</time>.</p>\n");
+//!
+//! markdown_it::plugins::extra::dates::format_dates(&mut ast, |date| {
+//!     format!("{}.{}.{}", date.day, date.month, date.year)
+//! });
+//! assert_eq!(ast.render(), "<p>Released on <time datetime=\"2024-05-01\">1.5.2024</time>.</p>\n");
+//! ```
+use derivative::Derivative;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::{InlineRule, InlineState};
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static DATE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\{\{\s*date\s+([0-9T:+Z-]+)\s*\}\}"#).unwrap()
+});
+
+static ISO_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(\d{4})-(\d{2})-(\d{2})(?:T(\d{2}):(\d{2})(?:(Z)|([+-])(\d{2}):(\d{2}))?)?$"#).unwrap()
+});
+
+const MONTHS : [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Date with optional time and UTC offset, as written in the source.
+pub struct DateValue {
+    pub year: u16,
+    /// Month (1-12).
+    pub month: u8,
+    /// Day of month (1-31).
+    pub day: u8,
+    /// Hours and minutes.
+    pub time: Option<(u8, u8)>,
+    /// Offset from UTC in minutes, `None` for local time.
+    pub offset: Option<i16>,
+}
+
+impl DateValue {
+    /// Parse ISO 8601 date (`YYYY-MM-DD`), optionally followed by time (`THH:MM`)
+    /// and UTC offset (`Z` or `+HH:MM`).
+    pub fn parse(str: &str) -> Option<Self> {
+        let captures = ISO_RE.captures(str)?;
+        let num = |idx| captures.get(idx).map(|m| m.as_str().parse::<u16>().unwrap());
+
+        let year = num(1)?;
+        let month = num(2)? as u8;
+        let day = num(3)? as u8;
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) { return None; }
+
+        let time = match (num(4), num(5)) {
+            (Some(h), Some(m)) if h < 24 && m < 60 => Some((h as u8, m as u8)),
+            (Some(_), Some(_)) => return None,
+            _ => None,
+        };
+
+        let offset = if captures.get(6).is_some() {
+            Some(0)
+        } else if let (Some(h), Some(m)) = (num(8), num(9)) {
+            if h > 23 || m > 59 { return None; }
+            let minutes = (h * 60 + m) as i16;
+            Some(if &captures[7] == "-" { -minutes } else { minutes })
+        } else {
+            None
+        };
+
+        Some(Self { year, month, day, time, offset })
+    }
+}
+
+impl Display for DateValue {
+    /// Format date back to ISO 8601.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)?;
+        if let Some((h, m)) = self.time {
+            write!(f, "T{:02}:{:02}", h, m)?;
+            match self.offset {
+                Some(0) => f.write_str("Z")?,
+                Some(offset) => {
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    let offset = offset.unsigned_abs();
+                    write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)?;
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Default formatter, returns english date like `May 1, 2024` or `May 1, 2024, 14:30 UTC+02:00`.
+pub fn format_date_en(date: &DateValue) -> String {
+    let mut result = format!("{} {}, {}", MONTHS[date.month as usize - 1], date.day, date.year);
+    if let Some((h, m)) = date.time {
+        result.push_str(&format!(", {:02}:{:02}", h, m));
+        match date.offset {
+            Some(0) => result.push_str(" UTC"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                result.push_str(&format!(" UTC{}{:02}:{:02}", sign, offset / 60, offset % 60));
+            }
+            None => {}
+        }
+    }
+    result
+}
+
+/// Callback that converts a date into text.
+pub type DateFormatter = Arc<dyn Fn(&DateValue) -> String + Send + Sync>;
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct DateFormatterExt(#[derivative(Debug = "ignore")] DateFormatter);
+impl MarkdownItExt for DateFormatterExt {}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+/// Date formatted when it's rendered.
+pub struct FormattedDate {
+    pub date: DateValue,
+    /// Formatter used to render the date, [format_date_en] if `None`.
+    #[derivative(Debug = "ignore")]
+    pub formatter: Option<DateFormatter>,
+}
+
+impl FormattedDate {
+    /// Text of the date produced by its formatter.
+    pub fn text(&self) -> String {
+        match &self.formatter {
+            Some(formatter) => formatter(&self.date),
+            None => format_date_en(&self.date),
+        }
+    }
+}
+
+impl NodeValue for FormattedDate {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("datetime".into(), self.date.to_string()));
+        fmt.open("time", &attrs);
+        fmt.text(&self.text());
+        fmt.close("time");
+    }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => fmt.text(&self.text()),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "date": self.date.to_string(), "text": self.text() })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        // formatter can't be restored, use format_dates to set it
+        let date = DateValue::parse(&field::<String>(data, "date")?)?;
+        Some(Self { date, formatter: None })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.inline.add_rule::<DateScanner>();
+}
+
+/// Set formatter of dates in parsed documents, [format_date_en] is used by default.
+pub fn set_formatter(md: &mut MarkdownIt, formatter: impl Fn(&DateValue) -> String + Send + Sync + 'static) {
+    md.ext.insert(DateFormatterExt(Arc::new(formatter)));
+}
+
+/// Render all dates in the document using another formatter.
+pub fn format_dates(root: &mut Node, formatter: impl Fn(&DateValue) -> String + Send + Sync + 'static) {
+    let formatter : DateFormatter = Arc::new(formatter);
+    root.walk_mut(|node, _| {
+        if let Some(data) = node.cast_mut::<FormattedDate>() {
+            data.formatter = Some(formatter.clone());
+        }
+    });
+}
+
+#[doc(hidden)]
+pub struct DateScanner;
+impl InlineRule for DateScanner {
+    const MARKER: char = '{';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        let captures = DATE_RE.captures(&state.src[state.pos..state.pos_max])?;
+        let date = DateValue::parse(&captures[1])?;
+
+        let formatter = state.md.ext.get::<DateFormatterExt>().map(|formatter| formatter.0.clone());
+        Some((Node::new(FormattedDate { date, formatter }), captures[0].len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DateValue;
+
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn parse() {
+        let date = |s| DateValue::parse(s).map(|d| d.to_string());
+        assert_eq!(date("2024-02-29"), Some("2024-02-29".into()));
+        assert_eq!(date("2024-05-01T09:05"), Some("2024-05-01T09:05".into()));
+        assert_eq!(date("2024-05-01T09:05Z"), Some("2024-05-01T09:05Z".into()));
+        assert_eq!(date("2024-05-01T09:05+00:00"), Some("2024-05-01T09:05Z".into()));
+        assert_eq!(date("2024-05-01T09:05-03:30").unwrap(), "2024-05-01T09:05-03:30");
+        assert_eq!(DateValue::parse("2024-05-01T09:05-03:30").unwrap().offset, Some(-210));
+        assert_eq!(date("2023-02-29"), None);
+        assert_eq!(date("2024-13-01"), None);
+        assert_eq!(date("2024-05-01T24:00"), None);
+        assert_eq!(date("2024-5-1"), None);
+        assert_eq!(date("2024-05-01Z"), None);
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            run("{{ date 2024-05-01T14:30+02:00 }} {{date 1900-02-29}} `{{date 2024-05-01}}`"),
+            "<p><time datetime=\"2024-05-01T14:30+02:00\">May 1, 2024, 14:30 UTC+02:00</time> {{date 1900-02-29}} <code>{{date 2024-05-01}}</code></p>\n"
+        );
+        assert_eq!(run("{{date 2024-12-31T23:59Z}}").trim(), "<p><time datetime=\"2024-12-31T23:59Z\">December 31, 2024, 23:59 UTC</time></p>");
+    }
+
+    #[test]
+    fn formatter() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        super::set_formatter(md, |date| format!("{:02}/{:02}/{}", date.month, date.day, date.year));
+        let ast = md.parse("{{date 2024-05-01}}");
+        assert_eq!(ast.render(), "<p><time datetime=\"2024-05-01\">05/01/2024</time></p>\n");
+        assert_eq!(ast.render_text(), "05/01/2024\n");
+    }
+
+    #[test]
+    fn render_time() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        super::set_formatter(md, move |date| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            date.year.to_string()
+        });
+
+        let ast = md.parse("{{date 2024-05-01}}");
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+        assert_eq!(ast.render(), "<p><time datetime=\"2024-05-01\">2024</time></p>\n");
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,123 @@
+//! Collapsible blocks rendered as `<details>` with a `<summary>`, for FAQ-style content.
+//!
+//! ```text
+//! ::: details How do I reset my password?
+//! Go to *Settings*.
+//! :::
+//! ```
+//!
+//! Summary is optional (`Details` is used if it's missing) and can be quoted.
+//! Collapsible `???` blocks are MkDocs syntax, see [admonition](super::admonition).
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::details::add(md);
+//!
+//! let html = md.parse("::: details \"Why?\"\n*Because.*\n:::").render();
+//! assert_eq!(html, "<details>\n<summary>Why?</summary>\n<p><em>Because.</em></p>\n</details>\n");
+//! ```
+use crate::parser::extset::MarkdownItExt;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Options for [add_with_options], used by the first call that adds the plugin.
+pub struct DetailsOptions {
+    /// Render blocks expanded, disabled by default.
+    pub open: bool,
+}
+
+impl MarkdownItExt for DetailsOptions {}
+
+#[derive(Debug)]
+/// Collapsible block, its children are contents shown when expanded.
+pub struct Details {
+    pub summary: String,
+    pub open: bool,
+}
+
+impl NodeValue for Details {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        if self.open {
+            attrs.push(("open".into(), String::new()));
+        }
+
+        fmt.cr();
+        fmt.open("details", &attrs);
+        fmt.cr();
+        fmt.open("summary", &[]);
+        fmt.text(&self.summary);
+        fmt.close("summary");
+        fmt.cr();
+        fmt.contents(&node.children);
+        fmt.cr();
+        fmt.close("details");
+        fmt.cr();
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "summary": self.summary, "open": self.open })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { summary: field(data, "summary")?, open: field(data, "open")? })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, DetailsOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: DetailsOptions) {
+    // fenced rules can't be replaced, so only the first call adds them
+    if md.ext.get::<DetailsOptions>().is_some() { return; }
+    md.ext.insert(options);
+
+    md.block.add_fenced(":::", move |info| {
+        let summary = info.strip_prefix("details")?;
+        if !summary.is_empty() && !summary.starts_with(char::is_whitespace) { return None; }
+        Some(make_details(summary, options.open))
+    });
+}
+
+fn make_details(summary: &str, open: bool) -> Details {
+    let summary = summary.trim();
+    let summary = summary.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(summary);
+    let summary = if summary.is_empty() { "Details" } else { summary };
+    Details { summary: summary.to_owned(), open }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DetailsOptions;
+
+    fn run(src: &str, options: DetailsOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn details() {
+        let options = DetailsOptions::default();
+        assert_eq!(run("::: details <a>\n- b\n:::", options),
+            "<details>\n<summary>&lt;a&gt;</summary>\n<ul>\n<li>b</li>\n</ul>\n</details>\n");
+        assert_eq!(run("::: details\n::: details b\nc\n:::\n:::", options),
+            "<details>\n<summary>Details</summary>\n<details>\n<summary>b</summary>\n<p>c</p>\n</details>\n</details>\n");
+        // not details, `???` is left to admonitions
+        assert_eq!(run("::: detailsx\n:::\n\n??? a\n???", options), "<p>::: detailsx\n:::</p>\n<p>??? a\n???</p>\n");
+    }
+
+    #[test]
+    fn options() {
+        let options = DetailsOptions { open: true };
+        assert_eq!(run("::: details a\n:::", options),
+            "<details open=\"\">\n<summary>a</summary>\n</details>\n");
+    }
+}
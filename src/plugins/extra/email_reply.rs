@@ -0,0 +1,358 @@
+//! Rendering for email replies, for mail clients that let users write in markdown.
+//!
+//!  - blockquotes become `>`-prefixed lines (plain text), or `<div>` elements
+//!    with `quote` and `quote-level-N` classes (html), so mail clients can style
+//!    each reply level
+//!  - links are replaced with their text followed by a reference (`[1]`),
+//!    urls are listed at the end of the message (if [EmailOptions::footnote_links] is set)
+//!  - images are replaced with `[image: name]` and listed at the end of the message as attachments
+//!    (if [EmailOptions::attachments] callback returns a name for them)
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::email_reply::{self, EmailOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("> > Lunch?\n>\n> See [menu](https://example.org)\n\nSure ![cat](/img/cat.png)");
+//! let options = EmailOptions {
+//!     footnote_links: true,
+//!     attachments: Some(Box::new(|url| url.rsplit('/').next().map(String::from))),
+//! };
+//!
+//! let reply = email_reply::render_text(&ast, &options);
+//! assert_eq!(reply.body, "\
+//! >> Lunch?
+//! >
+//! > See menu [1]
+//!
+//! Sure [image: cat.png]
+//!
+//! [1]: https://example.org
+//!
+//! Attachments:
+//! - cat.png
+//! ");
+//! assert_eq!(reply.attachments[0].url, "/img/cat.png");
+//! ```
+use derivative::Derivative;
+
+use crate::parser::core::Root;
+use crate::parser::extset::RenderExtSet;
+use crate::parser::renderer::{HTMLRenderer, RenderOptions};
+use crate::plugins::cmark::block::blockquote::Blockquote;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::cmark::inline::link::Link;
+use crate::{Node, Renderer};
+
+/// Callback that returns attachment name for an image url,
+/// or `None` to keep the image in the message.
+pub type AttachmentFn = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+#[derive(Derivative, Default)]
+#[derivative(Debug)]
+/// Options for [render_text] and [render_html], all of them are disabled by default.
+pub struct EmailOptions {
+    /// Replace links with references listed at the end of the message.
+    pub footnote_links: bool,
+    /// Callback used to turn images into attachments.
+    #[derivative(Debug = "ignore")]
+    pub attachments: Option<AttachmentFn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Image turned into an attachment.
+pub struct Attachment {
+    pub url: String,
+    /// Name returned by [EmailOptions::attachments] callback.
+    pub name: String,
+    /// Alt text of the image.
+    pub alt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Result of [render_text] and [render_html].
+pub struct EmailReply {
+    /// Message body, including the list of links and attachments.
+    pub body: String,
+    /// Urls of footnoted links, in order of their references.
+    pub links: Vec<String>,
+    /// Images turned into attachments, in order of appearance.
+    pub attachments: Vec<Attachment>,
+}
+
+/// Render document as a plain text email.
+pub fn render_text(node: &Node, options: &EmailOptions) -> EmailReply {
+    let mut fmt = EmailRenderer::new(options, None);
+    fmt.render(node);
+    fmt.finish()
+}
+
+/// Render document as an html email.
+pub fn render_html(node: &Node, options: &EmailOptions) -> EmailReply {
+    let mut fmt = EmailRenderer::new(options, Some(HTMLRenderer::new(&RenderOptions::default())));
+    fmt.render(node);
+    fmt.finish()
+}
+
+struct EmailRenderer<'a> {
+    options: &'a EmailOptions,
+    // html output is delegated to the default renderer, text output is written here
+    html: Option<HTMLRenderer>,
+    text: String,
+    ext: RenderExtSet,
+    quote_level: usize,
+    // children of the root and blockquotes are separated by empty lines in text mode
+    separate_blocks: bool,
+    links: Vec<String>,
+    attachments: Vec<Attachment>,
+}
+
+impl<'a> EmailRenderer<'a> {
+    fn new(options: &'a EmailOptions, html: Option<HTMLRenderer>) -> Self {
+        Self {
+            options,
+            html,
+            text: String::new(),
+            ext: RenderExtSet::new(),
+            quote_level: 0,
+            separate_blocks: false,
+            links: Vec::new(),
+            attachments: Vec::new(),
+        }
+    }
+
+    fn render(&mut self, node: &Node) {
+        self.separate_blocks = node.is::<Root>();
+        node.render_with(self);
+    }
+
+    fn render_blockquote(&mut self, node: &Node) {
+        self.quote_level += 1;
+        if self.html.is_some() {
+            let class = format!("quote quote-level-{}", self.quote_level);
+            self.cr();
+            self.open("div", &[("class".into(), class)]);
+            self.cr();
+            self.contents(&node.children);
+            self.cr();
+            self.close("div");
+            self.cr();
+        } else {
+            self.cr();
+            self.separate_blocks = true;
+            self.contents(&node.children);
+            self.cr();
+        }
+        self.quote_level -= 1;
+    }
+
+    fn render_link(&mut self, node: &Node, url: &str) {
+        self.contents(&node.children);
+        let idx = match self.links.iter().position(|link| link == url) {
+            Some(idx) => idx,
+            None => {
+                self.links.push(url.to_owned());
+                self.links.len() - 1
+            }
+        };
+        self.text(&format!(" [{}]", idx + 1));
+    }
+
+    fn render_image(&mut self, node: &Node, image: &Image) -> bool {
+        let Some(callback) = &self.options.attachments else { return false; };
+        let Some(name) = callback(&image.url) else { return false; };
+        self.text(&format!("[image: {name}]"));
+        self.attachments.push(Attachment { url: image.url.clone(), name, alt: node.collect_text() });
+        true
+    }
+
+    // write text, prefixing each line with quote markers
+    fn write(&mut self, text: &str) {
+        for (idx, line) in text.split('\n').enumerate() {
+            if idx > 0 { self.text.push('\n'); }
+            if line.is_empty() { continue; }
+            if self.text.is_empty() || self.text.ends_with('\n') {
+                self.write_prefix(true);
+            }
+            self.text.push_str(line);
+        }
+    }
+
+    fn write_prefix(&mut self, space: bool) {
+        if self.quote_level == 0 { return; }
+        for _ in 0..self.quote_level { self.text.push('>'); }
+        if space { self.text.push(' '); }
+    }
+
+    fn blank_line(&mut self) {
+        self.cr();
+        let last_line = self.text.strip_suffix('\n').unwrap_or_default().rsplit('\n').next().unwrap_or_default();
+        if self.text.is_empty() || last_line.trim_start_matches('>').trim().is_empty() { return; }
+        self.write_prefix(false);
+        self.text.push('\n');
+    }
+
+    fn finish(mut self) -> EmailReply {
+        let links = std::mem::take(&mut self.links);
+        let attachments = std::mem::take(&mut self.attachments);
+
+        if let Some(html) = self.html.as_mut() {
+            if !links.is_empty() {
+                html.cr();
+                html.open("ol", &[("class".into(), "email-links".into())]);
+                html.cr();
+                for link in &links {
+                    html.open("li", &[]);
+                    html.open("a", &[("href".into(), link.clone())]);
+                    html.text(link);
+                    html.close("a");
+                    html.close("li");
+                    html.cr();
+                }
+                html.close("ol");
+                html.cr();
+            }
+            if !attachments.is_empty() {
+                html.cr();
+                html.open("ul", &[("class".into(), "email-attachments".into())]);
+                html.cr();
+                for attachment in &attachments {
+                    html.open("li", &[]);
+                    html.text(&attachment.name);
+                    html.close("li");
+                    html.cr();
+                }
+                html.close("ul");
+                html.cr();
+            }
+            let body = self.html.take().unwrap().into();
+            return EmailReply { body, links, attachments };
+        }
+
+        let mut body = self.text.trim_end().to_owned();
+        if !body.is_empty() { body.push('\n'); }
+        if !links.is_empty() {
+            body.push('\n');
+            for (idx, link) in links.iter().enumerate() {
+                body.push_str(&format!("[{}]: {}\n", idx + 1, link));
+            }
+        }
+        if !attachments.is_empty() {
+            body.push_str("\nAttachments:\n");
+            for attachment in &attachments {
+                body.push_str(&format!("- {}\n", attachment.name));
+            }
+        }
+        EmailReply { body, links, attachments }
+    }
+}
+
+impl Renderer for EmailRenderer<'_> {
+    fn open(&mut self, tag: &str, attrs: &[(String, String)]) {
+        if let Some(html) = &mut self.html { html.open(tag, attrs); }
+    }
+
+    fn close(&mut self, tag: &str) {
+        if let Some(html) = &mut self.html { html.close(tag); }
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(String, String)]) {
+        if let Some(html) = &mut self.html { html.self_close(tag, attrs); }
+    }
+
+    fn contents(&mut self, nodes: &[Node]) {
+        let separate = std::mem::replace(&mut self.separate_blocks, false);
+
+        for (idx, node) in nodes.iter().enumerate() {
+            if node.is_hidden() { continue; }
+            if separate && idx > 0 && self.html.is_none() { self.blank_line(); }
+
+            if node.is::<Blockquote>() {
+                self.render_blockquote(node);
+            } else if let Some(link) = node.cast::<Link>().filter(|_| self.options.footnote_links) {
+                self.render_link(node, &link.url);
+            } else if let Some(image) = node.cast::<Image>() {
+                if !self.render_image(node, image) {
+                    node.render_with(self);
+                }
+            } else {
+                node.render_with(self);
+            }
+        }
+    }
+
+    fn cr(&mut self) {
+        if let Some(html) = &mut self.html { return html.cr(); }
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(html) = &mut self.html { return html.text(text); }
+        self.write(text);
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        if let Some(html) = &mut self.html { return html.text_raw(text); }
+        self.write(text);
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        &mut self.ext
+    }
+
+    fn format(&self) -> &'static str {
+        if self.html.is_some() { "html" } else { "text" }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EmailOptions;
+
+    fn parse(src: &str) -> crate::Node {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        md.parse(src)
+    }
+
+    #[test]
+    fn text_quotes() {
+        let reply = super::render_text(&parse("> a\n> > b\n> > c\n>\n> d\n\n- e\n- f\n\ng"), &EmailOptions::default());
+        assert_eq!(reply.body, "> a\n>\n>> b\n>> c\n>\n> d\n\n- e\n- f\n\ng\n");
+    }
+
+    #[test]
+    fn text_defaults() {
+        let reply = super::render_text(&parse("[a](/a) ![b](/b)"), &EmailOptions::default());
+        assert_eq!(reply.body, "a b\n");
+        assert!(reply.links.is_empty() && reply.attachments.is_empty());
+    }
+
+    #[test]
+    fn text_links() {
+        let options = EmailOptions { footnote_links: true, ..Default::default() };
+        let reply = super::render_text(&parse("[a](/a) [*b*](/b) [c](/a) <https://x.org>"), &options);
+        assert_eq!(reply.body, "a [1] b [2] c [1] https://x.org\n\n[1]: /a\n[2]: /b\n");
+        assert_eq!(reply.links, ["/a", "/b"]);
+    }
+
+    #[test]
+    fn html() {
+        let options = EmailOptions {
+            footnote_links: true,
+            attachments: Some(Box::new(|url| url.strip_prefix("cid:").map(String::from))),
+        };
+        let reply = super::render_html(&parse("> > [a](/a)\n>\n> ![b](cid:b.png) ![c](/c.png)\n\nd"), &options);
+        assert_eq!(
+            reply.body,
+            "<div class=\"quote quote-level-1\">\n<div class=\"quote quote-level-2\">\n<p>a [1]</p>\n</div>\n\
+             <p>[image: b.png] <img src=\"/c.png\" alt=\"c\"></p>\n</div>\n<p>d</p>\n\
+             <ol class=\"email-links\">\n<li><a href=\"/a\">/a</a></li>\n</ol>\n\
+             <ul class=\"email-attachments\">\n<li>b.png</li>\n</ul>\n"
+        );
+        assert_eq!(reply.attachments[0].alt, "b");
+    }
+}
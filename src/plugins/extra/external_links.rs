@@ -0,0 +1,157 @@
+//! Add `target`, `rel` and `class` attributes to links pointing to other websites.
+//!
+//! Link is considered external if it has an absolute url (`https://...` or `//host/...`)
+//! with a host that doesn't match any of [ExternalLinksOptions::internal_hosts]
+//! (subdomains of internal hosts are internal as well).
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::external_links::ExternalLinksOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::external_links::add(md, ExternalLinksOptions {
+//!     internal_hosts: vec!["example.org".into()],
+//!     ..Default::default()
+//! });
+//!
+//! let html = md.parse("[a](https://www.example.org) [b](https://example.com)").render();
+//! assert_eq!(
+//!     html.trim(),
+//!     r#"<p><a href="https://www.example.org">a</a> <a target="_blank" rel="nofollow noopener" href="https://example.com">b</a></p>"#
+//! );
+//! ```
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::link::Link;
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone)]
+/// Options for [add].
+pub struct ExternalLinksOptions {
+    /// Hosts that are considered internal, e.g. `example.org`.
+    pub internal_hosts: Vec<String>,
+    /// Value of `target` attribute, `_blank` by default.
+    pub target: Option<String>,
+    /// Value of `rel` attribute, `nofollow noopener` by default.
+    pub rel: Option<String>,
+    /// Class added to external links, none by default.
+    pub class: Option<String>,
+}
+
+impl Default for ExternalLinksOptions {
+    fn default() -> Self {
+        Self {
+            internal_hosts: Vec::new(),
+            target: Some("_blank".into()),
+            rel: Some("nofollow noopener".into()),
+            class: None,
+        }
+    }
+}
+
+impl ExternalLinksOptions {
+    fn is_external(&self, url: &str) -> bool {
+        let parsed = mdurl::parse_url(url);
+        if !parsed.slashes { return false; }
+
+        match parsed.protocol.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            None | Some("http:") | Some("https:") => {}
+            _ => return false,
+        }
+
+        let Some(hostname) = parsed.hostname else { return false; };
+        let hostname = hostname.to_ascii_lowercase();
+
+        !self.internal_hosts.iter().any(|host| {
+            let host = host.to_ascii_lowercase();
+            hostname == host || hostname.ends_with(&format!(".{host}"))
+        })
+    }
+}
+
+impl MarkdownItExt for ExternalLinksOptions {}
+
+pub fn add(md: &mut MarkdownIt, options: ExternalLinksOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<ExternalLinksRule>() {
+        md.add_rule::<ExternalLinksRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+fn get_url(node: &Node) -> Option<&str> {
+    if let Some(link) = node.cast::<Link>() {
+        return Some(&link.url);
+    }
+    if let Some(link) = node.cast::<Autolink>() {
+        return Some(&link.url);
+    }
+    #[cfg(feature = "linkify")]
+    if let Some(link) = node.cast::<crate::plugins::extra::linkify::Linkified>() {
+        return Some(&link.url);
+    }
+    None
+}
+
+#[doc(hidden)]
+pub struct ExternalLinksRule;
+impl CoreRule for ExternalLinksRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let Some(options) = parse_options.ext_or::<ExternalLinksOptions>(md) else { return; };
+
+        root.walk_mut(|node, _| {
+            let Some(url) = get_url(node) else { return; };
+            if !options.is_external(url) { return; }
+
+            if let Some(target) = &options.target {
+                node.attrs.push(("target".into(), target.clone()));
+            }
+            if let Some(rel) = &options.rel {
+                node.attrs.push(("rel".into(), rel.clone()));
+            }
+            if let Some(class) = &options.class {
+                node.attrs.push(("class".into(), class.clone()));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExternalLinksOptions;
+
+    #[test]
+    fn is_external() {
+        let options = ExternalLinksOptions { internal_hosts: vec!["Example.org".into()], ..Default::default() };
+        assert!(options.is_external("https://example.com/"));
+        assert!(options.is_external("//example.com/"));
+        assert!(options.is_external("HTTP://notexample.org"));
+        assert!(!options.is_external("https://EXAMPLE.org/a"));
+        assert!(!options.is_external("http://a.b.example.org"));
+        assert!(!options.is_external("/a/b"));
+        assert!(!options.is_external("#a"));
+        assert!(!options.is_external("mailto:a@example.com"));
+        assert!(!options.is_external("ftp://example.com"));
+    }
+
+    #[test]
+    fn attrs() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, ExternalLinksOptions {
+            target: None,
+            rel: Some("noopener".into()),
+            class: Some("ext".into()),
+            ..Default::default()
+        });
+        assert_eq!(
+            md.parse("<https://a.org> [b](/b)").render(),
+            "<p><a rel=\"noopener\" class=\"ext\" href=\"https://a.org\">https://a.org</a> <a href=\"/b\">b</a></p>\n"
+        );
+    }
+}
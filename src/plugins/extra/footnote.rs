@@ -7,9 +7,31 @@
 //! markdown_it::plugins::extra::footnote::add(parser);
 //! let node = parser.parse("[^note]\n\n[^note]: A footnote\n");
 //! ```
+//!
+//! Footnotes can be numbered with symbols instead of numbers, and rendered
+//! as `<aside>` elements next to the paragraph that references them (see [FootnoteOptions]):
+//!
+//! ```
+//! use markdown_it::plugins::extra::footnote::{self, FootnoteNumbering, FootnoteOptions};
+//!
+//! let parser = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(parser);
+//! footnote::add(parser);
+//! footnote::set_options(parser, FootnoteOptions { numbering: FootnoteNumbering::Symbols, aside: true });
+//!
+//! let html = parser.parse("Hello[^a]\n\nWorld\n\n[^a]: Note").render();
+//! assert_eq!(html, "<p>Hello<sup class=\"footnote-ref\"><a href=\"#fn1\" id=\"fnref1\">*</a></sup></p>\n\
+//!     <aside id=\"fn1\" class=\"footnote-item\">\n<p>Note <a href=\"#fnref1\" class=\"footnote-backref\">\u{21a9}\u{FE0E}</a></p>\n</aside>\n\
+//!     <p>World</p>\n");
+//! ```
 use std::collections::HashMap;
 
-use crate::{parser::extset::RootExt, MarkdownIt};
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::CoreRule;
+use crate::parser::extset::{MarkdownItExt, NodeExt, RootExt};
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::options::ParseOptions;
+use crate::{MarkdownIt, Node};
 
 pub mod back_refs;
 pub mod collect;
@@ -26,6 +48,121 @@ pub fn add(md: &mut MarkdownIt) {
     back_refs::add(md);
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+/// How footnotes are numbered.
+pub enum FootnoteNumbering {
+    /// `[1]`, `[2]`, `[3]`, ...
+    #[default]
+    Numbers,
+    /// `*`, `†`, `‡`, `§`, `‖`, `¶`, then `**`, `††`, and so on.
+    Symbols,
+    /// Custom function that returns a marker for the footnote number (starting from 1).
+    Custom(fn (usize) -> String),
+}
+
+impl FootnoteNumbering {
+    /// Marker displayed for the footnote with the given number.
+    pub fn marker(&self, number: usize) -> String {
+        const SYMBOLS : [char; 6] = ['*', '\u{2020}', '\u{2021}', '\u{a7}', '\u{2016}', '\u{b6}'];
+
+        match self {
+            Self::Numbers => format!("[{number}]"),
+            Self::Symbols => {
+                let idx = number.saturating_sub(1);
+                SYMBOLS[idx % SYMBOLS.len()].to_string().repeat(idx / SYMBOLS.len() + 1)
+            }
+            Self::Custom(f) => f(number),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Options for footnotes, see [set_options].
+pub struct FootnoteOptions {
+    pub numbering: FootnoteNumbering,
+    /// Render each footnote as `<aside>` element right after the top-level block
+    /// where it is first referenced, instead of a list at the end of the document.
+    pub aside: bool,
+}
+
+impl MarkdownItExt for FootnoteOptions {}
+
+/// Set footnote numbering and placement.
+pub fn set_options(md: &mut MarkdownIt, options: FootnoteOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<FootnoteMarkerRule>() {
+        md.add_rule::<FootnoteMarkerRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Text displayed for a footnote reference, added by [FootnoteNumbering]
+/// (default is footnote number in brackets).
+pub struct FootnoteMarker(pub String);
+impl NodeExt for FootnoteMarker {}
+
+#[derive(Debug, Clone, Copy)]
+/// Footnote definition is rendered as `<aside>`, see [FootnoteOptions::aside].
+pub struct FootnoteAside;
+impl NodeExt for FootnoteAside {}
+
+#[doc(hidden)]
+pub struct FootnoteMarkerRule;
+impl CoreRule for FootnoteMarkerRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let numbering = ParseOptions::of_root(root).ext_or::<FootnoteOptions>(md).copied().unwrap_or_default().numbering;
+        root.walk_mut(|node, _| {
+            if let Some(reference) = node.cast::<references::FootnoteReference>() {
+                let marker = numbering.marker(reference.def_id);
+                node.ext.insert(FootnoteMarker(marker));
+            }
+        });
+    }
+}
+
+/// Remove footnote definitions (and the list containing them) from the document
+/// and return them, e.g. to render footnotes separately from the body.
+///
+/// ```
+/// let parser = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(parser);
+/// markdown_it::plugins::extra::footnote::add(parser);
+///
+/// let mut root = parser.parse("Hello[^a]\n\n[^a]: Note");
+/// let notes = markdown_it::plugins::extra::footnote::extract_definitions(&mut root);
+/// assert_eq!(notes.len(), 1);
+/// assert_eq!(notes[0].collect_text(), "Note");
+/// assert!(!root.render().contains("Note"));
+/// ```
+pub fn extract_definitions(root: &mut Node) -> Vec<Node> {
+    fn walk_recursive(node: &mut Node, result: &mut Vec<Node>) {
+        let mut idx = 0;
+        while idx < node.children.len() {
+            if node.children[idx].is::<definitions::FootnoteDefinition>() {
+                result.push(node.children.remove(idx));
+                continue;
+            }
+
+            let child = &mut node.children[idx];
+            stacker::maybe_grow(64*1024, 1024*1024, || {
+                walk_recursive(child, result);
+            });
+
+            if child.is::<collect::FootnotesContainerNode>() && child.children.is_empty() {
+                node.children.remove(idx);
+                continue;
+            }
+            idx += 1;
+        }
+    }
+
+    let mut result = Vec::new();
+    walk_recursive(root, &mut result);
+    result
+}
+
 #[derive(Debug, Default)]
 /// The set of parsed footnote definition labels,
 /// stored in the root node.
@@ -34,6 +171,7 @@ pub struct FootnoteMap {
     ref_counter: usize,
     label_to_def: HashMap<String, usize>,
     def_to_refs: HashMap<usize, Vec<usize>>,
+    def_srcmaps: HashMap<usize, SourcePos>,
 }
 impl RootExt for FootnoteMap {}
 impl FootnoteMap {
@@ -74,6 +212,11 @@ impl FootnoteMap {
             .insert(self.def_counter, vec![self.ref_counter]);
         (self.def_counter, self.ref_counter)
     }
+    /// return source position of the definition with the given ID,
+    /// if it's defined with `[^label]: ...` block
+    pub fn def_srcmap(&self, def_id: usize) -> Option<SourcePos> {
+        self.def_srcmaps.get(&def_id).copied()
+    }
     /// return the IDs of all references to the given definition ID
     pub fn referenced_by(&self, def_id: usize) -> Vec<usize> {
         match self.def_to_refs.get(&def_id) {
@@ -82,3 +225,42 @@ impl FootnoteMap {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FootnoteNumbering, FootnoteOptions};
+
+    fn run(src: &str, options: FootnoteOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        super::set_options(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn symbols() {
+        let marker = |n| FootnoteNumbering::Symbols.marker(n);
+        assert_eq!([marker(1), marker(2), marker(6), marker(7), marker(14)], ["*", "\u{2020}", "\u{b6}", "**", "\u{2020}\u{2020}\u{2020}"]);
+        assert_eq!(FootnoteNumbering::Numbers.marker(3), "[3]");
+        assert_eq!(FootnoteNumbering::Custom(|n| format!("({n})")).marker(3), "(3)");
+    }
+
+    #[test]
+    fn custom_numbering() {
+        let options = FootnoteOptions { numbering: FootnoteNumbering::Custom(|n| n.to_string()), aside: false };
+        let html = run("a[^x] b^[c]\n\n[^x]: d", options);
+        assert!(html.starts_with("<p>a<sup class=\"footnote-ref\"><a href=\"#fn1\" id=\"fnref1\">1</a></sup> b<sup class=\"footnote-ref\"><a href=\"#fn2\" id=\"fnref2\">2</a></sup></p>\n"));
+        assert!(html.contains("<ol class=\"footnotes-list\">"));
+    }
+
+    #[test]
+    fn asides() {
+        let options = FootnoteOptions { aside: true, ..Default::default() };
+        let html = run("> a[^x]\n\nb^[c] [^x]\n\n[^x]: d\n\n[^y]: unused", options);
+        assert_eq!(html, "<blockquote>\n<p>a<sup class=\"footnote-ref\"><a href=\"#fn1\" id=\"fnref1\">[1]</a></sup></p>\n</blockquote>\n\
+            <aside id=\"fn1\" class=\"footnote-item\">\n<p>d <a href=\"#fnref1\" class=\"footnote-backref\">\u{21a9}\u{FE0E}</a> <a href=\"#fnref3\" class=\"footnote-backref\">\u{21a9}\u{FE0E}</a></p>\n</aside>\n\
+            <p>b<sup class=\"footnote-ref\"><a href=\"#fn3\" id=\"fnref2\">[3]</a></sup> <sup class=\"footnote-ref\"><a href=\"#fn1\" id=\"fnref3\">[1]</a></sup></p>\n\
+            <aside id=\"fn3\" class=\"footnote-item\">\n<p>c <a href=\"#fnref2\" class=\"footnote-backref\">\u{21a9}\u{FE0E}</a></p>\n</aside>\n");
+    }
+}
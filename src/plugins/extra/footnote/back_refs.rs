@@ -21,10 +21,13 @@
 //! ]);
 //! ```
 use crate::{
+    common::sourcemap::SourcePos,
     parser::core::{CoreRule, Root},
     plugins::cmark::block::paragraph::Paragraph,
     MarkdownIt, Node, NodeValue,
 };
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 use super::{definitions::FootnoteDefinition, FootnoteMap};
 
@@ -53,6 +56,16 @@ impl NodeValue for FootnoteRefAnchor {
             fmt.close("a");
         }
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "ref_ids": self.ref_ids })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { ref_ids: field(data, "ref_ids")? })
+    }
 }
 
 // This is an extension for the markdown parser.
@@ -63,11 +76,11 @@ impl CoreRule for FootnoteBackrefRule {
         // TODO this seems very cumbersome
         // but it is also how the markdown_it::InlineParserRule works
         let data = root.cast_mut::<Root>().unwrap();
+        if data.ext.get::<FootnoteMap>().is_none() {
+            return;
+        }
         let root_ext = std::mem::take(&mut data.ext);
-        let map = match root_ext.get::<FootnoteMap>() {
-            Some(map) => map,
-            None => return,
-        };
+        let map = root_ext.get::<FootnoteMap>().unwrap();
 
         // walk through the AST and add backref anchors to footnote definitions
         root.walk_mut(|node, _| {
@@ -79,19 +92,27 @@ impl CoreRule for FootnoteBackrefRule {
                     }
                 };
                 if !ref_ids.is_empty() {
+                    // anchor doesn't exist in the source, so it gets an empty span
+                    // at the end of the definition
+                    let mut anchor = Node::new(FootnoteRefAnchor { ref_ids });
+                    anchor.srcmap = node.srcmap.map(|map| {
+                        let (_, end) = map.get_byte_offsets();
+                        SourcePos::new(end, end)
+                    });
+
                     // if the final child is a paragraph node,
                     // append the anchor to its children,
                     // otherwise simply append to the end of the node children
                     match node.children.last_mut() {
                         Some(last) => {
                             if last.is::<Paragraph>() {
-                                last.children.push(Node::new(FootnoteRefAnchor { ref_ids }));
+                                last.children.push(anchor);
                             } else {
-                                node.children.push(Node::new(FootnoteRefAnchor { ref_ids }));
+                                node.children.push(anchor);
                             }
                         }
                         _ => {
-                            node.children.push(Node::new(FootnoteRefAnchor { ref_ids }));
+                            node.children.push(anchor);
                         }
                     }
                 }
@@ -25,12 +25,14 @@
 //! ]);
 //! ```
 use crate::{
+    common::sourcemap::SourcePos,
     parser::core::{CoreRule, Root},
+    parser::options::ParseOptions,
     plugins::cmark::block::paragraph::Paragraph,
     MarkdownIt, Node, NodeValue,
 };
 
-use super::{definitions::FootnoteDefinition, FootnoteMap};
+use super::{definitions::FootnoteDefinition, references::FootnoteReference, FootnoteAside, FootnoteMap, FootnoteOptions};
 
 pub fn add(md: &mut MarkdownIt) {
     // insert this rule into parser
@@ -61,6 +63,11 @@ impl NodeValue for FootnotesContainerNode {
         fmt.close("section");
         fmt.cr();
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 // This is an extension for the markdown parser.
@@ -72,15 +79,15 @@ impl CoreRule for FootnoteCollectRule {
     // It has `root` node of the AST as an argument and may modify its
     // contents as you like.
     //
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(root: &mut Node, md: &MarkdownIt) {
         // TODO this seems very cumbersome
         // but it is also how the markdown_it::InlineParserRule works
         let data = root.cast_mut::<Root>().unwrap();
+        if data.ext.get::<FootnoteMap>().is_none() {
+            return;
+        }
         let root_ext = std::mem::take(&mut data.ext);
-        let map = match root_ext.get::<FootnoteMap>() {
-            Some(map) => map,
-            None => return,
-        };
+        let map = root_ext.get::<FootnoteMap>().unwrap();
 
         // walk through the AST and extract all footnote definitions
         let mut defs = vec![];
@@ -110,6 +117,7 @@ impl CoreRule for FootnoteCollectRule {
                                 // for inline footnotes,
                                 // we need to wrap the definition's children in a paragraph
                                 let mut para = Node::new(Paragraph);
+                                para.srcmap = extracted.srcmap;
                                 std::mem::swap(&mut para.children, &mut extracted.children);
                                 extracted.children = vec![para];
                             }
@@ -122,11 +130,25 @@ impl CoreRule for FootnoteCollectRule {
             node.children.retain(|child| !child.is::<PlaceholderNode>());
         });
         if defs.is_empty() {
+            let data = root.cast_mut::<Root>().unwrap();
+            data.ext = root_ext;
+            return;
+        }
+
+        if ParseOptions::of(&root_ext).ext_or::<FootnoteOptions>(md).is_some_and(|options| options.aside) {
+            place_asides(root, defs);
+            let data = root.cast_mut::<Root>().unwrap();
+            data.ext = root_ext;
             return;
         }
 
         // wrap the definitions in a container and append them to the root
         let mut wrapper = Node::new(FootnotesContainerNode);
+        wrapper.srcmap = defs.iter().filter_map(|def| def.srcmap).reduce(|a, b| {
+            let (a_start, a_end) = a.get_byte_offsets();
+            let (b_start, b_end) = b.get_byte_offsets();
+            SourcePos::new(a_start.min(b_start), a_end.max(b_end))
+        });
         wrapper.children = defs;
         root.children.push(wrapper);
 
@@ -134,3 +156,33 @@ impl CoreRule for FootnoteCollectRule {
         data.ext = root_ext;
     }
 }
+
+// put each definition after the top-level block where it's first referenced
+fn place_asides(root: &mut Node, defs: Vec<Node>) {
+    let mut first_block = std::collections::HashMap::new();
+    for (idx, block) in root.children.iter().enumerate() {
+        block.walk(|node, _| {
+            if let Some(reference) = node.cast::<FootnoteReference>() {
+                first_block.entry(reference.def_id).or_insert(idx);
+            }
+        });
+    }
+
+    let mut asides : Vec<(usize, Node)> = defs.into_iter().map(|mut def| {
+        let def_id = def.cast::<FootnoteDefinition>().and_then(|def| def.def_id);
+        let idx = def_id.and_then(|id| first_block.get(&id).copied()).unwrap_or(usize::MAX);
+        def.ext.insert(FootnoteAside);
+        (idx, def)
+    }).collect();
+    asides.sort_by_key(|(idx, _)| *idx);
+
+    let blocks = std::mem::take(&mut root.children);
+    let mut asides = asides.into_iter().peekable();
+    for (idx, block) in blocks.into_iter().enumerate() {
+        root.children.push(block);
+        while let Some((_, def)) = asides.next_if(|(block_idx, _)| *block_idx == idx) {
+            root.children.push(def);
+        }
+    }
+    root.children.extend(asides.map(|(_, def)| def));
+}
@@ -16,10 +16,13 @@
 //! ```
 
 use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::definitions::Definitions;
 use crate::plugins::cmark::block::reference::ReferenceScanner;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
-use super::FootnoteMap;
+use super::{FootnoteAside, FootnoteMap};
 
 /// Add the footnote definition plugin to the parser
 pub fn add(md: &mut MarkdownIt) {
@@ -45,12 +48,27 @@ impl NodeValue for FootnoteDefinition {
         }
         attrs.push(("class".into(), "footnote-item".into()));
 
+        let tag = if node.ext.get::<FootnoteAside>().is_some() { "aside" } else { "li" };
         fmt.cr();
-        fmt.open("li", &attrs);
+        fmt.open(tag, &attrs);
         fmt.contents(&node.children);
-        fmt.close("li");
+        fmt.close(tag);
         fmt.cr();
     }
+
+    fn is_definition(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "label": self.label, "def_id": self.def_id, "inline": self.inline })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { label: field(data, "label")?, def_id: field(data, "def_id")?, inline: field(data, "inline")? })
+    }
 }
 
 /// An extension for the block subparser.
@@ -140,7 +158,9 @@ impl BlockRule for FootnoteDefinitionScanner {
         state.line_offsets[first_line].indent_nonspace += "[^]:".len() as i32 + spaces as i32;
         // tokenize with a +4 space indent
         state.blk_indent += 4;
+        state.level += 1;
         state.md.block.tokenize(state);
+        state.level -= 1;
         state.blk_indent -= 4;
 
         // get the number of lines the footnote definition occupies
@@ -150,6 +170,15 @@ impl BlockRule for FootnoteDefinitionScanner {
         state.line_offsets[first_line] = first_line_offsets;
         state.line = first_line;
 
+        let srcmap = state.get_map(first_line, first_line + num_lines.max(1) - 1);
+        if let (Some(def_id), Some(srcmap)) = (def_id, srcmap) {
+            let foot_map = state.root_ext.get_or_insert_default::<FootnoteMap>();
+            foot_map.def_srcmaps.insert(def_id, srcmap);
+        }
+
+        let definitions = state.root_ext.get_or_insert_default::<Definitions<FootnoteDefinition>>();
+        definitions.define(label.clone(), &label, srcmap);
+
         // restore the original node and return the footnote and number of lines it occupies
         Some((std::mem::replace(&mut state.node, old_node), num_lines))
     }
@@ -37,6 +37,11 @@ impl NodeValue for InlineFootnote {
         // simply pass-through to children
         fmt.contents(&node.children);
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 // This is an extension for the inline subparser.
@@ -89,18 +94,22 @@ impl InlineRule for InlineFootnoteScanner {
                 let max = state.pos_max;
                 state.pos = content_start;
                 state.pos_max = content_end;
+                state.level += 1;
                 state.md.inline.tokenize(state);
+                state.level -= 1;
                 state.pos = start;
                 state.pos_max = max;
 
                 // restore current node
-                let def_node = std::mem::replace(&mut state.node, current_node);
+                let mut def_node = std::mem::replace(&mut state.node, current_node);
+                def_node.srcmap = state.get_map(content_start, content_end);
 
-                let ref_node = Node::new(super::references::FootnoteReference {
+                let mut ref_node = Node::new(super::references::FootnoteReference {
                     label: None,
                     ref_id,
                     def_id,
                 });
+                ref_node.srcmap = state.get_map(state.pos, content_end + 1);
 
                 // wrap the footnote definition and reference in an outer node to return
                 let mut outer_node = Node::new(InlineFootnote);
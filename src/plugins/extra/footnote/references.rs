@@ -17,10 +17,15 @@
 //! "markdown_it::parser::inline::builtin::skip_text::Text"
 //! ]);
 //! ```
+use crate::parser::definitions::Definitions;
 use crate::parser::inline::{InlineRule, InlineState};
+use crate::plugins::cmark::block::reference::DefinitionSrcmap;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
-use super::FootnoteMap;
+use super::definitions::FootnoteDefinition;
+use super::{FootnoteMap, FootnoteMarker};
 
 /// Add the footnote reference parsing to the markdown parser
 pub fn add(md: &mut MarkdownIt) {
@@ -36,6 +41,15 @@ pub struct FootnoteReference {
     pub def_id: usize,
 }
 
+impl FootnoteReference {
+    fn marker(&self, node: &Node) -> String {
+        match node.ext.get::<FootnoteMarker>() {
+            Some(marker) => marker.0.clone(),
+            None => format!("[{}]", self.def_id),
+        }
+    }
+}
+
 impl NodeValue for FootnoteReference {
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
@@ -49,10 +63,29 @@ impl NodeValue for FootnoteReference {
                 ("id".into(), format!("fnref{}", self.ref_id)),
             ],
         );
-        fmt.text(&format!("[{}]", self.def_id));
+        fmt.text(&self.marker(node));
         fmt.close("a");
         fmt.close("sup");
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => fmt.text(&self.marker(node)),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "label": self.label, "ref_id": self.ref_id, "def_id": self.def_id })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { label: field(data, "label")?, ref_id: field(data, "ref_id")?, def_id: field(data, "def_id")? })
+    }
 }
 
 // This is an extension for the inline subparser.
@@ -86,19 +119,26 @@ impl InlineRule for FootnoteReferenceScanner {
             return None;
         }
 
+        let length = label.len() + 3; // 3 for '[^' and ']'
+
+        let srcmap = state.get_map(state.pos, state.pos + length);
+        let collected = state.root_ext.get_or_insert_default::<Definitions<FootnoteDefinition>>();
+        collected.reference(&label, &label, srcmap);
+
         let definitions = state.root_ext.get_or_insert_default::<FootnoteMap>();
         let (def_id, ref_id) = definitions.add_ref(&label)?;
+        let def_srcmap = definitions.def_srcmap(def_id);
 
-        let length = label.len() + 3; // 3 for '[^' and ']'
+        let mut node = Node::new(FootnoteReference {
+            label: Some(label),
+            ref_id,
+            def_id,
+        });
+        if let Some(srcmap) = def_srcmap {
+            node.ext.insert(DefinitionSrcmap(srcmap));
+        }
 
         // return new node and length of this structure
-        Some((
-            Node::new(FootnoteReference {
-                label: Some(label),
-                ref_id,
-                def_id,
-            }),
-            length,
-        ))
+        Some((node, length))
     }
 }
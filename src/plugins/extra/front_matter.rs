@@ -0,0 +1,141 @@
+//! Front matter with document metadata, written at the very start of the document:
+//!
+//! ```markdown
+//! ---
+//! title: Release notes
+//! version: "2.1"
+//! ---
+//! ```
+//!
+//! Only flat `key: value` pairs are recognized (quotes around values are removed),
+//! other lines (comments, nested values) are kept in the raw content but otherwise ignored.
+//! Front matter isn't rendered, its values are stored in [DocumentMetadata] in the root node.
+//!
+//! ```rust
+//! use markdown_it::parser::core::Root;
+//! use markdown_it::plugins::extra::front_matter::DocumentMetadata;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::front_matter::add(md);
+//!
+//! let ast = md.parse("---\nversion: 2.1\n---\n# Hello");
+//! assert_eq!(ast.render(), "<h1>Hello</h1>\n");
+//!
+//! let meta = ast.cast::<Root>().unwrap().ext.get::<DocumentMetadata>().unwrap();
+//! assert_eq!(meta.0["version"], "2.1");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::core::Root;
+use crate::parser::extset::RootExt;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static FIELD_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^([A-Za-z0-9_.-]+):[ \t]+(.*?)[ \t]*$"#).unwrap()
+});
+
+#[derive(Debug)]
+/// Front matter block, it isn't rendered.
+pub struct FrontMatter {
+    /// Raw content between the markers.
+    pub content: String,
+}
+
+impl NodeValue for FrontMatter {
+    fn render(&self, _: &Node, _: &mut dyn Renderer) {}
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "content": self.content })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { content: field(data, "content")? })
+    }
+}
+
+#[derive(Debug, Default)]
+/// Values defined in the front matter, stored in the root node.
+pub struct DocumentMetadata(pub HashMap<String, String>);
+impl RootExt for DocumentMetadata {}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.block.add_rule::<FrontMatterScanner>()
+        .before_all();
+}
+
+/// Parse flat `key: value` pairs from front matter content.
+pub fn parse_fields(content: &str) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    for line in content.lines() {
+        let Some(captures) = FIELD_RE.captures(line) else { continue; };
+        let value = &captures[2];
+        let value = ['"', '\''].iter()
+            .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+            .unwrap_or(value);
+        result.insert(captures[1].to_owned(), value.to_owned());
+    }
+    result
+}
+
+#[doc(hidden)]
+pub struct FrontMatterScanner;
+impl BlockRule for FrontMatterScanner {
+    fn check(_: &mut BlockState) -> Option<()> {
+        None // can only be at the start of the document
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        if state.line != 0 || !state.node.is::<Root>() { return None; }
+        if state.get_line(0).trim_end() != "---" { return None; }
+
+        let close_line = (1..state.line_max).find(|&line| {
+            matches!(state.get_line(line).trim_end(), "---" | "...")
+        })?;
+
+        let (content, _) = state.get_lines(1, close_line, 0, true);
+        let fields = parse_fields(&content);
+        state.root_ext.get_or_insert_default::<DocumentMetadata>().0.extend(fields);
+
+        Some((Node::new(FrontMatter { content }), close_line + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::core::Root;
+    use super::{DocumentMetadata, FrontMatter};
+
+    fn run(src: &str) -> crate::Node {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src)
+    }
+
+    #[test]
+    fn front_matter() {
+        let ast = run("---\ntitle: \"Hello: world\"\n# comment\nlist:\n  - a\nv: '1'\n---\ntext");
+        assert_eq!(ast.render(), "<p>text</p>\n");
+        assert_eq!(ast.children[0].cast::<FrontMatter>().unwrap().content, "title: \"Hello: world\"\n# comment\nlist:\n  - a\nv: '1'\n");
+
+        let meta = &ast.cast::<Root>().unwrap().ext.get::<DocumentMetadata>().unwrap().0;
+        assert_eq!(meta.len(), 2);
+        assert_eq!(meta["title"], "Hello: world");
+        assert_eq!(meta["v"], "1");
+    }
+
+    #[test]
+    fn not_front_matter() {
+        assert_eq!(run("---\na: b").render(), "<hr>\n<p>a: b</p>\n");
+        assert_eq!(run("text\n\n---\na: b\n---").render(), "<p>text</p>\n<hr>\n<h2>a: b</h2>\n");
+        assert_eq!(run("> ---\n> a: b\n> ---").render(), "<blockquote>\n<hr>\n<h2>a: b</h2>\n</blockquote>\n");
+    }
+}
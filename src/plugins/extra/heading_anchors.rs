@@ -14,17 +14,47 @@
 //!     "<h2 id=\"an-example-heading\">An example heading</h2>\n",
 //! );
 //! ```
+//!
+//! Anchors of published documents are often linked from other places, so they
+//! shouldn't change when a heading is retitled. [SlugMap] collected from
+//! the previous version of a document can be stored (e.g. with [SlugMap::to_json])
+//! and passed to the parser, edited to map new titles to old slugs:
+//!
+//! ```rust
+//! use markdown_it::parser::options::ParseOptions;
+//! use markdown_it::plugins::extra::heading_anchors::{self, SlugMap};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! heading_anchors::add(md, heading_anchors::simple_slugify_fn);
+//!
+//! let mut slugs = SlugMap::collect(&md.parse("# Install"));
+//! assert_eq!(slugs.0["Install"], "install");
+//!
+//! // heading was renamed, but the old anchor is kept
+//! slugs.0.insert("Installation".into(), "install".into());
+//! let mut options = ParseOptions::new();
+//! options.ext.insert(slugs);
+//!
+//! let html = md.parse_with_options("# Installation\n\n# Usage", options).render();
+//! assert_eq!(html, "<h1 id=\"install\">Installation</h1>\n<h1 id=\"usage\">Usage</h1>\n");
+//! ```
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 use crate::parser::core::CoreRule;
 use crate::parser::extset::MarkdownItExt;
-use crate::plugins::cmark::block::heading::ATXHeading;
-use crate::plugins::cmark::block::lheading::SetextHeader;
+use crate::parser::options::ParseOptions;
+use crate::plugins::extra::attrs::AttrsRule;
+use crate::plugins::extra::heading_text::{self, HeadingTextRule};
 use crate::{MarkdownIt, Node};
 
 pub fn add(md: &mut MarkdownIt, slugify: fn (&str) -> String) {
     md.ext.insert(SlugifyFunction(slugify));
-    md.add_rule::<AddHeadingAnchors>();
+    // explicit ids (`# heading {#id}`) take precedence over generated ones
+    md.add_rule::<AddHeadingAnchors>()
+        .after::<AttrsRule>()
+        .after::<HeadingTextRule>();
 }
 
 /// Simple built-in slugify function. It is added for testing and demonstration
@@ -39,6 +69,41 @@ pub fn simple_slugify_fn(s: &str) -> String {
     }).collect()
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Slugs of headings indexed by heading text (see [heading_text::slug_text]),
+/// used instead of generated ones.
+///
+/// Can be set in the parser or in [ParseOptions] of a document.
+pub struct SlugMap(pub BTreeMap<String, String>);
+impl MarkdownItExt for SlugMap {}
+
+impl SlugMap {
+    /// Collect ids of all headings in the document.
+    pub fn collect(root: &Node) -> Self {
+        let mut map = BTreeMap::new();
+        root.walk(|node, _| {
+            if !heading_text::is_heading(node) { return; }
+            if let Some((_, id)) = node.attrs.iter().find(|(key, _)| key == "id") {
+                map.entry(heading_text::slug_text(node)).or_insert_with(|| id.clone());
+            }
+        });
+        Self(map)
+    }
+
+    #[cfg(feature = "serde")]
+    /// Convert map to JSON object (`{ "heading text": "slug" }`).
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.0)
+    }
+
+    #[cfg(feature = "serde")]
+    /// Read map from JSON object created by [to_json](Self::to_json).
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        use serde::Deserialize;
+        BTreeMap::deserialize(value).ok().map(Self)
+    }
+}
+
 #[derive(Clone, Copy)]
 struct SlugifyFunction(fn (&str) -> String);
 impl MarkdownItExt for SlugifyFunction {}
@@ -59,13 +124,57 @@ pub struct AddHeadingAnchors;
 impl CoreRule for AddHeadingAnchors {
     fn run(root: &mut Node, md: &MarkdownIt) {
         let slugify = md.ext.get::<SlugifyFunction>().copied().unwrap_or_default().0;
+        let options = ParseOptions::of_root(root);
+        let slugs = options.ext_or::<SlugMap>(md);
 
         root.walk_mut(|node, _| {
-            if (node.is::<ATXHeading>() || node.is::<SetextHeader>())
-                && node.attrs.iter().all(|(key, _)| key != "id")
-            {
-                node.attrs.push(("id".into(), slugify(&node.collect_text())));
+            if heading_text::is_heading(node) && node.attrs.iter().all(|(key, _)| key != "id") {
+                let text = heading_text::slug_text(node);
+                let slug = slugs.and_then(|slugs| slugs.0.get(&text).cloned()).unwrap_or_else(|| slugify(&text));
+                node.attrs.push(("id".into(), slug));
             }
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SlugMap;
+
+    #[test]
+    fn slug_map() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::attrs::add(md);
+        super::add(md, super::simple_slugify_fn);
+        md.ext.insert(SlugMap([("B".to_owned(), "old-b".to_owned())].into()));
+
+        let ast = md.parse("# A\n\n## B\n\n### C {#c1}\n\n> # A");
+        assert_eq!(
+            ast.render(),
+            "<h1 id=\"a\">A</h1>\n<h2 id=\"old-b\">B</h2>\n<h3 id=\"c1\">C</h3>\n<blockquote>\n<h1 id=\"a\">A</h1>\n</blockquote>\n",
+        );
+
+        let slugs = SlugMap::collect(&ast);
+        assert_eq!(slugs.0.len(), 3);
+        assert_eq!(slugs.0["C"], "c1");
+
+        #[cfg(feature = "serde")]
+        {
+            let json = slugs.to_json();
+            assert_eq!(json["B"], "old-b");
+            assert_eq!(SlugMap::from_json(&json), Some(slugs));
+        }
+    }
+
+    #[test]
+    fn slug_text() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, super::simple_slugify_fn);
+        assert_eq!(md.parse("# a &amp; b").render(), "<h1 id=\"a--b\">a &amp; b</h1>\n");
+
+        crate::plugins::extra::heading_text::add(md);
+        assert_eq!(md.parse("# a &amp; b").render(), "<h1 id=\"a---b\">a &amp; b</h1>\n");
+    }
+}
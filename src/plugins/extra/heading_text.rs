@@ -0,0 +1,155 @@
+//! Display text of headings, shared by anchors, table of contents and outlines.
+//!
+//! Plugins that need a plain text version of a heading should use [heading_text],
+//! so that slugs and labels are made from the same text. By default it includes
+//! text of inline code, emphasis, links, escaped characters and entities
+//! (inline html is skipped), with whitespace collapsed. Applications can
+//! change it (e.g. to turn emoji shortcodes into emoji) with [add_with].
+//!
+//! `heading_anchors` and `sections` only use it if this plugin is added,
+//! otherwise they keep using [Node::collect_text] (see [slug_text]), so that
+//! existing anchors don't change.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::heading_text::heading_text;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::heading_text::add_with(md, |node| {
+//!     markdown_it::plugins::extra::heading_text::default_heading_text(node).replace(":tada:", "🎉")
+//! });
+//!
+//! let ast = md.parse("# Release `v1.0` &amp; *more* :tada:");
+//! assert_eq!(heading_text(&ast.children[0]), "Release v1.0 & more 🎉");
+//! ```
+use std::fmt::Debug;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::{MarkdownItExt, NodeExt};
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::{Text, TextSpecial};
+use crate::plugins::cmark::block::heading::ATXHeading;
+use crate::plugins::cmark::block::lheading::SetextHeader;
+use crate::plugins::cmark::inline::newline::{Hardbreak, Softbreak};
+use crate::plugins::extra::attrs::AttrsRule;
+use crate::{MarkdownIt, Node};
+
+/// Function that returns display text of a heading node.
+pub type HeadingTextFn = fn (&Node) -> String;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Display text of a heading, stored in heading nodes by this plugin.
+pub struct HeadingText(pub String);
+impl NodeExt for HeadingText {}
+
+#[derive(Clone, Copy)]
+struct HeadingTextFunction(HeadingTextFn);
+impl MarkdownItExt for HeadingTextFunction {}
+
+impl Debug for HeadingTextFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeadingTextFunction").finish()
+    }
+}
+
+/// Store display text in headings, using [default_heading_text].
+pub fn add(md: &mut MarkdownIt) {
+    add_with(md, default_heading_text);
+}
+
+/// Store display text in headings, using a custom function.
+pub fn add_with(md: &mut MarkdownIt, f: HeadingTextFn) {
+    md.ext.insert(HeadingTextFunction(f));
+
+    if !md.has_rule::<HeadingTextRule>() {
+        // attributes (`# heading {.class}`) are not a part of the text
+        md.add_rule::<HeadingTextRule>()
+            .after::<InlineParserRule>()
+            .after::<AttrsRule>();
+    }
+}
+
+pub fn is_heading(node: &Node) -> bool {
+    node.is::<ATXHeading>() || node.is::<SetextHeader>()
+}
+
+/// Display text of a heading: text stored by this plugin, or [default_heading_text]
+/// if it wasn't added.
+pub fn heading_text(node: &Node) -> String {
+    match node.ext.get::<HeadingText>() {
+        Some(text) => text.0.clone(),
+        None => default_heading_text(node),
+    }
+}
+
+/// Text stored by this plugin, or [Node::collect_text] if it wasn't added
+/// (escaped characters and entities are skipped), used for slugs and titles.
+pub fn slug_text(node: &Node) -> String {
+    match node.ext.get::<HeadingText>() {
+        Some(text) => text.0.clone(),
+        None => node.collect_text(),
+    }
+}
+
+/// Text of all descendants (including escaped characters and entities),
+/// line breaks are replaced with spaces, whitespace is collapsed.
+pub fn default_heading_text(node: &Node) -> String {
+    let mut result = String::new();
+
+    node.walk(|node, _| {
+        if let Some(text) = node.cast::<Text>() {
+            result.push_str(&text.content);
+        } else if let Some(text) = node.cast::<TextSpecial>() {
+            result.push_str(&text.content);
+        } else if node.is::<Softbreak>() || node.is::<Hardbreak>() {
+            result.push(' ');
+        }
+    });
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[doc(hidden)]
+pub struct HeadingTextRule;
+impl CoreRule for HeadingTextRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let Some(HeadingTextFunction(f)) = md.ext.get::<HeadingTextFunction>().copied() else { return; };
+
+        root.walk_mut(|node, _| {
+            if is_heading(node) {
+                let text = f(node);
+                node.ext.insert(HeadingText(text));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::heading_text;
+
+    #[test]
+    fn default_text() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        crate::plugins::extra::attrs::add(md);
+
+        let ast = md.parse("# `a  b` *c*\\* <b>d</b> [e](/) ![f](f.png) {.cls}\n\nG\\\nH\n===");
+        assert_eq!(heading_text(&ast.children[0]), "a b c* d e f");
+        assert_eq!(heading_text(&ast.children[1]), "G H");
+    }
+
+    #[test]
+    fn custom_text() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::heading_anchors::add(md, crate::plugins::extra::heading_anchors::simple_slugify_fn);
+        super::add_with(md, |node| super::default_heading_text(node).to_uppercase());
+
+        let ast = md.parse("> ## a &amp; b");
+        let heading = &ast.children[0].children[0];
+        assert_eq!(heading_text(heading), "A & B");
+        assert_eq!(ast.render(), "<blockquote>\n<h2 id=\"a---b\">a &amp; b</h2>\n</blockquote>\n");
+    }
+}
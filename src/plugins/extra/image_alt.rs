@@ -0,0 +1,165 @@
+//! Fill in missing image alt text, and find images that don't have any.
+//!
+//! Images like `![](cat.png)` are hard to use with screen readers. This plugin can
+//! use image title or file name as alt text for those (see [ImageAltOptions]),
+//! and [missing_alt_text] lists images that still have no alt text, so strict
+//! accessibility policies can reject such documents.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::image_alt::{self, ImageAltOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! image_alt::add(md, ImageAltOptions { from_title: true, from_filename: true });
+//!
+//! let ast = md.parse("![](/img/black_cat.png) ![](/a.png \"A dog\") ![](/)");
+//! assert_eq!(
+//!     ast.render(),
+//!     "<p><img src=\"/img/black_cat.png\" alt=\"black cat\"> <img src=\"/a.png\" alt=\"A dog\" title=\"A dog\"> <img src=\"/\" alt=\"\"></p>\n"
+//! );
+//!
+//! let missing = image_alt::missing_alt_text(&ast);
+//! assert_eq!(missing.len(), 1);
+//! assert_eq!(missing[0].to_string(), "image `/` at 44..50 has no alt text");
+//! ```
+use std::fmt::{self, Display};
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::Text;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::inline::image::Image;
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Copy, Default)]
+/// Options for [add], sources of alt text for images that have none
+/// (title is tried first).
+pub struct ImageAltOptions {
+    /// Use image title.
+    pub from_title: bool,
+    /// Use file name, without extension and with `-` and `_` replaced by spaces.
+    pub from_filename: bool,
+}
+
+impl MarkdownItExt for ImageAltOptions {}
+
+pub fn add(md: &mut MarkdownIt, options: ImageAltOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<ImageAltRule>() {
+        md.add_rule::<ImageAltRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Image without alt text, returned by [missing_alt_text].
+pub struct MissingAltText {
+    pub url: String,
+    pub srcmap: Option<SourcePos>,
+}
+
+impl Display for MissingAltText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "image `{}`", self.url)?;
+        if let Some(map) = self.srcmap {
+            let (start, end) = map.get_byte_offsets();
+            write!(f, " at {start}..{end}")?;
+        }
+        f.write_str(" has no alt text")
+    }
+}
+
+impl std::error::Error for MissingAltText {}
+
+fn has_alt_text(node: &Node) -> bool {
+    !node.collect_text().trim().is_empty()
+}
+
+/// Find all images without alt text.
+pub fn missing_alt_text(root: &Node) -> Vec<MissingAltText> {
+    let mut result = Vec::new();
+    root.walk(|node, _| {
+        if let Some(image) = node.cast::<Image>() {
+            if !has_alt_text(node) {
+                result.push(MissingAltText { url: image.url.clone(), srcmap: node.srcmap });
+            }
+        }
+    });
+    result
+}
+
+// `/img/black_cat.png?x=1` -> `black cat`
+fn alt_from_filename(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let filename = path.rsplit('/').next().unwrap_or_default();
+    let filename = mdurl::urlencode::decode(filename, mdurl::urlencode::DECODE_COMPONENT_CHARS);
+    let stem = match filename.rsplit_once('.') {
+        Some((stem, _)) if !stem.is_empty() => stem,
+        _ => &filename,
+    };
+    let alt = stem.replace(['-', '_'], " ");
+    let alt = alt.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!alt.is_empty()).then_some(alt)
+}
+
+#[doc(hidden)]
+pub struct ImageAltRule;
+impl CoreRule for ImageAltRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let Some(options) = parse_options.ext_or::<ImageAltOptions>(md) else { return; };
+
+        root.walk_mut(|node, _| {
+            let Some(image) = node.cast::<Image>() else { return; };
+            if has_alt_text(node) { return; }
+
+            let mut alt = None;
+            if options.from_title {
+                alt = image.title.as_ref().map(|title| title.trim()).filter(|title| !title.is_empty()).map(String::from);
+            }
+            if alt.is_none() && options.from_filename {
+                alt = alt_from_filename(&image.url);
+            }
+
+            if let Some(content) = alt {
+                node.children = vec![Node::new(Text { content })];
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{alt_from_filename, ImageAltOptions};
+
+    #[test]
+    fn filename() {
+        assert_eq!(alt_from_filename("photo.jpg"), Some("photo".into()));
+        assert_eq!(alt_from_filename("https://x.org/a/b/my--photo_1.tar.gz?w=1#top"), Some("my photo 1.tar".into()));
+        assert_eq!(alt_from_filename("/a/%D1%84%20b.png"), Some("ф b".into()));
+        assert_eq!(alt_from_filename(".hidden"), Some(".hidden".into()));
+        assert_eq!(alt_from_filename("/a/"), None);
+        assert_eq!(alt_from_filename("_.png"), None);
+    }
+
+    #[test]
+    fn options() {
+        let run = |options| {
+            let md = &mut crate::MarkdownIt::new();
+            crate::plugins::cmark::add(md);
+            super::add(md, options);
+            md.parse("![ ](a.png 'T') ![*b*](b.png) ![](c.png)").render()
+        };
+        assert_eq!(
+            run(ImageAltOptions { from_title: true, from_filename: false }),
+            "<p><img src=\"a.png\" alt=\"T\" title=\"T\"> <img src=\"b.png\" alt=\"b\"> <img src=\"c.png\" alt=\"\"></p>\n"
+        );
+        assert_eq!(
+            run(ImageAltOptions { from_title: false, from_filename: true }),
+            "<p><img src=\"a.png\" alt=\"a\" title=\"T\"> <img src=\"b.png\" alt=\"b\"> <img src=\"c.png\" alt=\"c\"></p>\n"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Lazy loading, dimensions and figures for images.
+//!
+//!  - `lazy` adds `loading="lazy"` and `decoding="async"` attributes, so that
+//!    browsers don't load images until they are needed
+//!  - `dimensions` callback returns width and height for an image url (e.g. by reading
+//!    local image file), they are added as attributes to avoid layout shifts
+//!  - `figure` wraps images that are the only content of a paragraph into `<figure>`,
+//!    with image title as `<figcaption>`
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::images::{self, ImagesOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! images::add(md, ImagesOptions {
+//!     lazy: true,
+//!     dimensions: Some(Box::new(|url| (url == "cat.png").then_some((640, 480)))),
+//!     figure: true,
+//! });
+//!
+//! let html = md.parse("![Cat](cat.png \"My cat\")").render();
+//! assert_eq!(
+//!     html,
+//!     "<figure><img loading=\"lazy\" decoding=\"async\" width=\"640\" height=\"480\" src=\"cat.png\" alt=\"Cat\" title=\"My cat\"><figcaption>My cat</figcaption></figure>\n"
+//! );
+//! ```
+use derivative::Derivative;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::Text;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::plugins::cmark::inline::image::Image;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+/// Callback that returns width and height of an image by its url.
+pub type DimensionsFn = Box<dyn Fn(&str) -> Option<(u32, u32)> + Send + Sync>;
+
+#[derive(Derivative, Default)]
+#[derivative(Debug)]
+/// Options for [add], all of them are disabled by default.
+pub struct ImagesOptions {
+    /// Add `loading="lazy"` and `decoding="async"` attributes.
+    pub lazy: bool,
+    /// Callback used to add `width` and `height` attributes.
+    #[derivative(Debug = "ignore")]
+    pub dimensions: Option<DimensionsFn>,
+    /// Wrap standalone images into `<figure>`, see [Figure].
+    pub figure: bool,
+}
+
+impl MarkdownItExt for ImagesOptions {}
+
+pub fn add(md: &mut MarkdownIt, options: ImagesOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<ImagesRule>() {
+        md.add_rule::<ImagesRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+#[derive(Debug)]
+/// Figure containing a single image, replaces paragraph with that image.
+pub struct Figure {
+    /// Image title, rendered as `<figcaption>`.
+    pub caption: Option<String>,
+}
+
+impl NodeValue for Figure {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.cr();
+        fmt.open("figure", &node.attrs);
+        fmt.contents(&node.children);
+        if let Some(caption) = &self.caption {
+            fmt.open("figcaption", &[]);
+            fmt.text(caption);
+            fmt.close("figcaption");
+        }
+        fmt.close("figure");
+        fmt.cr();
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "caption": self.caption })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { caption: field(data, "caption")? })
+    }
+}
+
+fn has_attr(node: &Node, name: &str) -> bool {
+    node.attrs.iter().any(|(key, _)| key == name)
+}
+
+fn add_attrs(node: &mut Node, options: &ImagesOptions) {
+    let Some(image) = node.cast::<Image>() else { return; };
+
+    let dimensions = if has_attr(node, "width") || has_attr(node, "height") {
+        None
+    } else {
+        options.dimensions.as_ref().and_then(|dimensions| dimensions(&image.url))
+    };
+
+    if options.lazy && !has_attr(node, "loading") {
+        node.attrs.push(("loading".into(), "lazy".into()));
+        node.attrs.push(("decoding".into(), "async".into()));
+    }
+
+    if let Some((width, height)) = dimensions {
+        node.attrs.push(("width".into(), width.to_string()));
+        node.attrs.push(("height".into(), height.to_string()));
+    }
+}
+
+// returns index of the image, if it is the only thing in the paragraph (except spaces)
+fn standalone_image(paragraph: &Node) -> Option<usize> {
+    let mut result = None;
+    for (idx, child) in paragraph.children.iter().enumerate() {
+        if child.is::<Image>() && result.is_none() {
+            result = Some(idx);
+        } else if !child.cast::<Text>().is_some_and(|text| text.content.trim().is_empty()) {
+            return None;
+        }
+    }
+    result
+}
+
+#[doc(hidden)]
+pub struct ImagesRule;
+impl CoreRule for ImagesRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let Some(options) = parse_options.ext_or::<ImagesOptions>(md) else { return; };
+
+        root.walk_mut(|node, _| {
+            add_attrs(node, options);
+
+            if !options.figure || !node.is::<Paragraph>() { return; }
+            let Some(idx) = standalone_image(node) else { return; };
+
+            let image = node.children.swap_remove(idx);
+            let caption = image.cast::<Image>().and_then(|image| image.title.clone());
+            let mut figure = Node::new(Figure { caption });
+            figure.srcmap = node.srcmap;
+            figure.attrs = std::mem::take(&mut node.attrs);
+            figure.children.push(image);
+            *node = figure;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImagesOptions;
+
+    fn run(src: &str, options: ImagesOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn lazy() {
+        assert_eq!(
+            run("a ![b](b.png) [![c](c.png)](/)", ImagesOptions { lazy: true, ..Default::default() }),
+            "<p>a <img loading=\"lazy\" decoding=\"async\" src=\"b.png\" alt=\"b\"> <a href=\"/\"><img loading=\"lazy\" decoding=\"async\" src=\"c.png\" alt=\"c\"></a></p>\n"
+        );
+    }
+
+    #[test]
+    fn dimensions() {
+        let options = ImagesOptions {
+            dimensions: Some(Box::new(|url| url.strip_suffix(".png")?.parse().ok().map(|n: u32| (n, n * 2)))),
+            ..Default::default()
+        };
+        assert_eq!(
+            run("![a](10.png) ![b](b.png)", options),
+            "<p><img width=\"10\" height=\"20\" src=\"10.png\" alt=\"a\"> <img src=\"b.png\" alt=\"b\"></p>\n"
+        );
+    }
+
+    #[test]
+    fn figure() {
+        let options = || ImagesOptions { figure: true, ..Default::default() };
+        assert_eq!(
+            run("![a](a.png)\n\n![b](b.png) text\n\n- ![c](c.png 'C')", options()),
+            "<figure><img src=\"a.png\" alt=\"a\"></figure>\n<p><img src=\"b.png\" alt=\"b\"> text</p>\n<ul>\n<li><img src=\"c.png\" alt=\"c\" title=\"C\"></li>\n</ul>\n"
+        );
+        assert_eq!(
+            run("> ![a](a.png \"<x>\")", options()),
+            "<blockquote>\n<figure><img src=\"a.png\" alt=\"a\" title=\"&lt;x&gt;\"><figcaption>&lt;x&gt;</figcaption></figure>\n</blockquote>\n"
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Include other files, written as `!!!include(path)!!!` (markdown-it-include)
+//! or `{{#include path}}` (mdBook) on a separate line.
+//!
+//! Files are read by a loader callback, so it's up to the application to find files
+//! and restrict access. Included content is parsed as a separate document with the
+//! same options, and can include other files itself. Relative paths in included files
+//! are resolved against the including file (see [resolve_path]), so the loader always
+//! gets paths relative to the main document.
+//!
+//! Lines with a file that can't be loaded, that is already being included (a cycle),
+//! or that exceeds [IncludeOptions::max_depth], are kept as text.
+//!
+//! Part of a file can be included as in mdBook, with `{{#include path:name}}`: lines
+//! between `ANCHOR: name` and `ANCHOR_END: name` markers (usually in comments) are
+//! included and dedented, see [extract_region]. Code fences can be filled the same
+//! way, with `file` and optional `region` in the info string (contents of the fence
+//! are replaced):
+//!
+//! ````text
+//! ```rust file=src/lib.rs region=example
+//! ```
+//! ````
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::include::add(md, |path| match path {
+//!     "intro/index.md" => Some("# Intro\n\n{{#include ../note.md}}".into()),
+//!     "note.md" => Some("*Note*".into()),
+//!     _ => None,
+//! });
+//!
+//! let html = md.parse("!!!include(intro/index.md)!!!\n\n{{#include missing.md}}").render();
+//! assert_eq!(html, "<h1>Intro</h1>\n<p><em>Note</em></p>\n<p>{{#include missing.md}}</p>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt::Debug;
+
+use crate::parser::block::builtin::BlockParserRule;
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::extset::{MarkdownItExt, RootExt, RootExtSet};
+use crate::parser::options::{self, ParseOptions};
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static INCLUDE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^(?:!!!include\(([^()]+)\)!!!|\{\{#include[ \t]+([^{}]+?)(?::([A-Za-z_][\w-]*))?\}\})[ \t]*$"#).unwrap()
+});
+
+static ANCHOR_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"ANCHOR(_END)?:[ \t]*([\w-]+)"#).unwrap()
+});
+
+/// Function that returns contents of a file by its path, or `None` if it can't be read.
+pub type IncludeLoader = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct IncludeOptions {
+    /// Maximum nesting of included files, 8 by default.
+    pub max_depth: usize,
+}
+
+impl Default for IncludeOptions {
+    fn default() -> Self {
+        Self { max_depth: 8 }
+    }
+}
+
+impl MarkdownItExt for IncludeOptions {}
+
+struct Loader(Box<IncludeLoader>);
+
+impl Debug for Loader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Loader").finish()
+    }
+}
+
+impl MarkdownItExt for Loader {}
+
+#[derive(Debug, Default)]
+// resolved paths of files being included, outermost first
+struct IncludeStack(Vec<String>);
+impl RootExt for IncludeStack {}
+
+// resolved path of the file being parsed, `None` for the main document
+pub(crate) fn current_file(root_ext: &RootExtSet) -> Option<&str> {
+    root_ext.get::<IncludeStack>()?.0.last().map(String::as_str)
+}
+
+#[derive(Debug)]
+/// Contents of an included file, its children are the parsed document.
+///
+/// Included nodes have no source maps, because they don't refer to the main document.
+pub struct Include {
+    pub path: String,
+    /// Name of the included region, if only a part of the file is included.
+    pub region: Option<String>,
+}
+
+impl NodeValue for Include {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.contents(&node.children);
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "path": self.path, "region": self.region })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { path: field(data, "path")?, region: field(data, "region")? })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt, loader: impl Fn(&str) -> Option<String> + Send + Sync + 'static) {
+    add_with_options(md, loader, IncludeOptions::default());
+}
+
+pub fn add_with_options(
+    md: &mut MarkdownIt,
+    loader: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    options: IncludeOptions,
+) {
+    md.ext.insert(Loader(Box::new(loader)));
+    md.ext.insert(options);
+
+    if !md.block.has_rule::<IncludeScanner>() {
+        md.block.add_rule::<IncludeScanner>();
+    }
+
+    if !md.has_rule::<IncludeFenceRule>() {
+        md.add_rule::<IncludeFenceRule>()
+            .after::<BlockParserRule>()
+            .before::<InlineParserRule>();
+    }
+}
+
+/// Lines between `ANCHOR: name` and `ANCHOR_END: name` markers (or until the end
+/// if there is no end marker), without lines with other markers, dedented.
+/// Returns `None` if there is no such region.
+///
+/// ```rust
+/// use markdown_it::plugins::extra::include::extract_region;
+///
+/// let src = "fn main() {\n    // ANCHOR: body\n    let x = 1;\n    // ANCHOR: inner\n    f(x);\n    // ANCHOR_END: body\n}\n";
+/// assert_eq!(extract_region(src, "body").unwrap(), "let x = 1;\nf(x);\n");
+/// assert_eq!(extract_region(src, "missing"), None);
+/// ```
+pub fn extract_region(content: &str, name: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut started = false;
+
+    for line in content.lines() {
+        let Some(captures) = ANCHOR_RE.captures(line) else {
+            if started { lines.push(line); }
+            continue;
+        };
+
+        if &captures[2] == name {
+            if captures.get(1).is_some() {
+                if started { break; }
+            } else {
+                started = true;
+            }
+        }
+    }
+
+    started.then(|| dedent(&lines.join("\n")))
+}
+
+/// Path of a file included from file `parent` (`None` for the main document):
+/// relative paths are joined with the directory of `parent`, and `.` and `..`
+/// segments are removed (unless they go above the main document).
+///
+/// ```rust
+/// use markdown_it::plugins::extra::include::resolve_path;
+///
+/// assert_eq!(resolve_path(None, "./a/../b.md"), "b.md");
+/// assert_eq!(resolve_path(Some("docs/intro/a.md"), "../b.md"), "docs/b.md");
+/// assert_eq!(resolve_path(Some("a.md"), "../b.md"), "../b.md");
+/// assert_eq!(resolve_path(Some("docs/a.md"), "/b.md"), "/b.md");
+/// ```
+pub fn resolve_path(parent: Option<&str>, path: &str) -> String {
+    let dir = match parent.and_then(|parent| parent.rsplit_once('/')) {
+        Some((dir, _)) if !path.starts_with('/') => dir,
+        _ => "",
+    };
+
+    let mut segments = Vec::new();
+    for segment in dir.split('/').chain(path.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." if segments.last().is_some_and(|last| *last != "..") => { segments.pop(); }
+            _ => segments.push(segment),
+        }
+    }
+
+    let resolved = segments.join("/");
+    if path.starts_with('/') || dir.starts_with('/') { format!("/{resolved}") } else { resolved }
+}
+
+/// Remove indentation common to all non-empty lines, and add a trailing newline.
+pub fn dedent(content: &str) -> String {
+    let indent = content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut result = String::with_capacity(content.len());
+    for line in content.lines() {
+        result.push_str(line.get(indent..).unwrap_or_else(|| line.trim_start()));
+        result.push('\n');
+    }
+    result
+}
+
+// file contents, or a region of it, `path` must be resolved already
+pub(crate) fn load(md: &MarkdownIt, path: &str, region: Option<&str>) -> Option<String> {
+    let loader = md.ext.get::<Loader>()?;
+    let content = (loader.0)(path)?;
+    match region {
+        Some(region) => extract_region(&content, region),
+        None => Some(content),
+    }
+}
+
+#[doc(hidden)]
+pub struct IncludeScanner;
+
+impl IncludeScanner {
+    // returns path and region
+    pub(crate) fn parse_line(line: &str) -> Option<(&str, Option<&str>)> {
+        let captures = INCLUDE_RE.captures(line)?;
+        let path = captures.get(1).or(captures.get(2))?.as_str().trim();
+        let region = captures.get(3).map(|region| region.as_str());
+        (!path.is_empty()).then_some((path, region))
+    }
+}
+
+impl BlockRule for IncludeScanner {
+    fn check(state: &mut BlockState) -> Option<()> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        Self::parse_line(state.get_line(state.line)).map(|_| ())
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        let (path, region) = Self::parse_line(state.get_line(state.line))?;
+        let (path, region) = (path.to_owned(), region.map(str::to_owned));
+        let resolved = resolve_path(current_file(state.root_ext), &path);
+
+        let parse_options = ParseOptions::of(state.root_ext);
+        let options = parse_options.ext_or::<IncludeOptions>(state.md).copied().unwrap_or_default();
+        let stack = state.root_ext.get::<IncludeStack>().map(|stack| stack.0.as_slice()).unwrap_or_default();
+        if stack.len() >= options.max_depth || stack.contains(&resolved) { return None; }
+
+        let content = load(state.md, &resolved, region.as_deref())?;
+
+        let mut root = Root::new(content);
+        // shares cancel token as well
+        options::share_options(state.root_ext, &mut root.ext);
+        let mut stack = stack.to_vec();
+        stack.push(resolved);
+        root.ext.insert(IncludeStack(stack));
+
+        let mut document = state.md.parse_root(root);
+        document.walk_mut(|node, _| node.srcmap = None);
+
+        let mut node = Node::new(Include { path, region });
+        node.children = std::mem::take(&mut document.children);
+        Some((node, 1))
+    }
+}
+
+#[doc(hidden)]
+pub struct IncludeFenceRule;
+impl CoreRule for IncludeFenceRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parent = root.cast::<Root>().and_then(|root| current_file(&root.ext)).map(str::to_owned);
+
+        root.walk_mut(|node, _| {
+            let Some(fence) = node.cast_mut::<CodeFence>() else { return; };
+
+            let mut path = None;
+            let mut region = None;
+            for word in fence.info.split_whitespace() {
+                match word.split_once('=') {
+                    Some(("file", value)) => path = Some(value),
+                    Some(("region", value)) => region = Some(value),
+                    _ => {}
+                }
+            }
+
+            let Some(path) = path else { return; };
+            if let Some(mut content) = load(md, &resolve_path(parent.as_deref(), path), region) {
+                if !content.is_empty() && !content.ends_with('\n') { content.push('\n'); }
+                fence.content = content;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dedent, extract_region, IncludeOptions, IncludeScanner};
+
+    fn run(src: &str, options: IncludeOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, |path| match path {
+            "a.md" => Some("a\n\n{{#include b.md}}".into()),
+            "b.md" => Some("- b\n\n!!!include(a.md)!!!".into()),
+            "c.md" => Some("[c]\n\n!!!include(c.md)!!!".into()),
+            _ => None,
+        }, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn parse_line() {
+        assert_eq!(IncludeScanner::parse_line("!!!include( a b.md )!!!"), Some(("a b.md", None)));
+        assert_eq!(IncludeScanner::parse_line("{{#include a.md}} "), Some(("a.md", None)));
+        assert_eq!(IncludeScanner::parse_line("{{#include a.md:b-c}}"), Some(("a.md", Some("b-c"))));
+        assert_eq!(IncludeScanner::parse_line("{{#include a.md:10}}"), Some(("a.md:10", None)));
+        assert_eq!(IncludeScanner::parse_line("{{#include}}"), None);
+        assert_eq!(IncludeScanner::parse_line("!!!include(a.md)!!! b"), None);
+    }
+
+    #[test]
+    fn include() {
+        let options = IncludeOptions::default();
+        // cycles are kept as text
+        assert_eq!(run("x\n!!!include(a.md)!!!\ny", options),
+            "<p>x</p>\n<p>a</p>\n<ul>\n<li>b</li>\n</ul>\n<p>!!!include(a.md)!!!</p>\n<p>y</p>\n");
+        assert_eq!(run("> {{#include c.md}}\n\n[c]: /url", options),
+            "<blockquote>\n<p>[c]</p>\n<p>!!!include(c.md)!!!</p>\n</blockquote>\n");
+        assert_eq!(run("    {{#include b.md}}", options), "<pre><code>{{#include b.md}}\n</code></pre>\n");
+    }
+
+    #[test]
+    fn relative_paths() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, |path| match path {
+            "docs/a.md" => Some("a\n\n{{#include b.md}}".into()),
+            "docs/b.md" => Some("```text file=../c.txt\n```\n\n{{#include ../docs/./a.md}}".into()),
+            "c.txt" => Some("c".into()),
+            _ => None,
+        });
+        // cycles are found by resolved paths
+        assert_eq!(md.parse("{{#include docs/a.md}}").render(),
+            "<p>a</p>\n<pre><code class=\"language-text\">c\n</code></pre>\n<p>{{#include ../docs/./a.md}}</p>\n");
+    }
+
+    #[test]
+    fn max_depth() {
+        let options = IncludeOptions { max_depth: 1 };
+        assert_eq!(run("{{#include a.md}}", options), "<p>a</p>\n<p>{{#include b.md}}</p>\n");
+    }
+
+    #[test]
+    fn regions() {
+        assert_eq!(extract_region("a\n  <!-- ANCHOR: x -->\n  b\n\n    c\n", "x").unwrap(), "b\n\n  c\n");
+        assert_eq!(extract_region("ANCHOR: xy\na\nANCHOR: x\nb\nANCHOR_END: x\nc", "x").unwrap(), "b\n");
+        assert_eq!(extract_region("ANCHOR_END: x\na", "x"), None);
+        assert_eq!(dedent(""), "");
+        assert_eq!(dedent("\t a\n\t  b"), "a\n b\n");
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, |path| match path {
+            "a.rs" => Some("fn f() {\n    // ANCHOR: body\n    x < 1\n    // ANCHOR_END: body\n}".into()),
+            "a.md" => Some("<!-- ANCHOR: x -->\n*a*\n<!-- ANCHOR_END: x -->\nb".into()),
+            _ => None,
+        });
+        assert_eq!(md.parse("```rust file=a.rs region=body\n```\n\n{{#include a.md:x}}").render(),
+            "<pre><code class=\"language-rust\">x &lt; 1\n</code></pre>\n<p><em>a</em></p>\n");
+        assert_eq!(md.parse("```text file=a.rs\n```").render(),
+            "<pre><code class=\"language-text\">fn f() {\n    // ANCHOR: body\n    x &lt; 1\n    // ANCHOR_END: body\n}\n</code></pre>\n");
+        // missing file or region
+        assert_eq!(md.parse("```rust file=a.rs region=z\nold\n```\n\n{{#include a.md:z}}").render(),
+            "<pre><code class=\"language-rust\">old\n</code></pre>\n<p>{{#include a.md:z}}</p>\n");
+    }
+
+    #[test]
+    fn no_srcmaps() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, |_| Some("*a*".into()));
+        let ast = md.parse("{{#include a.md}}");
+        assert!(ast.children[0].srcmap.is_some());
+        ast.children[0].walk(|node, depth| assert!(depth == 0 || node.srcmap.is_none()));
+    }
+}
@@ -0,0 +1,177 @@
+//! Extra restrictions and transformations for links.
+//!
+//!  - `obfuscate_mailto` encodes `mailto:` links (and their text) as html entities,
+//!    which stops the most basic email harvesters
+//!  - `forbid_protocol_relative` doesn't create links with destinations like
+//!    `//example.org` (same as `javascript:` urls are not allowed by default)
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::link_options::LinkOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::link_options::add(md, LinkOptions {
+//!     obfuscate_mailto: true,
+//!     forbid_protocol_relative: true,
+//! });
+//!
+//! let html = md.parse("<a@b.c>").render();
+//! assert_eq!(html.trim(), "<p><a href=\"&#109;&#97;&#105;&#108;&#116;&#111;&#58;&#97;&#64;&#98;&#46;&#99;\">&#97;&#64;&#98;&#46;&#99;</a></p>");
+//!
+//! let html = md.parse("[link](//example.org)").render();
+//! assert_eq!(html.trim(), "<p>[link](//example.org)</p>");
+//! ```
+use crate::parser::core::CoreRule;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::{Text, TextSpecial};
+use crate::parser::linkfmt::{LinkFormatter, MDLinkFormatter};
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::link::Link;
+use crate::{AttrValue, MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Options for [add], all of them are disabled by default.
+pub struct LinkOptions {
+    /// Render `mailto:` links with all characters of the address encoded as html entities.
+    pub obfuscate_mailto: bool,
+    /// Don't create links with protocol-relative destinations (`//host/path`).
+    pub forbid_protocol_relative: bool,
+}
+
+pub fn add(md: &mut MarkdownIt, options: LinkOptions) {
+    if options.forbid_protocol_relative {
+        let parent = std::mem::replace(&mut md.link_formatter, Box::new(MDLinkFormatter::new()));
+        md.link_formatter = Box::new(ProtocolRelativeFilter { parent });
+    }
+
+    if options.obfuscate_mailto {
+        md.add_rule::<ObfuscateMailtoRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+#[derive(Debug)]
+/// Link with `mailto:` destination, rendered with html entities instead of characters.
+pub struct ObfuscatedMailto {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+impl NodeValue for ObfuscatedMailto {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let href = obfuscate(&self.url);
+        let mut attrs : Vec<_> = node.attrs.iter()
+            .map(|(name, value)| (name.as_str(), AttrValue::Text(value)))
+            .collect();
+        attrs.push(("href", AttrValue::Html(&href)));
+        if let Some(title) = &self.title {
+            attrs.push(("title", AttrValue::Text(title)));
+        }
+        fmt.open_tag("a", &attrs);
+
+        for child in node.children.iter() {
+            if let Some(text) = child.cast::<Text>() {
+                fmt.text_raw(&obfuscate(&text.content));
+            } else if let Some(text) = child.cast::<TextSpecial>() {
+                fmt.text_raw(&obfuscate(&text.content));
+            } else {
+                child.render_with(fmt);
+            }
+        }
+
+        fmt.close_tag("a");
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "url": self.url, "title": self.title })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { url: field(data, "url")?, title: field(data, "title")? })
+    }
+}
+
+fn obfuscate(str: &str) -> String {
+    str.chars().map(|ch| format!("&#{};", ch as u32)).collect()
+}
+
+fn is_mailto(url: &str) -> bool {
+    url.get(..7).is_some_and(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+}
+
+#[doc(hidden)]
+pub struct ObfuscateMailtoRule;
+impl CoreRule for ObfuscateMailtoRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        root.walk_mut(|node, _| {
+            let (url, title) = if let Some(link) = node.cast::<Link>() {
+                (&link.url, link.title.clone())
+            } else if let Some(link) = node.cast::<Autolink>() {
+                (&link.url, None)
+            } else {
+                return;
+            };
+
+            if is_mailto(url) {
+                let url = url.clone();
+                node.replace(ObfuscatedMailto { url, title });
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+struct ProtocolRelativeFilter {
+    parent: Box<dyn LinkFormatter>,
+}
+
+impl LinkFormatter for ProtocolRelativeFilter {
+    fn validate_link(&self, url: &str) -> Option<()> {
+        if url.starts_with("//") { return None; }
+        self.parent.validate_link(url)
+    }
+
+    fn normalize_link(&self, url: &str) -> String {
+        self.parent.normalize_link(url)
+    }
+
+    fn normalize_link_text(&self, url: &str) -> String {
+        self.parent.normalize_link_text(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkOptions;
+
+    fn run(src: &str, options: LinkOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn mailto() {
+        let options = LinkOptions { obfuscate_mailto: true, ..Default::default() };
+        assert_eq!(
+            run("[*x*](MAILTO:a \"t\")", options),
+            "<p><a href=\"&#77;&#65;&#73;&#76;&#84;&#79;&#58;&#97;\" title=\"t\"><em>x</em></a></p>\n"
+        );
+        assert_eq!(run("[x](/a)", options), "<p><a href=\"/a\">x</a></p>\n");
+        assert_eq!(run("[//x](//a)", options), "<p><a href=\"//a\">//x</a></p>\n");
+    }
+
+    #[test]
+    fn protocol_relative() {
+        let options = LinkOptions { forbid_protocol_relative: true, ..Default::default() };
+        assert_eq!(run("[x](//a)", options), "<p>[x](//a)</p>\n");
+        assert_eq!(run("![x](//a.png)", options), "<p>![x](//a.png)</p>\n");
+        assert_eq!(run("[x](/a)", options), "<p><a href=\"/a\">x</a></p>\n");
+        assert_eq!(run("[x](javascript:a)", options), "<p>[x](javascript:a)</p>\n");
+    }
+}
@@ -0,0 +1,224 @@
+//! Resolve link and image destinations with a callback.
+//!
+//! Callback is called for each link, image, autolink and linkified url after the
+//! document is parsed, and decides what to do with it (see [LinkDecision]).
+//! Static site generators can use it to turn relative links to `.md` files into
+//! urls of generated pages, report dead links, or add dimensions to images.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::link_resolver::{self, LinkDecision, LinkKind};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! link_resolver::add(md, |link| {
+//!     if link.kind == LinkKind::Image {
+//!         return LinkDecision::Resolve {
+//!             url: link.url.to_owned(),
+//!             attrs: vec![("width".into(), "640".into())],
+//!         };
+//!     }
+//!     match link.url.strip_suffix(".md") {
+//!         Some("missing") => LinkDecision::Dead,
+//!         Some(page) => LinkDecision::Resolve { url: format!("{page}.html"), attrs: vec![] },
+//!         None => LinkDecision::Keep,
+//!     }
+//! });
+//!
+//! let ast = md.parse("[a](intro.md) [b](missing.md) [c](https://x.org) ![d](d.png)");
+//! assert_eq!(
+//!     ast.render(),
+//!     "<p><a href=\"intro.html\">a</a> b <a href=\"https://x.org\">c</a> <img width=\"640\" src=\"d.png\" alt=\"d\"></p>\n"
+//! );
+//!
+//! let dead = link_resolver::dead_links(&ast);
+//! assert_eq!(dead.len(), 1);
+//! assert_eq!(dead[0].url, "missing.md");
+//! ```
+use derivative::Derivative;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::extset::{MarkdownItExt, RootExt};
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::cmark::inline::link::Link;
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Type of the node a destination belongs to.
+pub enum LinkKind {
+    /// Inline or reference link, `[text](url)`.
+    Link,
+    /// Image, `![alt](url)`.
+    Image,
+    /// Autolink `<url>`, or url found by linkify plugin.
+    Autolink,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Link passed to resolver callback.
+pub struct LinkContext<'a> {
+    pub url: &'a str,
+    /// Url of the document, see [ParseOptions::base_url].
+    pub base_url: Option<&'a str>,
+    pub title: Option<&'a str>,
+    pub kind: LinkKind,
+    pub srcmap: Option<SourcePos>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// What to do with a link, returned by resolver callback.
+pub enum LinkDecision {
+    /// Leave the link as is.
+    Keep,
+    /// Replace destination, and add attributes to the link (e.g. `width` and `height`
+    /// for images).
+    Resolve { url: String, attrs: Vec<(String, String)> },
+    /// Link target doesn't exist: link is replaced with its contents,
+    /// and reported in [dead_links].
+    Dead,
+}
+
+/// Callback that decides what to do with a link.
+pub type LinkResolverFn = Box<dyn Fn(&LinkContext) -> LinkDecision + Send + Sync>;
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct LinkResolver(#[derivative(Debug = "ignore")] LinkResolverFn);
+impl MarkdownItExt for LinkResolver {}
+
+pub fn add(md: &mut MarkdownIt, resolver: impl Fn(&LinkContext) -> LinkDecision + Send + Sync + 'static) {
+    md.ext.insert(LinkResolver(Box::new(resolver)));
+
+    if !md.has_rule::<LinkResolverRule>() {
+        md.add_rule::<LinkResolverRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Link that was resolved as [LinkDecision::Dead].
+pub struct DeadLink {
+    pub url: String,
+    pub kind: LinkKind,
+    pub srcmap: Option<SourcePos>,
+}
+
+#[derive(Debug, Default)]
+struct DeadLinks(Vec<DeadLink>);
+impl RootExt for DeadLinks {}
+
+/// List all dead links found in the document.
+pub fn dead_links(root: &Node) -> &[DeadLink] {
+    root.cast::<Root>()
+        .and_then(|root| root.ext.get::<DeadLinks>())
+        .map(|links| links.0.as_slice())
+        .unwrap_or_default()
+}
+
+fn get_link_mut(node: &mut Node) -> Option<(&mut String, Option<&str>, LinkKind)> {
+    if node.is::<Link>() {
+        return node.cast_mut::<Link>().map(|link| (&mut link.url, link.title.as_deref(), LinkKind::Link));
+    }
+    if node.is::<Image>() {
+        return node.cast_mut::<Image>().map(|image| (&mut image.url, image.title.as_deref(), LinkKind::Image));
+    }
+    if node.is::<Autolink>() {
+        return node.cast_mut::<Autolink>().map(|link| (&mut link.url, None, LinkKind::Autolink));
+    }
+    #[cfg(feature = "linkify")]
+    if node.is::<crate::plugins::extra::linkify::Linkified>() {
+        return node.cast_mut::<crate::plugins::extra::linkify::Linkified>().map(|link| (&mut link.url, None, LinkKind::Autolink));
+    }
+    None
+}
+
+#[doc(hidden)]
+pub struct LinkResolverRule;
+impl CoreRule for LinkResolverRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        fn walk_recursive(node: &mut Node, resolver: &LinkResolverFn, base_url: Option<&str>, dead: &mut Vec<DeadLink>) {
+            let mut idx = 0;
+            while idx < node.children.len() {
+                let child = &mut node.children[idx];
+                let srcmap = child.srcmap;
+
+                if let Some((url, title, kind)) = get_link_mut(child) {
+                    match resolver(&LinkContext { url, base_url, title, kind, srcmap }) {
+                        LinkDecision::Keep => {}
+                        LinkDecision::Resolve { url: new_url, attrs } => {
+                            *url = new_url;
+                            child.attrs.extend(attrs);
+                        }
+                        LinkDecision::Dead => {
+                            dead.push(DeadLink { url: std::mem::take(url), kind, srcmap });
+                            let children = std::mem::take(&mut child.children);
+                            node.children.splice(idx..=idx, children);
+                            continue;
+                        }
+                    }
+                }
+
+                stacker::maybe_grow(64*1024, 1024*1024, || {
+                    walk_recursive(child, resolver, base_url, dead);
+                });
+                idx += 1;
+            }
+        }
+
+        let options = ParseOptions::of_root(root);
+        let Some(resolver) = options.ext_or::<LinkResolver>(md) else { return; };
+        let mut dead = Vec::new();
+        walk_recursive(root, &resolver.0, options.base_url.as_deref(), &mut dead);
+
+        if !dead.is_empty() {
+            let data = root.cast_mut::<Root>().unwrap();
+            data.ext.get_or_insert_default::<DeadLinks>().0.extend(dead);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LinkDecision, LinkKind};
+
+    #[test]
+    fn context() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, |link| {
+            assert_eq!(link.kind, if link.url == "/b" { LinkKind::Image } else { LinkKind::Link });
+            match link.title {
+                Some(title) => LinkDecision::Resolve { url: format!("{}#{title}", link.url), attrs: vec![] },
+                None => LinkDecision::Dead,
+            }
+        });
+
+        let ast = md.parse("[*a* ![b](/b \"t\")](/a)\n\n[c]\n\n[c]: /c 'u'");
+        assert_eq!(ast.render(), "<p><em>a</em> <img src=\"/b#t\" alt=\"b\" title=\"t\"></p>\n<p><a href=\"/c#u\" title=\"u\">c</a></p>\n");
+
+        let dead = super::dead_links(&ast);
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].url, "/a");
+        assert_eq!(dead[0].srcmap.unwrap().get_byte_offsets(), (0, 22));
+    }
+
+    #[test]
+    fn base_url() {
+        use crate::parser::options::ParseOptions;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, |link| {
+            let base = link.base_url.unwrap_or("/");
+            LinkDecision::Resolve { url: format!("{base}{}", link.url), attrs: vec![] }
+        });
+
+        assert_eq!(md.parse("[a](x)").render(), "<p><a href=\"/x\">a</a></p>\n");
+
+        let options = ParseOptions { base_url: Some("/docs/".into()), ..Default::default() };
+        assert_eq!(md.parse_with_options("[a](x)", options).render(), "<p><a href=\"/docs/x\">a</a></p>\n");
+    }
+}
@@ -1,4 +1,19 @@
 //! Find urls and emails, and turn them into links
+//!
+//! Trailing punctuation is not considered part of the url, as in
+//! [GFM](https://github.github.com/gfm/#extended-autolink-path-validation):
+//! characters from [DEFAULT_TRAILING_PUNCTUATION] (configurable with
+//! [set_trailing_punctuation]), unbalanced closing parentheses and
+//! things that look like entity references (`&amp;`).
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::linkify::add(md);
+//!
+//! let html = md.parse("see https://example.org/foo_(bar))_, please").render();
+//! assert_eq!(html.trim(), r#"<p>see <a href="https://example.org/foo_(bar)">https://example.org/foo_(bar)</a>)_, please</p>"#);
+//! ```
 
 use linkify::{LinkFinder, LinkKind};
 use once_cell::sync::Lazy;
@@ -6,15 +21,24 @@ use regex::Regex;
 use std::cmp::Ordering;
 
 use crate::parser::core::{CoreRule, Root};
-use crate::parser::extset::RootExt;
+use crate::parser::extset::{MarkdownItExt, RootExt};
 use crate::parser::inline::builtin::InlineParserRule;
 use crate::parser::inline::{InlineRule, InlineState, TextSpecial};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 static SCHEME_RE : Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?i)(?:^|[^a-z0-9.+-])([a-z][a-z0-9.+-]*)$").unwrap()
 });
 
+static ENTITY_LIKE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"&[A-Za-z0-9]+$").unwrap()
+});
+
+/// Characters that are never considered part of the url if they are at the end of it.
+pub const DEFAULT_TRAILING_PUNCTUATION : &str = "?!.,:*_~";
+
 #[derive(Debug)]
 pub struct Linkified {
     pub url: String,
@@ -29,6 +53,16 @@ impl NodeValue for Linkified {
         fmt.contents(&node.children);
         fmt.close("a");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "url": self.url })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { url: field(data, "url")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
@@ -38,6 +72,53 @@ pub fn add(md: &mut MarkdownIt) {
     md.inline.add_rule::<LinkifyScanner>();
 }
 
+/// Set characters that are trimmed from the end of urls, [DEFAULT_TRAILING_PUNCTUATION] by default.
+///
+/// Note that linkify crate trims some punctuation (e.g. `.` or `,`) on its own,
+/// this only allows you to trim more.
+pub fn set_trailing_punctuation(md: &mut MarkdownIt, chars: &str) {
+    md.ext.insert(LinkifySettings { trailing_punctuation: chars.to_owned() });
+}
+
+#[derive(Debug)]
+struct LinkifySettings {
+    trailing_punctuation: String,
+}
+impl MarkdownItExt for LinkifySettings {}
+
+impl Default for LinkifySettings {
+    fn default() -> Self {
+        Self { trailing_punctuation: DEFAULT_TRAILING_PUNCTUATION.to_owned() }
+    }
+}
+
+// Returns length of the url after trailing punctuation is removed,
+// `next` is the character right after the url in the source.
+fn trim_trailing(url: &str, next: Option<char>, trailing_punctuation: &str) -> usize {
+    let mut url = url;
+    let mut next = next;
+
+    while let Some(last) = url.chars().next_back() {
+        if trailing_punctuation.contains(last) {
+            // `example.com/a.` -> `example.com/a`
+        } else if last == ')' && url.matches(')').count() > url.matches('(').count() {
+            // `(see example.com/a)` -> `example.com/a`
+        } else if next == Some(';') && let Some(m) = ENTITY_LIKE_RE.find(url) {
+            // `example.com/a&amp;` -> `example.com/a`
+            url = &url[..m.start()];
+            next = Some('&');
+            continue;
+        } else {
+            break;
+        }
+
+        url = &url[..url.len() - last.len_utf8()];
+        next = Some(last);
+    }
+
+    url.len()
+}
+
 type LinkifyState = Vec<LinkifyPosition>;
 impl RootExt for LinkifyState {}
 
@@ -51,15 +132,21 @@ struct LinkifyPosition {
 #[doc(hidden)]
 pub struct LinkifyPrescan;
 impl CoreRule for LinkifyPrescan {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let settings = md.ext.get::<LinkifySettings>();
+        let trailing_punctuation = settings.map_or(DEFAULT_TRAILING_PUNCTUATION, |s| s.trailing_punctuation.as_str());
         let root_data = root.cast_mut::<Root>().unwrap();
         let source = root_data.content.as_str();
         let finder = LinkFinder::new();
         let positions = finder.links(source).filter_map(|link| {
             if *link.kind() == LinkKind::Url {
+                let next = source[link.end()..].chars().next();
+                let len = trim_trailing(link.as_str(), next, trailing_punctuation);
+                if link.as_str()[..len].ends_with("://") { return None; }
+
                 Some(LinkifyPosition {
                     start: link.start(),
-                    end:   link.end(),
+                    end:   link.start() + len,
                     //email: *link.kind() == LinkKind::Email,
                 })
             } else {
@@ -133,3 +220,34 @@ impl InlineRule for LinkifyScanner {
         Some((node, url_end - url_start))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{trim_trailing, DEFAULT_TRAILING_PUNCTUATION};
+
+    fn trim(url: &str, next: Option<char>) -> &str {
+        &url[..trim_trailing(url, next, DEFAULT_TRAILING_PUNCTUATION)]
+    }
+
+    #[test]
+    fn trailing_punctuation() {
+        assert_eq!(trim("http://a.com/b", None), "http://a.com/b");
+        assert_eq!(trim("http://a.com/b?!.", None), "http://a.com/b");
+        assert_eq!(trim("http://a.com/b_~*", None), "http://a.com/b");
+        assert_eq!(trim("http://a.com/b_c", None), "http://a.com/b_c");
+    }
+
+    #[test]
+    fn parentheses() {
+        assert_eq!(trim("http://a.com/b(c)", None), "http://a.com/b(c)");
+        assert_eq!(trim("http://a.com/b(c))", None), "http://a.com/b(c)");
+        assert_eq!(trim("http://a.com/b)).", None), "http://a.com/b");
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(trim("http://a.com/b&hl", Some(';')), "http://a.com/b");
+        assert_eq!(trim("http://a.com/b&hl", None), "http://a.com/b&hl");
+        assert_eq!(trim("http://a.com/b?c=d&amp", Some(';')), "http://a.com/b?c=d");
+    }
+}
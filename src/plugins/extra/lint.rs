@@ -0,0 +1,236 @@
+//! Common problems of markdown documents, reported as [diagnostics](crate::parser::diagnostics).
+//!
+//!  - `duplicate-id` - several elements have the same id (e.g. headings with the same title)
+//!  - `missing-reference` - `[text][label]` or `[^label]` without a definition
+//!  - `bare-url` - url in text that isn't a link (skipped if linkify is enabled)
+//!  - `heading-level` - heading level increased by more than one (`#` followed by `###`)
+//!
+//! Checks run after all other rules, each can be disabled in [LintOptions].
+//!
+//! ```rust
+//! use markdown_it::parser::diagnostics::Diagnostics;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::lint::add(md);
+//!
+//! let ast = md.parse("# Title\n\n### Usage\n\nSee [docs][] at https://example.com.");
+//! let diagnostics = Diagnostics::of(&ast).iter().map(|d| d.to_string()).collect::<Vec<_>>();
+//! assert_eq!(diagnostics, [
+//!     "warning[missing-reference]: reference `docs` is not defined (24..32)",
+//!     "warning[bare-url]: bare url `https://example.com` (36..55)",
+//!     "warning[heading-level]: heading level 3 after level 1 (9..18)",
+//! ]);
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::definitions::Definitions;
+use crate::parser::diagnostics::{self, Diagnostic};
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::Text;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::block::heading::ATXHeading;
+use crate::plugins::cmark::block::lheading::SetextHeader;
+use crate::plugins::cmark::block::reference::Definition;
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::backticks::CodeInline;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::cmark::inline::link::Link;
+use crate::plugins::extra::footnote::definitions::FootnoteDefinition;
+use crate::{MarkdownIt, Node};
+
+static BARE_URL_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\b(?:https?://|www\.)[^\s<>]*[^\s<>.,:;!?'")\]*_]"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+/// All checks are enabled by default.
+pub struct LintOptions {
+    pub duplicate_ids: bool,
+    pub missing_references: bool,
+    pub bare_urls: bool,
+    pub heading_levels: bool,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            duplicate_ids: true,
+            missing_references: true,
+            bare_urls: true,
+            heading_levels: true,
+        }
+    }
+}
+
+impl MarkdownItExt for LintOptions {}
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, LintOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: LintOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<LintRule>() {
+        md.add_rule::<LintRule>()
+            .after_all();
+    }
+}
+
+#[doc(hidden)]
+pub struct LintRule;
+impl CoreRule for LintRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let options = parse_options.ext_or::<LintOptions>(md).copied().unwrap_or_default();
+        let mut found = Vec::new();
+
+        if options.missing_references {
+            missing_references(root, &mut found);
+        }
+
+        if options.bare_urls && !linkify_enabled(md) {
+            let source = root.cast::<Root>().unwrap().content.as_str();
+            bare_urls(root, source, &mut found);
+        }
+
+        let mut ids = HashSet::new();
+        let mut last_level = None;
+
+        root.walk(|node, _| {
+            if options.duplicate_ids {
+                if let Some((_, id)) = node.attrs.iter().find(|(name, _)| name == "id") {
+                    if !ids.insert(id.clone()) {
+                        found.push(Diagnostic::warning("duplicate-id", format!("duplicate id `{id}`"), node.srcmap));
+                    }
+                }
+            }
+
+            if options.heading_levels {
+                let level = node.cast::<ATXHeading>().map(|h| h.level)
+                    .or_else(|| node.cast::<SetextHeader>().map(|h| h.level));
+                if let Some(level) = level {
+                    if let Some(last) = last_level.filter(|&last| level > last + 1) {
+                        let message = format!("heading level {level} after level {last}");
+                        found.push(Diagnostic::warning("heading-level", message, node.srcmap));
+                    }
+                    last_level = Some(level);
+                }
+            }
+        });
+
+        let root_ext = &mut root.cast_mut::<Root>().unwrap().ext;
+        for diagnostic in found {
+            diagnostics::report(root_ext, diagnostic);
+        }
+    }
+}
+
+fn linkify_enabled(md: &MarkdownIt) -> bool {
+    #[cfg(feature = "linkify")]
+    if md.inline.has_rule::<crate::plugins::extra::linkify::LinkifyScanner>() { return true; }
+    let _ = md;
+    false
+}
+
+fn missing_references(root: &Node, found: &mut Vec<Diagnostic>) {
+    let root_data = root.cast::<Root>().unwrap();
+    let links = root_data.ext.get::<Definitions<Definition>>().map(|defs| defs.missing()).unwrap_or_default();
+    let footnotes = root_data.ext.get::<Definitions<FootnoteDefinition>>().map(|defs| defs.missing()).unwrap_or_default();
+
+    for reference in links {
+        let message = format!("reference `{}` is not defined", reference.label);
+        found.push(Diagnostic::warning("missing-reference", message, reference.srcmap));
+    }
+
+    for reference in footnotes {
+        let message = format!("footnote `{}` is not defined", reference.label);
+        found.push(Diagnostic::warning("missing-reference", message, reference.srcmap));
+    }
+}
+
+// reports urls in text nodes outside of links and code
+fn bare_urls(node: &Node, source: &str, found: &mut Vec<Diagnostic>) {
+    if node.is::<Link>() || node.is::<Image>() || node.is::<Autolink>() || node.is::<CodeInline>() { return; }
+
+    if let Some(text) = node.cast::<Text>() {
+        for url in BARE_URL_RE.find_iter(&text.content) {
+            // text maps to the source exactly unless it has escapes or entities
+            let srcmap = node.srcmap.map(|srcmap| {
+                let (start, end) = srcmap.get_byte_offsets();
+                if source.get(start..end) == Some(text.content.as_str()) {
+                    SourcePos::new(start + url.start(), start + url.end())
+                } else {
+                    srcmap
+                }
+            });
+            found.push(Diagnostic::warning("bare-url", format!("bare url `{}`", url.as_str()), srcmap));
+        }
+    }
+
+    for child in node.children.iter() {
+        stacker::maybe_grow(64*1024, 1024*1024, || {
+            bare_urls(child, source, found);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LintOptions;
+    use crate::parser::diagnostics::Diagnostics;
+    use crate::parser::options::ParseOptions;
+
+    fn run(src: &str, options: Option<LintOptions>) -> Vec<(&'static str, String)> {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::footnote::add(md);
+        crate::plugins::extra::heading_anchors::add(md, crate::plugins::extra::heading_anchors::simple_slugify_fn);
+        super::add(md);
+
+        let mut parse_options = ParseOptions::new();
+        if let Some(options) = options { parse_options.ext.insert(options); }
+        let ast = md.parse_with_options(src, parse_options);
+        Diagnostics::of(&ast).iter().map(|d| (d.code, d.message.clone())).collect()
+    }
+
+    #[test]
+    fn duplicate_ids() {
+        assert_eq!(run("# A\n\n## B\n\n## A", None), [("duplicate-id", "duplicate id `a`".to_owned())]);
+    }
+
+    #[test]
+    fn heading_levels() {
+        assert_eq!(run("## A\n\n#### B\n\n> C\n> ====\n\n### D", None), [
+            ("heading-level", "heading level 4 after level 2".to_owned()),
+            ("heading-level", "heading level 3 after level 1".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn missing_references() {
+        assert_eq!(run("[a] [b][] [c][d] [x][c] [^e]\n\n[c]: /c", None), [
+            ("missing-reference", "reference `b` is not defined".to_owned()),
+            ("missing-reference", "reference `d` is not defined".to_owned()),
+            ("missing-reference", "footnote `e` is not defined".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn bare_urls() {
+        assert_eq!(run("<http://a.b> [www.c.d](/) `http://e` (http://f.g/h).", None),
+            [("bare-url", "bare url `http://f.g/h`".to_owned())]);
+    }
+
+    #[test]
+    fn options() {
+        let options = LintOptions { duplicate_ids: false, bare_urls: false, ..LintOptions::default() };
+        assert!(run("# A\n\n# A\n\nhttp://b.c", Some(options)).is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! Inline math, written as `$x^2$` or `$$\sum_i x_i$$`.
+//!
+//! Math is rendered as `<span class="math inline">\(...\)</span>` (or `math display`
+//! with `\[...\]`), the same way pandoc does it, to be typeset by MathJax or KaTeX
+//! in the browser.
+//!
+//! Dollar signs are also used for prices, so by default `$` only starts or ends math
+//! when it looks like a delimiter (see [MathOptions]):
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::math::add(md);
+//!
+//! let html = md.parse("Let $x^2 < y$, it costs $5 or $10, \\$x\\$ is escaped.").render();
+//! assert_eq!(html, "<p>Let <span class=\"math inline\">\\(x^2 &lt; y\\)</span>, \
+//!     it costs $5 or $10, $x$ is escaped.</p>\n");
+//! ```
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::{InlineRule, InlineState, Text};
+use crate::parser::options::ParseOptions;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct MathOptions {
+    /// Opening `$` must be followed by a non-space character, and closing `$`
+    /// must be preceded by one (`$ 5 $` isn't math), enabled by default.
+    /// Math can't contain `$` preceded by a space, it's an opening one.
+    pub tight: bool,
+    /// Treat dollar signs next to numbers as currency: math can't contain `$` followed
+    /// by a digit (`$5 and $10`), and opening `$` can't follow a letter or a digit
+    /// (`US$5`), enabled by default.
+    pub currency: bool,
+    /// Strict mode: `tight` and `currency` rules always apply, math can't start
+    /// with a digit and can't span multiple lines, disabled by default.
+    pub strict: bool,
+    /// Parse `$$...$$` as display math, enabled by default.
+    pub display: bool,
+}
+
+impl Default for MathOptions {
+    fn default() -> Self {
+        Self {
+            tight: true,
+            currency: true,
+            strict: false,
+            display: true,
+        }
+    }
+}
+
+impl MarkdownItExt for MathOptions {}
+
+#[derive(Debug)]
+/// Math formula, its content is TeX source as written in the document.
+pub struct Math {
+    pub content: String,
+    /// Written as `$$...$$`.
+    pub display: bool,
+}
+
+impl NodeValue for Math {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        let (class, open, close) = if self.display {
+            ("math display", "\\[", "\\]")
+        } else {
+            ("math inline", "\\(", "\\)")
+        };
+        attrs.push(("class".into(), class.into()));
+
+        fmt.open("span", &attrs);
+        fmt.text(open);
+        fmt.text(&self.content);
+        fmt.text(close);
+        fmt.close("span");
+    }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => fmt.text(&self.content),
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "content": self.content, "display": self.display })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { content: field(data, "content")?, display: field(data, "display")? })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, MathOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: MathOptions) {
+    md.ext.insert(options);
+
+    if !md.inline.has_rule::<MathScanner>() {
+        md.inline.add_rule::<MathScanner>();
+    }
+}
+
+// finds closing `$` in `src` (which starts after opening `$`), skipping escaped characters
+fn find_closing(src: &str, options: &MathOptions) -> Option<usize> {
+    let tight = options.tight || options.strict;
+    let currency = options.currency || options.strict;
+    let mut prev = None;
+    let mut chars = src.char_indices().peekable();
+
+    while let Some((pos, ch)) = chars.next() {
+        match ch {
+            '\\' => { chars.next(); }
+            '\n' if options.strict => return None,
+            '$' if pos > 0 => {
+                let next = chars.peek().map(|(_, ch)| *ch);
+                // another price (`$5 or $10`), so the opening one is a price as well
+                if currency && next.is_some_and(|ch| ch.is_ascii_digit()) { return None; }
+                // `$` after a space looks like an opening one (`$10, not $x$`)
+                if tight && prev.is_some_and(char::is_whitespace) { return None; }
+                return Some(pos);
+            }
+            _ => {}
+        }
+        prev = Some(ch);
+    }
+
+    None
+}
+
+fn scan_display(src: &str) -> Option<(Node, usize)> {
+    let content_len = src[2..].find("$$")?;
+    let content = src[2..2 + content_len].trim();
+    if content.is_empty() { return None; }
+    Some((Node::new(Math { content: content.into(), display: true }), content_len + 4))
+}
+
+#[doc(hidden)]
+pub struct MathScanner;
+impl InlineRule for MathScanner {
+    const MARKER: char = '$';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        let parse_options = ParseOptions::of(state.root_ext);
+        let options = parse_options.ext_or::<MathOptions>(state.md).copied().unwrap_or_default();
+
+        let src = &state.src[state.pos..state.pos_max];
+        if src.starts_with("$$") {
+            let display = if options.display { scan_display(src) } else { None };
+            // dollars that don't start display math are kept as text,
+            // so that the second one isn't parsed as an opening `$`
+            return display.or_else(|| {
+                let len = src.len() - src.trim_start_matches('$').len();
+                Some((Node::new(Text { content: src[..len].into() }), len))
+            });
+        }
+
+        let prev = state.src[..state.pos].chars().next_back();
+        let next = src[1..].chars().next()?;
+
+        if (options.currency || options.strict) && prev.is_some_and(char::is_alphanumeric) { return None; }
+        if (options.tight || options.strict) && next.is_whitespace() { return None; }
+        if options.strict && next.is_ascii_digit() { return None; }
+
+        let content_len = find_closing(&src[1..], &options)?;
+        let content = &src[1..1 + content_len];
+        Some((Node::new(Math { content: content.into(), display: false }), content_len + 2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MathOptions;
+
+    fn run(src: &str, options: MathOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, options);
+        md.parse(src).render()
+    }
+
+    fn math(src: &str, options: MathOptions) -> Vec<String> {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, options);
+        md.parse(src).find_all::<super::Math>().map(|node| node.cast::<super::Math>().unwrap().content.clone()).collect()
+    }
+
+    #[test]
+    fn default_heuristics() {
+        let options = MathOptions::default();
+        assert_eq!(math("$a$ $b\\$c$ $ d $ $e $f$", options), ["a", "b\\$c", "f"]);
+        assert_eq!(math("from $5 to $10", options), Vec::<String>::new());
+        assert_eq!(math("US$5 and $x$1 $y$", options), ["y"]);
+        assert_eq!(math("$5 or $10, not $x$", options), ["x"]);
+        assert_eq!(math("$1 + 1$ and $a\nb$", options), ["1 + 1", "a\nb"]);
+        assert_eq!(math("\\$a$ `$b$` $$ c $$", options), ["c"]);
+    }
+
+    #[test]
+    fn loose() {
+        let options = MathOptions { tight: false, currency: false, ..Default::default() };
+        assert_eq!(math("$ a $ from $5 to $10", options), [" a ", "5 to "]);
+    }
+
+    #[test]
+    fn strict() {
+        let options = MathOptions { tight: false, currency: false, strict: true, display: false };
+        assert_eq!(math("$1 + 1$ $a\nb$ $ c$ $d$ $$e$$", options), ["d"]);
+        assert_eq!(math("$$$a$", MathOptions::default()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            run("$a<b$ and $$\\sum_i x_i$$", MathOptions::default()),
+            "<p><span class=\"math inline\">\\(a&lt;b\\)</span> and <span class=\"math display\">\\[\\sum_i x_i\\]</span></p>\n",
+        );
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        assert_eq!(md.parse("*$x$*").render_text(), "x\n");
+    }
+}
@@ -9,7 +9,12 @@
 //!  - footnotes
 //!  - identifiers, classes and attributes (`{#id .class key=value}`)
 //!
+//! Each plugin is only available with cargo feature of the same name (e.g. `tables`),
+//! [add] adds the enabled ones from the list above. All of them are enabled by default
+//! with `extra` feature.
+//!
 //! ```rust
+//! # #[cfg(all(feature = "smartquotes", feature = "strikethrough", feature = "typographer"))] {
 //! let md = &mut markdown_it::MarkdownIt::new();
 //! markdown_it::plugins::cmark::add(md);
 //! markdown_it::plugins::extra::add(md);
@@ -19,30 +24,107 @@
 //!
 //! let html = md.parse(r#"Markdown done "The Right Way(TM)""#).render();
 //! assert_eq!(html.trim(), r#"<p>Markdown done “The Right Way™”</p>"#);
+//! # }
 //! ```
+#[cfg(feature = "admonition")]
+pub mod admonition;
+#[cfg(feature = "attr_templates")]
+pub mod attr_templates;
+#[cfg(feature = "attrs")]
 pub mod attrs;
+#[cfg(feature = "beautify_links")]
 pub mod beautify_links;
+#[cfg(feature = "budget")]
+pub mod budget;
+#[cfg(feature = "callouts")]
+pub mod callouts;
+#[cfg(feature = "conditional")]
+pub mod conditional;
+#[cfg(feature = "critic")]
+pub mod critic;
+#[cfg(feature = "dates")]
+pub mod dates;
+#[cfg(feature = "details")]
+pub mod details;
+#[cfg(feature = "email_reply")]
+pub mod email_reply;
+#[cfg(feature = "external_links")]
+pub mod external_links;
+#[cfg(feature = "footnote")]
 pub mod footnote;
+#[cfg(feature = "front_matter")]
+pub mod front_matter;
+#[cfg(feature = "heading_anchors")]
 pub mod heading_anchors;
+#[cfg(feature = "heading_text")]
+pub mod heading_text;
+#[cfg(feature = "image_alt")]
+pub mod image_alt;
+#[cfg(feature = "images")]
+pub mod images;
+#[cfg(feature = "include")]
+pub mod include;
+#[cfg(feature = "link_options")]
+pub mod link_options;
+#[cfg(feature = "link_resolver")]
+pub mod link_resolver;
 #[cfg(feature = "linkify")]
 pub mod linkify;
+#[cfg(feature = "lint")]
+pub mod lint;
+#[cfg(feature = "math")]
+pub mod math;
+#[cfg(feature = "print")]
+pub mod print;
+#[cfg(feature = "quote_attribution")]
+pub mod quote_attribution;
+#[cfg(feature = "raw_attribute")]
+pub mod raw_attribute;
+#[cfg(feature = "reflow")]
+pub mod reflow;
+#[cfg(feature = "ruby")]
+pub mod ruby;
+#[cfg(feature = "sections")]
+pub mod sections;
+#[cfg(feature = "smartquotes")]
 pub mod smartquotes;
+#[cfg(feature = "spacing")]
+pub mod spacing;
+#[cfg(feature = "spoiler")]
+pub mod spoiler;
+#[cfg(feature = "strikethrough")]
 pub mod strikethrough;
+#[cfg(feature = "substitutions")]
+pub mod substitutions;
 #[cfg(feature = "syntect")]
 pub mod syntect;
+#[cfg(feature = "tables")]
 pub mod tables;
+#[cfg(feature = "typographer")]
 pub mod typographer;
+#[cfg(feature = "unicode")]
+pub mod unicode;
+#[cfg(feature = "url_filter")]
+pub mod url_filter;
+#[cfg(feature = "variables")]
+pub mod variables;
 
 use crate::MarkdownIt;
 
+#[allow(unused_variables)] // if all of them are disabled
 pub fn add(md: &mut MarkdownIt) {
+    #[cfg(feature = "strikethrough")]
     strikethrough::add(md);
+    #[cfg(feature = "beautify_links")]
     beautify_links::add(md);
     #[cfg(feature = "linkify")]
     linkify::add(md);
+    #[cfg(feature = "tables")]
     tables::add(md);
     #[cfg(feature = "syntect")]
     syntect::add(md);
+    #[cfg(feature = "typographer")]
     typographer::add(md);
+    #[cfg(feature = "smartquotes")]
     smartquotes::add(md);
 }
@@ -0,0 +1,259 @@
+//! Output profile for print stylesheets and PDF generation in headless browsers.
+//!
+//!  - headings of selected levels get `break-before` class, so each of them
+//!    can start a new page
+//!  - tables and figures get `avoid-break` class, code blocks are wrapped
+//!    into `<div class="avoid-break">`, so they aren't split between pages
+//!  - footnotes can be moved to the place where they are referenced, as
+//!    `<span class="footnote">` elements (see [PrintOptions::page_footnotes]),
+//!    for print engines that place them at the bottom of the page
+//!
+//! Classes are only hints, they need a stylesheet like this one:
+//!
+//! ```css
+//! @media print {
+//!   .break-before { break-before: page; }
+//!   .avoid-break { break-inside: avoid; }
+//!   .footnote { float: footnote; }
+//! }
+//! ```
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::print::{self, PrintOptions};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! print::add(md, PrintOptions::default());
+//!
+//! let html = md.parse("# Intro\n\n### Details\n\n    code").render();
+//! assert_eq!(html, "<h1 class=\"break-before\">Intro</h1>\n<h3>Details</h3>\n\
+//!     <div class=\"avoid-break\">\n<pre><code>code\n</code></pre>\n</div>\n");
+//! ```
+use std::collections::HashMap;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::Text;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::block::code::CodeBlock;
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::plugins::cmark::block::heading::ATXHeading;
+use crate::plugins::cmark::block::lheading::SetextHeader;
+use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::plugins::extra::footnote::{self, FootnoteMarker};
+use crate::plugins::extra::footnote::back_refs::FootnoteRefAnchor;
+use crate::plugins::extra::footnote::definitions::FootnoteDefinition;
+use crate::plugins::extra::footnote::references::FootnoteReference;
+use crate::plugins::extra::images::Figure;
+use crate::plugins::extra::tables::Table;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+
+#[derive(Debug, Clone)]
+/// Options for [add].
+pub struct PrintOptions {
+    /// Heading levels that get `break-before` class, `[1, 2]` by default.
+    pub break_before: Vec<u8>,
+    /// Mark tables, figures and code blocks with `avoid-break` class, enabled by default.
+    pub avoid_breaks: bool,
+    /// Replace footnote references with footnote contents in `<span class="footnote">`
+    /// and remove the list of footnotes, disabled by default.
+    pub page_footnotes: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            break_before: vec![1, 2],
+            avoid_breaks: true,
+            page_footnotes: false,
+        }
+    }
+}
+
+impl MarkdownItExt for PrintOptions {}
+
+pub fn add(md: &mut MarkdownIt, options: PrintOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<PrintRule>() {
+        // runs last, so that footnotes are already collected
+        md.add_rule::<PrintRule>()
+            .after_all();
+    }
+}
+
+#[derive(Debug)]
+/// Block that shouldn't be split between pages (code blocks are wrapped into it).
+pub struct KeepTogether;
+
+impl NodeValue for KeepTogether {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), "avoid-break".into()));
+        fmt.cr();
+        fmt.open("div", &attrs);
+        fmt.cr();
+        fmt.contents(&node.children);
+        fmt.cr();
+        fmt.close("div");
+        fmt.cr();
+    }
+}
+
+#[derive(Debug)]
+/// Footnote contents placed where it is referenced, see [PrintOptions::page_footnotes].
+pub struct PageFootnote;
+
+impl NodeValue for PageFootnote {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), "footnote".into()));
+        fmt.open("span", &attrs);
+        fmt.contents(&node.children);
+        fmt.close("span");
+    }
+}
+
+#[derive(Debug)]
+/// Repeated reference to a footnote already placed on the page (without a link).
+pub struct PageFootnoteRef {
+    pub marker: String,
+}
+
+impl NodeValue for PageFootnoteRef {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), "footnote-ref".into()));
+        fmt.open("sup", &attrs);
+        fmt.text(&self.marker);
+        fmt.close("sup");
+    }
+}
+
+fn heading_level(node: &Node) -> Option<u8> {
+    if let Some(heading) = node.cast::<ATXHeading>() {
+        Some(heading.level)
+    } else {
+        node.cast::<SetextHeader>().map(|heading| heading.level)
+    }
+}
+
+fn add_break_hints(root: &mut Node, options: &PrintOptions) {
+    root.walk_mut(|node, _| {
+        if heading_level(node).is_some_and(|level| options.break_before.contains(&level)) {
+            node.attrs.push(("class".into(), "break-before".into()));
+        }
+
+        if !options.avoid_breaks || node.is::<KeepTogether>() { return; }
+        if node.is::<Table>() || node.is::<Figure>() {
+            node.attrs.push(("class".into(), "avoid-break".into()));
+        }
+        for child in node.children.iter_mut() {
+            if child.is::<CodeBlock>() || child.is::<CodeFence>() {
+                child.wrap_in(KeepTogether);
+            }
+        }
+    });
+}
+
+fn place_footnotes(root: &mut Node) {
+    let mut contents = HashMap::new();
+    for mut def in footnote::extract_definitions(root) {
+        let Some(def_id) = def.cast::<FootnoteDefinition>().and_then(|def| def.def_id) else { continue; };
+
+        // paragraphs are joined, since footnote is rendered inline
+        let mut children = Vec::new();
+        for mut block in std::mem::take(&mut def.children) {
+            if !block.is::<Paragraph>() {
+                children.push(block);
+                continue;
+            }
+            if !children.is_empty() {
+                children.push(Node::new(Text { content: " ".into() }));
+            }
+            children.extend(std::mem::take(&mut block.children).into_iter().filter(|node| !node.is::<FootnoteRefAnchor>()));
+        }
+        contents.insert(def_id, children);
+    }
+
+    root.walk_mut(|node, _| {
+        let Some(reference) = node.cast::<FootnoteReference>() else { return; };
+        let mut replacement = match contents.get_mut(&reference.def_id) {
+            Some(children) if !children.is_empty() => {
+                let mut footnote = Node::new(PageFootnote);
+                footnote.children = std::mem::take(children);
+                footnote
+            }
+            _ => {
+                let marker = match node.ext.get::<FootnoteMarker>() {
+                    Some(marker) => marker.0.clone(),
+                    None => format!("[{}]", reference.def_id),
+                };
+                Node::new(PageFootnoteRef { marker })
+            }
+        };
+        replacement.srcmap = node.srcmap;
+        replacement.attrs = std::mem::take(&mut node.attrs);
+        *node = replacement;
+    });
+}
+
+#[doc(hidden)]
+pub struct PrintRule;
+impl CoreRule for PrintRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let Some(options) = parse_options.ext_or::<PrintOptions>(md) else { return; };
+
+        add_break_hints(root, options);
+        if options.page_footnotes {
+            place_footnotes(root);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrintOptions;
+
+    fn run(src: &str, options: PrintOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::tables::add(md);
+        crate::plugins::extra::footnote::add(md);
+        crate::plugins::extra::images::add(md, crate::plugins::extra::images::ImagesOptions {
+            figure: true,
+            ..Default::default()
+        });
+        super::add(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn break_hints() {
+        let options = PrintOptions { break_before: vec![2], ..Default::default() };
+        assert_eq!(
+            run("Title\n=====\n\nChapter\n-------\n\n![a](a.png)\n\n| a |\n|---|\n\n> ```\n> x\n> ```", options),
+            "<h1>Title</h1>\n<h2 class=\"break-before\">Chapter</h2>\n\
+             <figure class=\"avoid-break\"><img src=\"a.png\" alt=\"a\"></figure>\n\
+             <table class=\"avoid-break\">\n<thead>\n<tr>\n<th>a</th>\n</tr>\n</thead>\n</table>\n\
+             <blockquote>\n<div class=\"avoid-break\">\n<pre><code>x\n</code></pre>\n</div>\n</blockquote>\n"
+        );
+    }
+
+    #[test]
+    fn disabled_hints() {
+        let options = PrintOptions { break_before: vec![], avoid_breaks: false, ..Default::default() };
+        assert_eq!(run("# a\n\n    b", options), "<h1>a</h1>\n<pre><code>b\n</code></pre>\n");
+    }
+
+    #[test]
+    fn page_footnotes() {
+        let options = PrintOptions { page_footnotes: true, ..Default::default() };
+        assert_eq!(
+            run("a[^1] b[^1] c^[*inline*]\n\n[^1]: One\n\n    Two", options),
+            "<p>a<span class=\"footnote\">One Two</span> b<sup class=\"footnote-ref\">[1]</sup> \
+             c<span class=\"footnote\"><em>inline</em></span></p>\n"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Author, timestamp and message id of quoted messages (e.g. in chat apps).
+//!
+//! Applications attach [QuoteAttribution] to blockquotes with [set_attribution],
+//! or let this plugin read it from the first line of a blockquote:
+//!
+//! `> @author (timestamp) #message-id:`
+//!
+//! (timestamp and message id are optional). Attribution is rendered as
+//! `<header class="quote-attribution">` inside the blockquote, message id
+//! is added to the blockquote as `data-message-id` attribute.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::quote_attribution::add(md);
+//!
+//! let html = md.parse("> @alice (2024-05-01 10:30) #m42:\n> Lunch at noon?\n\nSure!").render();
+//! assert_eq!(html, "<blockquote data-message-id=\"m42\">\n<header class=\"quote-attribution\">\
+//!     <cite>alice</cite> <time>2024-05-01 10:30</time></header>\n\
+//!     <p>Lunch at noon?</p>\n</blockquote>\n<p>Sure!</p>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::{MarkdownItExt, NodeExt};
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::plugins::cmark::block::blockquote::Blockquote;
+use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::plugins::cmark::inline::newline::{Hardbreak, Softbreak};
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static ATTRIBUTION_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^@(\S+)(?:\s+\(([^)]*)\))?(?:\s+#(\S+?))?\s*:$"#).unwrap()
+});
+
+/// Function that reads attribution from the first line of a blockquote
+/// (as plain text), see [add_with_parser].
+pub type ParseFn = fn (&str) -> Option<QuoteAttribution>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Information about the quoted message, stored in blockquote node.
+pub struct QuoteAttribution {
+    pub author: Option<String>,
+    /// Timestamp as written by the application, it isn't parsed or formatted.
+    pub timestamp: Option<String>,
+    pub message_id: Option<String>,
+}
+
+impl NodeExt for QuoteAttribution {}
+
+impl QuoteAttribution {
+    /// Attribution of a blockquote node, if any.
+    pub fn of(node: &Node) -> Option<&Self> {
+        node.ext.get::<Self>()
+    }
+
+    /// Read attribution from a line written as `@author (timestamp) #message-id:`.
+    pub fn parse(line: &str) -> Option<Self> {
+        let captures = ATTRIBUTION_RE.captures(line.trim())?;
+        Some(Self {
+            author: Some(captures[1].to_owned()),
+            timestamp: captures.get(2).map(|m| m.as_str().trim().to_owned()).filter(|s| !s.is_empty()),
+            message_id: captures.get(3).map(|m| m.as_str().to_owned()),
+        })
+    }
+}
+
+#[derive(Debug)]
+/// Header with attribution, first child of the blockquote.
+pub struct QuoteHeader(pub QuoteAttribution);
+
+impl NodeValue for QuoteHeader {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), "quote-attribution".into()));
+
+        fmt.cr();
+        fmt.open("header", &attrs);
+        if let Some(author) = &self.0.author {
+            fmt.open("cite", &[]);
+            fmt.text(author);
+            fmt.close("cite");
+        }
+        if let Some(timestamp) = &self.0.timestamp {
+            if self.0.author.is_some() { fmt.text(" "); }
+            fmt.open("time", &[]);
+            fmt.text(timestamp);
+            fmt.close("time");
+        }
+        fmt.close("header");
+        fmt.cr();
+    }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                let header = match (&self.0.author, &self.0.timestamp) {
+                    (Some(author), Some(timestamp)) => format!("{author} ({timestamp}):"),
+                    (Some(author), None) => format!("{author}:"),
+                    (None, Some(timestamp)) => format!("({timestamp}):"),
+                    (None, None) => return true,
+                };
+                fmt.cr();
+                fmt.text(&header);
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "author": self.0.author, "timestamp": self.0.timestamp, "message_id": self.0.message_id })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self(QuoteAttribution {
+            author: field(data, "author")?,
+            timestamp: field(data, "timestamp")?,
+            message_id: field(data, "message_id")?,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AttributionParser(ParseFn);
+impl MarkdownItExt for AttributionParser {}
+
+/// Read attribution from the first line of blockquotes, see [module documentation](self).
+pub fn add(md: &mut MarkdownIt) {
+    add_with_parser(md, QuoteAttribution::parse);
+}
+
+/// Same as [add], with a custom convention for the first line.
+pub fn add_with_parser(md: &mut MarkdownIt, parse: ParseFn) {
+    md.ext.insert(AttributionParser(parse));
+
+    if !md.has_rule::<QuoteAttributionRule>() {
+        md.add_rule::<QuoteAttributionRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+/// Attach attribution to a blockquote node, replacing existing one.
+pub fn set_attribution(node: &mut Node, attribution: QuoteAttribution) {
+    node.children.retain(|child| !child.is::<QuoteHeader>());
+    node.attrs.retain(|(name, _)| name != "data-message-id");
+
+    if let Some(message_id) = &attribution.message_id {
+        node.attrs.push(("data-message-id".into(), message_id.clone()));
+    }
+    if attribution.author.is_some() || attribution.timestamp.is_some() {
+        node.children.insert(0, Node::new(QuoteHeader(attribution.clone())));
+    }
+    node.ext.insert(attribution);
+}
+
+// returns attribution from the first line of the paragraph and number of nodes in that line
+fn first_line(paragraph: &Node, parse: ParseFn) -> Option<(QuoteAttribution, usize)> {
+    let end = paragraph.children.iter()
+        .position(|node| node.is::<Softbreak>() || node.is::<Hardbreak>())
+        .unwrap_or(paragraph.children.len());
+    let text : String = paragraph.children[..end].iter().map(|node| node.collect_text()).collect();
+    parse(&text).map(|attribution| (attribution, end))
+}
+
+#[doc(hidden)]
+pub struct QuoteAttributionRule;
+impl CoreRule for QuoteAttributionRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let Some(AttributionParser(parse)) = md.ext.get::<AttributionParser>().copied() else { return; };
+
+        root.walk_mut(|node, _| {
+            if !node.is::<Blockquote>() || QuoteAttribution::of(node).is_some() { return; }
+            let Some(paragraph) = node.children.first_mut().filter(|child| child.is::<Paragraph>()) else { return; };
+            let Some((attribution, end)) = first_line(paragraph, parse) else { return; };
+
+            // remove the first line along with the line break after it
+            let end = (end + 1).min(paragraph.children.len());
+            paragraph.children.drain(..end);
+            if paragraph.children.is_empty() {
+                node.children.remove(0);
+            }
+
+            set_attribution(node, attribution);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuoteAttribution;
+
+    fn parse(src: &str) -> crate::Node {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src)
+    }
+
+    #[test]
+    fn parse_line() {
+        assert_eq!(QuoteAttribution::parse("@bob:"), Some(QuoteAttribution { author: Some("bob".into()), ..Default::default() }));
+        assert_eq!(QuoteAttribution::parse("@bob #x1 :"), Some(QuoteAttribution {
+            author: Some("bob".into()),
+            message_id: Some("x1".into()),
+            ..Default::default()
+        }));
+        assert_eq!(QuoteAttribution::parse("@bob you're right"), None);
+        assert_eq!(QuoteAttribution::parse("bob:"), None);
+    }
+
+    #[test]
+    fn header_only_line() {
+        let ast = parse("> @bob:\n>\n> > @alice (yesterday):\n> > hi");
+        let html = ast.render();
+        assert_eq!(html, "<blockquote>\n<header class=\"quote-attribution\"><cite>bob</cite></header>\n\
+            <blockquote>\n<header class=\"quote-attribution\"><cite>alice</cite> <time>yesterday</time></header>\n\
+            <p>hi</p>\n</blockquote>\n</blockquote>\n");
+        assert_eq!(ast.render_text(), "bob:\nalice (yesterday):\nhi\n");
+        assert_eq!(QuoteAttribution::of(&ast.children[0]).unwrap().author.as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn no_attribution() {
+        assert_eq!(parse("> @bob you're right\n> indeed").render(), "<blockquote>\n<p>@bob you're right\nindeed</p>\n</blockquote>\n");
+    }
+
+    #[test]
+    fn set_by_application() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let mut ast = md.parse("> hello");
+        super::set_attribution(&mut ast.children[0], QuoteAttribution {
+            author: None,
+            timestamp: Some("10:00".into()),
+            message_id: Some("7".into()),
+        });
+        assert_eq!(ast.render(), "<blockquote data-message-id=\"7\">\n<header class=\"quote-attribution\"><time>10:00</time></header>\n<p>hello</p>\n</blockquote>\n");
+
+        super::set_attribution(&mut ast.children[0], QuoteAttribution::default());
+        assert_eq!(ast.render(), "<blockquote>\n<p>hello</p>\n</blockquote>\n");
+    }
+}
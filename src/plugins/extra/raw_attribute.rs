@@ -0,0 +1,202 @@
+//! Raw content for a specific output format, as in Pandoc's `raw_attribute` extension.
+//!
+//! Code span or code fence followed by `{=format}` is passed through verbatim
+//! by the renderer that produces `format`, and dropped by all the others.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::raw_attribute::add(md);
+//!
+//! let html = md.parse("`<b>bold</b>`{=html} and `\\textbf{bold}`{=latex}").render();
+//! assert_eq!(html.trim(), "<p><b>bold</b> and </p>");
+//!
+//! let html = md.parse("```{=html}\n<svg></svg>\n```").render();
+//! assert_eq!(html.trim(), "<svg></svg>");
+//! ```
+//!
+//! Note that raw html is not sanitized in any way, so same precautions as for
+//! [html plugin](crate::plugins::html) apply.
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::CoreRule;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::Text;
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::plugins::cmark::inline::backticks::CodeInline;
+use crate::plugins::extra::attrs::AttrsRule;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static RAW_ATTR_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\{=([A-Za-z0-9_-]+)\}").unwrap()
+});
+
+#[derive(Debug)]
+/// Block of content that is only rendered by renderer producing `format`.
+pub struct RawBlock {
+    pub format: String,
+    pub content: String,
+}
+
+impl NodeValue for RawBlock {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        self.render_format(node, fmt, "html");
+    }
+
+    fn render_format(&self, _: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        // every format is supported, it's just empty for most of them
+        if self.format.eq_ignore_ascii_case(format) {
+            fmt.cr();
+            fmt.text_raw(&self.content);
+            fmt.cr();
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "format": self.format, "content": self.content })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { format: field(data, "format")?, content: field(data, "content")? })
+    }
+}
+
+#[derive(Debug)]
+/// Inline content that is only rendered by renderer producing `format`.
+pub struct RawInline {
+    pub format: String,
+    pub content: String,
+}
+
+impl NodeValue for RawInline {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        self.render_format(node, fmt, "html");
+    }
+
+    fn render_format(&self, _: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        if self.format.eq_ignore_ascii_case(format) {
+            fmt.text_raw(&self.content);
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "format": self.format, "content": self.content })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { format: field(data, "format")?, content: field(data, "content")? })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.add_rule::<RawAttributeRule>()
+        .after::<InlineParserRule>()
+        .before::<AttrsRule>();
+}
+
+#[doc(hidden)]
+pub struct RawAttributeRule;
+impl CoreRule for RawAttributeRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        root.walk_mut(|node, _| {
+            // ```{=format}
+            if let Some(fence) = node.cast_mut::<CodeFence>() {
+                let info = fence.info.trim();
+                if let Some(captures) = RAW_ATTR_RE.captures(info) {
+                    if captures.get(0).unwrap().end() == info.len() {
+                        let format = captures[1].to_owned();
+                        let content = std::mem::take(&mut fence.content);
+                        node.replace(RawBlock { format, content });
+                    }
+                }
+                return;
+            }
+
+            // `code`{=format}
+            let mut idx = 0;
+            while idx + 1 < node.children.len() {
+                if !node.children[idx].is::<CodeInline>() { idx += 1; continue; }
+
+                let Some(text) = node.children[idx + 1].cast_mut::<Text>() else { idx += 1; continue; };
+                let Some(captures) = RAW_ATTR_RE.captures(&text.content) else { idx += 1; continue; };
+                let format = captures[1].to_owned();
+                let attr_len = captures.get(0).unwrap().end();
+
+                text.content.drain(..attr_len);
+                let text_is_empty = text.content.is_empty();
+
+                let next = &mut node.children[idx + 1];
+                let attr_end = next.srcmap.map(|map| {
+                    let (start, end) = map.get_byte_offsets();
+                    next.srcmap = Some(SourcePos::new(start + attr_len, end));
+                    start + attr_len
+                });
+
+                if text_is_empty {
+                    node.children.remove(idx + 1);
+                }
+
+                let code = &mut node.children[idx];
+                let content = code.collect_text();
+                code.children = Vec::new();
+                code.replace(RawInline { format, content });
+                if let (Some(map), Some(end)) = (code.srcmap, attr_end) {
+                    code.srcmap = Some(SourcePos::new(map.get_byte_offsets().0, end));
+                }
+
+                idx += 1;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn inline_raw() {
+        assert_eq!(run("a `<br>`{=html} b"), "<p>a <br> b</p>\n");
+        assert_eq!(run("a `<br>`{=HTML}"), "<p>a <br></p>\n");
+        assert_eq!(run("a `\\LaTeX`{=latex} b"), "<p>a  b</p>\n");
+    }
+
+    #[test]
+    fn inline_not_raw() {
+        assert_eq!(run("a `<br>` {=html}"), "<p>a <code>&lt;br&gt;</code> {=html}</p>\n");
+        assert_eq!(run("a `<br>`{=}"), "<p>a <code>&lt;br&gt;</code>{=}</p>\n");
+    }
+
+    #[test]
+    fn block_raw() {
+        assert_eq!(run("``` {=html}\n<div>\n```\ntext"), "<div>\n<p>text</p>\n");
+        assert_eq!(run("```{=latex}\n\\newpage\n```\ntext"), "<p>text</p>\n");
+        assert_eq!(run("```html\n<div>\n```"), "<pre><code class=\"language-html\">&lt;div&gt;\n</code></pre>\n");
+    }
+
+    #[test]
+    fn srcmaps() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        let node = md.parse("`x`{=html}y");
+        let para = &node.children[0];
+        assert_eq!(para.children[0].srcmap.unwrap().get_byte_offsets(), (0, 10));
+        assert_eq!(para.children[1].srcmap.unwrap().get_byte_offsets(), (10, 11));
+    }
+}
@@ -0,0 +1,218 @@
+//! Rewrap paragraphs in markdown source.
+//!
+//! Converts between hard-wrapped paragraphs, one sentence per line (semantic
+//! linefeeds) and one line per paragraph. Only line breaks inside paragraphs
+//! are changed, everything else (including hard breaks) is kept as is.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::reflow::{reflow, ReflowMode};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let src = "> First sentence. Second\n> sentence.";
+//! assert_eq!(reflow(md, src, ReflowMode::Unwrap), "> First sentence. Second sentence.");
+//! assert_eq!(reflow(md, src, ReflowMode::Sentences), "> First sentence.\n> Second sentence.");
+//! assert_eq!(reflow(md, src, ReflowMode::Width(20)), "> First sentence.\n> Second sentence.");
+//! ```
+use crate::parser::inline::Text;
+use crate::plugins::cmark::block::paragraph::Paragraph;
+use crate::plugins::cmark::block::list::ListItem;
+use crate::plugins::cmark::inline::newline::{Hardbreak, Softbreak};
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Where to put line breaks inside paragraphs.
+pub enum ReflowMode {
+    /// Join all lines, so each paragraph is on a single line.
+    Unwrap,
+    /// Put each sentence on its own line.
+    Sentences,
+    /// Wrap lines longer than a given number of characters (including
+    /// blockquote markers and indentation). Words longer than that are not split.
+    Width(usize),
+}
+
+/// Parse markdown source and return it with paragraphs rewrapped.
+pub fn reflow(md: &MarkdownIt, src: &str, mode: ReflowMode) -> String {
+    let ast = md.parse(src);
+    let mut result = String::with_capacity(src.len());
+    let mut pos = 0;
+
+    for nodes in paragraphs(&ast) {
+        let Some(start) = nodes.first().and_then(|node| node.srcmap) else { continue; };
+        let Some(end) = nodes.last().and_then(|node| node.srcmap) else { continue; };
+        let (start, end) = (start.get_byte_offsets().0, end.get_byte_offsets().1);
+        if start < pos { continue; }
+        result.push_str(&src[pos..start]);
+        result.push_str(&reflow_paragraph(nodes, src, start, end, mode));
+        pos = end;
+    }
+
+    result.push_str(&src[pos..]);
+    result
+}
+
+// inline content of paragraphs, tight lists have it directly inside list items
+fn paragraphs(root: &Node) -> Vec<&[Node]> {
+    let is_inline = |node: &Node| {
+        node.is::<Softbreak>() || node.is::<Hardbreak>() || !node.render().ends_with('\n')
+    };
+
+    let mut result = Vec::new();
+    for node in root.descendants() {
+        if node.is::<Paragraph>() {
+            result.push(node.children.as_slice());
+        } else if node.is::<ListItem>() {
+            let mut children = node.children.as_slice();
+            while !children.is_empty() {
+                let len = children.iter().position(|node| !is_inline(node)).unwrap_or(children.len());
+                if len > 0 { result.push(&children[..len]); }
+                children = &children[(len + 1).min(children.len())..];
+            }
+        }
+    }
+    result
+}
+
+// place in paragraph source where line break can be inserted or removed
+struct Gap {
+    start: usize,
+    end: usize,
+    softbreak: bool,
+}
+
+fn find_gaps(nodes: &[Node], src: &str) -> Vec<Gap> {
+    let mut gaps = Vec::<Gap>::new();
+
+    for node in nodes.iter().flat_map(Node::descendants) {
+        let Some(map) = node.srcmap else { continue; };
+        let (start, end) = map.get_byte_offsets();
+
+        if node.is::<Softbreak>() {
+            gaps.push(Gap { start, end, softbreak: true });
+        } else if let Some(text) = node.cast::<Text>() {
+            // escapes and entities make text differ from its source,
+            // such nodes are left as is
+            if src.get(start..end) != Some(text.content.as_str()) { continue; }
+
+            let bytes = text.content.as_bytes();
+            let mut pos = 0;
+            while pos < bytes.len() {
+                if bytes[pos] != b' ' {
+                    pos += 1;
+                    continue;
+                }
+                let space_start = pos;
+                while pos < bytes.len() && bytes[pos] == b' ' { pos += 1; }
+                gaps.push(Gap { start: start + space_start, end: start + pos, softbreak: false });
+            }
+        }
+    }
+
+    gaps.retain(|gap| gap.start < gap.end);
+    gaps
+}
+
+fn reflow_paragraph(nodes: &[Node], src: &str, start: usize, end: usize, mode: ReflowMode) -> String {
+    let line_start = src[..start].rfind('\n').map_or(0, |pos| pos + 1);
+    // continuation lines keep blockquote markers, list markers are replaced with spaces
+    let prefix : String = src[line_start..start].chars()
+        .map(|ch| if ch == '>' || ch.is_whitespace() { ch } else { ' ' })
+        .collect();
+    let prefix_width = prefix.chars().count();
+
+    let gaps = find_gaps(nodes, src);
+    let mut result = String::new();
+    let mut column = prefix_width;
+    let mut pos = start;
+
+    for (idx, gap) in gaps.iter().enumerate() {
+        let segment = &src[pos..gap.start];
+        match segment.rfind('\n') {
+            Some(nl) => column = segment[nl + 1..].chars().count(),
+            None => column += segment.chars().count(),
+        }
+        result.push_str(segment);
+
+        let next_word = &src[gap.end..gaps.get(idx + 1).map_or(end, |gap| gap.start)];
+        let next_width = next_word.split('\n').next().unwrap_or_default().chars().count();
+
+        let line_break = can_start_line(next_word) && match mode {
+            ReflowMode::Unwrap => false,
+            ReflowMode::Sentences => ends_sentence(&result),
+            ReflowMode::Width(width) => column > prefix_width && column + 1 + next_width > width,
+        };
+
+        if line_break {
+            result.push('\n');
+            result.push_str(&prefix);
+            column = prefix_width;
+        } else if gap.softbreak {
+            result.push(' ');
+            column += 1;
+        } else {
+            result.push_str(&src[gap.start..gap.end]);
+            column += gap.end - gap.start;
+        }
+
+        pos = gap.end;
+    }
+
+    result.push_str(&src[pos..end]);
+    result
+}
+
+fn ends_sentence(text: &str) -> bool {
+    let text = text.trim_end_matches(['"', '\'', ')', ']', '*', '_', '”', '’']);
+    text.ends_with(['.', '!', '?'])
+}
+
+// line starting with this word must not be parsed as a new block
+// (list item, heading, blockquote, etc.)
+fn can_start_line(word: &str) -> bool {
+    let word = word.lines().next().unwrap_or_default();
+    if word.starts_with(['>', '<', '|']) || word.starts_with("```") || word.starts_with("~~~") { return false; }
+    // list markers, headings, thematic breaks and setext underlines
+    if word.chars().all(|ch| matches!(ch, '#' | '-' | '+' | '*' | '=' | '_')) { return false; }
+    let digits = word.len() - word.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+    !(digits > 0 && word[digits..].starts_with(['.', ')']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reflow, ReflowMode};
+    use crate::MarkdownIt;
+
+    fn run(src: &str, mode: ReflowMode) -> String {
+        let md = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        reflow(md, src, mode)
+    }
+
+    #[test]
+    fn unwrap() {
+        assert_eq!(run("a\nb\n  c\n\nd\ne", ReflowMode::Unwrap), "a b c\n\nd e");
+        assert_eq!(run("- a\n  b\n\n      code\n      block", ReflowMode::Unwrap), "- a b\n\n      code\n      block");
+        assert_eq!(run("a  \nb\nc", ReflowMode::Unwrap), "a  \nb c");
+        assert_eq!(run("# a\nb\nc", ReflowMode::Unwrap), "# a\nb c");
+    }
+
+    #[test]
+    fn sentences() {
+        assert_eq!(run("One. Two\nthree? *Four.* Five", ReflowMode::Sentences), "One.\nTwo three?\n*Four.*\nFive");
+        assert_eq!(run("1. One. Two.", ReflowMode::Sentences), "1. One.\n   Two.");
+        assert_eq!(run("- # A. B.\n- C. D.\n  - E. F.", ReflowMode::Sentences), "- # A. B.\n- C.\n  D.\n  - E.\n    F.");
+        assert_eq!(run("See ch. 2. Next", ReflowMode::Sentences), "See ch. 2.\nNext");
+    }
+
+    #[test]
+    fn width() {
+        assert_eq!(run("aaa bbb ccc ddd", ReflowMode::Width(7)), "aaa bbb\nccc ddd");
+        assert_eq!(run("aaa\nbbb\nccc", ReflowMode::Width(80)), "aaa bbb ccc");
+        assert_eq!(run("> - aaa bbb ccc", ReflowMode::Width(10)), "> - aaa\n>   bbb\n>   ccc");
+        assert_eq!(run("aaaaaaaaaa b", ReflowMode::Width(5)), "aaaaaaaaaa\nb");
+        assert_eq!(run("aaa - b", ReflowMode::Width(5)), "aaa -\nb");
+        assert_eq!(run("a \\* b", ReflowMode::Width(1)), "a\n\\*\nb");
+    }
+}
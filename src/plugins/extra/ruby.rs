@@ -0,0 +1,200 @@
+//! Ruby annotations (furigana) for East Asian text, written as `{漢字|かんじ}`
+//! or `[漢字]{かんじ}`.
+//!
+//! Reading can be split into groups, one per character of the base text
+//! (`{漢字|かん|じ}`), to place each group above its character:
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::ruby::add(md);
+//!
+//! let html = md.parse("{東京|とうきょう} and [漢字]{かん|じ}").render();
+//! assert_eq!(html.trim(), "<p><ruby>東京<rt>とうきょう</rt></ruby> and \
+//!     <ruby>漢<rt>かん</rt>字<rt>じ</rt></ruby></p>");
+//! ```
+//!
+//! Separators can be changed with [RubyOptions], e.g. to use `.` between groups
+//! like markdown-it-ruby does (`{漢字|かん.じ}`).
+use crate::generics::inline::full_link::LinkScanner;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::{InlineRule, InlineState};
+use crate::parser::options::ParseOptions;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct RubyOptions {
+    /// Separator between base text and reading in `{base|reading}`, `|` by default.
+    pub separator: char,
+    /// Separator between groups of a reading, `|` by default.
+    pub group_separator: char,
+    /// Parse `[base]{reading}` syntax, enabled by default.
+    pub brackets: bool,
+}
+
+impl Default for RubyOptions {
+    fn default() -> Self {
+        Self {
+            separator: '|',
+            group_separator: '|',
+            brackets: true,
+        }
+    }
+}
+
+impl MarkdownItExt for RubyOptions {}
+
+#[derive(Debug)]
+/// Base text with its reading, split into pairs if reading is given per character.
+pub struct Ruby {
+    pub pairs: Vec<(String, String)>,
+}
+
+impl NodeValue for Ruby {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.open("ruby", &node.attrs);
+        for (base, reading) in self.pairs.iter() {
+            fmt.text(base);
+            fmt.open("rt", &[]);
+            fmt.text(reading);
+            fmt.close("rt");
+        }
+        fmt.close("ruby");
+    }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => for (base, _) in self.pairs.iter() { fmt.text(base); },
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "pairs": self.pairs })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { pairs: field(data, "pairs")? })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, RubyOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: RubyOptions) {
+    md.ext.insert(options);
+
+    if !md.inline.has_rule::<RubyScanner>() {
+        md.inline.add_rule::<RubyScanner>();
+        // `[base]` followed by `{reading}` is a shortcut reference if it's defined
+        md.inline.add_rule::<RubyBracketScanner>().after::<LinkScanner<false>>();
+    }
+}
+
+// pairs base text with groups of reading if there is one group per character,
+// uses whole reading for whole base text otherwise
+//
+// text around separators can't be padded with spaces, so `{x | x > 0}` stays as is
+fn make_pairs(base: &str, reading: &str, group_separator: char) -> Option<Ruby> {
+    let groups = reading.split(group_separator).collect::<Vec<_>>();
+    if !is_unpadded(base) || !groups.iter().all(|group| is_unpadded(group)) { return None; }
+
+    let pairs = if groups.len() > 1 && groups.len() == base.chars().count() {
+        base.chars().zip(groups).map(|(ch, group)| (ch.to_string(), group.to_owned())).collect()
+    } else {
+        vec![(base.to_owned(), groups.concat())]
+    };
+
+    Some(Ruby { pairs })
+}
+
+fn is_unpadded(text: &str) -> bool {
+    !text.is_empty() && !text.starts_with(char::is_whitespace) && !text.ends_with(char::is_whitespace)
+}
+
+// finds `}` closing a group started at the beginning of `src`, groups can't be nested
+fn find_closing(src: &str) -> Option<usize> {
+    let end = src.find(['{', '}', '\n'])?;
+    src[end..].starts_with('}').then_some(end)
+}
+
+#[doc(hidden)]
+pub struct RubyScanner;
+impl InlineRule for RubyScanner {
+    const MARKER: char = '{';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        if !state.src[state.pos..state.pos_max].starts_with('{') { return None; }
+
+        let parse_options = ParseOptions::of(state.root_ext);
+        let options = parse_options.ext_or::<RubyOptions>(state.md).copied().unwrap_or_default();
+
+        let src = &state.src[state.pos + 1..state.pos_max];
+        let end = find_closing(src)?;
+        let (base, reading) = src[..end].split_once(options.separator)?;
+        let ruby = make_pairs(base, reading, options.group_separator)?;
+        Some((Node::new(ruby), end + 2))
+    }
+}
+
+#[doc(hidden)]
+pub struct RubyBracketScanner;
+impl InlineRule for RubyBracketScanner {
+    const MARKER: char = '[';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        if !state.src[state.pos..state.pos_max].starts_with('[') { return None; }
+
+        let parse_options = ParseOptions::of(state.root_ext);
+        let options = parse_options.ext_or::<RubyOptions>(state.md).copied().unwrap_or_default();
+        if !options.brackets { return None; }
+
+        let src = &state.src[state.pos + 1..state.pos_max];
+        let base_end = src.find(['[', ']', '\n'])?;
+        let reading_src = src[base_end..].strip_prefix("]{")?;
+        let reading_end = find_closing(reading_src)?;
+        let ruby = make_pairs(&src[..base_end], &reading_src[..reading_end], options.group_separator)?;
+        Some((Node::new(ruby), base_end + reading_end + 4))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RubyOptions;
+
+    fn run(src: &str, options: RubyOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn ruby() {
+        let options = RubyOptions::default();
+        assert_eq!(run("{漢字|かんじ}", options), "<p><ruby>漢字<rt>かんじ</rt></ruby></p>\n");
+        // groups don't match characters, so they are joined
+        assert_eq!(run("{漢字|か|ん|じ}", options), "<p><ruby>漢字<rt>かんじ</rt></ruby></p>\n");
+        assert_eq!(run("[<b>]{&}", options), "<p><ruby>&lt;b&gt;<rt>&amp;</rt></ruby></p>\n");
+        // not ruby
+        assert_eq!(run("{a} {|a} {a|} [a] {a\nb|c}", options), "<p>{a} {|a} {a|} [a] {a\nb|c}</p>\n");
+        assert_eq!(run("{x | x > 0} {a |b}", options), "<p>{x | x &gt; 0} {a |b}</p>\n");
+        // shortcut references take precedence over ruby
+        assert_eq!(run("[a]{b} [c]{d}\n\n[a]: /url", options), "<p><a href=\"/url\">a</a>{b} <ruby>c<rt>d</rt></ruby></p>\n");
+    }
+
+    #[test]
+    fn separators() {
+        let options = RubyOptions { separator: ':', group_separator: '.', brackets: false };
+        assert_eq!(run("{漢字:かん.じ} [漢字]{かんじ}", options),
+            "<p><ruby>漢<rt>かん</rt>字<rt>じ</rt></ruby> [漢字]{かんじ}</p>\n");
+    }
+}
@@ -0,0 +1,144 @@
+//! Manifest of document sections (for analytics, scroll tracking, table of contents).
+//!
+//! Each top-level heading starts a section, which lasts until the next top-level
+//! heading. Slugs are taken from `id` attributes of headings (see
+//! [heading_anchors](super::heading_anchors) plugin), or generated with
+//! [simple_slugify_fn](super::heading_anchors::simple_slugify_fn) if there are none.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::sections;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("# Intro\n\nHello world!\n\n## Details\n\nMore *words* here.");
+//! let (html, sections) = sections::render_with_sections(&ast);
+//!
+//! assert_eq!(html, "<h1>Intro</h1>\n<p>Hello world!</p>\n<h2>Details</h2>\n<p>More <em>words</em> here.</p>\n");
+//! assert_eq!(sections[0].slug, "intro");
+//! assert_eq!(sections[0].word_count, 2);
+//! assert_eq!(sections[1].title, "Details");
+//! assert_eq!(sections[1].depth, 2);
+//! assert_eq!(sections[1].word_count, 3);
+//! ```
+use crate::common::sourcemap::SourcePos;
+use crate::plugins::cmark::block::heading::ATXHeading;
+use crate::plugins::cmark::block::lheading::SetextHeader;
+use crate::plugins::extra::heading_anchors::simple_slugify_fn;
+use crate::plugins::extra::heading_text::slug_text;
+use crate::Node;
+
+#[derive(Debug, Clone)]
+/// Single document section, starting with a heading.
+pub struct Section {
+    /// Heading id.
+    pub slug: String,
+    /// Heading text (see [slug_text](super::heading_text::slug_text)).
+    pub title: String,
+    /// Heading level (1-6).
+    pub depth: u8,
+    /// Number of words in section content (excluding heading).
+    pub word_count: usize,
+    /// Source position of the entire section, including heading.
+    pub srcmap: Option<SourcePos>,
+}
+
+impl Section {
+    #[cfg(feature = "serde")]
+    /// Convert section to JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "slug": self.slug,
+            "title": self.title,
+            "depth": self.depth,
+            "word_count": self.word_count,
+            "srcmap": self.srcmap.map(|map| map.get_byte_offsets()),
+        })
+    }
+}
+
+fn heading_level(node: &Node) -> Option<u8> {
+    if let Some(heading) = node.cast::<ATXHeading>() {
+        return Some(heading.level);
+    }
+    if let Some(heading) = node.cast::<SetextHeader>() {
+        return Some(heading.level);
+    }
+    None
+}
+
+/// Collect sections from top-level headings of the document.
+pub fn collect_sections(root: &Node) -> Vec<Section> {
+    let mut sections = Vec::<Section>::new();
+
+    for node in root.children.iter() {
+        if let Some(depth) = heading_level(node) {
+            let title = slug_text(node);
+            let slug = node.attrs.iter()
+                .find(|(key, _)| key == "id")
+                .map_or_else(|| simple_slugify_fn(&title), |(_, value)| value.clone());
+
+            sections.push(Section { slug, title, depth, word_count: 0, srcmap: node.srcmap });
+        } else if let Some(section) = sections.last_mut() {
+            // skip list markers and other punctuation
+            section.word_count += node.render_text().split_whitespace()
+                .filter(|word| word.chars().any(char::is_alphanumeric))
+                .count();
+
+            if let (Some(section_map), Some(map)) = (section.srcmap, node.srcmap) {
+                let (start, _) = section_map.get_byte_offsets();
+                let (_, end) = map.get_byte_offsets();
+                section.srcmap = Some(SourcePos::new(start, end.max(start)));
+            }
+        }
+    }
+
+    sections
+}
+
+/// Render document to HTML and collect its sections, same as [Node::render]
+/// followed by [collect_sections] (the document is walked twice).
+pub fn render_with_sections(root: &Node) -> (String, Vec<Section>) {
+    (root.render(), collect_sections(root))
+}
+
+#[cfg(feature = "serde")]
+/// Create JSON manifest of document sections.
+pub fn to_json(sections: &[Section]) -> serde_json::Value {
+    serde_json::Value::Array(sections.iter().map(Section::to_json).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_sections;
+
+    #[test]
+    fn sections() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::heading_anchors::add(md, |s| format!("h-{s}"));
+        let src = "preface\n\nA\n===\n\n- one two\n- three\n\n> # quoted\n> four\n\n### B\n";
+        let sections = collect_sections(&md.parse(src));
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].slug, "h-A");
+        assert_eq!(sections[0].depth, 1);
+        assert_eq!(sections[0].word_count, 5);
+        let (start, end) = sections[0].srcmap.unwrap().get_byte_offsets();
+        assert_eq!(&src[start..end], "A\n===\n\n- one two\n- three\n\n> # quoted\n> four");
+        assert_eq!(sections[1].slug, "h-B");
+        assert_eq!(sections[1].depth, 3);
+        assert_eq!(sections[1].word_count, 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let json = super::to_json(&collect_sections(&md.parse("# A b\ntext")));
+        assert_eq!(json, serde_json::json!([
+            { "slug": "a-b", "title": "A b", "depth": 1, "word_count": 1, "srcmap": [0, 10] }
+        ]));
+    }
+}
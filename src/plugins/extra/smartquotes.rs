@@ -1,8 +1,28 @@
 //! Replaces `"` and `'` quotes with "nicer" ones like `‘`, `’`, `“`, `”`, or
 //! with `’` for words like "isn't".
 //!
-//! This currently only supports single character quotes, which is a limitation
-//! of the Rust implementation due to the use of `const` generics.
+//! This currently only supports single character quotes.
+//!
+//! Quotes of other languages can be selected with [QuoteStyle], or by locale of
+//! a document ([ParseOptions::locale]):
+//!
+//! ```rust
+//! use markdown_it::parser::options::ParseOptions;
+//! use markdown_it::plugins::extra::smartquotes::{self, Quotes, QuoteStyle};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! smartquotes::add_with_options(md, QuoteStyle::French);
+//! assert_eq!(md.parse("\"a 'b'\"").render(), "<p>«a ‹b›»</p>\n");
+//!
+//! let mut options = ParseOptions::new();
+//! options.locale = Some("de-DE".into());
+//! assert_eq!(md.parse_with_options("\"a 'b'\"", options).render(), "<p>„a ‚b‘“</p>\n");
+//!
+//! let mut options = ParseOptions::new();
+//! options.ext.insert(Quotes::new('‹', '›', '«', '»'));
+//! assert_eq!(md.parse_with_options("\"a 'b'\"", options).render(), "<p>«a ‹b›»</p>\n");
+//! ```
 //!
 //! ## Implementation notes
 //!
@@ -28,7 +48,9 @@ use std::collections::HashMap;
 
 use crate::common::utils::is_punct_char;
 use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
 use crate::parser::inline::Text;
+use crate::parser::options::ParseOptions;
 use crate::plugins::cmark::block::paragraph::Paragraph;
 use crate::plugins::cmark::inline::newline::{Hardbreak, Softbreak};
 use crate::plugins::html::html_inline::HtmlInline;
@@ -39,11 +61,82 @@ const SINGLE_QUOTE: char = '\'';
 const DOUBLE_QUOTE: char = '"';
 const SPACE: char = ' ';
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Quote characters, can also be set in [ParseOptions] of a document.
+pub struct Quotes {
+    pub open_single: char,
+    pub close_single: char,
+    pub open_double: char,
+    pub close_double: char,
+}
+
+impl Quotes {
+    pub const fn new(open_single: char, close_single: char, open_double: char, close_double: char) -> Self {
+        Self { open_single, close_single, open_double, close_double }
+    }
+}
+
+impl MarkdownItExt for Quotes {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Quote profiles for [add_with_options].
+pub enum QuoteStyle {
+    /// `‘single’` and `“double”` quotes.
+    English,
+    /// `‚single‘` and `„double“` quotes.
+    German,
+    /// `‹single›` and `«double»` quotes.
+    French,
+    /// `„single“` and `«double»` quotes.
+    Russian,
+    Custom(Quotes),
+}
+
+impl QuoteStyle {
+    /// Quote profile used for a language tag (e.g. `"de-CH"`), `None` if the language is unknown.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let language = locale.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Self::English),
+            "de" | "cs" | "sk" | "da" => Some(Self::German),
+            "fr" => Some(Self::French),
+            // `“single”` and `«double»` quotes
+            "it" | "es" | "pt" | "el" | "ca" => Some(Self::Custom(Quotes::new('“', '”', '«', '»'))),
+            "ru" | "uk" | "be" | "bg" => Some(Self::Russian),
+            // `«single»` and `„double”` quotes
+            "pl" => Some(Self::Custom(Quotes::new('«', '»', '„', '”'))),
+            _ => None,
+        }
+    }
+
+    pub fn quotes(&self) -> Quotes {
+        match self {
+            Self::English => Quotes::new('‘', '’', '“', '”'),
+            Self::German => Quotes::new('‚', '‘', '„', '“'),
+            Self::French => Quotes::new('‹', '›', '«', '»'),
+            Self::Russian => Quotes::new('„', '“', '«', '»'),
+            Self::Custom(quotes) => *quotes,
+        }
+    }
+}
+
 /// Add smartquotes with the "classic" quote set of `‘`, `’`, `“`, and `”`.
 pub fn add(md: &mut MarkdownIt) {
     add_with::<'‘', '’', '“', '”'>(md);
 }
 
+/// Add smartquotes with given quote profile.
+pub fn add_with_options(md: &mut MarkdownIt, style: QuoteStyle) {
+    md.ext.insert(style.quotes());
+
+    if !md.has_rule::<SmartQuotesRule<'‘', '’', '“', '”'>>() {
+        add(md);
+    }
+}
+
+/// Add smartquotes with quote characters set at compile time, they are used
+/// unless other ones are set with [add_with_options], by document locale, or in
+/// [ParseOptions].
 pub fn add_with<
     const OPEN_SINGLE_QUOTE: char,
     const CLOSE_SINGLE_QUOTE: char,
@@ -143,10 +236,16 @@ impl<
         CLOSE_DOUBLE_QUOTE,
     >
 {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let options = ParseOptions::of_root(root);
+        let quotes = options.ext.get::<Quotes>().copied()
+            .or_else(|| options.locale.as_deref().and_then(QuoteStyle::from_locale).map(|style| style.quotes()))
+            .or_else(|| md.ext.get::<Quotes>().copied())
+            .unwrap_or(Quotes::new(OPEN_SINGLE_QUOTE, CLOSE_SINGLE_QUOTE, OPEN_DOUBLE_QUOTE, CLOSE_DOUBLE_QUOTE));
+
         let text_tokens = all_text_tokens(root);
 
-        let replacement_ops = Self::compute_replacements(text_tokens);
+        let replacement_ops = compute_replacements(text_tokens, &quotes);
 
         // now that we know what we want to replace where, we go over the nodes a _third_ time to do all the actual replacements.
         let mut current_index: usize = 0;
@@ -162,148 +261,142 @@ impl<
     }
 }
 
-impl<
-        const OPEN_SINGLE_QUOTE: char,
-        const CLOSE_SINGLE_QUOTE: char,
-        const OPEN_DOUBLE_QUOTE: char,
-        const CLOSE_DOUBLE_QUOTE: char,
-    >
-    SmartQuotesRule<OPEN_SINGLE_QUOTE, CLOSE_SINGLE_QUOTE, OPEN_DOUBLE_QUOTE, CLOSE_DOUBLE_QUOTE>
-{
-    /// Walk the list of tokens to figure out what needs replacing where. to do
-    /// this, we need to search back and forth over the nodes to find matching
-    /// quotes across nodes. The borrow checker won't let us handle the entire
-    /// set of nodes as mutable at the same time however, so all we do here is
-    /// figure out what we _want_ to replace in which node.
-    fn compute_replacements(text_tokens: Vec<FlatToken>) -> HashMap<usize, HashMap<usize, char>> {
-        let mut quote_stack: Vec<QuoteMarker> = Vec::new();
-        let mut replacement_ops: HashMap<usize, HashMap<usize, char>> = HashMap::new();
-        for (walk_index, token) in text_tokens.iter().enumerate() {
-            if let FlatToken::Text {
+/// Walk the list of tokens to figure out what needs replacing where. to do
+/// this, we need to search back and forth over the nodes to find matching
+/// quotes across nodes. The borrow checker won't let us handle the entire
+/// set of nodes as mutable at the same time however, so all we do here is
+/// figure out what we _want_ to replace in which node.
+fn compute_replacements(text_tokens: Vec<FlatToken>, quotes: &Quotes) -> HashMap<usize, HashMap<usize, char>> {
+    let mut quote_stack: Vec<QuoteMarker> = Vec::new();
+    let mut replacement_ops: HashMap<usize, HashMap<usize, char>> = HashMap::new();
+    for (walk_index, token) in text_tokens.iter().enumerate() {
+        if let FlatToken::Text {
+            content,
+            nesting_level,
+        } = token
+        {
+            for op in replace_smartquotes(
+                quotes,
                 content,
-                nesting_level,
-            } = token
-            {
-                for op in Self::replace_smartquotes(
-                    content,
-                    walk_index,
-                    *nesting_level,
-                    &text_tokens,
-                    &mut quote_stack,
-                ) {
-                    replacement_ops
-                        .entry(op.walk_index)
-                        .or_default()
-                        .insert(op.quote_position, op.quote);
-                }
+                walk_index,
+                *nesting_level,
+                &text_tokens,
+                &mut quote_stack,
+            ) {
+                replacement_ops
+                    .entry(op.walk_index)
+                    .or_default()
+                    .insert(op.quote_position, op.quote);
             }
         }
-        replacement_ops
     }
+    replacement_ops
+}
 
-    /// Compute quote replacements found by looking at a single text block
-    fn replace_smartquotes(
-        content: &str,
-        walk_index: usize,
-        level: u32,
-        text_tokens: &[FlatToken],
-        quote_stack: &mut Vec<QuoteMarker>,
-    ) -> Vec<ReplacementOp> {
-        truncate_stack(quote_stack, level);
-
-        let mut result: Vec<_> = Vec::new();
-        for (quote_position, quote_type) in find_quotes(content) {
-            let last_char = find_last_char_before(text_tokens, walk_index, quote_position);
-            let next_char = find_first_char_after(text_tokens, walk_index, quote_position);
-
-            let (can_open, can_close): (bool, bool) =
-                can_open_or_close(&quote_type, last_char, next_char);
-
-            if !can_open && !can_close {
-                // if this is a single quote then we're in the middle of a word and
-                // assume it to be an apostrophe
-                if quote_type == QuoteType::Single {
-                    result.push(ReplacementOp {
-                        walk_index,
-                        quote_position,
-                        quote: APOSTROPHE,
-                    });
-                }
-                // in any case, we're done with this quote and continue searching
-                // for more quotes in this text block
-                continue;
-            }
-
-            if can_close {
-                if let Some((opening_op, closing_op, new_stack_len)) =
-                    Self::try_close(quote_stack, walk_index, level, quote_type, quote_position)
-                {
-                    quote_stack.truncate(new_stack_len);
-                    result.push(opening_op);
-                    result.push(closing_op);
-                    continue;
-                }
-            }
-
-            if can_open {
-                quote_stack.push(QuoteMarker {
-                    walk_index,
-                    quote_position,
-                    quote_type,
-                    level,
-                });
-            } else if can_close && quote_type == QuoteType::Single {
+/// Compute quote replacements found by looking at a single text block
+fn replace_smartquotes(
+    quotes: &Quotes,
+    content: &str,
+    walk_index: usize,
+    level: u32,
+    text_tokens: &[FlatToken],
+    quote_stack: &mut Vec<QuoteMarker>,
+) -> Vec<ReplacementOp> {
+    truncate_stack(quote_stack, level);
+
+    let mut result: Vec<_> = Vec::new();
+    for (quote_position, quote_type) in find_quotes(content) {
+        let last_char = find_last_char_before(text_tokens, walk_index, quote_position);
+        let next_char = find_first_char_after(text_tokens, walk_index, quote_position);
+
+        let (can_open, can_close): (bool, bool) =
+            can_open_or_close(&quote_type, last_char, next_char);
+
+        if !can_open && !can_close {
+            // if this is a single quote then we're in the middle of a word and
+            // assume it to be an apostrophe
+            if quote_type == QuoteType::Single {
                 result.push(ReplacementOp {
                     walk_index,
                     quote_position,
                     quote: APOSTROPHE,
                 });
             }
+            // in any case, we're done with this quote and continue searching
+            // for more quotes in this text block
+            continue;
         }
-        result
-    }
 
-    /// Try to find a matching opening quote to the given one.
-    ///
-    /// If a match is found, returns `Some` with two `ReplacementOp`s to be
-    /// added to the result, and with the resulting length of the `quote_stack`.
-    fn try_close(
-        quote_stack: &[QuoteMarker],
-        walk_index: usize,
-        level: u32,
-        quote_type: QuoteType,
-        quote_position: usize,
-    ) -> Option<(ReplacementOp, ReplacementOp, usize)> {
-        for (j, other_item) in quote_stack.iter().enumerate().rev() {
-            if other_item.level < level {
-                return None;
+        if can_close {
+            if let Some((opening_op, closing_op, new_stack_len)) =
+                try_close(quotes, quote_stack, walk_index, level, quote_type, quote_position)
+            {
+                quote_stack.truncate(new_stack_len);
+                result.push(opening_op);
+                result.push(closing_op);
+                continue;
             }
-            if other_item.quote_type == quote_type && other_item.level == level {
-                return Some((
-                    ReplacementOp {
-                        walk_index: other_item.walk_index,
-                        quote_position: other_item.quote_position,
-                        quote: if quote_type == QuoteType::Single {
-                            OPEN_SINGLE_QUOTE
-                        } else {
-                            OPEN_DOUBLE_QUOTE
-                        },
+        }
+
+        if can_open {
+            quote_stack.push(QuoteMarker {
+                walk_index,
+                quote_position,
+                quote_type,
+                level,
+            });
+        } else if can_close && quote_type == QuoteType::Single {
+            result.push(ReplacementOp {
+                walk_index,
+                quote_position,
+                quote: APOSTROPHE,
+            });
+        }
+    }
+    result
+}
+
+/// Try to find a matching opening quote to the given one.
+///
+/// If a match is found, returns `Some` with two `ReplacementOp`s to be
+/// added to the result, and with the resulting length of the `quote_stack`.
+fn try_close(
+    quotes: &Quotes,
+    quote_stack: &[QuoteMarker],
+    walk_index: usize,
+    level: u32,
+    quote_type: QuoteType,
+    quote_position: usize,
+) -> Option<(ReplacementOp, ReplacementOp, usize)> {
+    for (j, other_item) in quote_stack.iter().enumerate().rev() {
+        if other_item.level < level {
+            return None;
+        }
+        if other_item.quote_type == quote_type && other_item.level == level {
+            return Some((
+                ReplacementOp {
+                    walk_index: other_item.walk_index,
+                    quote_position: other_item.quote_position,
+                    quote: if quote_type == QuoteType::Single {
+                        quotes.open_single
+                    } else {
+                        quotes.open_double
                     },
-                    ReplacementOp {
-                        walk_index,
-                        quote_position,
-                        quote: if quote_type == QuoteType::Single {
-                            CLOSE_SINGLE_QUOTE
-                        } else {
-                            CLOSE_DOUBLE_QUOTE
-                        },
+                },
+                ReplacementOp {
+                    walk_index,
+                    quote_position,
+                    quote: if quote_type == QuoteType::Single {
+                        quotes.close_single
+                    } else {
+                        quotes.close_double
                     },
-                    j,
-                ));
-            }
+                },
+                j,
+            ));
         }
-        None
     }
+    None
 }
 
 /// Produces a simplified flat list of all tokens, with the necessary
@@ -564,4 +657,28 @@ mod tests {
         let html = md.parse("\"**...**\"").render();
         assert_eq!(html.trim(), "<p>“<strong>…</strong>”</p>");
     }
+
+    #[test]
+    fn smartquotes_locales() {
+        use super::QuoteStyle;
+        use crate::parser::options::ParseOptions;
+
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, QuoteStyle::Russian);
+        assert_eq!(md.parse("\"a 'b'\"").render(), "<p>«a „b“»</p>\n");
+
+        let render = |locale: &str| {
+            let mut options = ParseOptions::new();
+            options.locale = Some(locale.into());
+            md.parse_with_options("\"a 'b'\" c's", options).render()
+        };
+        assert_eq!(render("en-US"), "<p>“a ‘b’” c’s</p>\n");
+        assert_eq!(render("fr"), "<p>«a ‹b›» c’s</p>\n");
+        assert_eq!(render("it"), "<p>«a “b”» c’s</p>\n");
+        assert_eq!(render("pt-BR"), "<p>«a “b”» c’s</p>\n");
+        assert_eq!(render("pl"), "<p>„a «b»” c’s</p>\n");
+        // unknown locale, parser setting is used
+        assert_eq!(render("xx"), "<p>«a „b“» c’s</p>\n");
+    }
 }
@@ -0,0 +1,183 @@
+//! Blank lines between blocks.
+//!
+//! Renderers don't need them, so they are normally lost after parsing.
+//! This plugin stores runs of blank lines in block nodes as [Spacing],
+//! so formatters and round-trip renderers can keep vertical spacing
+//! the way author wrote it.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::spacing::Spacing;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::spacing::add(md);
+//!
+//! let src = "# Title\n\n\n\nText\n***\n";
+//! let ast = md.parse(src);
+//!
+//! assert_eq!(Spacing::before(&ast.children[1]), 3);
+//! assert_eq!(Spacing::before(&ast.children[2]), 0);
+//!
+//! let blank_lines = Spacing::of(&ast.children[1]).unwrap().before.unwrap();
+//! let (start, end) = blank_lines.srcmap.get_byte_offsets();
+//! assert_eq!(&src[start..end], "\n\n\n");
+//! ```
+use crate::common::sourcemap::SourcePos;
+use crate::parser::block::builtin::BlockParserRule;
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::extset::NodeExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Copy)]
+/// Run of consecutive blank lines.
+pub struct BlankLines {
+    /// Number of blank lines.
+    pub count: usize,
+    /// Position of the blank lines, including their line breaks
+    /// (container markers like `>` are included as well).
+    pub srcmap: SourcePos,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Blank lines around a block, stored in blocks that have any.
+pub struct Spacing {
+    /// Blank lines between this block and the previous one
+    /// (or the start of the document for the first block).
+    pub before: Option<BlankLines>,
+    /// Blank lines at the end of the document, only set for the last block.
+    pub after: Option<BlankLines>,
+}
+
+impl NodeExt for Spacing {}
+
+impl Spacing {
+    pub fn of(node: &Node) -> Option<&Self> {
+        node.ext.get::<Self>()
+    }
+
+    /// Number of blank lines before a block.
+    pub fn before(node: &Node) -> usize {
+        Self::of(node).and_then(|spacing| spacing.before).map_or(0, |blank| blank.count)
+    }
+
+    /// Number of blank lines after a block (at the end of the document).
+    pub fn after(node: &Node) -> usize {
+        Self::of(node).and_then(|spacing| spacing.after).map_or(0, |blank| blank.count)
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    if !md.has_rule::<SpacingRule>() {
+        md.add_rule::<SpacingRule>()
+            .after::<BlockParserRule>()
+            .before::<InlineParserRule>();
+    }
+}
+
+// end of the content of a block, list items and other containers
+// include blank lines after them in their srcmap
+fn content_end(node: &Node) -> Option<usize> {
+    match node.children.last().and_then(content_end) {
+        Some(end) => Some(end),
+        None => Some(node.srcmap?.get_byte_offsets().1),
+    }
+}
+
+// blank lines in `src[start..end]`, which contains line breaks between two blocks
+// (first one is the end of the line of the previous block, unless it's the start of the document)
+fn blank_lines(src: &str, start: usize, end: usize, after_block: bool) -> Option<BlankLines> {
+    let gap = src.get(start..end)?;
+    let mut breaks = gap.match_indices('\n').map(|(pos, _)| start + pos);
+    let first = if after_block { breaks.next()? + 1 } else { start };
+    let last = breaks.next_back()? + 1;
+    let count = src[first..last].matches('\n').count();
+    Some(BlankLines { count, srcmap: SourcePos::new(first, last) })
+}
+
+fn set_spacing(node: &mut Node, src: &str, is_root: bool) {
+    let mut prev_end = if is_root { Some(0) } else { None };
+    let mut after_block = false;
+
+    for child in node.children.iter_mut() {
+        let Some(srcmap) = child.srcmap else {
+            prev_end = None;
+            continue;
+        };
+
+        let before = prev_end.and_then(|end| blank_lines(src, end, srcmap.get_byte_offsets().0, after_block));
+        if before.is_some() {
+            child.ext.get_or_insert_default::<Spacing>().before = before;
+        }
+
+        prev_end = content_end(child);
+        after_block = true;
+    }
+
+    if is_root {
+        if let (Some(end), Some(last)) = (prev_end, node.children.last_mut()) {
+            let after = blank_lines(src, end, src.len(), true);
+            if after.is_some() {
+                last.ext.get_or_insert_default::<Spacing>().after = after;
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct SpacingRule;
+impl CoreRule for SpacingRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        let Some(data) = root.cast::<Root>() else { return; };
+        let src = data.content.clone();
+
+        set_spacing(root, &src, true);
+        for child in root.children.iter_mut() {
+            child.walk_mut(|node, _| set_spacing(node, &src, false));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spacing;
+
+    fn parse(src: &str) -> crate::Node {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        md.parse(src)
+    }
+
+    #[test]
+    fn document() {
+        let ast = parse("\n\na\n\n\n> b\n>\n> c\n\n- x\n\n\n- y\n  \n\n");
+        let counts : Vec<_> = ast.children.iter().map(|node| (Spacing::before(node), Spacing::after(node))).collect();
+        assert_eq!(counts, [(2, 0), (2, 0), (1, 2)]);
+
+        let quote = &ast.children[1];
+        assert_eq!(Spacing::before(&quote.children[0]), 0);
+        assert_eq!(Spacing::before(&quote.children[1]), 1);
+
+        let list = &ast.children[2];
+        assert_eq!(Spacing::before(&list.children[1]), 2);
+    }
+
+    #[test]
+    fn no_blank_lines() {
+        let ast = parse("a\n***\n- b\n- c");
+        ast.walk(|node, _| assert!(Spacing::of(node).is_none(), "{node:?}"));
+    }
+
+    #[test]
+    fn whitespace_lines() {
+        let src = "a\n  \n\t\n\n```\n\n```";
+        let ast = parse(src);
+        let blank = Spacing::of(&ast.children[1]).unwrap().before.unwrap();
+        assert_eq!(blank.count, 3);
+        let (start, end) = blank.srcmap.get_byte_offsets();
+        assert_eq!(&src[start..end], "  \n\t\n\n");
+        // blank lines inside code blocks are content
+        assert_eq!(Spacing::after(&ast.children[1]), 0);
+    }
+}
@@ -0,0 +1,56 @@
+//! Inline spoilers (like `||this||`), hidden until the reader reveals them.
+//!
+//! Rendered as `<span class="spoiler">`, it's up to the stylesheet to hide them.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::spoiler::add(md);
+//!
+//! let html = md.parse("It was ||*the butler*||").render();
+//! assert_eq!(html.trim(), "<p>It was <span class=\"spoiler\"><em>the butler</em></span></p>");
+//! ```
+use crate::generics::inline::emph_pair;
+use crate::parser::rules;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+
+#[derive(Debug)]
+pub struct Spoiler;
+
+impl NodeValue for Spoiler {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class".into(), "spoiler".into()));
+        fmt.open("span", &attrs);
+        fmt.contents(&node.children);
+        fmt.close("span");
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    rules::set_rule_name::<emph_pair::EmphPairScanner<'|', true>>(md, "spoiler");
+    emph_pair::add_with::<'|', 2, true>(md, || Node::new(Spoiler));
+}
+
+#[cfg(test)]
+mod tests {
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::tables::add(md);
+        super::add(md);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn spoilers() {
+        assert_eq!(run("||a|| b ||c d||"), "<p><span class=\"spoiler\">a</span> b <span class=\"spoiler\">c d</span></p>\n");
+        assert_eq!(run("a || b | c"), "<p>a || b | c</p>\n");
+        assert_eq!(run("|||a|||"), "<p>|<span class=\"spoiler\">a</span>|</p>\n");
+    }
+}
@@ -1,6 +1,9 @@
 //! Strikethrough syntax (like `~~this~~`)
 use crate::generics::inline::emph_pair;
+use crate::parser::rules;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct Strikethrough {
@@ -13,8 +16,19 @@ impl NodeValue for Strikethrough {
         fmt.contents(&node.children);
         fmt.close("s");
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "marker": self.marker })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { marker: field(data, "marker")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
+    rules::set_rule_name::<emph_pair::EmphPairScanner<'~', true>>(md, "strikethrough");
     emph_pair::add_with::<'~', 2, true>(md, || Node::new(Strikethrough { marker: '~' }));
 }
@@ -0,0 +1,161 @@
+//! Text substitutions, to keep product names and other terms consistent.
+//!
+//! Substitutions are defined in the document with `*[:name:]: text` lines
+//! (or passed with [set_substitutions]), and every `:name:` in the text is
+//! replaced with its value. Code spans and code blocks are not affected.
+//! Definitions in the document override the ones passed to the parser.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::substitutions::add(md);
+//!
+//! let html = md.parse("*[:product:]: Acme SuperTool 3000\n\nUse :product:, not `:product:`.").render();
+//! assert_eq!(html.trim(), "<p>Use Acme SuperTool 3000, not <code>:product:</code>.</p>");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::definitions::Definitions;
+use crate::parser::extset::{MarkdownItExt, RootExt};
+use crate::parser::inline::{InlineRule, InlineState, Text};
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static DEFINITION_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\*\[:([A-Za-z0-9_-]+):\]:[ \t]*(.*?)[ \t]*$"#).unwrap()
+});
+
+static USAGE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^:([A-Za-z0-9_-]+):"#).unwrap()
+});
+
+#[derive(Debug)]
+/// Substitution definition (`*[:name:]: value`), it isn't rendered.
+pub struct SubstitutionDefinition {
+    pub name: String,
+    pub value: String,
+}
+
+impl NodeValue for SubstitutionDefinition {
+    fn render(&self, _: &Node, _: &mut dyn Renderer) {}
+
+    fn is_definition(&self) -> bool {
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "name": self.name, "value": self.value })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { name: field(data, "name")?, value: field(data, "value")? })
+    }
+}
+
+#[derive(Debug, Default)]
+/// Substitutions defined in the document, stored in the root node.
+pub struct SubstitutionMap(pub HashMap<String, String>);
+impl RootExt for SubstitutionMap {}
+
+#[derive(Debug, Default)]
+struct DefaultSubstitutions(HashMap<String, String>);
+impl MarkdownItExt for DefaultSubstitutions {}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.block.add_rule::<SubstitutionDefinitionScanner>();
+    md.inline.add_rule::<SubstitutionScanner>();
+}
+
+/// Set substitutions available in all documents (e.g. provided by front matter or config).
+pub fn set_substitutions(md: &mut MarkdownIt, substitutions: HashMap<String, String>) {
+    md.ext.insert(DefaultSubstitutions(substitutions));
+}
+
+#[doc(hidden)]
+pub struct SubstitutionDefinitionScanner;
+impl BlockRule for SubstitutionDefinitionScanner {
+    fn check(_: &mut BlockState) -> Option<()> {
+        None // can't interrupt anything
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+
+        let captures = DEFINITION_RE.captures(state.get_line(state.line))?;
+        let name = captures[1].to_owned();
+        let value = captures[2].to_owned();
+
+        let map = state.root_ext.get_or_insert_default::<SubstitutionMap>();
+        map.0.entry(name.clone()).or_insert(value.clone());
+
+        let srcmap = state.get_map(state.line, state.line);
+        let definitions = state.root_ext.get_or_insert_default::<Definitions<SubstitutionDefinition>>();
+        definitions.define(name.clone(), &name, srcmap);
+
+        Some((Node::new(SubstitutionDefinition { name, value }), 1))
+    }
+}
+
+#[doc(hidden)]
+pub struct SubstitutionScanner;
+impl InlineRule for SubstitutionScanner {
+    const MARKER: char = ':';
+
+    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+        // don't replace anything in the middle of a word, e.g. `12:30:00`
+        if state.src[..state.pos].chars().next_back().is_some_and(|ch| ch.is_alphanumeric()) { return None; }
+
+        let captures = USAGE_RE.captures(&state.src[state.pos..state.pos_max])?;
+        let name = captures[1].to_owned();
+        let length = captures[0].len();
+
+        let value = state.root_ext.get::<SubstitutionMap>().and_then(|map| map.0.get(&name))
+            .or_else(|| state.md.ext.get::<DefaultSubstitutions>().and_then(|map| map.0.get(&name)))?
+            .clone();
+
+        // unknown substitutions aren't recorded, they may be plain text
+        let srcmap = state.get_map(state.pos, state.pos + length);
+        if let Some(definitions) = state.root_ext.get_mut::<Definitions<SubstitutionDefinition>>() {
+            if definitions.get(&name).is_some() {
+                definitions.reference(&name, &name, srcmap);
+            }
+        }
+
+        Some((Node::new(Text { content: value }), length))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+        super::set_substitutions(md, HashMap::from([
+            ("a".to_owned(), "default".to_owned()),
+            ("b".to_owned(), "<b>".to_owned()),
+        ]));
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn substitutions() {
+        assert_eq!(run(":a: :b: :c: a:b:"), "<p>default &lt;b&gt; :c: a:b:</p>\n");
+        assert_eq!(run("*[:a:]: defined\n*[:a:]: ignored\n\n# :a:\n\n    :a:"), "<h1>defined</h1>\n<pre><code>:a:\n</code></pre>\n");
+        assert_eq!(run("[:b:](/:a:) *:a:*"), "<p><a href=\"/:a:\">&lt;b&gt;</a> <em>default</em></p>\n");
+    }
+
+    #[test]
+    fn not_definitions() {
+        assert_eq!(run("text\n*[:z:]: x\n\n:z:"), "<p>text\n*[:z:]: x</p>\n<p>:z:</p>\n");
+        assert_eq!(run("*[:a b:]: x"), "<p>*[:a b:]: x</p>\n");
+    }
+}
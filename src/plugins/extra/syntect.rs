@@ -12,7 +12,10 @@ use crate::{
     MarkdownIt, Node, NodeValue, Renderer,
     parser::core::CoreRule,
     plugins::cmark::block::{code::CodeBlock, fence::CodeFence},
+    plugins::extra::attrs::AttrsRule,
 };
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct SyntectSnippet {
@@ -23,55 +26,100 @@ impl NodeValue for SyntectSnippet {
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.text_raw(&self.html);
     }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "html": self.html })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { html: field(data, "html")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.add_rule::<SyntectRule>();
+    // attributes of code fences must be parsed before they are replaced
+    md.add_rule::<SyntectRule>()
+        .after::<AttrsRule>();
 }
 
 pub struct SyntectRule;
 impl CoreRule for SyntectRule {
     fn run(root: &mut Node, _: &MarkdownIt) {
+        // collect all code blocks first, highlighting dominates render time
+        // of large documents, so it's done on multiple threads with `rayon` feature
+        let mut blocks = Vec::new();
+        root.walk(|node, _| {
+            if let Some(data) = node.cast::<CodeBlock>() {
+                blocks.push((data.content.as_str(), None));
+            } else if let Some(data) = node.cast::<CodeFence>() {
+                blocks.push((data.content.as_str(), Some(data.info.as_str())));
+            }
+        });
+        if blocks.is_empty() { return; }
+
         let ss = SyntaxSet::load_defaults_newlines();
 
+        #[cfg(feature = "rayon")]
+        let results : Vec<_> = {
+            use rayon::prelude::*;
+            blocks.par_iter().map(|(content, language)| highlight(&ss, content, *language)).collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let results : Vec<_> = blocks.iter().map(|(content, language)| highlight(&ss, content, *language)).collect();
+
+        // nodes are visited in the same order as above
+        let mut results = results.into_iter();
         root.walk_mut(|node, _| {
-            let (content, language) = if let Some(data) = node.cast::<CodeBlock>() {
-                (Some(&data.content), None)
-            } else if let Some(data) = node.cast::<CodeFence>() {
-                (Some(&data.content), Some(&data.info))
-            } else {
-                Default::default()
-            };
-
-            if let Some(content) = content {
-                let syntax = language
-                    .and_then(|language| ss.find_syntax_by_token(language))
-                    .unwrap_or_else(|| ss.find_syntax_plain_text());
-
-                let mut html_generator =
-                    ClassedHTMLGenerator::new_with_class_style(syntax, &ss, ClassStyle::Spaced);
-
-                for line in LinesWithEndings::from(content) {
-                    if html_generator
-                        .parse_html_for_line_which_includes_newline(line)
-                        .is_err()
-                    {
-                        return;
-                    }
-                }
-
-                let content = html_generator.finalize();
-
-                if let Some(data) = node.cast_mut::<CodeBlock>() {
-                    data.content = content;
-                    data.raw = true;
-                } else if let Some(data) = node.cast_mut::<CodeFence>() {
-                    data.content = content;
-                    data.raw = true;
-                }
-
-                node.attrs.push(("class".into(), "code".into()));
+            if !node.is::<CodeBlock>() && !node.is::<CodeFence>() { return; }
+            let Some(Some(content)) = results.next() else { return; };
+
+            if let Some(data) = node.cast_mut::<CodeBlock>() {
+                data.content = content;
+                data.raw = true;
+            } else if let Some(data) = node.cast_mut::<CodeFence>() {
+                data.content = content;
+                data.raw = true;
             }
+
+            node.attrs.push(("class".into(), "code".into()));
         });
     }
 }
+
+fn highlight(ss: &SyntaxSet, content: &str, language: Option<&str>) -> Option<String> {
+    let syntax = language
+        .and_then(|language| ss.find_syntax_by_token(language))
+        .unwrap_or_else(|| ss.find_syntax_plain_text());
+
+    let mut html_generator =
+        ClassedHTMLGenerator::new_with_class_style(syntax, ss, ClassStyle::Spaced);
+
+    for line in LinesWithEndings::from(content) {
+        html_generator.parse_html_for_line_which_includes_newline(line).ok()?;
+    }
+
+    Some(html_generator.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn many_blocks() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md);
+
+        let src : String = (0..20).map(|n| format!("```rust\nlet x = {n};\n```\n\n    plain {n}\n\n")).collect();
+        let ast = md.parse(&src);
+
+        let blocks : Vec<_> = ast.children.iter().map(|node| node.render()).collect();
+        assert_eq!(blocks.len(), 40);
+        for (n, pair) in blocks.chunks(2).enumerate() {
+            assert!(pair[0].starts_with("<pre><code class=\"code language-rust\"><span class=\"source rust\">"), "{}", pair[0]);
+            assert!(pair[0].contains(&format!(">{n}</span>")), "{}", pair[0]);
+            assert_eq!(pair[1], format!("<pre><code class=\"code\"><span class=\"text plain\">plain {n}\n</span></code></pre>\n"));
+        }
+    }
+}
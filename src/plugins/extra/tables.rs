@@ -29,6 +29,42 @@ impl NodeValue for Table {
 
         old_context.map(|ctx| fmt.ext().insert(ctx));
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.cr();
+                fmt.contents(&node.children);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({
+            "alignments": self.alignments.iter().map(|a| match a {
+                ColumnAlignment::None => "none",
+                ColumnAlignment::Left => "left",
+                ColumnAlignment::Right => "right",
+                ColumnAlignment::Center => "center",
+            }).collect::<Vec<_>>(),
+        })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        let alignments = data.get("alignments")?.as_array()?.iter().map(|a| match a.as_str()? {
+            "none" => Some(ColumnAlignment::None),
+            "left" => Some(ColumnAlignment::Left),
+            "right" => Some(ColumnAlignment::Right),
+            "center" => Some(ColumnAlignment::Center),
+            _ => None,
+        }).collect::<Option<Vec<_>>>()?;
+        Some(Self { alignments })
+    }
 }
 
 #[derive(Debug, Default)]
@@ -59,6 +95,11 @@ impl NodeValue for TableHead {
         let ctx = fmt.ext().get_or_insert_default::<TableRenderContext>();
         ctx.head = false;
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 #[derive(Debug)]
@@ -74,6 +115,11 @@ impl NodeValue for TableBody {
         fmt.close("tbody");
         fmt.cr();
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 #[derive(Debug)]
@@ -92,6 +138,26 @@ impl NodeValue for TableRow {
         fmt.close("tr");
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                for (idx, cell) in node.children.iter().enumerate() {
+                    if idx > 0 { fmt.text("\t"); }
+                    cell.render_with(fmt);
+                }
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 #[derive(Debug)]
@@ -118,6 +184,11 @@ impl NodeValue for TableCell {
         fmt.close(tag);
         fmt.cr();
     }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(_: &serde_json::Value) -> Option<Self> {
+        Some(Self)
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
@@ -135,18 +206,15 @@ struct RowContent {
     srcmap: Vec<(usize, usize)>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy)]
 pub enum ColumnAlignment {
+    #[default]
     None,
     Left,
     Right,
     Center,
 }
 
-impl Default for ColumnAlignment {
-    fn default() -> Self { Self::None }
-}
-
 impl TableScanner {
     fn scan_row(line: &str) -> Vec<RowContent> {
         let mut result = Vec::new();
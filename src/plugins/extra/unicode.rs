@@ -0,0 +1,230 @@
+//! Unicode normalization and protection against invisible text direction changes.
+//!
+//!  - text is normalized to NFC, so that the same words are encoded the same way
+//!    (for search, anchors and comparison of documents)
+//!  - bidirectional control characters (`U+202A`-`U+202E`, `U+2066`-`U+2069`) are
+//!    removed from code and links, where they can make code look different from
+//!    what it does ([Trojan Source](https://trojansource.codes/)) or hide link targets
+//!
+//! Each removed character is reported as a [diagnostic](crate::parser::diagnostics)
+//! with `bidi-control` code. Text outside of code and links isn't changed, because
+//! these characters are legitimately used in right-to-left languages.
+//!
+//! ```rust
+//! use markdown_it::parser::diagnostics::Diagnostics;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::unicode::add(md);
+//!
+//! let ast = md.parse("Cafe\u{301}: `if admin\u{202E} {`");
+//! assert_eq!(ast.render(), "<p>Café: <code>if admin {</code></p>\n");
+//! assert_eq!(Diagnostics::of(&ast)[0].to_string(),
+//!     "warning[bidi-control]: bidi control character U+202E in code span (9..22)");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::diagnostics::{self, Diagnostic};
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::{Text, TextSpecial};
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::block::code::CodeBlock;
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::backticks::CodeInline;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::cmark::inline::link::Link;
+use crate::{MarkdownIt, Node};
+
+// bidi controls in urls after they are percent-encoded
+static ENCODED_BIDI_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)%E2%80%A[A-E]|%E2%81%A[6-9]"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct UnicodeOptions {
+    /// Normalize text to NFC, enabled by default.
+    pub normalize: bool,
+    /// Remove bidi control characters from code and links, enabled by default
+    /// (they are reported either way).
+    pub strip_bidi: bool,
+}
+
+impl Default for UnicodeOptions {
+    fn default() -> Self {
+        Self {
+            normalize: true,
+            strip_bidi: true,
+        }
+    }
+}
+
+impl MarkdownItExt for UnicodeOptions {}
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, UnicodeOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: UnicodeOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<UnicodeRule>() {
+        md.add_rule::<UnicodeRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+/// Check if a character changes direction of the following text
+/// (embeddings, overrides and isolates, not marks).
+pub fn is_bidi_control(ch: char) -> bool {
+    matches!(ch, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+#[doc(hidden)]
+pub struct UnicodeRule;
+impl CoreRule for UnicodeRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let options = parse_options.ext_or::<UnicodeOptions>(md).copied().unwrap_or_default();
+        let mut found = Vec::new();
+
+        check_recursive(root, None, &options, &mut found);
+
+        let root_ext = &mut root.cast_mut::<Root>().unwrap().ext;
+        for diagnostic in found {
+            diagnostics::report(root_ext, diagnostic);
+        }
+    }
+}
+
+// `place` is set inside of code spans and links
+fn check_recursive(node: &mut Node, mut place: Option<&'static str>, options: &UnicodeOptions, found: &mut Vec<Diagnostic>) {
+    let srcmap = node.srcmap;
+
+    #[cfg(feature = "linkify")]
+    if let Some(data) = node.cast_mut::<crate::plugins::extra::linkify::Linkified>() {
+        check_url(&mut data.url, srcmap, options, found);
+        place = Some("link text");
+    }
+
+    if node.is::<CodeInline>() {
+        place = Some("code span");
+    } else if let Some(data) = node.cast_mut::<CodeFence>() {
+        check_bidi(&mut data.content, "code block", srcmap, options, found);
+    } else if let Some(data) = node.cast_mut::<CodeBlock>() {
+        check_bidi(&mut data.content, "code block", srcmap, options, found);
+    } else if let Some(data) = node.cast_mut::<Link>() {
+        check_url(&mut data.url, srcmap, options, found);
+        if let Some(title) = &mut data.title { check_bidi(title, "link title", srcmap, options, found); }
+        place = Some("link text");
+    } else if let Some(data) = node.cast_mut::<Image>() {
+        check_url(&mut data.url, srcmap, options, found);
+        if let Some(title) = &mut data.title { check_bidi(title, "link title", srcmap, options, found); }
+        place = Some("image description");
+    } else if let Some(data) = node.cast_mut::<Autolink>() {
+        check_url(&mut data.url, srcmap, options, found);
+        place = Some("link text");
+    } else if let Some(text) = node.cast_mut::<Text>() {
+        match place {
+            Some(place) => { check_bidi(&mut text.content, place, srcmap, options, found); }
+            None if options.normalize && !is_nfc(&text.content) => {
+                text.content = text.content.nfc().collect();
+            }
+            None => {}
+        }
+    } else if let Some(text) = node.cast_mut::<TextSpecial>() {
+        if let Some(place) = place {
+            // markup is rendered instead of content in some modes, and entities encode the character
+            if check_bidi(&mut text.content, place, srcmap, options, found) && options.strip_bidi {
+                text.markup = text.content.clone();
+            }
+        }
+    }
+
+    for child in node.children.iter_mut() {
+        stacker::maybe_grow(64*1024, 1024*1024, || {
+            check_recursive(child, place, options, found);
+        });
+    }
+}
+
+fn report(ch: char, place: &str, srcmap: Option<SourcePos>, found: &mut Vec<Diagnostic>) {
+    let message = format!("bidi control character U+{:04X} in {place}", ch as u32);
+    found.push(Diagnostic::warning("bidi-control", message, srcmap));
+}
+
+// returns true if any were found
+fn check_bidi(value: &mut String, place: &str, srcmap: Option<SourcePos>, options: &UnicodeOptions, found: &mut Vec<Diagnostic>) -> bool {
+    let Some(ch) = value.chars().find(|&ch| is_bidi_control(ch)) else { return false; };
+    report(ch, place, srcmap, found);
+    if options.strip_bidi { value.retain(|ch| !is_bidi_control(ch)); }
+    true
+}
+
+fn check_url(url: &mut String, srcmap: Option<SourcePos>, options: &UnicodeOptions, found: &mut Vec<Diagnostic>) {
+    check_bidi(url, "link url", srcmap, options, found);
+
+    let Some(encoded) = ENCODED_BIDI_RE.find(url) else { return; };
+    let ch = encoded.as_str().split('%').skip(1)
+        .map(|byte| u8::from_str_radix(byte, 16).unwrap())
+        .collect::<Vec<_>>();
+    let ch = std::str::from_utf8(&ch).unwrap().chars().next().unwrap();
+    report(ch, "link url", srcmap, found);
+    if options.strip_bidi { *url = ENCODED_BIDI_RE.replace_all(url, "").into_owned(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnicodeOptions;
+    use crate::parser::diagnostics::Diagnostics;
+
+    fn run(src: &str, options: UnicodeOptions) -> (String, Vec<String>) {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add_with_options(md, options);
+        let ast = md.parse(src);
+        let diagnostics = Diagnostics::of(&ast).iter().map(|d| d.message.clone()).collect();
+        (ast.render(), diagnostics)
+    }
+
+    #[test]
+    fn normalize() {
+        let options = UnicodeOptions::default();
+        assert_eq!(run("# A\u{30A}ngstro\u{308}m `o\u{308}`", options).0, "<h1>Ångström <code>o\u{308}</code></h1>\n");
+        assert_eq!(run("\u{2067}right\u{2069}", options), ("<p>\u{2067}right\u{2069}</p>\n".into(), vec![]));
+
+        let options = UnicodeOptions { normalize: false, ..options };
+        assert_eq!(run("A\u{30A}", options).0, "<p>A\u{30A}</p>\n");
+    }
+
+    #[test]
+    fn bidi() {
+        let src = "```\n\u{202E}a\n```\n\n[b\u{2066}](/c%E2%80%AEd \"\u{202B}\") ![\u{202A}](e) <http://f\u{202D}>";
+        let (html, diagnostics) = run(src, UnicodeOptions::default());
+        assert_eq!(html, "<pre><code>a\n</code></pre>\n\
+            <p><a href=\"/cd\" title=\"\">b</a> <img src=\"e\" alt=\"\"> <a href=\"http://f\">http://f</a></p>\n");
+        assert_eq!(diagnostics, [
+            "bidi control character U+202E in code block",
+            "bidi control character U+202E in link url",
+            "bidi control character U+202B in link title",
+            "bidi control character U+2066 in link text",
+            "bidi control character U+202A in image description",
+            "bidi control character U+202D in link url",
+            "bidi control character U+202D in link text",
+        ]);
+
+        let (html, diagnostics) = run("[&#x202E;x](/)", UnicodeOptions::default());
+        assert_eq!(html, "<p><a href=\"/\">x</a></p>\n");
+        assert_eq!(diagnostics, ["bidi control character U+202E in link text"]);
+
+        let (html, diagnostics) = run("`\u{202E}`", UnicodeOptions { strip_bidi: false, ..UnicodeOptions::default() });
+        assert_eq!(html, "<p><code>\u{202E}</code></p>\n");
+        assert_eq!(diagnostics.len(), 1);
+    }
+}
@@ -0,0 +1,206 @@
+//! Filter link and image destinations by url scheme, and rewrite them.
+//!
+//! This runs after the document is parsed, and checks urls of links, images,
+//! autolinks and linkified urls against an allowlist of schemes. Urls without
+//! a scheme (relative links, anchors) are always allowed. Rejected links and images
+//! are replaced with their contents (link text or image description).
+//!
+//! Optional rewrite callback is called for each allowed url, and can be used to
+//! proxy images or add tracking parameters. It can also return `None` to
+//! reject the url.
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::url_filter::UrlFilterOptions;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::url_filter::add(md, UrlFilterOptions {
+//!     rewrite: Some(Box::new(|url| {
+//!         if url.starts_with("http:") {
+//!             Some(format!("/proxy?url={url}"))
+//!         } else {
+//!             Some(url.to_owned())
+//!         }
+//!     })),
+//!     ..Default::default()
+//! });
+//!
+//! let html = md.parse("[a](http://x.org) [b](ssh://x.org) ![c](/c.png)").render();
+//! assert_eq!(html.trim(), r#"<p><a href="/proxy?url=http://x.org">a</a> b <img src="/c.png" alt="c"></p>"#);
+//! ```
+use derivative::Derivative;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::image::Image;
+use crate::plugins::cmark::inline::link::Link;
+use crate::plugins::extra::link_options::ObfuscatedMailto;
+use crate::{MarkdownIt, Node};
+
+/// Url schemes allowed by default.
+pub const DEFAULT_ALLOWED_SCHEMES : [&str; 5] = ["http", "https", "ftp", "mailto", "tel"];
+
+/// Callback that rewrites an url, or returns `None` to reject it.
+pub type RewriteFn = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+/// Options for [add].
+pub struct UrlFilterOptions {
+    /// Allowed url schemes (lowercase, without `:`), see [DEFAULT_ALLOWED_SCHEMES].
+    pub allowed_schemes: Vec<String>,
+    /// Callback called with every allowed url.
+    #[derivative(Debug = "ignore")]
+    pub rewrite: Option<RewriteFn>,
+}
+
+impl Default for UrlFilterOptions {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: DEFAULT_ALLOWED_SCHEMES.iter().map(|s| s.to_string()).collect(),
+            rewrite: None,
+        }
+    }
+}
+
+impl UrlFilterOptions {
+    fn filter(&self, url: &str) -> Option<String> {
+        if let Some(scheme) = get_scheme(url) {
+            if !self.allowed_schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+                return None;
+            }
+        }
+
+        match &self.rewrite {
+            Some(rewrite) => rewrite(url),
+            None => Some(url.to_owned()),
+        }
+    }
+}
+
+impl MarkdownItExt for UrlFilterOptions {}
+
+// returns scheme (without `:`) if url has one
+fn get_scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(':')?;
+    let mut chars = scheme.chars();
+    if !chars.next()?.is_ascii_alphabetic() { return None; }
+    if !chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.')) { return None; }
+    Some(scheme)
+}
+
+pub fn add(md: &mut MarkdownIt, options: UrlFilterOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<UrlFilterRule>() {
+        md.add_rule::<UrlFilterRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+fn get_url_mut(node: &mut Node) -> Option<&mut String> {
+    if node.is::<Link>() {
+        return node.cast_mut::<Link>().map(|link| &mut link.url);
+    }
+    if node.is::<Image>() {
+        return node.cast_mut::<Image>().map(|image| &mut image.url);
+    }
+    if node.is::<Autolink>() {
+        return node.cast_mut::<Autolink>().map(|link| &mut link.url);
+    }
+    if node.is::<ObfuscatedMailto>() {
+        return node.cast_mut::<ObfuscatedMailto>().map(|link| &mut link.url);
+    }
+    #[cfg(feature = "linkify")]
+    if node.is::<crate::plugins::extra::linkify::Linkified>() {
+        return node.cast_mut::<crate::plugins::extra::linkify::Linkified>().map(|link| &mut link.url);
+    }
+    None
+}
+
+#[doc(hidden)]
+pub struct UrlFilterRule;
+impl CoreRule for UrlFilterRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        fn walk_recursive(node: &mut Node, options: &UrlFilterOptions) {
+            let mut idx = 0;
+            while idx < node.children.len() {
+                let child = &mut node.children[idx];
+
+                if let Some(url) = get_url_mut(child) {
+                    if let Some(new_url) = options.filter(url) {
+                        *url = new_url;
+                    } else {
+                        // rejected url, keep only link contents
+                        let children = std::mem::take(&mut child.children);
+                        node.children.splice(idx..=idx, children);
+                        continue;
+                    }
+                }
+
+                stacker::maybe_grow(64*1024, 1024*1024, || {
+                    walk_recursive(child, options);
+                });
+                idx += 1;
+            }
+        }
+
+        let parse_options = ParseOptions::of_root(root);
+        let Some(options) = parse_options.ext_or::<UrlFilterOptions>(md) else { return; };
+        walk_recursive(root, options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_scheme, UrlFilterOptions};
+
+    fn run(src: &str, options: UrlFilterOptions) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        super::add(md, options);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn scheme() {
+        assert_eq!(get_scheme("http://x"), Some("http"));
+        assert_eq!(get_scheme("a+b.c-d:x"), Some("a+b.c-d"));
+        assert_eq!(get_scheme("/a:b"), None);
+        assert_eq!(get_scheme("a/b:c"), None);
+        assert_eq!(get_scheme("#a"), None);
+    }
+
+    #[test]
+    fn default_schemes() {
+        // `data:` urls are valid for default link formatter, but not allowed here
+        assert_eq!(
+            run("![*a*](data:image/png;base64,AAAA) <HTTPS://x> [b](tel:1) [c](#d)", UrlFilterOptions::default()),
+            "<p><em>a</em> <a href=\"HTTPS://x\">HTTPS://x</a> <a href=\"tel:1\">b</a> <a href=\"#d\">c</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn custom_schemes() {
+        let options = UrlFilterOptions { allowed_schemes: vec!["gopher".into()], ..Default::default() };
+        assert_eq!(
+            run("[a](gopher://x) [b](http://x) [c](e:f) [d](/d)", options),
+            "<p><a href=\"gopher://x\">a</a> b c <a href=\"/d\">d</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn rewrite() {
+        let options = UrlFilterOptions {
+            rewrite: Some(Box::new(|url| (url != "/x").then(|| format!("{url}?ref=1")))),
+            ..Default::default()
+        };
+        assert_eq!(
+            run("[a](/a) [b](/x) ![c](javascript:x)", options),
+            "<p><a href=\"/a?ref=1\">a</a> b ![c](javascript:x)</p>\n"
+        );
+    }
+}
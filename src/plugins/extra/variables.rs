@@ -0,0 +1,177 @@
+//! Replace `{{name}}` placeholders with values of variables, e.g. for product
+//! versions that change between releases of documentation.
+//!
+//! Values come from a map set with [set_variables] (or [Variables] in [ParseOptions::ext]
+//! of a document), then from a callback set with [set_resolver]. They are inserted as
+//! text, so html in values is escaped.
+//!
+//! Only text is affected: code spans, code blocks and link urls are kept as is, and
+//! placeholders can be escaped with a backslash (`\{{name}}`). Unknown variables are
+//! kept too, unless [VariablesOptions::keep_unknown] is disabled.
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::variables::add(md);
+//! markdown_it::plugins::extra::variables::set_variables(md, HashMap::from([
+//!     ("version".into(), "2.1".into()),
+//! ]));
+//!
+//! let html = md.parse("Install *{{ version }}*, not `{{version}}` or \\{{version}}.").render();
+//! assert_eq!(html, "<p>Install <em>2.1</em>, not <code>{{version}}</code> or {{version}}.</p>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::Text;
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::inline::backticks::CodeInline;
+use crate::{MarkdownIt, Node};
+
+static PLACEHOLDER_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{\{[ \t]*([A-Za-z_][A-Za-z0-9_.-]*)[ \t]*\}\}"#).unwrap()
+});
+
+/// Function that returns value of a variable by its name.
+pub type VariableResolverFn = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+#[derive(Debug, Default, Clone)]
+/// Values of variables, set with [set_variables] or in [ParseOptions::ext].
+pub struct Variables(pub HashMap<String, String>);
+impl MarkdownItExt for Variables {}
+
+struct VariableResolver(Box<VariableResolverFn>);
+
+impl Debug for VariableResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VariableResolver").finish()
+    }
+}
+
+impl MarkdownItExt for VariableResolver {}
+
+#[derive(Debug, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct VariablesOptions {
+    /// Keep placeholders of unknown variables as is, otherwise they are removed;
+    /// enabled by default.
+    pub keep_unknown: bool,
+}
+
+impl Default for VariablesOptions {
+    fn default() -> Self {
+        Self { keep_unknown: true }
+    }
+}
+
+impl MarkdownItExt for VariablesOptions {}
+
+pub fn add(md: &mut MarkdownIt) {
+    add_with_options(md, VariablesOptions::default());
+}
+
+pub fn add_with_options(md: &mut MarkdownIt, options: VariablesOptions) {
+    md.ext.insert(options);
+
+    if !md.has_rule::<VariablesRule>() {
+        md.add_rule::<VariablesRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+/// Set values of variables available in all documents.
+pub fn set_variables(md: &mut MarkdownIt, variables: HashMap<String, String>) {
+    md.ext.insert(Variables(variables));
+}
+
+/// Set a callback for variables that don't have values in [Variables].
+pub fn set_resolver(md: &mut MarkdownIt, resolver: impl Fn(&str) -> Option<String> + Send + Sync + 'static) {
+    md.ext.insert(VariableResolver(Box::new(resolver)));
+}
+
+#[doc(hidden)]
+pub struct VariablesRule;
+impl CoreRule for VariablesRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let options = parse_options.ext_or::<VariablesOptions>(md).copied().unwrap_or_default();
+        let variables = parse_options.ext_or::<Variables>(md);
+        let resolver = md.ext.get::<VariableResolver>();
+
+        let lookup = |name: &str| {
+            variables.and_then(|variables| variables.0.get(name).cloned())
+                .or_else(|| resolver.and_then(|resolver| (resolver.0)(name)))
+        };
+
+        replace_recursive(root, &mut |text| {
+            if !text.content.contains("{{") { return; }
+
+            let content = PLACEHOLDER_RE.replace_all(&text.content, |captures: &Captures| {
+                match lookup(&captures[1]) {
+                    Some(value) => value,
+                    None if options.keep_unknown => captures[0].to_owned(),
+                    None => String::new(),
+                }
+            });
+            text.content = content.into_owned();
+        });
+    }
+}
+
+// calls `f` on text nodes, skipping contents of code spans
+fn replace_recursive(node: &mut Node, f: &mut impl FnMut(&mut Text)) {
+    if node.is::<CodeInline>() { return; }
+
+    if let Some(text) = node.cast_mut::<Text>() {
+        f(text);
+    }
+
+    for child in node.children.iter_mut() {
+        stacker::maybe_grow(64*1024, 1024*1024, || {
+            replace_recursive(child, f);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Variables, VariablesOptions};
+    use crate::parser::options::ParseOptions;
+    use std::collections::HashMap;
+
+    fn make_md(options: VariablesOptions) -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        super::add_with_options(&mut md, options);
+        super::set_variables(&mut md, HashMap::from([("a".into(), "<x>".into())]));
+        super::set_resolver(&mut md, |name| name.strip_prefix("env.").map(str::to_uppercase));
+        md
+    }
+
+    #[test]
+    fn variables() {
+        let md = make_md(VariablesOptions::default());
+        assert_eq!(md.parse("{{a}}{{env.b}} {{c}} {{ a b }} [{{a}}](/{{a}})").render(),
+            "<p>&lt;x&gt;B {{c}} {{ a b }} <a href=\"/%7B%7Ba%7D%7D\">&lt;x&gt;</a></p>\n");
+        assert_eq!(md.parse("# {{a}} `{{a}}`\n\n    {{a}}").render(),
+            "<h1>&lt;x&gt; <code>{{a}}</code></h1>\n<pre><code>{{a}}\n</code></pre>\n");
+    }
+
+    #[test]
+    fn options() {
+        let md = make_md(VariablesOptions { keep_unknown: false });
+        assert_eq!(md.parse("a{{b}}c \\{{b}}").render(), "<p>ac {{b}}</p>\n");
+
+        let mut options = ParseOptions::new();
+        options.ext.insert(Variables(HashMap::from([("a".into(), "doc".into())])));
+        options.ext.insert(VariablesOptions { keep_unknown: true });
+        assert_eq!(md.parse_with_options("{{a}} {{b}}", options).render(), "<p>doc {{b}}</p>\n");
+    }
+}
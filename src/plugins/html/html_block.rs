@@ -5,9 +5,12 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 
 use super::utils::blocks::*;
+use crate::common::utils::html_to_text;
 use super::utils::regexps::*;
 use crate::parser::block::{BlockRule, BlockState};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
 
 #[derive(Debug)]
 pub struct HtmlBlock {
@@ -20,6 +23,29 @@ impl NodeValue for HtmlBlock {
         fmt.text_raw(&self.content);
         fmt.cr();
     }
+
+    fn render_format(&self, node: &Node, fmt: &mut dyn Renderer, format: &str) -> bool {
+        match format {
+            "html" => self.render(node, fmt),
+            "text" => {
+                fmt.cr();
+                fmt.text(html_to_text(&self.content).trim());
+                fmt.cr();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "content": self.content })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { content: field(data, "content")? })
+    }
 }
 
 pub fn add(md: &mut MarkdownIt) {
@@ -0,0 +1,231 @@
+//! Markdown inside of html blocks, as in PHP Markdown Extra.
+//!
+//! Contents of html elements with `markdown="1"` attribute are parsed as markdown
+//! instead of being passed through as is. Opening and closing tags must be on
+//! their own lines, otherwise element is treated as a regular html block.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::html::add(md);
+//! markdown_it::plugins::html::markdown_in_html::add(md);
+//!
+//! let html = md.parse("<div markdown=\"1\" class=\"note\">\n*hello*\n</div>").render();
+//! assert_eq!(html, "<div class=\"note\">\n<p><em>hello</em></p>\n</div>\n");
+//! ```
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::html_block::HtmlBlockScanner;
+use super::utils::regexps::HTML_OPEN_TAG_LINE_RE;
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::extset::RootExt;
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+#[cfg(feature = "serde")]
+use crate::parser::deserialize::field;
+
+static MARKDOWN_ATTR_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\s+markdown\s*=\s*(?:"1"|'1'|1)(\s|$)"#).unwrap()
+});
+
+static TAG_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<(/?)([A-Za-z][A-Za-z0-9\-]*)(?:\s[^>]*)?>").unwrap()
+});
+
+#[derive(Debug)]
+/// Html element with markdown content, children are the parsed blocks.
+pub struct HtmlMarkdownBlock {
+    /// Tag name, e.g. `div`.
+    pub tag: String,
+    /// Opening tag as written in the source, minus `markdown` attribute.
+    pub open: String,
+}
+
+impl NodeValue for HtmlMarkdownBlock {
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        fmt.cr();
+        fmt.text_raw(&self.open);
+        fmt.cr();
+        fmt.contents(&node.children);
+        fmt.cr();
+        fmt.text_raw(&format!("</{}>", self.tag));
+        fmt.cr();
+    }
+
+    #[cfg(feature = "serde")]
+    fn serialize_data(&self) -> serde_json::Value {
+        serde_json::json!({ "tag": self.tag, "open": self.open })
+    }
+
+    #[cfg(feature = "serde")]
+    fn deserialize_data(data: &serde_json::Value) -> Option<Self> {
+        Some(Self { tag: field(data, "tag")?, open: field(data, "open")? })
+    }
+}
+
+pub fn add(md: &mut MarkdownIt) {
+    md.block.add_rule::<HtmlMarkdownScanner>()
+        .before::<HtmlBlockScanner>();
+}
+
+#[doc(hidden)]
+pub struct HtmlMarkdownScanner;
+
+impl HtmlMarkdownScanner {
+    // returns (tag, opening tag without markdown attr, line with closing tag)
+    fn find_block(state: &mut BlockState) -> Option<(String, String, usize)> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+
+        let line_text = state.get_line(state.line);
+        let captures = HTML_OPEN_TAG_LINE_RE.captures(line_text)?;
+        let tag = captures[1].to_owned();
+        let attrs = captures.get(2).unwrap();
+        let markdown_attr = MARKDOWN_ATTR_RE.captures(attrs.as_str())?;
+
+        let mut open = String::new();
+        open.push_str(&line_text[..attrs.start() + markdown_attr.get(0).unwrap().start()]);
+        open.push_str(&markdown_attr[1]);
+        open.push_str(line_text[attrs.start() + markdown_attr.get(0).unwrap().end()..].trim_end());
+
+        let close_line = Self::find_close(state, &tag)?;
+        Some((tag, open, close_line))
+    }
+
+    // returns line with the tag closing element opened at current line
+    fn find_close(state: &mut BlockState, tag: &str) -> Option<usize> {
+        let key = (tag.to_ascii_lowercase(), state.line_max, state.blk_indent);
+        let cache = state.root_ext.get_or_insert_default::<HtmlMarkdownScanCache>();
+        if let Some(scan) = cache.0.get(&key) {
+            if scan.from <= state.line && state.line < scan.to {
+                if let Some(&close) = scan.closes.get(&state.line) { return close; }
+            }
+        }
+
+        let scan = Self::scan(state, tag);
+        let close = scan.closes.get(&state.line).copied().flatten();
+        let cache = state.root_ext.get_or_insert_default::<HtmlMarkdownScanCache>();
+        cache.0.insert(key, scan);
+        close
+    }
+
+    // matches all tags from current line to the end of the parent block in one pass,
+    // so elements opened inside of the current one don't need to be scanned again
+    fn scan(state: &BlockState, tag: &str) -> TagScan {
+        let mut scan = TagScan { from: state.line, to: state.line + 1, closes: HashMap::new() };
+        let mut open = vec![state.line];
+
+        while scan.to < state.line_max && state.line_indent(scan.to) >= 0 {
+            let line = scan.to;
+            let line_text = state.get_line(line);
+            let tags = TAG_RE.captures_iter(line_text)
+                .filter(|captures| captures[2].eq_ignore_ascii_case(tag) && !captures[0].ends_with("/>"));
+
+            for (idx, captures) in tags.enumerate() {
+                if captures[1].is_empty() {
+                    // only elements opened at the beginning of a line can be looked up
+                    open.push(if idx == 0 { line } else { usize::MAX });
+                } else if let Some(open_line) = open.pop() {
+                    // closing tag must be alone on its line
+                    let alone = captures[0].len() == line_text.trim_end().len();
+                    scan.closes.insert(open_line, alone.then_some(line));
+                }
+            }
+
+            scan.to += 1;
+        }
+
+        for open_line in open {
+            scan.closes.insert(open_line, None);
+        }
+        scan
+    }
+}
+
+#[derive(Debug)]
+struct TagScan {
+    // lines scanned, elements opened outside of them aren't known
+    from: usize,
+    to: usize,
+    // opening line -> closing line, `None` if element isn't closed properly
+    closes: HashMap<usize, Option<usize>>,
+}
+
+#[derive(Debug, Default)]
+// (tag, line_max, blk_indent) -> last scan for closing tags
+struct HtmlMarkdownScanCache(HashMap<(String, usize, usize), TagScan>);
+impl RootExt for HtmlMarkdownScanCache {}
+
+impl BlockRule for HtmlMarkdownScanner {
+    fn check(state: &mut BlockState) -> Option<()> {
+        Self::find_block(state).map(|_| ())
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        let (tag, open, close_line) = Self::find_block(state)?;
+
+        let start_line = state.line;
+        let old_line_max = state.line_max;
+        let old_node = std::mem::replace(&mut state.node, Node::new(HtmlMarkdownBlock { tag, open }));
+
+        state.line = start_line + 1;
+        state.line_max = close_line;
+        state.level += 1;
+        state.md.block.tokenize(state);
+        state.level -= 1;
+
+        state.line = start_line;
+        state.line_max = old_line_max;
+
+        let node = std::mem::replace(&mut state.node, old_node);
+        Some((node, close_line + 1 - start_line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn run(src: &str) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        super::add(md);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn nested_markdown() {
+        assert_eq!(
+            run("<div markdown=1>\n# title\n\n- a\n- b\n</div>\n\ntext"),
+            "<div>\n<h1>title</h1>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n</div>\n<p>text</p>\n"
+        );
+        assert_eq!(
+            run("<section markdown='1'>\n<div markdown=\"1\">\n**a**\n</div>\n</section>"),
+            "<section>\n<div>\n<p><strong>a</strong></p>\n</div>\n</section>\n"
+        );
+    }
+
+    #[test]
+    fn same_tag_inside() {
+        assert_eq!(
+            run("<div markdown=\"1\">\n<div>\n*raw*\n</div>\n\n*md*\n</div>"),
+            "<div>\n<div>\n*raw*\n</div>\n<p><em>md</em></p>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn not_markdown() {
+        assert_eq!(run("<div>\n*a*\n</div>"), "<div>\n*a*\n</div>\n");
+        assert_eq!(run("<div markdown=\"0\">\n*a*\n</div>"), "<div markdown=\"0\">\n*a*\n</div>\n");
+        assert_eq!(run("<div markdown=\"1\">*a*</div>"), "<div markdown=\"1\">*a*</div>\n");
+        assert_eq!(run("<div markdown=\"1\">\n*a*"), "<div markdown=\"1\">\n*a*\n");
+    }
+
+    #[test]
+    fn unclosed_outside() {
+        assert_eq!(
+            run("<div markdown=\"1\">\n\n<DIV markdown=\"1\">\n*a*\n</div>"),
+            "<div markdown=\"1\">\n<DIV>\n<p><em>a</em></p>\n</DIV>\n"
+        );
+    }
+}
@@ -6,7 +6,7 @@
 //! You can enable it if you're:
 //!  - looking for strict CommonMark compatibility
 //!  - only have trusted input (i.e. writing markdown yourself)
-//!  - or took some care to sanitize html yourself
+//!  - or took some care to sanitize html yourself (see [policy] module)
 //!
 //! ```rust
 //! let md = &mut markdown_it::MarkdownIt::new();
@@ -19,6 +19,8 @@
 
 pub mod html_block;
 pub mod html_inline;
+pub mod markdown_in_html;
+pub mod policy;
 mod utils;
 
 use crate::MarkdownIt;
@@ -0,0 +1,370 @@
+//! Policy for raw html found in the document.
+//!
+//! By default, raw html is passed through as is, which is unsafe for untrusted
+//! input. You can escape all of it instead, or only keep a small set of tags,
+//! similar to GFM's disallowed raw html extension:
+//!
+//!  - tags that are not in the allowlist are escaped (`<script>` -> `&lt;script>`),
+//!    same goes for comments, processing instructions, declarations and CDATA
+//!  - attributes of allowed tags are kept only if they are allowed for that tag
+//!    ([DEFAULT_ATTRIBUTES] unless set with [SanitizePolicy::attributes]),
+//!    event handlers (`onclick`, ...) are always removed
+//!  - attributes with urls ([URL_ATTRIBUTES]) are removed if link validation
+//!    fails on them (e.g. `javascript:` urls)
+//!
+//! ```rust
+//! use markdown_it::plugins::html::policy::HtmlPolicy;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::html::add(md);
+//! markdown_it::plugins::html::policy::set_policy(md, HtmlPolicy::sanitize(["b", "a"]));
+//!
+//! let html = md.parse("<b onclick=\"x()\">hi</b> <a href=\"javascript:x()\">a</a> <script>x()</script>").render();
+//! assert_eq!(html.trim(), "<p><b>hi</b> <a>a</a> &lt;script>x()&lt;/script></p>");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+
+use super::html_block::HtmlBlock;
+use super::html_inline::HtmlInline;
+use super::markdown_in_html::HtmlMarkdownBlock;
+use super::utils::regexps::{HTML_ATTRIBUTE_RE, HTML_OPEN_CLOSE_TAG_RE, HTML_TAG_PARTS_RE};
+use crate::common::utils::escape_html;
+use crate::parser::core::CoreRule;
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::inline::builtin::InlineParserRule;
+use crate::{MarkdownIt, Node};
+
+/// Attributes kept by [HtmlPolicy::sanitize] as `(tag, attribute)` pairs,
+/// `*` tag means any allowed tag.
+pub const DEFAULT_ATTRIBUTES: &[(&str, &str)] = &[
+    ("*", "class"), ("*", "dir"), ("*", "id"), ("*", "lang"), ("*", "title"),
+    ("a", "href"), ("a", "name"),
+    ("abbr", "title"),
+    ("blockquote", "cite"), ("del", "cite"), ("ins", "cite"), ("q", "cite"),
+    ("details", "open"),
+    ("img", "alt"), ("img", "height"), ("img", "src"), ("img", "width"),
+    ("li", "value"),
+    ("ol", "reversed"), ("ol", "start"), ("ol", "type"),
+    ("td", "align"), ("td", "colspan"), ("td", "rowspan"),
+    ("th", "align"), ("th", "colspan"), ("th", "rowspan"), ("th", "scope"),
+    ("time", "datetime"),
+];
+
+/// Attributes that contain urls, their values are checked with
+/// [LinkFormatter::validate_link](crate::parser::linkfmt::LinkFormatter::validate_link)
+/// (each candidate for `srcset`).
+pub const URL_ATTRIBUTES: &[&str] = &[
+    "action", "background", "cite", "codebase", "data", "formaction", "href",
+    "longdesc", "manifest", "ping", "poster", "src", "srcset", "usemap", "xlink:href",
+];
+
+#[derive(Debug, Clone)]
+/// Tags and attributes kept by [HtmlPolicy::Sanitize] (lowercase names).
+pub struct SanitizePolicy {
+    pub tags: Vec<String>,
+    /// Allowed attributes as `(tag, attribute)` pairs, `*` tag means any allowed tag.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl SanitizePolicy {
+    fn allows_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    fn allows_attribute(&self, tag: &str, name: &str) -> bool {
+        // event handlers can't be allowed
+        if name.starts_with("on") { return false; }
+        self.attributes.iter().any(|(t, n)| (t == "*" || t.eq_ignore_ascii_case(tag)) && n == name)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+/// What to do with raw html blocks and inline html.
+pub enum HtmlPolicy {
+    /// Output html as is (default).
+    #[default]
+    Allow,
+    /// Escape all html, so it's displayed as text.
+    Escape,
+    /// Only keep tags and attributes from the allowlist, escape other tags.
+    Sanitize(SanitizePolicy),
+}
+
+impl HtmlPolicy {
+    /// Create [HtmlPolicy::Sanitize] policy from a list of tag names,
+    /// keeping [DEFAULT_ATTRIBUTES].
+    pub fn sanitize<T: AsRef<str>>(tags: impl IntoIterator<Item = T>) -> Self {
+        let attributes = DEFAULT_ATTRIBUTES.iter().map(|&(tag, name)| (tag.to_owned(), name.to_owned()));
+        Self::sanitize_with_attributes(tags, attributes)
+    }
+
+    /// Create [HtmlPolicy::Sanitize] policy from a list of tag names
+    /// and `(tag, attribute)` pairs.
+    pub fn sanitize_with_attributes<T: AsRef<str>, A: AsRef<str>>(
+        tags: impl IntoIterator<Item = T>,
+        attributes: impl IntoIterator<Item = (A, A)>,
+    ) -> Self {
+        Self::Sanitize(SanitizePolicy {
+            tags: tags.into_iter().map(|tag| tag.as_ref().to_ascii_lowercase()).collect(),
+            attributes: attributes.into_iter()
+                .map(|(tag, name)| (tag.as_ref().to_ascii_lowercase(), name.as_ref().to_ascii_lowercase()))
+                .collect(),
+        })
+    }
+
+    fn allows(&self, tag: &str) -> bool {
+        match self {
+            Self::Allow => true,
+            Self::Escape => false,
+            Self::Sanitize(policy) => policy.allows_tag(tag),
+        }
+    }
+
+    fn apply(&self, md: &MarkdownIt, content: &str) -> String {
+        match self {
+            Self::Allow => content.to_owned(),
+            Self::Escape => escape_html(content).into_owned(),
+            Self::Sanitize(policy) => sanitize_html(md, policy, content),
+        }
+    }
+
+}
+
+fn sanitize_html(md: &MarkdownIt, policy: &SanitizePolicy, content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut pos = 0;
+
+    while let Some(offset) = content[pos..].find('<') {
+        let start = pos + offset;
+        result.push_str(&content[pos..start]);
+
+        if let Some(tag) = HTML_OPEN_CLOSE_TAG_RE.find(&content[start..]) {
+            let tag = tag.as_str();
+            if let Some(captures) = HTML_TAG_PARTS_RE.captures(tag) {
+                if policy.allows_tag(&captures[2]) {
+                    if captures[1].is_empty() {
+                        result.push_str(&clean_open_tag(md, policy, &captures));
+                    } else {
+                        result.push_str(tag);
+                    }
+                    pos = start + tag.len();
+                    continue;
+                }
+            }
+        }
+
+        result.push_str("&lt;");
+        pos = start + 1;
+    }
+
+    result.push_str(&content[pos..]);
+    result
+}
+
+// rebuild opening tag, only keeping allowed attributes with safe urls
+fn clean_open_tag(md: &MarkdownIt, policy: &SanitizePolicy, captures: &regex::Captures) -> String {
+    let tag = &captures[2];
+    let mut result = format!("<{tag}");
+
+    for attr in HTML_ATTRIBUTE_RE.captures_iter(&captures[3]) {
+        let name = attr[1].to_ascii_lowercase();
+        if !policy.allows_attribute(tag, &name) { continue; }
+
+        if URL_ATTRIBUTES.contains(&name.as_str()) {
+            let value = attr.get(2).map_or("", |m| m.as_str());
+            let value = clean_url(&decode_attribute(value.trim_matches(|c| c == '"' || c == '\'')));
+            let valid = |url: &str| md.link_formatter.validate_link(&md.link_formatter.normalize_link(url)).is_some();
+            let safe = if name == "srcset" {
+                // `url 2x, url 100w`
+                value.split(',').all(|candidate| valid(&clean_url(candidate.split_whitespace().next().unwrap_or(""))))
+            } else {
+                valid(&value)
+            };
+            if !safe { continue; }
+
+            // checked value is written, so that browsers see the same url
+            result.push_str(&format!(" {}=\"{}\"", &attr[1], escape_html(&value)));
+            continue;
+        }
+
+        result.push_str(&attr[0]);
+    }
+
+    result.push_str(&captures[4]);
+    result.push('>');
+    result
+}
+
+// decode character references in attribute value the way browsers do it,
+// including control characters (markdown escapes don't apply in html)
+fn decode_attribute(value: &str) -> Cow<'_, str> {
+    static CHAR_REF_RE : Lazy<Regex> = Lazy::new(||
+        Regex::new(r"&(?:#[xX]([0-9a-fA-F]{1,8})|#([0-9]{1,10})|[a-zA-Z][a-zA-Z0-9]*);?").unwrap()
+    );
+
+    if !value.contains('&') { return Cow::Borrowed(value); }
+
+    CHAR_REF_RE.replace_all(value, |captures: &regex::Captures| {
+        let code = match (captures.get(1), captures.get(2)) {
+            (Some(hex), _) => u32::from_str_radix(hex.as_str(), 16).ok(),
+            (_, Some(dec)) => dec.as_str().parse().ok(),
+            _ => return html_escape::decode_html_entities(&captures[0]).into_owned(),
+        };
+        let ch = code.filter(|code| *code != 0).and_then(char::from_u32).unwrap_or('\u{FFFD}');
+        ch.to_string()
+    })
+}
+
+// url as browsers read it: without tabs and newlines, and without
+// control characters and spaces at both ends
+fn clean_url(url: &str) -> String {
+    url.replace(['\t', '\n', '\r'], "").trim_matches(|c: char| c <= ' ').to_owned()
+}
+
+#[derive(Debug, Default)]
+struct HtmlPolicySettings(HtmlPolicy);
+impl MarkdownItExt for HtmlPolicySettings {}
+
+/// Set policy for raw html, it affects [HtmlBlock], [HtmlInline] and [HtmlMarkdownBlock] nodes.
+pub fn set_policy(md: &mut MarkdownIt, policy: HtmlPolicy) {
+    md.ext.insert(HtmlPolicySettings(policy));
+
+    if !md.has_rule::<HtmlPolicyRule>() {
+        md.add_rule::<HtmlPolicyRule>()
+            .after::<InlineParserRule>();
+    }
+}
+
+#[doc(hidden)]
+pub struct HtmlPolicyRule;
+impl CoreRule for HtmlPolicyRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let Some(HtmlPolicySettings(policy)) = md.ext.get::<HtmlPolicySettings>() else { return; };
+        if let HtmlPolicy::Allow = policy { return; }
+        apply_recursive(root, md, policy);
+    }
+}
+
+fn apply_recursive(node: &mut Node, md: &MarkdownIt, policy: &HtmlPolicy) {
+    let children = std::mem::take(&mut node.children);
+    node.children.reserve(children.len());
+
+    for mut child in children {
+        if let Some(html) = child.cast_mut::<HtmlBlock>() {
+            html.content = policy.apply(md, &html.content);
+        } else if let Some(html) = child.cast_mut::<HtmlInline>() {
+            html.content = policy.apply(md, &html.content);
+        } else if let Some(html) = child.cast_mut::<HtmlMarkdownBlock>() {
+            if policy.allows(&html.tag) {
+                html.open = policy.apply(md, &html.open);
+            } else {
+                // element is not allowed, but its markdown content is still fine,
+                // so only tags around it are escaped
+                let open = escape_html(&html.open).into_owned();
+                let close = escape_html(&format!("</{}>", html.tag)).into_owned();
+
+                apply_recursive(&mut child, md, policy);
+
+                let mut open_node = Node::new(HtmlBlock { content: open });
+                open_node.srcmap = child.srcmap;
+                let mut close_node = Node::new(HtmlBlock { content: close });
+                close_node.srcmap = child.srcmap;
+
+                node.children.push(open_node);
+                node.children.append(&mut child.children);
+                node.children.push(close_node);
+                continue;
+            }
+        }
+
+        stacker::maybe_grow(64*1024, 1024*1024, || {
+            apply_recursive(&mut child, md, policy);
+        });
+        node.children.push(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtmlPolicy;
+
+    fn run(src: &str, policy: HtmlPolicy) -> String {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        crate::plugins::html::markdown_in_html::add(md);
+        super::set_policy(md, policy);
+        md.parse(src).render()
+    }
+
+    #[test]
+    fn allow() {
+        assert_eq!(run("<script>x</script>\n\na<i>b</i>", HtmlPolicy::Allow), "<script>x</script>\n<p>a<i>b</i></p>\n");
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(
+            run("<div>\n*x*\n</div>\n\na<i>b</i>", HtmlPolicy::Escape),
+            "&lt;div&gt;\n*x*\n&lt;/div&gt;\n<p>a&lt;i&gt;b&lt;/i&gt;</p>\n"
+        );
+    }
+
+    #[test]
+    fn sanitize() {
+        let policy = HtmlPolicy::sanitize(["div", "IMG"]);
+        assert_eq!(
+            run("<div class=\"a\" onClick=\"x\"><!-- c --><span>\n", policy.clone()),
+            "<div class=\"a\">&lt;!-- c -->&lt;span>\n"
+        );
+        assert_eq!(
+            run("<img src='javascript&#58;x' alt=y/> <img src=\"a.png\" />", policy.clone()),
+            "<p><img alt=y/> <img src=\"a.png\" /></p>\n"
+        );
+        assert_eq!(run("a <x-y>b</x-y>", policy), "<p>a &lt;x-y>b&lt;/x-y></p>\n");
+    }
+
+    #[test]
+    fn sanitize_attributes() {
+        let policy = HtmlPolicy::sanitize(["a", "img", "form", "button", "iframe", "svg", "video", "object"]);
+        let src = "<form action=\"javascript:x\"><button formaction=\"javascript:x\" type=submit>\
+            <iframe srcdoc=\"&lt;script>x&lt;/script>\" style=\"x\"><svg xlink:href=\"javascript:x\">\
+            <video poster=\"javascript:x\"><object data=\"javascript:x\"><a HREF=\"/a\" name=b target=_blank>";
+        assert_eq!(run(src, policy), "<form><button><iframe><svg><video><object><a HREF=\"/a\" name=b>\n");
+
+        let policy = HtmlPolicy::sanitize_with_attributes(["img", "a"], [
+            ("img", "srcset"), ("*", "style"), ("a", "onclick"), ("a", "xlink:href"),
+        ]);
+        assert_eq!(
+            run("<img srcset=\"a.png 1x, javascript:x 2x\" style=\"x\"> <img srcset=\"a.png 1x, b.png 2x\"> \
+                <a onclick=x xlink:href=\"javascript:x\">", policy),
+            "<p><img style=\"x\"> <img srcset=\"a.png 1x, b.png 2x\"> <a></p>\n"
+        );
+    }
+
+    #[test]
+    fn sanitize_obfuscated_urls() {
+        let policy = HtmlPolicy::sanitize(["a"]);
+        assert_eq!(run("<a href=\"java&#x09;script:alert(1)\">x</a>", policy.clone()), "<p><a>x</a></p>\n");
+        assert_eq!(run("<a href=\"java\tscript:alert(1)\">x</a>", policy.clone()), "<p><a>x</a></p>\n");
+        assert_eq!(run("<a href=\"&#1;javascript:alert(1)\">x</a>", policy.clone()), "<p><a>x</a></p>\n");
+        // url is written as it was checked
+        assert_eq!(run("<a href=' /a&amp;b '>x</a>", policy), "<p><a href=\"/a&amp;b\">x</a></p>\n");
+    }
+
+    #[test]
+    fn markdown_in_html() {
+        let src = "<section markdown=\"1\" onclick=\"x\">\n*a*\n</section>";
+        assert_eq!(
+            run(src, HtmlPolicy::sanitize(["section"])),
+            "<section>\n<p><em>a</em></p>\n</section>\n"
+        );
+        assert_eq!(
+            run(src, HtmlPolicy::sanitize(["p"])),
+            "&lt;section onclick=&quot;x&quot;&gt;\n<p><em>a</em></p>\n&lt;/section&gt;\n"
+        );
+    }
+}
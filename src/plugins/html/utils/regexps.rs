@@ -44,3 +44,24 @@ pub static HTML_LINK_OPEN : Lazy<Regex> = Lazy::new(|| {
 pub static HTML_LINK_CLOSE : Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"^</a\s*>"#).unwrap()
 });
+
+// opening tag that takes an entire line, e.g. `<div class="foo">`
+pub static HTML_OPEN_TAG_LINE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        formatcp!("^<([A-Za-z][A-Za-z0-9\\-]*)({attribute}*)\\s*>\\s*$")
+    ).unwrap()
+});
+
+// opening or closing tag split into parts: slash, tag name, attributes, self-closing slash
+pub static HTML_TAG_PARTS_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        formatcp!("^<(/?)([A-Za-z][A-Za-z0-9\\-]*)({attribute}*)(\\s*/?)>")
+    ).unwrap()
+});
+
+// single attribute: name and optional value (with quotes)
+pub static HTML_ATTRIBUTE_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        formatcp!("\\s+({attr_name})(?:\\s*=\\s*({attr_value}))?")
+    ).unwrap()
+});
@@ -0,0 +1,256 @@
+//! Syntax extensions of [mdBook](https://rust-lang.github.io/mdBook/), to render
+//! its books without mdBook itself.
+//!
+//!  - `{{#include path}}` and `{{#include path:anchor}}`, also inside of code blocks
+//!    (see [include](crate::plugins::extra::include))
+//!  - `{{#playground path attributes}}`, included as a rust code block
+//!  - attributes of code blocks (` ```rust,ignore `) are added as classes,
+//!    and are available in [CodeBlockAttributes]
+//!  - lines starting with `# ` in rust code blocks are hidden (see [HiddenLines])
+//!
+//! Files are read by a loader callback, same as in [include](crate::plugins::extra::include).
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::mdbook::add(md, |path| match path {
+//!     "main.rs" => Some("fn main() {\n    // ANCHOR: body\n    run();\n    // ANCHOR_END: body\n}\n".into()),
+//!     _ => None,
+//! });
+//!
+//! let html = md.parse("```rust,no_run\n# fn run() {}\n{{#include main.rs:body}}\n```").render();
+//! assert_eq!(html, "<pre><code class=\"no_run language-rust\">\
+//!     <span class=\"boring\">fn run() {}\n</span>run();\n</code></pre>\n");
+//! ```
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::common::utils::escape_html;
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::extset::{MarkdownItExt, NodeExt};
+use crate::parser::options::ParseOptions;
+use crate::plugins::cmark::block::fence::{self, CodeFence};
+use crate::plugins::extra::include::{self, IncludeFenceRule, IncludeScanner};
+use crate::{MarkdownIt, Node};
+
+static PLAYGROUND_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^\{\{#playground[ \t]+([^{}\s:]+)(?::([A-Za-z_][\w-]*))?((?:[ \t]+[\w-]+)*)[ \t]*\}\}[ \t]*$"#).unwrap()
+});
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// What to do with hidden lines (starting with `# `) of rust code blocks.
+pub enum HiddenLines {
+    #[default]
+    /// Wrap them in `<span class="boring">`, for a stylesheet or script to hide
+    /// (same as mdBook).
+    Mark,
+    /// Remove them.
+    Remove,
+    /// Show them as normal lines (without `# `).
+    Show,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Options for [add_with_options], can also be set in [ParseOptions] of a document.
+pub struct MdBookOptions {
+    pub hidden_lines: HiddenLines,
+}
+
+impl MarkdownItExt for MdBookOptions {}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Attributes of a code block after its language (` ```rust,ignore,edition2021 `),
+/// stored in [CodeFence] nodes that have any.
+pub struct CodeBlockAttributes(pub Vec<String>);
+impl NodeExt for CodeBlockAttributes {}
+
+pub fn add(md: &mut MarkdownIt, loader: impl Fn(&str) -> Option<String> + Send + Sync + 'static) {
+    add_with_options(md, loader, MdBookOptions::default());
+}
+
+pub fn add_with_options(
+    md: &mut MarkdownIt,
+    loader: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    options: MdBookOptions,
+) {
+    include::add(md, loader);
+    md.ext.insert(options);
+
+    if !md.block.has_rule::<PlaygroundScanner>() {
+        md.block.add_rule::<PlaygroundScanner>();
+    }
+
+    if !md.has_rule::<MdBookFenceRule>() {
+        md.add_rule::<MdBookFenceRule>()
+            .after::<IncludeFenceRule>();
+    }
+}
+
+#[doc(hidden)]
+pub struct PlaygroundScanner;
+impl BlockRule for PlaygroundScanner {
+    fn check(state: &mut BlockState) -> Option<()> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+        PLAYGROUND_RE.is_match(state.get_line(state.line)).then_some(())
+    }
+
+    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+
+        let captures = PLAYGROUND_RE.captures(state.get_line(state.line))?;
+        let path = include::resolve_path(include::current_file(state.root_ext), &captures[1]);
+        let mut content = include::load(state.md, &path, captures.get(2).map(|m| m.as_str()))?;
+        if !content.is_empty() && !content.ends_with('\n') { content.push('\n'); }
+
+        let info = std::iter::once("rust").chain(captures[3].split_whitespace()).collect::<Vec<_>>().join(",");
+        let node = Node::new(CodeFence {
+            info,
+            marker: '`',
+            marker_len: 3,
+            content,
+            lang_prefix: fence::lang_prefix(state.md),
+            raw: false,
+        });
+        Some((node, 1))
+    }
+}
+
+// replace `{{#include}}` lines in code blocks of file `parent`
+fn include_lines(content: &str, md: &MarkdownIt, parent: Option<&str>) -> String {
+    let mut result = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let included = IncludeScanner::parse_line(line.trim())
+            .and_then(|(path, region)| include::load(md, &include::resolve_path(parent, path), region));
+
+        match included {
+            Some(included) => {
+                result.push_str(&included);
+                if !included.is_empty() && !included.ends_with('\n') { result.push('\n'); }
+            }
+            None => result.push_str(line),
+        }
+    }
+    result
+}
+
+// returns line without `# ` if it's hidden, `##` is an escaped `#`
+fn hidden_line(line: &str) -> (bool, String) {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    if trimmed == "#" || trimmed == "#\n" {
+        (true, trimmed[1..].to_owned())
+    } else if let Some(rest) = trimmed.strip_prefix("# ") {
+        (true, format!("{indent}{rest}"))
+    } else if let Some(rest) = trimmed.strip_prefix("##") {
+        (false, format!("{indent}#{rest}"))
+    } else {
+        (false, line.to_owned())
+    }
+}
+
+fn hide_lines(fence: &mut CodeFence, mode: HiddenLines) {
+    let mut result = String::with_capacity(fence.content.len());
+
+    for line in fence.content.split_inclusive('\n') {
+        let (hidden, line) = hidden_line(line);
+        match (hidden, mode) {
+            (true, HiddenLines::Remove) => {}
+            (true, HiddenLines::Mark) => {
+                result.push_str("<span class=\"boring\">");
+                result.push_str(&escape_html(&line));
+                result.push_str("</span>");
+            }
+            (_, HiddenLines::Mark) => result.push_str(&escape_html(&line)),
+            _ => result.push_str(&line),
+        }
+    }
+
+    fence.content = result;
+    if mode == HiddenLines::Mark { fence.raw = true; }
+}
+
+#[doc(hidden)]
+pub struct MdBookFenceRule;
+impl CoreRule for MdBookFenceRule {
+    fn run(root: &mut Node, md: &MarkdownIt) {
+        let parse_options = ParseOptions::of_root(root);
+        let options = parse_options.ext_or::<MdBookOptions>(md).copied().unwrap_or_default();
+        let parent = root.cast::<Root>().and_then(|root| include::current_file(&root.ext)).map(str::to_owned);
+
+        root.walk_mut(|node, _| {
+            let Some(fence) = node.cast_mut::<CodeFence>() else { return; };
+
+            if fence.content.contains("{{#include") {
+                fence.content = include_lines(&fence.content, md, parent.as_deref());
+            }
+
+            let (lang, rest) = fence.info.split_once(char::is_whitespace).unwrap_or((&fence.info, ""));
+            let rest = rest.to_owned();
+            let mut attributes = lang.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_owned);
+            let lang = attributes.next().unwrap_or_default();
+            let attributes = attributes.collect::<Vec<_>>();
+
+            if lang == "rust" && !fence.raw {
+                hide_lines(fence, options.hidden_lines);
+            }
+
+            if attributes.is_empty() { return; }
+            fence.info = if rest.is_empty() { lang } else { format!("{lang} {rest}") };
+            node.attrs.push(("class".into(), attributes.join(" ")));
+            node.ext.insert(CodeBlockAttributes(attributes));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CodeBlockAttributes, HiddenLines, MdBookOptions};
+
+    fn make_md(options: MdBookOptions) -> crate::MarkdownIt {
+        let mut md = crate::MarkdownIt::new();
+        crate::plugins::cmark::add(&mut md);
+        super::add_with_options(&mut md, |path| match path {
+            "a.rs" => Some("// ANCHOR: x\nfn a() {}\n// ANCHOR_END: x\n# fn b() {}".into()),
+            _ => None,
+        }, options);
+        md
+    }
+
+    #[test]
+    fn hidden_lines() {
+        let src = "```rust\n#\n  # a<b>\n## c\n#[d]\n```\n\n```text\n# e\n```";
+        assert_eq!(make_md(MdBookOptions::default()).parse(src).render(),
+            "<pre><code class=\"language-rust\"><span class=\"boring\">\n</span><span class=\"boring\">  a&lt;b&gt;\n</span>\
+            # c\n#[d]\n</code></pre>\n<pre><code class=\"language-text\"># e\n</code></pre>\n");
+        assert_eq!(make_md(MdBookOptions { hidden_lines: HiddenLines::Remove }).parse(src).render(),
+            "<pre><code class=\"language-rust\"># c\n#[d]\n</code></pre>\n<pre><code class=\"language-text\"># e\n</code></pre>\n");
+        assert_eq!(make_md(MdBookOptions { hidden_lines: HiddenLines::Show }).parse(src).render(),
+            "<pre><code class=\"language-rust\">\n  a&lt;b&gt;\n# c\n#[d]\n</code></pre>\n<pre><code class=\"language-text\"># e\n</code></pre>\n");
+    }
+
+    #[test]
+    fn attributes() {
+        let md = make_md(MdBookOptions::default());
+        let ast = md.parse("```rust,ignore,,edition2021 x\n```");
+        assert_eq!(ast.children[0].ext.get::<CodeBlockAttributes>().unwrap().0, ["ignore", "edition2021"]);
+        assert_eq!(ast.render(), "<pre><code class=\"ignore edition2021 language-rust\"></code></pre>\n");
+    }
+
+    #[test]
+    fn playground() {
+        let md = make_md(MdBookOptions { hidden_lines: HiddenLines::Remove });
+        assert_eq!(md.parse("{{#playground a.rs editable}}").render(),
+            "<pre><code class=\"editable language-rust\">// ANCHOR: x\nfn a() {}\n// ANCHOR_END: x\n</code></pre>\n");
+        assert_eq!(md.parse("{{#playground a.rs:x}}\n{{#playground b.rs}}").render(),
+            "<pre><code class=\"language-rust\">fn a() {}\n</code></pre>\n<p>{{#playground b.rs}}</p>\n");
+    }
+
+    #[test]
+    fn includes() {
+        let md = make_md(MdBookOptions::default());
+        assert_eq!(md.parse("```\n{{#include a.rs:x}}\n  {{#include b.rs}}\n```").render(),
+            "<pre><code>fn a() {}\n  {{#include b.rs}}\n</code></pre>\n");
+    }
+}
@@ -14,7 +14,19 @@
 //! markdown_it::plugins::sourcepos::add(md);
 //! // ...
 //! ```
+//!
+//! If the list of plugins is only known at runtime, use [PluginId] with
+//! [MarkdownIt::with_plugins](crate::MarkdownIt::with_plugins), or one of the
+//! [presets](preset) with [MarkdownIt::from_preset](crate::MarkdownIt::from_preset).
 pub mod cmark;
+#[cfg(feature = "comments")]
+pub mod comments;
 pub mod extra;
 pub mod html;
+#[cfg(feature = "mdbook")]
+pub mod mdbook;
+pub mod preset;
+pub mod registry;
 pub mod sourcepos;
+
+pub use registry::PluginId;
@@ -0,0 +1,274 @@
+//! Named sets of plugins and parser limits.
+//!
+//! Built-in presets cover common flavors of markdown, custom ones can be
+//! defined in code or read from a config file (with `serde` feature):
+//!
+//! ```rust
+//! # #[cfg(all(feature = "math", feature = "strikethrough"))] {
+//! use markdown_it::plugins::preset::{CustomPreset, Preset};
+//! use markdown_it::plugins::PluginId;
+//! use markdown_it::MarkdownIt;
+//!
+//! let md = MarkdownIt::from_preset(Preset::Gfm);
+//! assert_eq!(md.parse("~~a~~").render(), "<p><s>a</s></p>\n");
+//!
+//! let mut preset = Preset::CommonMark.to_custom();
+//! preset.plugins.push(PluginId::Math);
+//! preset.max_nesting = Some(20);
+//!
+//! let md = MarkdownIt::from_preset(Preset::Custom(preset));
+//! assert_eq!(md.max_nesting, 20);
+//!
+//! // presets can also be selected by name
+//! let md = MarkdownIt::from_preset("zero".parse().unwrap());
+//! assert_eq!(md.parse("*a*").render(), "*a*\n");
+//! # }
+//! ```
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::plugins::PluginId;
+use crate::MarkdownIt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Preset to create a parser with, see [MarkdownIt::from_preset].
+pub enum Preset {
+    /// No syntax at all, text is escaped and rendered line by line (a base for custom presets).
+    Zero,
+    /// CommonMark syntax including raw html, as defined by its spec.
+    CommonMark,
+    /// GitHub Flavored Markdown: CommonMark with tables, strikethrough,
+    /// autolinks (with `linkify` feature), footnotes and callouts.
+    Gfm,
+    /// Extensions enabled in pandoc markdown: tables, strikethrough, footnotes,
+    /// attributes, math, raw attributes, front matter, heading identifiers
+    /// and smart punctuation.
+    Pandoc,
+    Custom(CustomPreset),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// User-defined preset, plugins are added in order.
+pub struct CustomPreset {
+    pub plugins: Vec<PluginId>,
+    /// See [MarkdownIt::max_nesting], default is kept if not set.
+    pub max_nesting: Option<u32>,
+    /// See [MarkdownIt::max_input_size], default is kept if not set.
+    pub max_input_size: Option<usize>,
+}
+
+impl Preset {
+    /// Names of built-in presets, accepted by [FromStr].
+    pub const NAMES: &'static [&'static str] = &["zero", "commonmark", "gfm", "pandoc"];
+
+    /// Plugins added by this preset.
+    pub fn plugins(&self) -> Vec<PluginId> {
+        match self {
+            Self::Zero => vec![],
+            Self::CommonMark => vec![PluginId::Cmark, PluginId::Html],
+            Self::Gfm => vec![
+                PluginId::Cmark,
+                PluginId::Html,
+                #[cfg(feature = "tables")]
+                PluginId::Tables,
+                #[cfg(feature = "strikethrough")]
+                PluginId::Strikethrough,
+                #[cfg(feature = "linkify")]
+                PluginId::Linkify,
+                #[cfg(feature = "footnote")]
+                PluginId::Footnote,
+                #[cfg(feature = "callouts")]
+                PluginId::Callouts,
+            ],
+            Self::Pandoc => vec![
+                PluginId::Cmark,
+                PluginId::Html,
+                #[cfg(feature = "tables")]
+                PluginId::Tables,
+                #[cfg(feature = "strikethrough")]
+                PluginId::Strikethrough,
+                #[cfg(feature = "footnote")]
+                PluginId::Footnote,
+                #[cfg(feature = "attrs")]
+                PluginId::Attrs,
+                #[cfg(feature = "math")]
+                PluginId::Math,
+                #[cfg(feature = "raw_attribute")]
+                PluginId::RawAttribute,
+                #[cfg(feature = "front_matter")]
+                PluginId::FrontMatter,
+                #[cfg(feature = "heading_anchors")]
+                PluginId::HeadingAnchors,
+                #[cfg(feature = "smartquotes")]
+                PluginId::Smartquotes,
+                #[cfg(feature = "typographer")]
+                PluginId::Typographer,
+            ],
+            Self::Custom(preset) => preset.plugins.clone(),
+        }
+    }
+
+    /// Copy of this preset that can be changed.
+    pub fn to_custom(&self) -> CustomPreset {
+        match self {
+            Self::Custom(preset) => preset.clone(),
+            _ => CustomPreset { plugins: self.plugins(), ..Default::default() },
+        }
+    }
+
+    /// Add plugins of this preset to the parser and set its limits.
+    pub fn add(&self, md: &mut MarkdownIt) {
+        for plugin in self.plugins() {
+            plugin.add(md);
+        }
+
+        if let Self::Custom(preset) = self {
+            if let Some(max_nesting) = preset.max_nesting {
+                md.max_nesting = max_nesting;
+            }
+            if let Some(max_input_size) = preset.max_input_size {
+                md.max_input_size = max_input_size;
+            }
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = PresetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "zero" => Ok(Self::Zero),
+            "commonmark" => Ok(Self::CommonMark),
+            "gfm" => Ok(Self::Gfm),
+            "pandoc" => Ok(Self::Pandoc),
+            _ => Err(PresetError::UnknownPreset(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl CustomPreset {
+    /// Convert preset to JSON object (`{ "plugins": ["cmark"], "max_nesting": 20 }`).
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "plugins": self.plugins.iter().map(|id| id.name()).collect::<Vec<_>>(),
+        });
+        if let Some(max_nesting) = self.max_nesting {
+            value["max_nesting"] = max_nesting.into();
+        }
+        if let Some(max_input_size) = self.max_input_size {
+            value["max_input_size"] = max_input_size.into();
+        }
+        value
+    }
+
+    /// Read preset from JSON object created by [to_json](Self::to_json).
+    ///
+    /// It may also have `extends` field with a name of built-in preset,
+    /// its plugins are added before the listed ones.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, PresetError> {
+        use serde_json::Value;
+
+        let mut preset = match value.get("extends") {
+            Some(Value::String(name)) => name.parse::<Preset>()?.to_custom(),
+            Some(_) => return Err(PresetError::InvalidField("extends")),
+            None => Self::default(),
+        };
+
+        match value.get("plugins") {
+            Some(Value::Array(names)) => for name in names {
+                let name = name.as_str().ok_or(PresetError::InvalidField("plugins"))?;
+                preset.plugins.push(name.parse().map_err(PresetError::UnknownPlugin)?);
+            }
+            Some(_) => return Err(PresetError::InvalidField("plugins")),
+            None => {}
+        }
+
+        let limit = |name: &'static str| match value.get(name) {
+            Some(value) => value.as_u64().map(Some).ok_or(PresetError::InvalidField(name)),
+            None => Ok(None),
+        };
+
+        let max_nesting = limit("max_nesting")?;
+        preset.max_nesting = max_nesting.map(|n| n.try_into().unwrap_or(u32::MAX));
+        let max_input_size = limit("max_input_size")?;
+        preset.max_input_size = max_input_size.map(|n| n.try_into().unwrap_or(usize::MAX));
+
+        Ok(preset)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Error returned when preset can't be read.
+pub enum PresetError {
+    /// Name isn't one of [Preset::NAMES].
+    UnknownPreset(String),
+    /// Field has a wrong type (e.g. `"plugins": "cmark"`).
+    InvalidField(&'static str),
+    UnknownPlugin(super::registry::UnknownPlugin),
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownPreset(name) => write!(f, "unknown preset: {name}"),
+            Self::InvalidField(name) => write!(f, "invalid preset: wrong type of `{name}`"),
+            Self::UnknownPlugin(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::Preset;
+    use crate::MarkdownIt;
+
+    #[test]
+    fn builtin() {
+        let src = "| e |\n|---|\n\n<b>*a*</b> ~~b~~ $c$ \"d\"";
+
+        let render = |preset| MarkdownIt::from_preset(preset).parse(src).render();
+        assert_eq!(render(Preset::Zero), "| e |\n|---|\n&lt;b&gt;*a*&lt;/b&gt; ~~b~~ $c$ &quot;d&quot;\n");
+        assert_eq!(render(Preset::CommonMark), "<p>| e |\n|---|</p>\n<p><b><em>a</em></b> ~~b~~ $c$ &quot;d&quot;</p>\n");
+    }
+
+    #[cfg(all(feature = "math", feature = "smartquotes", feature = "strikethrough", feature = "tables"))]
+    #[test]
+    fn builtin_extra() {
+        let src = "| e |\n|---|\n\n<b>*a*</b> ~~b~~ $c$ \"d\"";
+
+        let render = |preset| MarkdownIt::from_preset(preset).parse(src).render();
+        let table = "<table>\n<thead>\n<tr>\n<th>e</th>\n</tr>\n</thead>\n</table>\n";
+        assert_eq!(render(Preset::Gfm), format!("{table}<p><b><em>a</em></b> <s>b</s> $c$ &quot;d&quot;</p>\n"));
+        assert_eq!(render(Preset::Pandoc), format!("{table}<p><b><em>a</em></b> <s>b</s> \
+            <span class=\"math inline\">\\(c\\)</span> “d”</p>\n"));
+    }
+
+    #[test]
+    fn names() {
+        for name in Preset::NAMES {
+            assert!(name.parse::<Preset>().is_ok());
+        }
+        assert_eq!("github".parse::<Preset>().unwrap_err().to_string(), "unknown preset: github");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json() {
+        use super::CustomPreset;
+        use crate::plugins::PluginId;
+
+        let value = serde_json::json!({ "extends": "commonmark", "plugins": ["math"], "max_nesting": 5 });
+        let preset = CustomPreset::from_json(&value).unwrap();
+        assert_eq!(preset.plugins, [PluginId::Cmark, PluginId::Html, PluginId::Math]);
+        assert_eq!(preset.max_nesting, Some(5));
+        assert_eq!(CustomPreset::from_json(&preset.to_json()), Ok(preset));
+
+        let error = |value| CustomPreset::from_json(&value).unwrap_err().to_string();
+        assert_eq!(error(serde_json::json!({ "plugins": ["foo"] })), "unknown plugin: foo");
+        assert_eq!(error(serde_json::json!({ "max_nesting": -1 })), "invalid preset: wrong type of `max_nesting`");
+    }
+}
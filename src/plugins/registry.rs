@@ -0,0 +1,236 @@
+//! Built-in plugins selected at runtime.
+//!
+//! Each plugin is normally added by calling its `add` function, which is
+//! resolved at compile time. When the list of plugins comes from configuration
+//! (e.g. site config or command line), use [PluginId] instead:
+//!
+//! ```rust
+//! # #[cfg(all(feature = "strikethrough", feature = "tables"))] {
+//! use markdown_it::plugins::PluginId;
+//!
+//! let plugins : Vec<PluginId> = "cmark, strikethrough, tables".split(", ")
+//!     .map(|name| name.parse().unwrap())
+//!     .collect();
+//!
+//! let md = markdown_it::MarkdownIt::with_plugins(&plugins);
+//! assert_eq!(md.parse("~~a~~").render(), "<p><s>a</s></p>\n");
+//! # }
+//! ```
+//!
+//! Plugins are only available with respective cargo features enabled (all of
+//! them are enabled by default, see [extra](crate::plugins::extra)). Plugins that require options
+//! (e.g. `url_filter`) can't be selected this way, add them with their `add` function.
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+#[cfg(feature = "comments")]
+use crate::plugins::comments;
+use crate::plugins::{cmark, extra, html, sourcepos};
+use crate::MarkdownIt;
+
+macro_rules! plugin_ids {
+    ($( $(#[cfg($cfg:meta)])? $(#[doc = $doc:literal])* $id:ident => $name:literal => $add:expr, )*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        /// Identifier of a built-in plugin, see [module documentation](self).
+        pub enum PluginId {
+            $( $(#[cfg($cfg)])? $(#[doc = $doc])* $id, )*
+        }
+
+        impl PluginId {
+            /// All plugins available with enabled cargo features.
+            pub const ALL: &'static [PluginId] = &[ $( $(#[cfg($cfg)])? PluginId::$id, )* ];
+
+            /// Name of the plugin, same as its module name.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $( $(#[cfg($cfg)])? PluginId::$id => $name, )*
+                }
+            }
+
+            /// Add the plugin to the parser.
+            pub fn add(self, md: &mut MarkdownIt) {
+                let add : fn(&mut MarkdownIt) = match self {
+                    $( $(#[cfg($cfg)])? PluginId::$id => $add, )*
+                };
+                add(md);
+            }
+        }
+    };
+}
+
+plugin_ids! {
+    /// All of CommonMark syntax, see [cmark].
+    Cmark => "cmark" => cmark::add,
+    /// Raw html blocks and inlines, see [html].
+    Html => "html" => html::add,
+    /// Source positions in `data-sourcepos` attributes, see [sourcepos].
+    Sourcepos => "sourcepos" => sourcepos::add,
+    #[cfg(feature = "comments")]
+    /// Preset for comment systems, see [comments].
+    Comments => "comments" => comments::add,
+    /// All plugins added by [extra::add].
+    Extra => "extra" => extra::add,
+    #[cfg(feature = "admonition")]
+    /// See [extra::admonition].
+    Admonition => "admonition" => extra::admonition::add,
+    #[cfg(feature = "attr_templates")]
+    /// See [extra::attr_templates].
+    AttrTemplates => "attr_templates" => extra::attr_templates::add,
+    #[cfg(feature = "attrs")]
+    /// See [extra::attrs].
+    Attrs => "attrs" => extra::attrs::add,
+    #[cfg(feature = "beautify_links")]
+    /// See [extra::beautify_links].
+    BeautifyLinks => "beautify_links" => extra::beautify_links::add,
+    #[cfg(feature = "callouts")]
+    /// See [extra::callouts].
+    Callouts => "callouts" => extra::callouts::add,
+    #[cfg(feature = "conditional")]
+    /// See [extra::conditional].
+    Conditional => "conditional" => extra::conditional::add,
+    #[cfg(feature = "critic")]
+    /// See [extra::critic].
+    Critic => "critic" => extra::critic::add,
+    #[cfg(feature = "dates")]
+    /// See [extra::dates].
+    Dates => "dates" => extra::dates::add,
+    #[cfg(feature = "details")]
+    /// See [extra::details].
+    Details => "details" => extra::details::add,
+    #[cfg(feature = "footnote")]
+    /// See [extra::footnote].
+    Footnote => "footnote" => extra::footnote::add,
+    #[cfg(feature = "front_matter")]
+    /// See [extra::front_matter].
+    FrontMatter => "front_matter" => extra::front_matter::add,
+    #[cfg(feature = "heading_anchors")]
+    /// See [extra::heading_anchors], uses [simple_slugify_fn](extra::heading_anchors::simple_slugify_fn).
+    HeadingAnchors => "heading_anchors" => |md| extra::heading_anchors::add(md, extra::heading_anchors::simple_slugify_fn),
+    #[cfg(feature = "heading_text")]
+    /// See [extra::heading_text].
+    HeadingText => "heading_text" => extra::heading_text::add,
+    #[cfg(feature = "linkify")]
+    /// See [extra::linkify].
+    Linkify => "linkify" => extra::linkify::add,
+    #[cfg(feature = "lint")]
+    /// See [extra::lint].
+    Lint => "lint" => extra::lint::add,
+    #[cfg(feature = "math")]
+    /// See [extra::math].
+    Math => "math" => extra::math::add,
+    #[cfg(feature = "quote_attribution")]
+    /// See [extra::quote_attribution].
+    QuoteAttribution => "quote_attribution" => extra::quote_attribution::add,
+    #[cfg(feature = "raw_attribute")]
+    /// See [extra::raw_attribute].
+    RawAttribute => "raw_attribute" => extra::raw_attribute::add,
+    #[cfg(feature = "ruby")]
+    /// See [extra::ruby].
+    Ruby => "ruby" => extra::ruby::add,
+    #[cfg(feature = "smartquotes")]
+    /// See [extra::smartquotes].
+    Smartquotes => "smartquotes" => extra::smartquotes::add,
+    #[cfg(feature = "spacing")]
+    /// See [extra::spacing].
+    Spacing => "spacing" => extra::spacing::add,
+    #[cfg(feature = "spoiler")]
+    /// See [extra::spoiler].
+    Spoiler => "spoiler" => extra::spoiler::add,
+    #[cfg(feature = "strikethrough")]
+    /// See [extra::strikethrough].
+    Strikethrough => "strikethrough" => extra::strikethrough::add,
+    #[cfg(feature = "substitutions")]
+    /// See [extra::substitutions].
+    Substitutions => "substitutions" => extra::substitutions::add,
+    #[cfg(feature = "syntect")]
+    /// See [extra::syntect].
+    Syntect => "syntect" => extra::syntect::add,
+    #[cfg(feature = "tables")]
+    /// See [extra::tables].
+    Tables => "tables" => extra::tables::add,
+    #[cfg(feature = "typographer")]
+    /// See [extra::typographer].
+    Typographer => "typographer" => extra::typographer::add,
+    #[cfg(feature = "unicode")]
+    /// See [extra::unicode].
+    Unicode => "unicode" => extra::unicode::add,
+    #[cfg(feature = "variables")]
+    /// See [extra::variables].
+    Variables => "variables" => extra::variables::add,
+}
+
+impl Display for PluginId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for PluginId {
+    type Err = UnknownPlugin;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter().copied()
+            .find(|id| id.name() == s)
+            .ok_or_else(|| UnknownPlugin(s.to_owned()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Error returned when plugin name isn't recognized (or its cargo feature is disabled).
+pub struct UnknownPlugin(pub String);
+
+impl Display for UnknownPlugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown plugin: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPlugin {}
+
+#[cfg(test)]
+mod tests {
+    use super::{PluginId, UnknownPlugin};
+    use crate::MarkdownIt;
+
+    #[test]
+    fn names() {
+        for &id in PluginId::ALL {
+            assert_eq!(id.name().parse::<PluginId>(), Ok(id));
+        }
+        assert_eq!("foo".parse::<PluginId>(), Err(UnknownPlugin("foo".into())));
+        #[cfg(feature = "beautify_links")]
+        assert_eq!(PluginId::BeautifyLinks.to_string(), "beautify_links");
+    }
+
+    #[test]
+    fn with_plugins() {
+        #[cfg(feature = "heading_anchors")] {
+            let md = MarkdownIt::with_plugins(&[PluginId::Cmark, PluginId::HeadingAnchors]);
+            assert_eq!(md.parse("# A b\n\n<b>").render(), "<h1 id=\"a-b\">A b</h1>\n<p>&lt;b&gt;</p>\n");
+        }
+
+        let md = MarkdownIt::with_plugins(&[PluginId::Cmark, PluginId::Html]);
+        assert_eq!(md.parse("<b>").render(), "<b>\n");
+    }
+
+    #[cfg(all(feature = "footnote", feature = "strikethrough", feature = "tables", feature = "typographer"))]
+    #[test]
+    fn same_as_add() {
+        let md1 = MarkdownIt::with_plugins(&[
+            PluginId::Cmark, PluginId::Strikethrough, PluginId::Tables, PluginId::Footnote, PluginId::Typographer,
+        ]);
+
+        let md2 = &mut MarkdownIt::new();
+        crate::plugins::cmark::add(md2);
+        crate::plugins::extra::strikethrough::add(md2);
+        crate::plugins::extra::tables::add(md2);
+        crate::plugins::extra::footnote::add(md2);
+        crate::plugins::extra::typographer::add(md2);
+
+        let src = "# a\n\n*b* ~~c~~ (c) --\n\n| d |\n|---|\n| e[^1] |\n\n[^1]: f";
+        let html = md2.parse(src).render();
+        assert!(html.contains("<s>c</s> ©") && html.contains("<table>") && html.contains("footnote"));
+        assert_eq!(md1.parse(src).render(), html);
+    }
+}
@@ -7,10 +7,23 @@
 //! let html = md.parse("# hello").render();
 //! assert_eq!(html.trim(), r#"<h1 data-sourcepos="1:1-1:7">hello</h1>"#);
 //! ```
+//!
+//! Live preview editors usually only need positions of block-level elements
+//! to synchronize scrolling, use [add_blocks] for that:
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::sourcepos::add_blocks(md);
+//!
+//! let html = md.parse("# hello\n\n> *world*").render();
+//! assert_eq!(html, "<h1 data-sourcepos=\"1:1-1:7\">hello</h1>\n<blockquote data-sourcepos=\"3:1-3:9\">\n<p data-sourcepos=\"3:3-3:9\"><em>world</em></p>\n</blockquote>\n");
+//! ```
 use crate::common::sourcemap::SourceWithLineStarts;
 use crate::parser::block::builtin::BlockParserRule;
 use crate::parser::core::{CoreRule, Root};
 use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::InlineRoot;
 use crate::{MarkdownIt, Node};
 
 pub fn add(md: &mut MarkdownIt) {
@@ -19,6 +32,20 @@ pub fn add(md: &mut MarkdownIt) {
         .after::<InlineParserRule>();
 }
 
+/// Only add source mapping to block-level elements (paragraphs, headings, lists, etc.).
+pub fn add_blocks(md: &mut MarkdownIt) {
+    md.add_rule::<BlockPosRule>()
+        .after::<BlockParserRule>()
+        .before::<InlineParserRule>();
+}
+
+fn add_sourcepos(node: &mut Node, mapping: &SourceWithLineStarts) {
+    if let Some(map) = node.srcmap {
+        let ((startline, startcol), (endline, endcol)) = map.get_positions(mapping);
+        node.attrs.push(("data-sourcepos".into(), format!("{}:{}-{}:{}", startline, startcol, endline, endcol)));
+    }
+}
+
 #[doc(hidden)]
 pub struct SyntaxPosRule;
 impl CoreRule for SyntaxPosRule {
@@ -26,12 +53,26 @@ impl CoreRule for SyntaxPosRule {
         let source = root.cast::<Root>().unwrap().content.as_str();
         let mapping = SourceWithLineStarts::new(source);
 
-        root.walk_mut(|node, _| {
-            if let Some(map) = node.srcmap {
-                let ((startline, startcol), (endline, endcol)) = map.get_positions(&mapping);
-                node.attrs.push(("data-sourcepos".into(), format!("{}:{}-{}:{}", startline, startcol, endline, endcol)));
-            }
-        });
+        root.walk_mut(|node, _| add_sourcepos(node, &mapping));
+    }
+}
+
+#[doc(hidden)]
+pub struct BlockPosRule;
+impl CoreRule for BlockPosRule {
+    fn run(root: &mut Node, _: &MarkdownIt) {
+        let source = root.cast::<Root>().unwrap().content.as_str();
+        let mapping = SourceWithLineStarts::new(source);
+
+        // inline content isn't parsed yet, so all nodes except for root
+        // and inline placeholders are block-level
+        for node in root.children.iter_mut() {
+            node.walk_mut(|node, _| {
+                if !node.is::<InlineRoot>() {
+                    add_sourcepos(node, &mapping);
+                }
+            });
+        }
     }
 }
 
@@ -49,4 +90,18 @@ mod tests {
         let html = md.parse("# hello").render();
         assert_eq!(html.trim(), r#"<h1 data-sourcepos="1:1-1:7">hello</h1>"#);
     }
+
+    #[cfg(feature = "tables")]
+    #[test]
+    fn blocks_test() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::extra::tables::add(md);
+        crate::plugins::sourcepos::add_blocks(md);
+
+        let html = md.parse("- a `b`\n\n| c |\n|---|\n| d |\n").render();
+        assert_eq!(html, "<ul data-sourcepos=\"1:1-2:0\">\n<li data-sourcepos=\"1:1-2:0\">a <code>b</code></li>\n</ul>\n\
+<table data-sourcepos=\"3:1-5:5\">\n<thead data-sourcepos=\"3:1-4:5\">\n<tr data-sourcepos=\"3:1-3:5\">\n<th data-sourcepos=\"3:3-3:3\">c</th>\n</tr>\n</thead>\n\
+<tbody data-sourcepos=\"5:1-5:5\">\n<tr data-sourcepos=\"5:1-5:5\">\n<td data-sourcepos=\"5:3-5:3\">d</td>\n</tr>\n</tbody>\n</table>\n");
+    }
 }
@@ -0,0 +1,402 @@
+//! CommonMark spec examples for testing parsers with plugins.
+//!
+//! Plugins that add or replace core rules can break CommonMark compliance
+//! in subtle ways. [run] renders every example of the embedded CommonMark spec
+//! with a configured parser, and returns which of them produce a different result.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::html::add(md);
+//!
+//! let report = markdown_it::testing::run(md);
+//! for result in report.failed() {
+//!     println!("{result}");
+//! }
+//! assert_eq!(report.failed().count(), 0, "{report}");
+//! ```
+//!
+//! Plugin tests can compare html with [assert_html_eq], which ignores differences
+//! in whitespace, attribute order and quoting (see [normalize_html]):
+//!
+//! ```rust
+//! use markdown_it::testing::assert_html_eq;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::attrs::add(md);
+//!
+//! let html = md.parse("# Title {.big #top}\n\n![a](a.png)").render();
+//! assert_html_eq!(html, r#"
+//!     <h1 id="top" class="big">Title</h1>
+//!     <p><img alt='a' src="a.png" /></p>
+//! "#);
+//! ```
+use std::fmt::{self, Display};
+
+use crate::MarkdownIt;
+
+const SPEC: &str = include_str!("../tests/fixtures/commonmark/spec.txt");
+const EXAMPLE_FENCE: &str = "````````````````````````````````";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Single example from the spec.
+pub struct SpecExample {
+    /// Number of the example, as shown in the spec (starting from 1).
+    pub number: usize,
+    /// Line of `spec.txt` where the example starts.
+    pub line: usize,
+    /// Title of the section the example belongs to.
+    pub section: String,
+    pub markdown: String,
+    /// Expected result (rendered as xhtml).
+    pub html: String,
+}
+
+/// Examples from the embedded CommonMark spec (version 0.30).
+pub fn spec_examples() -> Vec<SpecExample> {
+    let mut examples = Vec::new();
+    let mut section = String::new();
+    let mut lines = SPEC.lines().enumerate();
+
+    while let Some((idx, line)) = lines.next() {
+        if line.starts_with('#') {
+            section = line.trim_start_matches('#').trim().to_owned();
+            continue;
+        }
+        if line != format!("{EXAMPLE_FENCE} example") { continue; }
+
+        let mut markdown = String::new();
+        let mut html = String::new();
+        let mut in_html = false;
+        for (_, line) in lines.by_ref() {
+            if line == EXAMPLE_FENCE { break; }
+            if line == "." && !in_html {
+                in_html = true;
+                continue;
+            }
+            let text = if in_html { &mut html } else { &mut markdown };
+            text.push_str(&line.replace('→', "\t"));
+            text.push('\n');
+        }
+
+        examples.push(SpecExample {
+            number: examples.len() + 1,
+            line: idx + 1,
+            section: section.clone(),
+            markdown,
+            html,
+        });
+    }
+
+    examples
+}
+
+#[derive(Debug, Clone)]
+/// Result of rendering a spec example.
+pub struct SpecResult {
+    pub example: SpecExample,
+    /// Result produced by the parser.
+    pub html: String,
+}
+
+impl SpecResult {
+    pub fn passed(&self) -> bool {
+        self.html == self.example.html
+    }
+}
+
+impl Display for SpecResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.passed() { "passed" } else { "failed" };
+        write!(f, "example {} ({}, line {}): {}", self.example.number, self.example.section, self.example.line, status)?;
+        if !self.passed() {
+            write!(f, "\n--- markdown\n{}--- expected\n{}--- actual\n{}", self.example.markdown, self.example.html, self.html)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Results of all examples, returned by [run].
+pub struct SpecReport {
+    pub results: Vec<SpecResult>,
+}
+
+impl SpecReport {
+    pub fn passed(&self) -> impl Iterator<Item = &SpecResult> {
+        self.results.iter().filter(|result| result.passed())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &SpecResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+impl Display for SpecReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} spec examples passed", self.passed().count(), self.results.len())?;
+        for result in self.failed() {
+            write!(f, "\n{result}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Render all spec examples with a given parser.
+pub fn run(md: &MarkdownIt) -> SpecReport {
+    run_examples(md, spec_examples())
+}
+
+/// Render selected examples (e.g. from a single section) with a given parser.
+pub fn run_examples(md: &MarkdownIt, examples: impl IntoIterator<Item = SpecExample>) -> SpecReport {
+    let results = examples.into_iter().map(|example| {
+        let html = md.parse(&example.markdown).xrender();
+        SpecResult { example, html }
+    }).collect();
+    SpecReport { results }
+}
+
+const BLOCK_TAGS : &[&str] = &[
+    "address", "article", "aside", "blockquote", "br", "dd", "details", "div", "dl", "dt",
+    "figcaption", "figure", "footer", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr",
+    "li", "nav", "ol", "p", "pre", "section", "summary", "table", "tbody", "td", "tfoot",
+    "th", "thead", "tr", "ul",
+];
+
+#[derive(Debug)]
+enum HtmlToken<'a> {
+    Text(&'a str),
+    // name (lowercase, with `/` for closing tags) and normalized tag
+    Tag(String, String),
+    // comments, doctype, etc.
+    Other(&'a str),
+}
+
+// returns the end of a tag starting at `html[0]`, skipping `>` in quoted attributes
+fn tag_end(html: &str) -> usize {
+    let mut quote = None;
+    for (pos, ch) in html.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), _) if q == ch => quote = None,
+            (None, '>') => return pos + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+fn normalize_tag(tag: &str) -> (String, String) {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/').trim();
+    let closing = inner.starts_with('/');
+    let inner = inner.trim_start_matches('/');
+    let name_end = inner.find(|ch: char| ch.is_whitespace()).unwrap_or(inner.len());
+    let name = inner[..name_end].to_ascii_lowercase();
+
+    let mut attrs = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let end = rest.find(|ch: char| ch.is_whitespace() || ch == '=').unwrap_or(rest.len());
+        let attr_name = rest[..end].to_ascii_lowercase();
+        rest = rest[end..].trim_start();
+
+        let mut value = None;
+        if let Some(after_eq) = rest.strip_prefix('=') {
+            let after_eq = after_eq.trim_start();
+            let (val, len) = match after_eq.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = after_eq[1..].find(q).map_or(after_eq.len(), |pos| pos + 1);
+                    (&after_eq[1..end], (end + 1).min(after_eq.len()))
+                }
+                _ => {
+                    let end = after_eq.find(char::is_whitespace).unwrap_or(after_eq.len());
+                    (&after_eq[..end], end)
+                }
+            };
+            value = Some(val.replace('"', "&quot;"));
+            rest = after_eq[len..].trim_start();
+        }
+        attrs.push((attr_name, value));
+    }
+    attrs.sort();
+
+    let mut result = format!("<{}{}", if closing { "/" } else { "" }, name);
+    for (attr_name, value) in attrs {
+        match value {
+            Some(value) => result.push_str(&format!(" {attr_name}=\"{value}\"")),
+            None => result.push_str(&format!(" {attr_name}")),
+        }
+    }
+    result.push('>');
+
+    (if closing { format!("/{name}") } else { name }, result)
+}
+
+fn tokenize_html(mut html: &str) -> Vec<HtmlToken<'_>> {
+    let mut tokens = Vec::new();
+    while !html.is_empty() {
+        let len = if let Some(comment) = html.strip_prefix("<!--") {
+            let len = comment.find("-->").map_or(html.len(), |pos| pos + 7);
+            tokens.push(HtmlToken::Other(&html[..len]));
+            len
+        } else if html.starts_with("<!") || html.starts_with("<?") {
+            let len = tag_end(html);
+            tokens.push(HtmlToken::Other(&html[..len]));
+            len
+        } else if html.starts_with('<') && html[1..].starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '/') {
+            let len = tag_end(html);
+            let (name, tag) = normalize_tag(&html[..len]);
+            tokens.push(HtmlToken::Tag(name, tag));
+            len
+        } else {
+            let first = html.chars().next().map_or(1, char::len_utf8);
+            let len = html[first..].find('<').map_or(html.len(), |pos| pos + first);
+            tokens.push(HtmlToken::Text(&html[..len]));
+            len
+        };
+        html = &html[len..];
+    }
+    tokens
+}
+
+/// Normalize html for comparison in tests:
+///
+///  - attributes are sorted by name and written in double quotes
+///  - self-closing tags are written as opening tags (`<br />` as `<br>`)
+///  - whitespace is collapsed into a single space, and removed
+///    around block-level tags (except inside `<pre>`)
+///
+/// ```rust
+/// use markdown_it::testing::normalize_html;
+///
+/// assert_eq!(
+///     normalize_html("<p class='a'  id=x>\n  hello\n  <br/>world </p>\n"),
+///     normalize_html("<p id=\"x\" class=\"a\">hello<br>world</p>"),
+/// );
+/// ```
+pub fn normalize_html(html: &str) -> String {
+    let tokens = tokenize_html(html);
+    let is_block = |token: Option<&HtmlToken>| match token {
+        Some(HtmlToken::Tag(name, _)) => BLOCK_TAGS.contains(&name.trim_start_matches('/')),
+        Some(_) => false,
+        None => true,
+    };
+
+    let mut result = String::new();
+    let mut pre_depth = 0usize;
+    for (idx, token) in tokens.iter().enumerate() {
+        match token {
+            HtmlToken::Tag(name, tag) => {
+                match name.as_str() {
+                    "pre" => pre_depth += 1,
+                    "/pre" => pre_depth = pre_depth.saturating_sub(1),
+                    _ => {}
+                }
+                result.push_str(tag);
+            }
+            HtmlToken::Other(text) => result.push_str(text),
+            HtmlToken::Text(text) if pre_depth > 0 => result.push_str(text),
+            HtmlToken::Text(raw) => {
+                let mut text = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+                if text.is_empty() {
+                    // whitespace between inline elements is significant
+                    if !is_block(idx.checked_sub(1).and_then(|idx| tokens.get(idx))) && !is_block(tokens.get(idx + 1)) {
+                        result.push(' ');
+                    }
+                    continue;
+                }
+                if raw.starts_with(char::is_whitespace) && !is_block(idx.checked_sub(1).and_then(|idx| tokens.get(idx))) {
+                    text.insert(0, ' ');
+                }
+                if raw.ends_with(char::is_whitespace) && !is_block(tokens.get(idx + 1)) {
+                    text.push(' ');
+                }
+                result.push_str(&text);
+            }
+        }
+    }
+    result
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_html_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::__assert_html_eq!($left, $right, "html is different")
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let left = $crate::testing::normalize_html(left);
+                let right = $crate::testing::normalize_html(right);
+                if left != right {
+                    panic!("{}\n  left: {}\n right: {}", format_args!($($arg)+), left, right);
+                }
+            }
+        }
+    };
+}
+
+/// Same as [assert_eq], but both sides are html compared after [normalize_html].
+pub use crate::__assert_html_eq as assert_html_eq;
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn examples() {
+        let examples = super::spec_examples();
+        assert_eq!(examples.len(), 652);
+        assert_eq!(examples[0].number, 1);
+        assert_eq!(examples[0].line, 356);
+        assert_eq!(examples[0].section, "Tabs");
+        assert_eq!(examples[0].markdown, "\tfoo\tbaz\t\tbim\n");
+        assert_eq!(examples[0].html, "<pre><code>foo\tbaz\t\tbim\n</code></pre>\n");
+    }
+
+    #[test]
+    fn cmark_passes() {
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        crate::plugins::html::add(md);
+        let report = super::run(md);
+        assert_eq!(report.failed().count(), 0, "{report}");
+    }
+
+    #[test]
+    fn failures() {
+        // raw html examples fail without html plugin
+        let md = &mut crate::MarkdownIt::new();
+        crate::plugins::cmark::add(md);
+        let examples = super::spec_examples().into_iter().filter(|example| example.section == "Raw HTML");
+        let report = super::run_examples(md, examples);
+
+        assert_eq!(report.results.len(), 21);
+        assert!(report.failed().count() > 0);
+        assert!(report.failed().all(|result| result.to_string().contains("\n--- actual\n")));
+        assert!(report.to_string().starts_with(&format!("{} of 21 spec examples passed\nexample ", report.passed().count())));
+    }
+
+    #[test]
+    fn normalize() {
+        use super::normalize_html;
+
+        assert_eq!(normalize_html("<P Class=\"x\"  ID='y'>a  <em>b</em>\n c</P>\n"), "<p class=\"x\" id=\"y\">a <em>b</em> c</p>");
+        assert_eq!(normalize_html("<img src=a.png alt='say \"hi\"' />"), "<img alt=\"say &quot;hi&quot;\" src=\"a.png\">");
+        assert_eq!(normalize_html("<ul>\n  <li>a</li>\n  <li>b</li>\n</ul>\n"), "<ul><li>a</li><li>b</li></ul>");
+        assert_eq!(normalize_html("<em>a</em> <em>b</em>"), "<em>a</em> <em>b</em>");
+        assert_eq!(normalize_html("<pre><code>a\n  b\n</code></pre>\n"), "<pre><code>a\n  b\n</code></pre>");
+        assert_eq!(normalize_html("<!-- a  b --> 1 < 2 <input disabled>"), "<!-- a  b --> 1 < 2 <input disabled>");
+        assert_eq!(normalize_html("<a title=\"x > y\" href=\"/\">"), "<a href=\"/\" title=\"x > y\">");
+        assert_eq!(normalize_html("é<p>x</p>"), "é<p>x</p>");
+        assert_eq!(normalize_html("<p>été</p> à <em>b</em>"), "<p>été</p>à <em>b</em>");
+    }
+
+    #[test]
+    fn assert_html_eq() {
+        super::assert_html_eq!("<p>\n<br />a</p>", String::from("<p><br>a</p>\n"));
+        let result = std::panic::catch_unwind(|| super::assert_html_eq!("<p>a</p>", "<p>b</p>", "example {}", 1));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(message, "example 1\n  left: <p>a</p>\n right: <p>b</p>");
+    }
+}
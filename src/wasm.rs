@@ -0,0 +1,191 @@
+//! String-in, string-out API for JavaScript bindings (requires `wasm` feature).
+//!
+//! Functions in this module only take and return strings (options and AST are JSON),
+//! they are exported to JavaScript with `wasm-bindgen` as `parseToHtml(src, options)`,
+//! `parseToJson(src, options)` and `new Parser(options)` with `parseToHtml(src)`
+//! and `parseToJson(src)` methods (errors in options are thrown). They end up in
+//! a module built from a `cdylib` crate that re-exports them:
+//!
+//! ```rust,ignore
+//! // crate-type = ["cdylib"], built with `wasm-pack` or `cargo build --target wasm32-unknown-unknown`
+//! pub use markdown_it::wasm::*;
+//! ```
+//!
+//! Options are a JSON object, all fields are optional:
+//!
+//!  - `plugins` - list of [plugin names](crate::plugins::PluginId), `["cmark"]` by default
+//!  - `xhtml` - use XHTML-style self-closing tags, `false` by default
+//!  - `unwrap_single_paragraph` - see [RenderOptions], `false` by default
+//!  - `entities` - `"decode"`, `"raw"` or `"escape"`, see [EntityMode](crate::EntityMode), `"decode"` by default
+//!
+//! Crate has no dependencies that can't be compiled to `wasm32-unknown-unknown`
+//! (`syntect` is built with a pure Rust regex engine), and `rayon` feature runs
+//! on a single thread there.
+//!
+//! ```rust
+//! let options = r#"{ "plugins": ["cmark", "strikethrough"], "xhtml": true }"#;
+//! let html = markdown_it::wasm::parse_to_html("~~a~~  \nb", options).unwrap();
+//! assert_eq!(html, "<p><s>a</s><br />\nb</p>\n");
+//!
+//! let ast = markdown_it::wasm::parse_to_json("*a*", "{}").unwrap();
+//! assert!(ast.starts_with(r#"{"type":"markdown_it::parser::core::root::Root""#));
+//! ```
+use std::fmt::{self, Display};
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::plugins::registry::UnknownPlugin;
+use crate::plugins::PluginId;
+use crate::{EntityMode, MarkdownIt, RenderOptions};
+
+#[derive(Debug)]
+/// Error returned when options can't be read.
+pub enum OptionsError {
+    /// Options aren't valid JSON.
+    Json(serde_json::Error),
+    /// Field has a wrong type (e.g. `"xhtml": "yes"`).
+    InvalidField(&'static str),
+    UnknownPlugin(UnknownPlugin),
+}
+
+impl Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid options: {err}"),
+            Self::InvalidField(name) => write!(f, "invalid options: wrong type of `{name}`"),
+            Self::UnknownPlugin(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
+#[derive(Debug)]
+/// Parser configured with JSON options, create it once to render many documents.
+pub struct Parser {
+    md: MarkdownIt,
+    render_options: RenderOptions,
+}
+
+impl Parser {
+    pub fn new(options: &str) -> Result<Self, OptionsError> {
+        let options : Value = serde_json::from_str(options).map_err(OptionsError::Json)?;
+
+        let plugins = match options.get("plugins") {
+            Some(Value::Array(names)) => names.iter().map(|name| {
+                let name = name.as_str().ok_or(OptionsError::InvalidField("plugins"))?;
+                name.parse::<PluginId>().map_err(OptionsError::UnknownPlugin)
+            }).collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err(OptionsError::InvalidField("plugins")),
+            None => vec![PluginId::Cmark],
+        };
+
+        let flag = |name: &'static str| match options.get(name) {
+            Some(value) => value.as_bool().ok_or(OptionsError::InvalidField(name)),
+            None => Ok(false),
+        };
+
+        let entities = match options.get("entities").map(Value::as_str) {
+            None | Some(Some("decode")) => EntityMode::Decode,
+            Some(Some("raw")) => EntityMode::Raw,
+            Some(Some("escape")) => EntityMode::Escape,
+            Some(_) => return Err(OptionsError::InvalidField("entities")),
+        };
+
+        let render_options = RenderOptions {
+            xhtml: flag("xhtml")?,
+            unwrap_single_paragraph: flag("unwrap_single_paragraph")?,
+            entities,
+            ..Default::default()
+        };
+
+        Ok(Self { md: MarkdownIt::with_plugins(&plugins), render_options })
+    }
+
+    pub fn parse_to_html(&self, src: &str) -> String {
+        self.md.parse(src).render_with_options(&self.render_options)
+    }
+
+    /// Parse document and serialize its AST to JSON.
+    pub fn parse_to_json(&self, src: &str) -> String {
+        serde_json::to_string(&self.md.parse(src)).unwrap_or_default()
+    }
+}
+
+/// Render document to html with given JSON options.
+pub fn parse_to_html(src: &str, options: &str) -> Result<String, OptionsError> {
+    Ok(Parser::new(options)?.parse_to_html(src))
+}
+
+/// Parse document with given JSON options and serialize its AST to JSON.
+pub fn parse_to_json(src: &str, options: &str) -> Result<String, OptionsError> {
+    Ok(Parser::new(options)?.parse_to_json(src))
+}
+
+#[wasm_bindgen(js_name = parseToHtml)]
+/// [parse_to_html] exported to JavaScript.
+pub fn js_parse_to_html(src: &str, options: &str) -> Result<String, JsError> {
+    Ok(parse_to_html(src, options)?)
+}
+
+#[wasm_bindgen(js_name = parseToJson)]
+/// [parse_to_json] exported to JavaScript.
+pub fn js_parse_to_json(src: &str, options: &str) -> Result<String, JsError> {
+    Ok(parse_to_json(src, options)?)
+}
+
+#[wasm_bindgen(js_name = Parser)]
+/// [Parser] exported to JavaScript.
+pub struct JsParser(Parser);
+
+#[wasm_bindgen(js_class = Parser)]
+impl JsParser {
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: &str) -> Result<JsParser, JsError> {
+        Ok(Self(Parser::new(options)?))
+    }
+
+    #[wasm_bindgen(js_name = parseToHtml)]
+    pub fn parse_to_html(&self, src: &str) -> String {
+        self.0.parse_to_html(src)
+    }
+
+    #[wasm_bindgen(js_name = parseToJson)]
+    pub fn parse_to_json(&self, src: &str) -> String {
+        self.0.parse_to_json(src)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OptionsError, Parser};
+
+    #[test]
+    fn options() {
+        let parser = Parser::new(r#"{ "plugins": ["cmark", "html"], "unwrap_single_paragraph": true }"#).unwrap();
+        assert_eq!(parser.parse_to_html("<b>*a*</b>"), "<b><em>a</em></b>");
+
+        let parser = Parser::new("{}").unwrap();
+        assert_eq!(parser.parse_to_html("<b>"), "<p>&lt;b&gt;</p>\n");
+
+        let parser = Parser::new(r#"{ "entities": "raw" }"#).unwrap();
+        assert_eq!(parser.parse_to_html("&nbsp;"), "<p>&nbsp;</p>\n");
+    }
+
+    #[test]
+    fn invalid_options() {
+        let error = |options| Parser::new(options).unwrap_err().to_string();
+        assert!(matches!(Parser::new("{"), Err(OptionsError::Json(_))));
+        assert_eq!(error(r#"{ "plugins": ["foo"] }"#), "unknown plugin: foo");
+        assert_eq!(error(r#"{ "plugins": "cmark" }"#), "invalid options: wrong type of `plugins`");
+        assert_eq!(error(r#"{ "xhtml": 1 }"#), "invalid options: wrong type of `xhtml`");
+        assert_eq!(error(r#"{ "entities": "keep" }"#), "invalid options: wrong type of `entities`");
+    }
+
+    #[test]
+    fn json() {
+        let ast : serde_json::Value = serde_json::from_str(&super::parse_to_json("# a", "{}").unwrap()).unwrap();
+        assert_eq!(ast["children"][0]["data"]["level"], 1);
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "extra")]
 use once_cell::sync::Lazy;
 
 
@@ -45,6 +46,34 @@ fn no_max_indent() {
     assert_eq!(result, "<p>paragraph</p>\n<ul>\n<li>item</li>\n</ul>\n");
 }
 
+#[test]
+fn max_nesting() {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    md.max_nesting = 2;
+
+    let node = md.parse("> > > *a*\n> > > b\n\nc");
+    let result = node.render();
+    assert_eq!(result, "<blockquote>\n<blockquote>\n&gt; *a*\n&gt; b\n</blockquote>\n</blockquote>\n<p>c</p>\n");
+
+    let node = md.parse("- - - a\n\n[*a* ![*b*](/b)](/c)");
+    let result = node.render();
+    assert_eq!(result, "<ul>\n<li>\n<ul>\n<li>- a</li>\n</ul>\n</li>\n</ul>\n<p><a href=\"/c\"><em>a</em> <img src=\"/b\" alt=\"*b*\"></a></p>\n");
+}
+
+#[test]
+fn max_input_size() {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    md.max_input_size = 10;
+
+    let node = md.parse("# *a*\n*b*\n*c*\n");
+    assert_eq!(node.render(), "<h1><em>a</em></h1>\n<p><em>b</em></p>\n*c*\n");
+    assert_eq!(node.children[2].srcmap.unwrap().get_byte_offsets(), (10, 14));
+
+    let node = md.parse_fragment("*a* *b* *c*");
+    assert_eq!(node.render(), "*a* *b* *c*");
+}
 
 /*#[test]
 fn no_block_parser() {
@@ -200,3 +229,58 @@ mod examples {
     }
 }
 
+#[test]
+fn render_options_xhtml() {
+    use markdown_it::RenderOptions;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    let node = md.parse("a  \nb ![c](d)\n\n---");
+    assert_eq!(node.render_with_options(&RenderOptions::default()), "<p>a<br>\nb <img src=\"d\" alt=\"c\"></p>\n<hr>\n");
+    assert_eq!(node.render_with_options(&RenderOptions::new().xhtml(true)), "<p>a<br />\nb <img src=\"d\" alt=\"c\" /></p>\n<hr />\n");
+}
+
+#[test]
+fn render_options_unwrap_single_paragraph() {
+    use markdown_it::RenderOptions;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    let options = RenderOptions::new().unwrap_single_paragraph(true);
+    assert_eq!(md.parse("*a*\nb").render_with_options(&options), "<em>a</em>\nb");
+    assert_eq!(md.parse("[a]: /x\n\n[a]").render_with_options(&options), "<a href=\"/x\">a</a>");
+    assert_eq!(md.parse("a\n\nb").render_with_options(&options), "<p>a</p>\n<p>b</p>\n");
+    assert_eq!(md.parse("# a").render_with_options(&options), "<h1>a</h1>\n");
+    assert_eq!(md.parse("> a").render_with_options(&options), "<blockquote>\n<p>a</p>\n</blockquote>\n");
+    assert_eq!(md.parse("a").render(), "<p>a</p>\n");
+}
+
+#[test]
+fn newline_breaks() {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    assert_eq!(md.parse("a\nb").render(), "<p>a\nb</p>\n");
+
+    markdown_it::plugins::cmark::inline::newline::set_breaks(md, true);
+    assert_eq!(md.parse("a\nb  \nc\n\nd").render(), "<p>a<br>\nb<br>\nc</p>\n<p>d</p>\n");
+
+    markdown_it::plugins::cmark::inline::newline::set_breaks(md, false);
+    assert_eq!(md.parse("a\nb").render(), "<p>a\nb</p>\n");
+}
+
+#[test]
+fn parse_fragment() {
+    use markdown_it::parser::core::Root;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::add(md);
+
+    let node = md.parse_fragment("- a ~~b~~\n\n[c](d)");
+    assert!(node.cast::<Root>().unwrap().fragment);
+    assert_eq!(node.render(), "- a <s>b</s>\n<a href=\"d\">c</a>");
+    assert_eq!(node.children[1].srcmap.unwrap().get_byte_offsets(), (4, 9));
+
+    let node = md.parse("- a");
+    assert!(!node.cast::<Root>().unwrap().fragment);
+}
@@ -0,0 +1,150 @@
+#![cfg(all(feature = "attrs", feature = "heading_anchors"))]
+// Ports of markdown-it.js plugin tests, compared token by token
+// using the token stream view of the AST.
+use markdown_it::parser::tokens::Token;
+
+fn kinds(tokens: &[Token]) -> Vec<&str> {
+    tokens.iter().map(|t| t.kind.as_str()).collect()
+}
+
+fn attrs(token: &Token) -> Vec<(&str, &str)> {
+    token.attrs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+}
+
+mod attrs {
+    // edge cases from markdown-it-attrs test suite (heading and fence attributes)
+    use super::{attrs, kinds};
+    use markdown_it::parser::tokens;
+
+    fn parse(src: &str) -> Vec<tokens::Token> {
+        let md = &mut markdown_it::MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::extra::attrs::add(md);
+        tokens::from_ast(&md.parse(src))
+    }
+
+    #[test]
+    fn heading_class() {
+        let tokens = parse("# header {.style-me}");
+        assert_eq!(kinds(&tokens), ["heading_open", "inline", "heading_close"]);
+        assert_eq!(attrs(&tokens[0]), [("class", "style-me")]);
+        assert_eq!(tokens[1].content, "header");
+    }
+
+    #[test]
+    fn classes_are_joined() {
+        let tokens = parse("# header {#id .a .b key=val}");
+        assert_eq!(attrs(&tokens[0]), [("id", "id"), ("class", "a b"), ("key", "val")]);
+    }
+
+    #[test]
+    fn quoted_value() {
+        let tokens = parse("## x {data-a=\"b c\"}");
+        assert_eq!(attrs(&tokens[0]), [("data-a", "b c")]);
+        assert_eq!(tokens[1].content, "x");
+    }
+
+    #[test]
+    fn fence() {
+        let tokens = parse("```js {.c}\nx\n```");
+        assert_eq!(kinds(&tokens), ["fence"]);
+        assert_eq!(tokens[0].info, "js");
+        assert_eq!(attrs(&tokens[0]), [("class", "c")]);
+    }
+
+    #[test]
+    fn not_attributes() {
+        // empty braces and braces in the middle of the text are left as is
+        assert_eq!(parse("# header {}")[0].attrs, []);
+        assert_eq!(parse("# header {}")[1].content, "header {}");
+        assert_eq!(parse("# a {.b} c")[1].content, "a {.b} c");
+    }
+}
+
+mod anchor {
+    // permalink modes from markdown-it-anchor, ported by translating token
+    // manipulations into the corresponding node manipulations
+    use super::{attrs, kinds};
+    use markdown_it::parser::tokens;
+    use markdown_it::plugin_api::*;
+    use markdown_it::plugins::cmark::block::heading::ATXHeading;
+    use markdown_it::plugins::cmark::inline::link::Link;
+    use markdown_it::plugins::html::html_inline::HtmlInline;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Permalink {
+        // `children.push(space, link_open, html_inline(symbol), link_close)`
+        LinkInsideHeader,
+        // `children = [link_open, ...children, link_close]`
+        HeaderLink,
+    }
+
+    #[derive(Debug)]
+    struct PermalinkMode(Permalink);
+    impl MarkdownItExt for PermalinkMode {}
+
+    fn anchor_link(slug: &str) -> Node {
+        let mut link = Node::new(Link { url: format!("#{slug}"), title: None });
+        link.attrs.push(("class".into(), "header-anchor".into()));
+        link
+    }
+
+    struct PermalinkRule;
+    impl CoreRule for PermalinkRule {
+        fn run(root: &mut Node, md: &MarkdownIt) {
+            let mode = md.ext.get::<PermalinkMode>().unwrap().0;
+            root.walk_mut(|node, _| {
+                if !node.is::<ATXHeading>() { return; }
+                let Some((_, slug)) = node.attrs.iter().find(|(k, _)| k == "id") else { return; };
+                let mut link = anchor_link(slug);
+
+                match mode {
+                    Permalink::LinkInsideHeader => {
+                        link.children.push(Node::new(HtmlInline { content: "#".into() }));
+                        node.children.push(Node::new(Text { content: " ".into() }));
+                        node.children.push(link);
+                    }
+                    Permalink::HeaderLink => {
+                        link.children = std::mem::take(&mut node.children);
+                        node.children.push(link);
+                    }
+                }
+            });
+        }
+    }
+
+    fn parse(src: &str, mode: Permalink) -> Node {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::html::add(md);
+        markdown_it::plugins::extra::heading_anchors::add(md, markdown_it::plugins::extra::heading_anchors::simple_slugify_fn);
+        md.ext.insert(PermalinkMode(mode));
+        md.add_rule::<PermalinkRule>().after_all();
+        md.parse(src)
+    }
+
+    #[test]
+    fn link_inside_header() {
+        let ast = parse("# Title", Permalink::LinkInsideHeader);
+        assert_eq!(ast.render(), "<h1 id=\"title\">Title <a class=\"header-anchor\" href=\"#title\">#</a></h1>\n");
+
+        let tokens = tokens::from_ast(&ast);
+        assert_eq!(kinds(&tokens), ["heading_open", "inline", "heading_close"]);
+        assert_eq!(attrs(&tokens[0]), [("id", "title")]);
+
+        let inline = &tokens[1].children;
+        assert_eq!(kinds(inline), ["text", "text", "link_open", "html_inline", "link_close"]);
+        assert_eq!(attrs(&inline[2]), [("class", "header-anchor"), ("href", "#title")]);
+        assert_eq!(inline[3].content, "#");
+    }
+
+    #[test]
+    fn header_link() {
+        let ast = parse("# Title *x*", Permalink::HeaderLink);
+        assert_eq!(ast.render(), "<h1 id=\"title-x\"><a class=\"header-anchor\" href=\"#title-x\">Title <em>x</em></a></h1>\n");
+
+        let inline = &tokens::from_ast(&ast)[1].children;
+        assert_eq!(kinds(inline), ["link_open", "text", "em_open", "text", "em_close", "link_close"]);
+        assert_eq!(attrs(&inline[0]), [("class", "header-anchor"), ("href", "#title-x")]);
+    }
+}
@@ -168,3 +168,23 @@ fn emphasis_with_real_link_1() {
     run(input, output);
 }
 
+
+#[test]
+fn trailing_punctuation_gfm() {
+    run("http://example.com/a~~", r#"<p><a href="http://example.com/a">http://example.com/a</a>~~</p>"#);
+    run("http://example.com/a_ x", r#"<p><a href="http://example.com/a">http://example.com/a</a>_ x</p>"#);
+    run("http://example.com/a&hl; x", r#"<p><a href="http://example.com/a">http://example.com/a</a>&amp;hl; x</p>"#);
+    run("(http://example.com/a_(b)).", r#"<p>(<a href="http://example.com/a_(b)">http://example.com/a_(b)</a>).</p>"#);
+}
+
+#[test]
+fn trailing_punctuation_custom() {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::linkify::add(md);
+    markdown_it::plugins::extra::linkify::set_trailing_punctuation(md, "-");
+    assert_eq!(
+        md.parse("http://example.com/a-- http://example.com/b_").render(),
+        "<p><a href=\"http://example.com/a\">http://example.com/a</a>-- <a href=\"http://example.com/b_\">http://example.com/b_</a></p>\n"
+    );
+}
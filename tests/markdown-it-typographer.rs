@@ -1,3 +1,4 @@
+#![cfg(all(feature = "linkify", feature = "typographer"))]
 fn run(input: &str, output: &str) {
     let output = if output.is_empty() {
         "".to_owned()
@@ -1,4 +1,4 @@
-
+#![cfg(all(feature = "tables", feature = "typographer"))]
 fn run(input: &str, output: &str) {
     let output = if output.is_empty() { "".to_owned() } else { output.to_owned() + "\n" };
     let md = &mut markdown_it::MarkdownIt::new();
@@ -138,3 +138,55 @@ mod markdownit {
     }
 }
 
+
+mod extras {
+    // Patterns that used to be quadratic in plugins and parser extensions
+    use markdown_it::MarkdownIt;
+    use std::time::SystemTime;
+
+    fn run(add: fn(&mut MarkdownIt), src: &str) {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        add(md);
+        let now = SystemTime::now();
+        md.parse(src);
+        dbg!(now.elapsed().ok().unwrap());
+    }
+
+    #[test]
+    fn many_references_to_one_label() {
+        run(|_| {}, &format!("{}\n\n[a]: /a", "[a] ".repeat(40000)));
+    }
+
+    #[cfg(feature = "details")]
+    #[test]
+    fn unclosed_fenced_blocks() {
+        run(markdown_it::plugins::extra::details::add, &"::: details a\n".repeat(10000));
+    }
+
+    #[cfg(feature = "details")]
+    #[test]
+    fn fenced_blocks_closed_at_the_end() {
+        run(markdown_it::plugins::extra::details::add, &format!("{}:::", "::: details a\n".repeat(10000)));
+    }
+
+    #[cfg(feature = "critic")]
+    #[test]
+    fn unclosed_critic_markup() {
+        run(markdown_it::plugins::extra::critic::add, &"{++a ".repeat(50000));
+    }
+
+    #[cfg(feature = "ruby")]
+    #[test]
+    fn link_openers_with_ruby() {
+        run(markdown_it::plugins::extra::ruby::add, &"[a".repeat(40000));
+    }
+
+    #[test]
+    fn unclosed_markdown_in_html() {
+        run(|md| {
+            markdown_it::plugins::html::add(md);
+            markdown_it::plugins::html::markdown_in_html::add(md);
+        }, &"<div markdown=\"1\">\n\n".repeat(10000));
+    }
+}
@@ -0,0 +1,41 @@
+fn run(input: &str) -> String {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::add(md);
+    markdown_it::plugins::html::add(md);
+    md.parse(input).render_text()
+}
+
+#[cfg(feature = "strikethrough")]
+#[test]
+fn inline_markup() {
+    assert_eq!(run("*foo* **bar** `baz` ~~quux~~"), "foo bar baz quux\n");
+    assert_eq!(run("[link](/url \"title\") and ![alt *text*](/img.png)"), "link and alt text\n");
+    assert_eq!(run("a <b>b</b> &amp; \\*c\\*"), "a b & *c*\n");
+    assert_eq!(run("line\nbreak  \nhard"), "line\nbreak\nhard\n");
+}
+
+#[test]
+fn blocks() {
+    assert_eq!(run("# Title\n\nparagraph\n\n---\n\n> quote\n\ntext"), "Title\nparagraph\nquote\ntext\n");
+    assert_eq!(run("<div>\nhtml <b>b</b> &amp;\n</div>\n\ntext"), "html b &\ntext\n");
+}
+
+#[test]
+fn code() {
+    assert_eq!(run("```rust\nfn main() { 1 < 2 }\n```"), "fn main() { 1 < 2 }\n");
+    assert_eq!(run("    indented <code>"), "indented <code>\n");
+}
+
+#[test]
+fn lists() {
+    assert_eq!(run("- a\n- b\n  - c\n\n  d\n"), "- a\n- b\n  - c\n  d\n");
+    assert_eq!(run("3) a\n4) b\n   1. c"), "3) a\n4) b\n   1. c\n");
+    assert_eq!(run("- a\n  - b\n\n    ```\n    c\n    d\n    ```\n- e"), "- a\n  - b\n    c\n    d\n- e\n");
+}
+
+#[cfg(feature = "tables")]
+#[test]
+fn tables() {
+    assert_eq!(run("| a | b |\n|---|---|\n| *c* | d |"), "a\tb\nc\td\n");
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "serde")]
+use serde_json::json;
+
+fn parse(input: &str) -> serde_json::Value {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    let node = md.parse(input);
+    serde_json::to_value(&node).unwrap()
+}
+
+#[test]
+fn heading() {
+    let ast = parse("# hello");
+    assert_eq!(ast["type"], "markdown_it::parser::core::root::Root");
+    assert_eq!(ast["srcmap"], json!([0, 7]));
+    assert_eq!(ast["children"][0], json!({
+        "type": "markdown_it::plugins::cmark::block::heading::ATXHeading",
+        "srcmap": [0, 7],
+        "attrs": [],
+        "data": { "level": 1 },
+        "children": [{
+            "type": "markdown_it::parser::inline::builtin::skip_text::Text",
+            "srcmap": [2, 7],
+            "attrs": [],
+            "data": { "content": "hello" },
+            "children": [],
+        }],
+    }));
+}
+
+#[test]
+fn link_with_attrs() {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::sourcepos::add(md);
+    let node = md.parse("[foo](/bar \"baz\")");
+    let ast = serde_json::to_value(&node).unwrap();
+    let link = &ast["children"][0]["children"][0];
+    assert_eq!(link["data"], json!({ "url": "/bar", "title": "baz" }));
+    assert_eq!(link["attrs"], json!([["data-sourcepos", "1:1-1:17"]]));
+}
+
+#[test]
+fn node_without_data() {
+    let ast = parse("> quote");
+    assert_eq!(ast["children"][0]["data"], json!(null));
+}
+
+#[test]
+fn roundtrip() {
+    use markdown_it::parser::deserialize::NodeDeserializer;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::add(md);
+    markdown_it::plugins::html::add(md);
+    let src = "# hi\n\n> *a* **b** `c` [d](/e \"f\") ![g](/h)\\\nhttps://example.org\n\n1) x\n2) <b>y</b>\n\n| a | b |\n|:--|--:|\n| ~~c~~ | d |\n\n```rust\nfn main() {}\n```\n\n---\n";
+    let node = md.parse(src);
+    let json = serde_json::to_string(&node).unwrap();
+    let node2 = NodeDeserializer::new().from_json(&json).unwrap();
+    assert_eq!(node2.render(), node.render());
+    assert_eq!(serde_json::to_string(&node2).unwrap(), json);
+}
+
+#[test]
+fn roundtrip_replaced_node() {
+    use markdown_it::parser::deserialize::NodeDeserializer;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    let mut node = md.parse("a");
+    node.replace_with(vec![md.parse("*b*"), md.parse("c")]);
+    let json = serde_json::to_string(&node).unwrap();
+    let node2 = NodeDeserializer::new().from_json(&json).unwrap();
+    assert_eq!(node2.render(), "<p><em>b</em></p>\n<p>c</p>\n");
+}
+
+#[test]
+fn deserialize_errors() {
+    use markdown_it::parser::deserialize::NodeDeserializer;
+
+    let de = NodeDeserializer::new();
+    assert!(de.from_value(&json!({ "data": null })).is_err());
+    assert!(de.from_value(&json!({ "type": "foo::Bar" })).is_err());
+    assert!(de.from_value(&json!({
+        "type": "markdown_it::plugins::cmark::block::heading::ATXHeading",
+        "data": { "level": "one" },
+    })).is_err());
+    for level in [0, 7] {
+        assert!(de.from_value(&json!({
+            "type": "markdown_it::plugins::cmark::block::heading::ATXHeading",
+            "data": { "level": level },
+        })).is_err());
+    }
+    assert!(de.from_value(&json!({
+        "type": "markdown_it::plugins::cmark::block::lheading::SetextHeader",
+        "data": { "level": 3, "marker": "=" },
+    })).is_err());
+    assert!(NodeDeserializer::empty().from_value(&json!({
+        "type": "markdown_it::plugins::cmark::block::paragraph::Paragraph",
+    })).is_err());
+}
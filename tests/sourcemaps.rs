@@ -374,3 +374,69 @@ fn imglink() {
     });
 }
 
+
+#[cfg(feature = "footnote")]
+#[test]
+fn definitions() {
+    use markdown_it::plugins::cmark::block::reference::DefinitionSrcmap;
+    use markdown_it::plugins::cmark::inline::link::Link;
+    use markdown_it::plugins::extra::footnote::references::FootnoteReference;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::footnote::add(md);
+    let input = "[a] [b][A] [c] [x](/x) [^n]\n\n> [A]: /a\n\n[^n]: note\n  continued\n";
+    let node = md.parse(input);
+    let map = SourceWithLineStarts::new(input);
+
+    let mut found = vec![];
+    node.walk(|node, _| {
+        if node.is::<Link>() || node.is::<FootnoteReference>() {
+            found.push(node.ext.get::<DefinitionSrcmap>().map(|d| d.0.get_positions(&map)));
+        }
+    });
+
+    assert_eq!(found, vec![
+        Some(((3, 3), (3, 9))),
+        Some(((3, 3), (3, 9))),
+        None,
+        Some(((5, 1), (6, 11))),
+    ]);
+}
+
+#[cfg(feature = "extra")]
+#[test]
+fn all_nodes_with_extras() {
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::html::add(md);
+    markdown_it::plugins::extra::add(md);
+    markdown_it::plugins::extra::footnote::add(md);
+    let input = "# a\n\nx[^1] ^[*inline*] http://x.org \"q\" -- (c) ~~s~~\n\n| a | b |\n|---|---|\n| c | d |\n\n[^1]: foot\n\n```rust\nfn main() {}\n```\n";
+    let node = md.parse(input);
+    node.walk(|node, _| assert!(node.srcmap.is_some(), "no srcmap for {}", node.name()));
+}
+
+#[cfg(feature = "tables")]
+#[test]
+fn node_at_offset() {
+    use markdown_it::plugins::cmark::inline::emphasis::Em;
+    use markdown_it::plugins::extra::tables::TableRow;
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::add(md);
+    let input = "- a *b*\n\n| x | y |\n|---|---|\n| z | w |\n";
+    let node = md.parse(input);
+
+    assert!(node.node_at_offset(4).unwrap().is::<Em>());
+    assert_eq!(node.node_at_offset(5).unwrap().collect_text(), "b");
+    assert_eq!(node.node_at_offset(2).unwrap().collect_text(), "a ");
+
+    let cell = input.find('w').unwrap();
+    assert_eq!(node.node_at_offset(cell).unwrap().collect_text(), "w");
+    let row = node.node_at_offset(cell - 1).unwrap();
+    assert!(row.is::<TableRow>());
+
+    assert!(node.node_at_offset(input.len()).is_none());
+}